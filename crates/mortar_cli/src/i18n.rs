@@ -123,6 +123,36 @@ fn init_texts() -> &'static HashMap<&'static str, HashMap<Language, &'static str
             .into(),
         );
 
+        texts.insert(
+            "message_format_help",
+            [
+                (
+                    Language::English,
+                    "Diagnostic output format (human, short, json, sarif)",
+                ),
+                (
+                    Language::Chinese,
+                    "诊断输出格式 (human, short, json, sarif)",
+                ),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "emit_help",
+            [
+                (
+                    Language::English,
+                    "What to write: the compiled output (mortared), the AST as JSON (ast) or the tokens with spans (tokens)",
+                ),
+                (
+                    Language::Chinese,
+                    "输出内容：编译结果 (mortared)、JSON 格式的 AST (ast) 或带位置的记号流 (tokens)",
+                ),
+            ]
+            .into(),
+        );
+
         // Runtime messages
         texts.insert(
             "error_reading_file",
@@ -231,6 +261,24 @@ fn init_texts() -> &'static HashMap<&'static str, HashMap<Language, &'static str
             [(Language::English, "warning"), (Language::Chinese, "警告")].into(),
         );
 
+        texts.insert(
+            "deny_warnings_help",
+            [
+                (Language::English, "Treat all warnings as errors"),
+                (Language::Chinese, "将所有警告视为错误"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "error_reading_config",
+            [
+                (Language::English, "Failed to load mortar.toml:"),
+                (Language::Chinese, "加载 mortar.toml 失败:"),
+            ]
+            .into(),
+        );
+
         texts.insert(
             "output_same_as_input",
             [
@@ -243,6 +291,116 @@ fn init_texts() -> &'static HashMap<&'static str, HashMap<Language, &'static str
             .into(),
         );
 
+        // `mortar explain`
+        texts.insert(
+            "explain_about",
+            [
+                (
+                    Language::English,
+                    "Print a detailed explanation of a diagnostic code",
+                ),
+                (Language::Chinese, "打印诊断代码的详细说明"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "explain_code_help",
+            [
+                (Language::English, "Diagnostic code, e.g. M0101"),
+                (Language::Chinese, "诊断代码，例如 M0101"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "unknown_diagnostic_code",
+            [
+                (Language::English, "Unknown diagnostic code:"),
+                (Language::Chinese, "未知的诊断代码:"),
+            ]
+            .into(),
+        );
+
+        // `mortar fmt`
+        texts.insert(
+            "fmt_about",
+            [
+                (Language::English, "Format .mortar files in place"),
+                (Language::Chinese, "就地格式化 .mortar 文件"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "fmt_files_help",
+            [
+                (Language::English, "Files to format"),
+                (Language::Chinese, "要格式化的文件"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "fmt_check_help",
+            [
+                (
+                    Language::English,
+                    "Do not write files; exit with an error if any file is not formatted",
+                ),
+                (
+                    Language::Chinese,
+                    "不写入文件；若有文件未格式化则以错误退出",
+                ),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "fmt_would_reformat",
+            [
+                (Language::English, "Would reformat:"),
+                (Language::Chinese, "需要重新格式化:"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "fmt_formatted",
+            [
+                (Language::English, "Formatted:"),
+                (Language::Chinese, "已格式化:"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "fmt_syntax_error",
+            [
+                (Language::English, "Skipped, file has syntax errors:"),
+                (Language::Chinese, "已跳过，文件包含语法错误:"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "fmt_check_failed",
+            [
+                (Language::English, "Some files are not formatted."),
+                (Language::Chinese, "部分文件未格式化。"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "fmt_failed",
+            [
+                (Language::English, "Some files could not be formatted."),
+                (Language::Chinese, "部分文件无法格式化。"),
+            ]
+            .into(),
+        );
+
         texts
     })
 }
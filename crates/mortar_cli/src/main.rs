@@ -1,6 +1,9 @@
 use anyhow::{Context, Result, bail};
 use clap::{Arg, Command};
-use mortar_compiler::{FileHandler, Language, ParseHandler, Serializer};
+use mortar_compiler::{
+    FileHandler, FormatOptions, Language, MessageFormat, ParseHandler, ProjectConfig, Serializer,
+    codes, format_source,
+};
 use std::process;
 
 mod i18n;
@@ -18,6 +21,7 @@ fn build_command(language: CliLanguage) -> Command {
         .version(env!("CARGO_PKG_VERSION"))
         .author("Bli-AIk <haikun2333@gmail.com>")
         .about(get_text("app_about", language))
+        .subcommand_negates_reqs(true)
         .arg(
             Arg::new("input")
                 .help(get_text("input_help", language))
@@ -59,13 +63,66 @@ fn build_command(language: CliLanguage) -> Command {
                 .action(clap::ArgAction::SetTrue)
                 .help(get_text("check_only_help", language)),
         )
+        .arg(
+            Arg::new("deny-warnings")
+                .long("deny-warnings")
+                .action(clap::ArgAction::SetTrue)
+                .help(get_text("deny_warnings_help", language)),
+        )
+        .arg(
+            Arg::new("message-format")
+                .long("message-format")
+                .value_name("FORMAT")
+                .value_parser(clap::builder::PossibleValuesParser::new(
+                    MessageFormat::NAMES,
+                ))
+                .default_value("human")
+                .help(get_text("message_format_help", language)),
+        )
+        .arg(
+            Arg::new("emit")
+                .long("emit")
+                .value_name("KIND")
+                .value_parser(clap::builder::PossibleValuesParser::new([
+                    "mortared", "ast", "tokens",
+                ]))
+                .default_value("mortared")
+                .help(get_text("emit_help", language)),
+        )
         .arg(
             Arg::new("lang")
                 .short('L')
                 .long("lang")
                 .value_name("LANGUAGE")
+                .global(true)
                 .help(get_text("language_help", language)),
         )
+        .subcommand(
+            Command::new("explain")
+                .about(get_text("explain_about", language))
+                .arg(
+                    Arg::new("code")
+                        .help(get_text("explain_code_help", language))
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about(get_text("fmt_about", language))
+                .arg(
+                    Arg::new("files")
+                        .help(get_text("fmt_files_help", language))
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(get_text("fmt_check_help", language)),
+                ),
+        )
 }
 
 fn main() {
@@ -90,34 +147,88 @@ fn run() -> Result<()> {
 
     let matches = build_command(language).get_matches();
 
+    if let Some(("explain", explain_matches)) = matches.subcommand() {
+        let code = explain_matches.get_one::<String>("code").unwrap();
+        return explain(code, language);
+    }
+
+    if let Some(("fmt", fmt_matches)) = matches.subcommand() {
+        let files: Vec<&String> = fmt_matches.get_many::<String>("files").unwrap().collect();
+        return format_files(&files, fmt_matches.get_flag("check"), language);
+    }
+
     let input_path = matches.get_one::<String>("input").unwrap();
     let pretty = matches.get_flag("pretty");
     let verbose_lexer = matches.get_flag("verbose-lexer");
     let show_source = matches.get_flag("show-source");
     let check_only = matches.get_flag("check-only");
+    let deny_warnings = matches.get_flag("deny-warnings");
+    let emit = matches
+        .get_one::<String>("emit")
+        .map(String::as_str)
+        .unwrap_or("mortared");
+    let output = matches.get_one::<String>("output");
+    let message_format = matches
+        .get_one::<String>("message-format")
+        .and_then(|name| MessageFormat::from_name(name))
+        .unwrap_or_default();
+
+    // Keep stdout clean for tools when emitting JSON or SARIF
+    let print_status = |message: &str| {
+        if message_format.is_machine_readable() {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    };
 
     // Read source file
     let content = FileHandler::read_source_file(input_path)
         .with_context(|| get_text("error_reading_file", language))?;
 
     if show_source {
-        println!("{}", get_text("original_source", language));
-        println!("{}", content);
-        println!("{}", get_text("end_source", language));
-        println!();
+        print_status(get_text("original_source", language));
+        print_status(&content);
+        print_status(get_text("end_source", language));
+        print_status("");
+    }
+
+    // Dumping tokens is for debugging the lexer, so it does not need the source to parse
+    if emit == "tokens" && !check_only {
+        let json_content = Serializer::tokens_to_json(&content, pretty)
+            .map_err(|e| anyhow::anyhow!(e))
+            .with_context(|| get_text("failed_to_generate", language))?;
+        let output_path = write_output(output, input_path, "tokens.json", json_content, language)?;
+        print_status(&format!(
+            "{} {}",
+            get_text("generated", language),
+            output_path.display()
+        ));
+        return Ok(());
+    }
+
+    // Load mortar.toml from the input's directory or one of its parents
+    let mut config = match ProjectConfig::discover(std::path::Path::new(input_path)) {
+        Ok(Some((_path, config))) => config,
+        Ok(None) => ProjectConfig::default(),
+        Err(e) => bail!("{} {}", get_text("error_reading_config", language), e),
+    };
+    if deny_warnings {
+        config.diagnostics.deny_warnings = true;
     }
 
     // Parse with diagnostics
     let compiler_language = cli_language_to_compiler_language(language);
-    let (parse_result, diagnostics) = ParseHandler::parse_source_code_with_diagnostics_and_language(
+    let (parse_result, diagnostics) = ParseHandler::parse_source_code_with_config(
         &content,
         input_path.clone(),
         verbose_lexer,
         compiler_language,
+        &config,
     );
 
     // Print diagnostics
-    diagnostics.print_diagnostics(&content);
+    diagnostics.emit_diagnostics(&content, message_format);
 
     // Check for errors (including parse errors)
     if diagnostics.has_errors() {
@@ -126,40 +237,106 @@ fn run() -> Result<()> {
 
     let program = parse_result.map_err(|_| anyhow::anyhow!("Parse failed (internal)"))?;
 
-    println!("{}", get_text("parsed_successfully", language));
+    print_status(get_text("parsed_successfully", language));
 
     // Only generate output if not in check-only mode
     if !check_only {
-        // Generate .mortared file
-        let json_content = Serializer::serialize_to_json(&program, pretty)
+        // Generate the .mortared file, or the AST with `--emit ast`
+        let (json_content, extension) = match emit {
+            "ast" => (Serializer::ast_to_json(&program, pretty), "ast.json"),
+            _ => (Serializer::serialize_to_json(&program, pretty), "mortared"),
+        };
+        let json_content = json_content
             .map_err(|e| anyhow::anyhow!(e))
             .with_context(|| get_text("failed_to_generate", language))?;
 
-        let output_path = if let Some(out) = matches.get_one::<String>("output") {
-            std::path::PathBuf::from(out)
-        } else {
-            std::path::Path::new(input_path).with_extension("mortared")
-        };
+        let output_path = write_output(output, input_path, extension, json_content, language)?;
 
-        if std::path::Path::new(input_path) == output_path {
-            bail!("{}", get_text("output_same_as_input", language));
-        }
+        print_status(&format!(
+            "{} {}",
+            get_text("generated", language),
+            output_path.display()
+        ));
+    }
 
-        std::fs::write(&output_path, json_content).with_context(|| {
-            format!(
-                "{} {}",
-                get_text("failed_to_generate", language),
-                output_path.display()
-            )
-        })?;
+    Ok(())
+}
 
-        println!(
+/// Write generated content to `--output`, or next to the input with the given extension.
+fn write_output(
+    output: Option<&String>,
+    input_path: &str,
+    extension: &str,
+    content: String,
+    language: CliLanguage,
+) -> Result<std::path::PathBuf> {
+    let output_path = if let Some(out) = output {
+        std::path::PathBuf::from(out)
+    } else {
+        std::path::Path::new(input_path).with_extension(extension)
+    };
+
+    if std::path::Path::new(input_path) == output_path {
+        bail!("{}", get_text("output_same_as_input", language));
+    }
+
+    std::fs::write(&output_path, content).with_context(|| {
+        format!(
             "{} {}",
-            get_text("generated", language),
+            get_text("failed_to_generate", language),
             output_path.display()
-        );
+        )
+    })?;
+
+    Ok(output_path)
+}
+
+/// Print the long-form explanation of a diagnostic code.
+fn explain(code: &str, language: CliLanguage) -> Result<()> {
+    match codes::explain(code, cli_language_to_compiler_language(language)) {
+        Some(explanation) => {
+            println!("{}", explanation);
+            Ok(())
+        }
+        None => bail!("{} {}", get_text("unknown_diagnostic_code", language), code),
     }
+}
+
+/// Format files in place, or with `check` only report the ones that would change.
+fn format_files(files: &[&String], check: bool, language: CliLanguage) -> Result<()> {
+    let mut unformatted = false;
+    let mut failed = false;
 
+    for path in files {
+        let content = FileHandler::read_source_file(path)
+            .with_context(|| get_text("error_reading_file", language))?;
+
+        if ParseHandler::parse_source_code(&content, false).is_err() {
+            eprintln!("{} {}", get_text("fmt_syntax_error", language), path);
+            failed = true;
+            continue;
+        }
+
+        let formatted = format_source(&content, FormatOptions::default());
+        if formatted == content {
+            continue;
+        }
+
+        if check {
+            println!("{} {}", get_text("fmt_would_reformat", language), path);
+            unformatted = true;
+        } else {
+            std::fs::write(path, formatted).with_context(|| path.to_string())?;
+            println!("{} {}", get_text("fmt_formatted", language), path);
+        }
+    }
+
+    if failed {
+        bail!("{}", get_text("fmt_failed", language));
+    }
+    if unformatted {
+        bail!("{}", get_text("fmt_check_failed", language));
+    }
     Ok(())
 }
 
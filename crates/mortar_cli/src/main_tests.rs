@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod tests {
     use crate::i18n::Language as CliLanguage;
-    use crate::{build_command, cli_language_to_compiler_language};
+    use crate::{
+        build_command, cli_language_to_compiler_language, explain, format_files, write_output,
+    };
     use mortar_compiler::Language;
     use std::fs;
     use tempfile::TempDir;
@@ -62,6 +64,159 @@ mod tests {
         assert!(matches.get_flag("check-only"));
     }
 
+    #[test]
+    fn test_command_parsing_explain() {
+        let cmd = build_command(CliLanguage::English);
+
+        let matches = cmd
+            .try_get_matches_from(vec!["mortar", "explain", "M0101", "--lang", "zh"])
+            .unwrap();
+        let (name, explain_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, "explain");
+        assert_eq!(explain_matches.get_one::<String>("code").unwrap(), "M0101");
+        assert!(matches.get_one::<String>("input").is_none());
+    }
+
+    #[test]
+    fn test_explain_unknown_code() {
+        assert!(explain("M0101", CliLanguage::English).is_ok());
+        assert!(explain("M9999", CliLanguage::English).is_err());
+    }
+
+    #[test]
+    fn test_command_parsing_fmt() {
+        let matches = build_command(CliLanguage::English)
+            .try_get_matches_from(vec!["mortar", "fmt", "a.mortar", "b.mortar", "--check"])
+            .unwrap();
+        let (name, fmt_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, "fmt");
+        assert_eq!(
+            fmt_matches
+                .get_many::<String>("files")
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec!["a.mortar", "b.mortar"]
+        );
+        assert!(fmt_matches.get_flag("check"));
+    }
+
+    #[test]
+    fn test_format_files_check_and_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.mortar");
+        let path_str = path.to_str().unwrap().to_string();
+        fs::write(&path, "nd start{text:'Hello'}").unwrap();
+
+        assert!(format_files(&[&path_str], true, CliLanguage::English).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nd start{text:'Hello'}");
+
+        assert!(format_files(&[&path_str], false, CliLanguage::English).is_ok());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "node start {\n    text: \"Hello\"\n}\n"
+        );
+        assert!(format_files(&[&path_str], true, CliLanguage::English).is_ok());
+    }
+
+    #[test]
+    fn test_format_files_skips_syntax_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("broken.mortar");
+        let path_str = path.to_str().unwrap().to_string();
+        fs::write(&path, "nd start { text: 42 }").unwrap();
+
+        assert!(format_files(&[&path_str], false, CliLanguage::English).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nd start { text: 42 }");
+    }
+
+    #[test]
+    fn test_command_parsing_message_format() {
+        let matches = build_command(CliLanguage::English)
+            .try_get_matches_from(vec!["mortar", "test.mortar"])
+            .unwrap();
+        assert_eq!(
+            matches.get_one::<String>("message-format").unwrap(),
+            "human"
+        );
+
+        let matches = build_command(CliLanguage::English)
+            .try_get_matches_from(vec!["mortar", "test.mortar", "--message-format", "sarif"])
+            .unwrap();
+        assert_eq!(
+            matches.get_one::<String>("message-format").unwrap(),
+            "sarif"
+        );
+
+        let result = build_command(CliLanguage::English).try_get_matches_from(vec![
+            "mortar",
+            "test.mortar",
+            "--message-format",
+            "xml",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_command_parsing_emit() {
+        let matches = build_command(CliLanguage::English)
+            .try_get_matches_from(vec!["mortar", "test.mortar"])
+            .unwrap();
+        assert_eq!(matches.get_one::<String>("emit").unwrap(), "mortared");
+
+        let matches = build_command(CliLanguage::English)
+            .try_get_matches_from(vec!["mortar", "test.mortar", "--emit", "tokens"])
+            .unwrap();
+        assert_eq!(matches.get_one::<String>("emit").unwrap(), "tokens");
+
+        let result = build_command(CliLanguage::English).try_get_matches_from(vec![
+            "mortar",
+            "test.mortar",
+            "--emit",
+            "ir",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_write_output_uses_emit_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("test.mortar");
+        let input_str = input.to_str().unwrap();
+
+        let path = write_output(
+            None,
+            input_str,
+            "ast.json",
+            "{}".to_string(),
+            CliLanguage::English,
+        )
+        .unwrap();
+        assert_eq!(path, temp_dir.path().join("test.ast.json"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+
+        let same = input_str.to_string();
+        assert!(
+            write_output(
+                Some(&same),
+                input_str,
+                "tokens.json",
+                "[]".to_string(),
+                CliLanguage::English
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_command_parsing_deny_warnings() {
+        let cmd = build_command(CliLanguage::English);
+
+        let matches = cmd
+            .try_get_matches_from(vec!["mortar", "test.mortar", "--deny-warnings"])
+            .unwrap();
+        assert!(matches.get_flag("deny-warnings"));
+    }
+
     #[test]
     fn test_main_workflow_components() {
         // Test individual components that main() uses
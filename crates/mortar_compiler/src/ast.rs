@@ -22,12 +22,20 @@
 //!
 //! 包含所有语言构造的结构体和枚举定义，包括 `Program`、`NodeDef`、`Event` 和 `Statement`。
 
-#[derive(Debug, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+/// A byte range `(start, end)` in the source file.
+///
+/// Every node carries the span of the source text it was parsed from.
+pub type Span = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Program {
     pub body: Vec<TopLevel>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TopLevel {
     NodeDef(NodeDef),
     FunctionDecl(FunctionDecl),
@@ -38,19 +46,34 @@ pub enum TopLevel {
     TimelineDef(TimelineDef),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl TopLevel {
+    pub fn span(&self) -> Span {
+        match self {
+            TopLevel::NodeDef(node) => node.span,
+            TopLevel::FunctionDecl(function) => function.span,
+            TopLevel::VarDecl(var_decl) => var_decl.span,
+            TopLevel::ConstDecl(const_decl) => const_decl.span,
+            TopLevel::EnumDef(enum_def) => enum_def.span,
+            TopLevel::EventDef(event_def) => event_def.span,
+            TopLevel::TimelineDef(timeline) => timeline.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NodeDef {
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub body: Vec<NodeStmt>,
     pub jump: Option<NodeJump>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum NodeStmt {
-    Text(String),
-    InterpolatedText(InterpolatedString),
-    Choice(Vec<ChoiceItem>),
+    Text(String, Span),
+    InterpolatedText(InterpolatedString, Span),
+    Choice(Vec<ChoiceItem>, Span),
     Branch(BranchDef),
     IfElse(IfElseStmt),
     Run(RunStmt),
@@ -59,36 +82,70 @@ pub enum NodeStmt {
     Assignment(Assignment),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl NodeStmt {
+    /// The span of the whole statement, including its keyword.
+    pub fn span(&self) -> Span {
+        match self {
+            NodeStmt::Text(_, span)
+            | NodeStmt::InterpolatedText(_, span)
+            | NodeStmt::Choice(_, span) => *span,
+            NodeStmt::Branch(branch) => branch.span,
+            NodeStmt::IfElse(if_else) => if_else.span,
+            NodeStmt::Run(run) => run.span,
+            NodeStmt::WithEvents(with_events) => with_events.span,
+            NodeStmt::VarDecl(var_decl) => var_decl.span,
+            NodeStmt::Assignment(assignment) => assignment.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IfElseStmt {
     pub condition: IfCondition,
     pub then_body: Vec<NodeStmt>,
     pub else_body: Option<Vec<NodeStmt>>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IfCondition {
     Binary(Box<BinaryCondition>),
     Unary(Box<UnaryCondition>),
-    Identifier(String),
-    EnumMember(String, String), // EnumName.member
-    Literal(bool),
+    Identifier(String, Span),
+    EnumMember(String, String, Span), // EnumName.member
+    Literal(bool, Span),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl IfCondition {
+    pub fn span(&self) -> Span {
+        match self {
+            IfCondition::Binary(binary) => binary.span,
+            IfCondition::Unary(unary) => unary.span,
+            IfCondition::Identifier(_, span)
+            | IfCondition::EnumMember(_, _, span)
+            | IfCondition::Literal(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BinaryCondition {
     pub left: IfCondition,
     pub operator: ComparisonOp,
+    pub operator_span: Span,
     pub right: IfCondition,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UnaryCondition {
     pub operator: UnaryOp,
+    pub operator_span: Span,
     pub operand: IfCondition,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ComparisonOp {
     Greater,      // >
     Less,         // <
@@ -100,209 +157,333 @@ pub enum ComparisonOp {
     Or,           // ||
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UnaryOp {
     Not, // !
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InterpolatedString {
     pub parts: Vec<StringPart>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StringPart {
-    Text(String),
+    Text(String, Span),
     Expression(FuncCall),
-    Placeholder(String), // e.g., {place}, {object}
+    Placeholder(String, Span), // e.g., {place}, {object}
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl StringPart {
+    /// For expressions and placeholders, the span of the text between the braces.
+    pub fn span(&self) -> Span {
+        match self {
+            StringPart::Text(_, span) | StringPart::Placeholder(_, span) => *span,
+            StringPart::Expression(call) => call.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BranchDef {
     pub name: String, // e.g., "place", "object"
     pub name_span: Option<(usize, usize)>,
     pub enum_type: Option<String>, // Some("ExampleEnum") or None for bool branches
     pub cases: Vec<BranchCase>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BranchCase {
     pub condition: String, // e.g., "is_forest", "tree"
     pub text: String,
     pub events: Option<Vec<Event>>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum NodeJump {
-    Identifier(String, Option<(usize, usize)>),
-    Return,
-    Break,
+    Identifier(String, Span),
+    Return(Span),
+    Break(Span),
+}
+
+impl NodeJump {
+    /// The span of the jump target, without the `->`.
+    pub fn span(&self) -> Span {
+        match self {
+            NodeJump::Identifier(_, span) | NodeJump::Return(span) | NodeJump::Break(span) => *span,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Event {
     pub index: f64,
+    pub index_span: Span,
     pub action: EventAction,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EventAction {
     pub call: FuncCall,
     pub chains: Vec<FuncCall>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EventDef {
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub index: Option<f64>,
     pub action: EventAction,
     pub duration: Option<f64>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimelineDef {
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub body: Vec<TimelineStmt>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TimelineStmt {
     Run(RunStmt),
-    Wait(f64),
+    Wait(f64, Span),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl TimelineStmt {
+    pub fn span(&self) -> Span {
+        match self {
+            TimelineStmt::Run(run) => run.span,
+            TimelineStmt::Wait(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RunStmt {
     pub event_name: String,
     pub event_name_span: Option<(usize, usize)>,
     pub args: Vec<Arg>,
     pub index_override: Option<IndexOverride>,
     pub ignore_duration: bool,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IndexOverride {
-    Value(f64),
-    Variable(String),
+    Value(f64, Span),
+    Variable(String, Span),
+}
+
+impl IndexOverride {
+    pub fn span(&self) -> Span {
+        match self {
+            IndexOverride::Value(_, span) | IndexOverride::Variable(_, span) => *span,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WithEventsStmt {
     pub events: Vec<WithEventItem>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WithEventItem {
-    EventRef(String, Option<(usize, usize)>),
-    EventRefWithOverride(String, Option<(usize, usize)>, IndexOverride), // Event name, span, index override
+    EventRef(String, Span),
+    EventRefWithOverride(String, Span, IndexOverride), // Event name, span, index override
     InlineEvent(Event),
-    EventList(Vec<WithEventItem>),
+    EventList(Vec<WithEventItem>, Span),
+}
+
+impl WithEventItem {
+    pub fn span(&self) -> Span {
+        match self {
+            WithEventItem::EventRef(_, span) | WithEventItem::EventList(_, span) => *span,
+            WithEventItem::EventRefWithOverride(_, span, index_override) => {
+                (span.0, index_override.span().1)
+            }
+            WithEventItem::InlineEvent(event) => event.span,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChoiceItem {
     pub text: String,
     pub condition: Option<Condition>,
     pub target: ChoiceDest,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Condition {
-    Identifier(String),
+    Identifier(String, Span),
     FuncCall(FuncCall),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl Condition {
+    pub fn span(&self) -> Span {
+        match self {
+            Condition::Identifier(_, span) => *span,
+            Condition::FuncCall(call) => call.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ChoiceDest {
-    Identifier(String, Option<(usize, usize)>),
-    Return,
-    Break,
-    NestedChoices(Vec<ChoiceItem>),
+    Identifier(String, Span),
+    Return(Span),
+    Break(Span),
+    NestedChoices(Vec<ChoiceItem>, Span),
+}
+
+impl ChoiceDest {
+    pub fn span(&self) -> Span {
+        match self {
+            ChoiceDest::Identifier(_, span)
+            | ChoiceDest::Return(span)
+            | ChoiceDest::Break(span)
+            | ChoiceDest::NestedChoices(_, span) => *span,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FunctionDecl {
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub params: Vec<Param>,
     pub return_type: Option<String>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Param {
     pub name: String,
     pub type_name: String,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FuncCall {
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub args: Vec<Arg>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Arg {
-    String(String),
-    Number(f64),
-    Boolean(bool),
-    Identifier(String),
+    String(String, Span),
+    Number(f64, Span),
+    Boolean(bool, Span),
+    Identifier(String, Span),
     FuncCall(Box<FuncCall>),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl Arg {
+    pub fn span(&self) -> Span {
+        match self {
+            Arg::String(_, span)
+            | Arg::Number(_, span)
+            | Arg::Boolean(_, span)
+            | Arg::Identifier(_, span) => *span,
+            Arg::FuncCall(call) => call.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VarDecl {
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub type_name: String,
     pub value: Option<VarValue>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConstDecl {
     pub is_public: bool,
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub type_name: String,
     pub value: VarValue,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumDef {
     pub name: String,
     pub name_span: Option<(usize, usize)>,
     pub variants: Vec<String>,
+    pub variant_spans: Vec<Span>, // Same order as `variants`
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum VarValue {
-    String(String),
-    Number(f64),
-    Boolean(bool),
+    String(String, Span),
+    Number(f64, Span),
+    Boolean(bool, Span),
     Branch(BranchValue),
-    EnumMember(String, String), // EnumName.member
+    EnumMember(String, String, Span), // EnumName.member
+}
+
+impl VarValue {
+    pub fn span(&self) -> Span {
+        match self {
+            VarValue::String(_, span)
+            | VarValue::Number(_, span)
+            | VarValue::Boolean(_, span)
+            | VarValue::EnumMember(_, _, span) => *span,
+            VarValue::Branch(branch) => branch.span,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Assignment {
     pub var_name: String,
     pub var_name_span: Option<(usize, usize)>,
     pub value: AssignValue,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AssignValue {
-    EnumMember(String, String), // EnumName.member
-    Identifier(String),
-    Number(f64),
-    Boolean(bool),
-    String(String),
-}
-
-#[derive(Debug, Clone, PartialEq)]
+    EnumMember(String, String, Span), // EnumName.member
+    Identifier(String, Span),
+    Number(f64, Span),
+    Boolean(bool, Span),
+    String(String, Span),
+}
+
+impl AssignValue {
+    pub fn span(&self) -> Span {
+        match self {
+            AssignValue::EnumMember(_, _, span)
+            | AssignValue::Identifier(_, span)
+            | AssignValue::Number(_, span)
+            | AssignValue::Boolean(_, span)
+            | AssignValue::String(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BranchValue {
     pub enum_type: Option<String>, // Some("EnumType") for enum-based, None for bool-based
     pub cases: Vec<BranchCase>,
+    pub span: Span,
 }
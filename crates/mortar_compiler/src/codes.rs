@@ -0,0 +1,994 @@
+//! # codes.rs
+//!
+//! # codes.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Stable diagnostic codes and their long-form explanations.
+//!
+//! 稳定的诊断代码及其详细说明。
+//!
+//! Every diagnostic carries a code such as `M0101` that never changes between releases or languages, so logs and bug reports can be searched reliably.
+//!
+//! 每条诊断都带有一个类似 `M0101` 的代码，它在不同版本和语言之间保持不变，因此可以可靠地搜索日志和问题报告。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! This file lists every code together with its English and Chinese explanation, used by `mortar explain`.
+//!
+//! 此文件列出了所有代码及其中英文说明，供 `mortar explain` 使用。
+//!
+//! Codes are grouped by range: `M00xx` syntax errors, `M01xx` semantic errors, `M02xx` duplicate definitions, `M03xx` lints.
+//!
+//! 代码按区间分组：`M00xx` 语法错误，`M01xx` 语义错误，`M02xx` 重复定义，`M03xx` lint。
+
+use crate::Language;
+
+pub const UNEXPECTED_TOKEN: &str = "M0001";
+pub const EXPECTED_IDENTIFIER: &str = "M0002";
+pub const EXPECTED_STRING: &str = "M0003";
+pub const UNEXPECTED_EOF: &str = "M0004";
+pub const INVALID_NUMBER: &str = "M0005";
+pub const SYNTAX_ERROR: &str = "M0006";
+pub const INVALID_ESCAPE: &str = "M0007";
+
+pub const NODE_NOT_FOUND: &str = "M0101";
+pub const FUNCTION_NOT_FOUND: &str = "M0102";
+pub const TYPE_ERROR: &str = "M0103";
+pub const ARGUMENT_COUNT_MISMATCH: &str = "M0104";
+pub const ARGUMENT_TYPE_MISMATCH: &str = "M0105";
+pub const CONDITION_TYPE_MISMATCH: &str = "M0106";
+
+pub const DUPLICATE_NODE: &str = "M0201";
+pub const DUPLICATE_FUNCTION: &str = "M0202";
+pub const DUPLICATE_VARIABLE: &str = "M0203";
+pub const DUPLICATE_CONSTANT: &str = "M0204";
+pub const DUPLICATE_ENUM: &str = "M0205";
+pub const DUPLICATE_ENUM_VARIANT: &str = "M0206";
+pub const DUPLICATE_EVENT: &str = "M0207";
+pub const DUPLICATE_TIMELINE: &str = "M0208";
+
+pub const NON_SNAKE_CASE_FUNCTION: &str = "M0301";
+pub const NON_PASCAL_CASE_NODE: &str = "M0302";
+pub const UNUSED_FUNCTION: &str = "M0303";
+pub const UNUSED_VARIABLE: &str = "M0304";
+pub const UNUSED_CONSTANT: &str = "M0305";
+pub const UNUSED_ENUM: &str = "M0306";
+pub const UNUSED_EVENT: &str = "M0307";
+pub const UNUSED_TIMELINE: &str = "M0308";
+pub const EVENT_INDEX_OUT_OF_BOUNDS: &str = "M0309";
+pub const EVENT_INDEX_OUT_OF_ORDER: &str = "M0310";
+
+/// Every code, paired with its explanation in English and Chinese.
+const EXPLANATIONS: &[(&str, &str, &str)] = &[
+    (
+        UNEXPECTED_TOKEN,
+        r#"The parser found a token it did not expect at this position.
+
+Erroneous example:
+
+    node Start {
+        text: "Hello" "World"
+    }
+
+Two strings cannot follow each other directly. Put each line of text in its own
+`text:` statement:
+
+    node Start {
+        text: "Hello"
+        text: "World"
+    }
+"#,
+        r#"解析器在此位置遇到了一个意料之外的记号。
+
+错误示例：
+
+    node Start {
+        text: "Hello" "World"
+    }
+
+两个字符串不能直接相连。请把每一行文本放在单独的 `text:` 语句中：
+
+    node Start {
+        text: "Hello"
+        text: "World"
+    }
+"#,
+    ),
+    (
+        EXPECTED_IDENTIFIER,
+        r#"A name was expected, but something else was found.
+
+Erroneous example:
+
+    let "score": Number = 0
+
+Node, function, variable, enum, event and timeline names are identifiers and
+must not be quoted:
+
+    let score: Number = 0
+"#,
+        r#"此处需要一个名称，但找到了其他内容。
+
+错误示例：
+
+    let "score": Number = 0
+
+节点、函数、变量、枚举、事件和时间轴的名称都是标识符，不能加引号：
+
+    let score: Number = 0
+"#,
+    ),
+    (
+        EXPECTED_STRING,
+        r#"A string literal was expected, but something else was found.
+
+Erroneous example:
+
+    node Start {
+        text: Hello
+    }
+
+Text and choice labels must be quoted:
+
+    node Start {
+        text: "Hello"
+    }
+"#,
+        r#"此处需要一个字符串字面量，但找到了其他内容。
+
+错误示例：
+
+    node Start {
+        text: Hello
+    }
+
+文本和选项标签必须加引号：
+
+    node Start {
+        text: "Hello"
+    }
+"#,
+    ),
+    (
+        UNEXPECTED_EOF,
+        r#"The file ended while a construct was still open.
+
+Erroneous example:
+
+    node Start {
+        text: "Hello"
+
+This usually means a closing `}`, `]` or `)` is missing. The message lists what
+the parser was still waiting for. Close every block:
+
+    node Start {
+        text: "Hello"
+    }
+"#,
+        r#"文件在某个结构尚未结束时就结束了。
+
+错误示例：
+
+    node Start {
+        text: "Hello"
+
+这通常意味着缺少了右括号 `}`、`]` 或 `)`。消息中会列出解析器仍在等待的内容。
+请闭合每一个代码块：
+
+    node Start {
+        text: "Hello"
+    }
+"#,
+    ),
+    (
+        INVALID_NUMBER,
+        r#"A number literal could not be read.
+
+Erroneous example:
+
+    fn format_score(value: Number) -> String
+
+    node Start {
+        text: $"Score: {format_score(1.2.3)}"
+    }
+
+Numbers must be plain integers or decimals:
+
+    node Start {
+        text: $"Score: {format_score(1.5)}"
+    }
+"#,
+        r#"无法读取一个数字字面量。
+
+错误示例：
+
+    fn format_score(value: Number) -> String
+
+    node Start {
+        text: $"Score: {format_score(1.2.3)}"
+    }
+
+数字必须是普通的整数或小数：
+
+    node Start {
+        text: $"Score: {format_score(1.5)}"
+    }
+"#,
+    ),
+    (
+        SYNTAX_ERROR,
+        r#"The source could not be parsed.
+
+This is a general syntax error for constructs that have no more specific code.
+The message describes what the parser was trying to read. Compare the code with
+the examples in the Mortar documentation, and check for missing commas,
+brackets or keywords.
+"#,
+        r#"源代码无法被解析。
+
+这是一个通用的语法错误，用于没有更具体代码的情况。错误信息描述了解析器当时正在读取的内容。
+请对照 Mortar 文档中的示例检查代码，留意是否缺少逗号、括号或关键字。
+"#,
+    ),
+    (
+        INVALID_ESCAPE,
+        r#"A string contains a backslash escape that Mortar does not know.
+
+Erroneous example:
+
+    node Start {
+        text: "C:\games\save"
+    }
+
+`\g` and `\s` are not escape sequences. The supported ones are `\n`, `\r`,
+`\t`, `\0`, `\\`, `\"`, `\'`, `\{`, `\}` and `\u{...}` with one to six hex
+digits. Double the backslash, or use a raw string, which keeps every backslash:
+
+    node Start {
+        text: r"C:\games\save"
+    }
+"#,
+        r#"字符串中包含 Mortar 无法识别的反斜杠转义。
+
+错误示例：
+
+    node Start {
+        text: "C:\games\save"
+    }
+
+`\g` 和 `\s` 不是转义序列。支持的转义有 `\n`、`\r`、`\t`、`\0`、`\\`、`\"`、
+`\'`、`\{`、`\}` 以及带一到六位十六进制数字的 `\u{...}`。请将反斜杠写两次，
+或者使用会保留所有反斜杠的原始字符串：
+
+    node Start {
+        text: r"C:\games\save"
+    }
+"#,
+    ),
+    (
+        NODE_NOT_FOUND,
+        r#"A jump or choice refers to a node that does not exist.
+
+Erroneous example:
+
+    node Start {
+        text: "Hello"
+    } -> Ending
+
+    node End {
+        text: "Bye"
+    }
+
+Make sure the target node is defined and spelled exactly the same way:
+
+    node Start {
+        text: "Hello"
+    } -> End
+"#,
+        r#"跳转或选项引用了一个不存在的节点。
+
+错误示例：
+
+    node Start {
+        text: "Hello"
+    } -> Ending
+
+    node End {
+        text: "Bye"
+    }
+
+请确认目标节点已定义，并且拼写完全一致：
+
+    node Start {
+        text: "Hello"
+    } -> End
+"#,
+    ),
+    (
+        FUNCTION_NOT_FOUND,
+        r#"A function is called but never declared.
+
+Erroneous example:
+
+    node Start {
+        text: "Hello"
+        with events: [
+            0, play_sound("ding.wav")
+        ]
+    }
+
+Every function used in a script must be declared with `fn`, so the game knows
+which functions it has to provide:
+
+    fn play_sound(file: String)
+"#,
+        r#"调用了一个从未声明的函数。
+
+错误示例：
+
+    node Start {
+        text: "Hello"
+        with events: [
+            0, play_sound("ding.wav")
+        ]
+    }
+
+脚本中使用的每个函数都必须用 `fn` 声明，这样游戏才知道需要提供哪些函数：
+
+    fn play_sound(file: String)
+"#,
+    ),
+    (
+        TYPE_ERROR,
+        r#"A value has a type that cannot be used here.
+
+This is the general type error, used for type mismatches that have no more
+specific code such as M0105 or M0106. Make sure values match the declared
+type:
+
+    let score: Number = 100
+"#,
+        r#"某个值的类型不能在此处使用。
+
+这是通用的类型错误，用于没有更具体代码（例如 M0105 或 M0106）的类型不匹配。
+请确保值与声明的类型一致：
+
+    let score: Number = 100
+"#,
+    ),
+    (
+        ARGUMENT_COUNT_MISMATCH,
+        r#"A function is called with the wrong number of arguments.
+
+Erroneous example:
+
+    fn play_sound(file: String)
+
+    node Start {
+        text: "Hello"
+        with events: [
+            0, play_sound()
+        ]
+    }
+
+Pass exactly one argument for every declared parameter:
+
+    with events: [
+        0, play_sound("ding.wav")
+    ]
+"#,
+        r#"调用函数时传入的参数数量不正确。
+
+错误示例：
+
+    fn play_sound(file: String)
+
+    node Start {
+        text: "Hello"
+        with events: [
+            0, play_sound()
+        ]
+    }
+
+请为每个声明的参数恰好传入一个实参：
+
+    with events: [
+        0, play_sound("ding.wav")
+    ]
+"#,
+    ),
+    (
+        ARGUMENT_TYPE_MISMATCH,
+        r#"A function argument does not have the type of its parameter.
+
+Erroneous example:
+
+    fn set_speed(speed: Number)
+
+    node Start {
+        text: "Hello"
+        with events: [
+            0, set_speed("fast")
+        ]
+    }
+
+Pass a value of the declared type:
+
+    with events: [
+        0, set_speed(2)
+    ]
+"#,
+        r#"函数实参的类型与其参数类型不符。
+
+错误示例：
+
+    fn set_speed(speed: Number)
+
+    node Start {
+        text: "Hello"
+        with events: [
+            0, set_speed("fast")
+        ]
+    }
+
+请传入声明类型的值：
+
+    with events: [
+        0, set_speed(2)
+    ]
+"#,
+    ),
+    (
+        CONDITION_TYPE_MISMATCH,
+        r#"A condition does not evaluate to a boolean.
+
+Erroneous example:
+
+    fn get_name() -> String
+
+    node Start {
+        choice: [
+            "Continue" when get_name() -> Next
+        ]
+    }
+
+Conditions must be `Bool` values, such as a function returning `Bool`:
+
+    fn has_key() -> Bool
+
+    node Start {
+        choice: [
+            "Continue" when has_key() -> Next
+        ]
+    }
+"#,
+        r#"条件的结果不是布尔值。
+
+错误示例：
+
+    fn get_name() -> String
+
+    node Start {
+        choice: [
+            "Continue" when get_name() -> Next
+        ]
+    }
+
+条件必须是 `Bool` 值，例如返回 `Bool` 的函数：
+
+    fn has_key() -> Bool
+
+    node Start {
+        choice: [
+            "Continue" when has_key() -> Next
+        ]
+    }
+"#,
+    ),
+    (
+        DUPLICATE_NODE,
+        r#"Two nodes have the same name.
+
+Erroneous example:
+
+    node Start { text: "Hello" }
+    node Start { text: "Hello again" }
+
+Jumps refer to nodes by name, so every node name must be unique. Rename or
+merge one of the nodes.
+"#,
+        r#"两个节点同名。
+
+错误示例：
+
+    node Start { text: "Hello" }
+    node Start { text: "Hello again" }
+
+跳转通过名称引用节点，因此每个节点名称都必须唯一。请重命名或合并其中一个节点。
+"#,
+    ),
+    (
+        DUPLICATE_FUNCTION,
+        r#"A function is declared more than once.
+
+Erroneous example:
+
+    fn play_sound(file: String)
+    fn play_sound(file: String, volume: Number)
+
+Functions cannot be overloaded. Give the second declaration a different name.
+"#,
+        r#"同一个函数被声明了多次。
+
+错误示例：
+
+    fn play_sound(file: String)
+    fn play_sound(file: String, volume: Number)
+
+函数不能重载。请为第二个声明使用不同的名称。
+"#,
+    ),
+    (
+        DUPLICATE_VARIABLE,
+        r#"A variable is declared more than once.
+
+Erroneous example:
+
+    let score: Number = 0
+    let score: Number = 10
+
+Variables and constants share one namespace, so a variable also conflicts with
+a constant of the same name. Remove or rename one declaration.
+"#,
+        r#"同一个变量被声明了多次。
+
+错误示例：
+
+    let score: Number = 0
+    let score: Number = 10
+
+变量和常量共用同一个命名空间，因此变量也会与同名常量冲突。请删除或重命名其中一个声明。
+"#,
+    ),
+    (
+        DUPLICATE_CONSTANT,
+        r#"A constant is declared more than once.
+
+Erroneous example:
+
+    const greeting: String = "Hi"
+    const greeting: String = "Hello"
+
+Variables and constants share one namespace. Remove or rename one declaration.
+"#,
+        r#"同一个常量被声明了多次。
+
+错误示例：
+
+    const greeting: String = "Hi"
+    const greeting: String = "Hello"
+
+变量和常量共用同一个命名空间。请删除或重命名其中一个声明。
+"#,
+    ),
+    (
+        DUPLICATE_ENUM,
+        r#"An enum is declared more than once.
+
+Erroneous example:
+
+    enum Mood {
+        happy
+        sad
+    }
+    enum Mood {
+        angry
+    }
+
+Merge the variants into a single enum, or rename one of them.
+"#,
+        r#"同一个枚举被声明了多次。
+
+错误示例：
+
+    enum Mood {
+        happy
+        sad
+    }
+    enum Mood {
+        angry
+    }
+
+请把变体合并到一个枚举中，或重命名其中一个。
+"#,
+    ),
+    (
+        DUPLICATE_ENUM_VARIANT,
+        r#"An enum lists the same variant twice.
+
+Erroneous example:
+
+    enum Mood {
+        happy
+        sad
+        happy
+    }
+
+Remove the repeated variant:
+
+    enum Mood {
+        happy
+        sad
+    }
+"#,
+        r#"枚举中同一个变体出现了两次。
+
+错误示例：
+
+    enum Mood {
+        happy
+        sad
+        happy
+    }
+
+请删除重复的变体：
+
+    enum Mood {
+        happy
+        sad
+    }
+"#,
+    ),
+    (
+        DUPLICATE_EVENT,
+        r#"An event is defined more than once.
+
+Erroneous example:
+
+    event Flash { action: flash() }
+    event Flash { action: shake() }
+
+Each named event must be unique. Rename one of them.
+"#,
+        r#"同一个事件被定义了多次。
+
+错误示例：
+
+    event Flash { action: flash() }
+    event Flash { action: shake() }
+
+每个具名事件都必须唯一。请重命名其中一个。
+"#,
+    ),
+    (
+        DUPLICATE_TIMELINE,
+        r#"A timeline is defined more than once.
+
+Erroneous example:
+
+    timeline Intro { wait 1 }
+    timeline Intro { wait 2 }
+
+Each timeline must have a unique name. Rename one of them.
+"#,
+        r#"同一个时间轴被定义了多次。
+
+错误示例：
+
+    timeline Intro { wait 1 }
+    timeline Intro { wait 2 }
+
+每个时间轴都必须有唯一的名称。请重命名其中一个。
+"#,
+    ),
+    (
+        NON_SNAKE_CASE_FUNCTION,
+        r#"A function name is not written in snake_case.
+
+Example:
+
+    fn PlaySound(file: String)
+
+Function names are conventionally snake_case:
+
+    fn play_sound(file: String)
+
+This lint is named `non_snake_case_function` and can be configured in
+`mortar.toml` or silenced with `// mortar: allow(non_snake_case_function)`.
+"#,
+        r#"函数名没有使用 snake_case 命名。
+
+示例：
+
+    fn PlaySound(file: String)
+
+函数名按惯例使用 snake_case：
+
+    fn play_sound(file: String)
+
+此 lint 名为 `non_snake_case_function`，可以在 `mortar.toml` 中配置，
+或用 `// mortar: allow(non_snake_case_function)` 关闭。
+"#,
+    ),
+    (
+        NON_PASCAL_CASE_NODE,
+        r#"A node name is not written in PascalCase.
+
+Example:
+
+    node start_scene { text: "Hello" }
+
+Node names are conventionally PascalCase:
+
+    node StartScene { text: "Hello" }
+
+This lint is named `non_pascal_case_node` and can be configured in
+`mortar.toml` or silenced with `// mortar: allow(non_pascal_case_node)`.
+"#,
+        r#"节点名没有使用 PascalCase 命名。
+
+示例：
+
+    node start_scene { text: "Hello" }
+
+节点名按惯例使用 PascalCase：
+
+    node StartScene { text: "Hello" }
+
+此 lint 名为 `non_pascal_case_node`，可以在 `mortar.toml` 中配置，
+或用 `// mortar: allow(non_pascal_case_node)` 关闭。
+"#,
+    ),
+    (
+        UNUSED_FUNCTION,
+        r#"A function is declared but never called.
+
+Example:
+
+    fn play_sound(file: String)
+
+    node Start { text: "Hello" }
+
+Remove the declaration, or call the function from an event or condition.
+This lint is named `unused_function`.
+"#,
+        r#"声明了函数但从未调用。
+
+示例：
+
+    fn play_sound(file: String)
+
+    node Start { text: "Hello" }
+
+请删除该声明，或在事件或条件中调用该函数。此 lint 名为 `unused_function`。
+"#,
+    ),
+    (
+        UNUSED_VARIABLE,
+        r#"A variable is declared but never read or assigned.
+
+Example:
+
+    let score: Number = 0
+
+    node Start { text: "Hello" }
+
+Remove the variable, or use it in text, conditions or assignments.
+This lint is named `unused_variable`.
+"#,
+        r#"声明了变量但从未读取或赋值。
+
+示例：
+
+    let score: Number = 0
+
+    node Start { text: "Hello" }
+
+请删除该变量，或在文本、条件或赋值中使用它。此 lint 名为 `unused_variable`。
+"#,
+    ),
+    (
+        UNUSED_CONSTANT,
+        r#"A private constant is declared but never used.
+
+Example:
+
+    const greeting: String = "Hi"
+
+    node Start { text: "Hello" }
+
+Remove the constant or use it. Constants marked `pub` are exported to the game
+and are never reported. This lint is named `unused_constant`.
+"#,
+        r#"声明了私有常量但从未使用。
+
+示例：
+
+    const greeting: String = "Hi"
+
+    node Start { text: "Hello" }
+
+请删除该常量或使用它。标记为 `pub` 的常量会导出给游戏，因此不会被报告。
+此 lint 名为 `unused_constant`。
+"#,
+    ),
+    (
+        UNUSED_ENUM,
+        r#"An enum is declared but never used as a type or in a branch.
+
+Example:
+
+    enum Mood {
+        happy
+        sad
+    }
+
+    node Start { text: "Hello" }
+
+Remove the enum, or use it as a variable type. This lint is named `unused_enum`.
+"#,
+        r#"声明了枚举但从未作为类型或在分支中使用。
+
+示例：
+
+    enum Mood {
+        happy
+        sad
+    }
+
+    node Start { text: "Hello" }
+
+请删除该枚举，或将其用作变量类型。此 lint 名为 `unused_enum`。
+"#,
+    ),
+    (
+        UNUSED_EVENT,
+        r#"A named event is defined but never referenced.
+
+Example:
+
+    event Flash { action: flash() }
+
+    node Start { text: "Hello" }
+
+Remove the event, or attach it to text with `with Flash` or use it in a timeline.
+This lint is named `unused_event`.
+"#,
+        r#"定义了具名事件但从未引用。
+
+示例：
+
+    event Flash { action: flash() }
+
+    node Start { text: "Hello" }
+
+请删除该事件，或通过 `with Flash` 将其附加到文本上，或在时间轴中使用它。
+此 lint 名为 `unused_event`。
+"#,
+    ),
+    (
+        UNUSED_TIMELINE,
+        r#"A timeline is defined but never run.
+
+Example:
+
+    timeline Intro { wait 1 }
+
+    node Start { text: "Hello" }
+
+Remove the timeline, or start it with `run Intro`. This lint is named
+`unused_timeline`.
+"#,
+        r#"定义了时间轴但从未运行。
+
+示例：
+
+    timeline Intro { wait 1 }
+
+    node Start { text: "Hello" }
+
+请删除该时间轴，或用 `run Intro` 启动它。此 lint 名为 `unused_timeline`。
+"#,
+    ),
+    (
+        EVENT_INDEX_OUT_OF_BOUNDS,
+        r#"An event index points past the end of its text.
+
+Example:
+
+    node Start {
+        text: "Hi"
+        with events: [
+            5, flash()
+        ]
+    }
+
+The text has only 2 characters, so the event would never fire while the text
+is being typed. Use an index within the text length. Whether characters or
+grapheme clusters are counted is set by `text_length_unit` in `mortar.toml`.
+This lint is named `event_index_out_of_bounds`.
+"#,
+        r#"事件索引超出了文本末尾。
+
+示例：
+
+    node Start {
+        text: "Hi"
+        with events: [
+            5, flash()
+        ]
+    }
+
+该文本只有 2 个字符，因此在逐字显示文本时这个事件永远不会触发。请使用文本长度以内的索引。
+按字符还是按字素簇计数由 `mortar.toml` 中的 `text_length_unit` 设置。
+此 lint 名为 `event_index_out_of_bounds`。
+"#,
+    ),
+    (
+        EVENT_INDEX_OUT_OF_ORDER,
+        r#"Event indices are not in ascending order.
+
+Example:
+
+    node Start {
+        text: "Hello"
+        with events: [
+            3, flash()
+            1, shake()
+        ]
+    }
+
+Events fire as the text is typed, so listing them in index order keeps the
+script readable:
+
+    with events: [
+        1, shake()
+        3, flash()
+    ]
+
+This lint is named `event_index_out_of_order`.
+"#,
+        r#"事件索引没有按升序排列。
+
+示例：
+
+    node Start {
+        text: "Hello"
+        with events: [
+            3, flash()
+            1, shake()
+        ]
+    }
+
+事件会随着文本的显示依次触发，按索引顺序排列可以让脚本更易读：
+
+    with events: [
+        1, shake()
+        3, flash()
+    ]
+
+此 lint 名为 `event_index_out_of_order`。
+"#,
+    ),
+];
+
+/// All stable diagnostic codes, in ascending order.
+pub fn all_codes() -> impl Iterator<Item = &'static str> {
+    EXPLANATIONS.iter().map(|(code, _, _)| *code)
+}
+
+/// The long-form explanation of a code. Codes are matched case-insensitively.
+pub fn explain(code: &str, language: Language) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(known, _, _)| known.eq_ignore_ascii_case(code.trim()))
+        .map(|(_, english, chinese)| match language {
+            Language::English => *english,
+            Language::Chinese => *chinese,
+        })
+}
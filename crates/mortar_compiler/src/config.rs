@@ -0,0 +1,142 @@
+//! # config.rs
+//!
+//! # config.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Loads the optional `mortar.toml` project configuration file.
+//!
+//! 加载可选的 `mortar.toml` 项目配置文件。
+//!
+//! The file is looked up from the directory of the compiled script upwards, so one file can configure a whole project.
+//!
+//! 配置文件会从被编译脚本所在的目录开始向上查找，因此一个文件即可配置整个项目。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! This file defines `ProjectConfig`, its TOML schema and the `ConfigError` type.
+//!
+//! 此文件定义了 `ProjectConfig`、其 TOML 结构以及 `ConfigError` 类型。
+
+use crate::diagnostics::{EventIndexOptions, TextLengthUnit};
+use crate::lint::{LINT_NAMES, LintConfig, LintLevel};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name of the project configuration.
+pub const CONFIG_FILE_NAME: &str = "mortar.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    IoError(io::Error),
+    InvalidToml(String),
+    UnknownLint(String),
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::IoError(error)
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::IoError(err) => write!(f, "IO error: {}", err),
+            ConfigError::InvalidToml(err) => write!(f, "Invalid {}: {}", CONFIG_FILE_NAME, err),
+            ConfigError::UnknownLint(name) => write!(f, "Unknown lint '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Contents of `mortar.toml`.
+///
+/// ```toml
+/// [lints]
+/// unused_function = "allow"
+/// non_pascal_case_node = "deny"
+///
+/// [diagnostics]
+/// deny_warnings = false
+/// text_length_unit = "graphemes"
+/// min_interpolation_length = 1
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub lints: HashMap<String, LintLevel>,
+    pub diagnostics: DiagnosticsConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiagnosticsConfig {
+    pub deny_warnings: bool,
+    pub text_length_unit: TextLengthUnit,
+    pub min_interpolation_length: Option<usize>,
+}
+
+impl ProjectConfig {
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let config: ProjectConfig =
+            toml::from_str(content).map_err(|e| ConfigError::InvalidToml(e.to_string()))?;
+
+        if let Some(unknown) = config
+            .lints
+            .keys()
+            .find(|name| !LINT_NAMES.contains(&name.as_str()))
+        {
+            return Err(ConfigError::UnknownLint(unknown.clone()));
+        }
+
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Find the nearest `mortar.toml` in `start` or any of its ancestors.
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Load the configuration that applies to the given source file, if any.
+    pub fn discover(source_path: &Path) -> Result<Option<(PathBuf, Self)>, ConfigError> {
+        let dir = source_path.parent().unwrap_or(Path::new("."));
+        match Self::find(dir) {
+            Some(path) => Ok(Some((path.clone(), Self::load(&path)?))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn lint_config(&self) -> LintConfig {
+        LintConfig {
+            levels: self.lints.clone(),
+            deny_warnings: self.diagnostics.deny_warnings,
+        }
+    }
+
+    pub fn event_index_options(&self) -> EventIndexOptions {
+        let defaults = EventIndexOptions::default();
+        EventIndexOptions {
+            length_unit: self.diagnostics.text_length_unit,
+            min_interpolation_length: self
+                .diagnostics
+                .min_interpolation_length
+                .unwrap_or(defaults.min_interpolation_length),
+        }
+    }
+}
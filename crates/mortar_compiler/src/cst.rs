@@ -0,0 +1,924 @@
+//! # cst.rs
+//!
+//! # cst.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! A lossless concrete syntax tree (CST) for Mortar source files.
+//!
+//! Mortar 源文件的无损具体语法树（CST）。
+//!
+//! Unlike the AST, the CST keeps every byte of the source: whitespace and comments are stored as trivia on the neighbouring tokens, so printing the tree gives back the original text. Tools that rewrite files (formatters, refactorings, code actions) edit the CST and keep the authors' comments intact.
+//!
+//! 与 AST 不同，CST 保留源码的每一个字节：空白和注释作为琐碎内容（trivia）附着在相邻的记号上，因此打印这棵树就能得到原始文本。需要改写文件的工具（格式化、重构、代码操作）基于 CST 编辑，从而保留作者的注释。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! This file builds the CST from `tokenize` output. The tree groups tokens into top-level items and bracketed blocks, then the blocks of nodes into statements, and the lists of `choice` and `with events` statements into one node per entry. `Item`, `Statement`, `ChoiceEntry` and `EventEntry` are typed views over these nodes; items and statements link to the matching parts of a parsed `Program`.
+//!
+//! 此文件基于 `tokenize` 的输出构建 CST。树将记号分组为顶层条目和括号块，再把节点的块分组为语句，并把 `choice` 与 `with events` 语句中的列表按条目分组。`Item`、`Statement`、`ChoiceEntry` 和 `EventEntry` 是这些节点上的类型化视图；条目和语句可与解析得到的 `Program` 中对应的部分关联起来。
+
+use crate::ast::{NodeStmt, Program, Span, TopLevel};
+use crate::token::{Token, TokenInfo, tokenize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces and tabs.
+    Whitespace,
+    /// A single `\n` or `\r\n`.
+    Newline,
+    LineComment,
+    BlockComment,
+}
+
+/// Source text that carries no meaning for the parser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia<'a> {
+    pub kind: TriviaKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+impl Trivia<'_> {
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            self.kind,
+            TriviaKind::LineComment | TriviaKind::BlockComment
+        )
+    }
+}
+
+/// A significant token with the trivia around it.
+///
+/// Trailing trivia is everything up to (not including) the next newline; the rest belongs to the next token's leading trivia.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstToken<'a> {
+    pub token: Token<'a>,
+    pub text: &'a str,
+    pub span: Span,
+    pub leading: Vec<Trivia<'a>>,
+    pub trailing: Vec<Trivia<'a>>,
+}
+
+impl CstToken<'_> {
+    /// The span including leading and trailing trivia.
+    pub fn full_span(&self) -> Span {
+        let start = self.leading.first().map_or(self.span.0, |t| t.span.0);
+        let end = self.trailing.last().map_or(self.span.1, |t| t.span.1);
+        (start, end)
+    }
+
+    fn write_to(&self, out: &mut String) {
+        for trivia in &self.leading {
+            out.push_str(trivia.text);
+        }
+        out.push_str(self.text);
+        for trivia in &self.trailing {
+            out.push_str(trivia.text);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Node,
+    Function,
+    Variable,
+    Constant,
+    Enum,
+    Event,
+    Timeline,
+    /// Tokens that do not start a declaration, e.g. stray text before the first item.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    Text,
+    Choice,
+    Branch,
+    If,
+    Run,
+    WithEvents,
+    /// `let` inside a node, which the parser rejects.
+    Variable,
+    Assignment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    SourceFile,
+    Item(ItemKind),
+    /// A statement in the block of a node or of an `if`/`else`.
+    Statement(StatementKind),
+    /// One option in the list of a `choice` statement.
+    ChoiceEntry,
+    /// One event, event reference or nested list in the list of a `with` statement.
+    EventEntry,
+    /// `{ ... }`
+    Block,
+    /// `[ ... ]`
+    List,
+    /// `( ... )`
+    Group,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstElement<'a> {
+    Node(CstNode<'a>),
+    Token(CstToken<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstNode<'a> {
+    pub kind: SyntaxKind,
+    pub children: Vec<CstElement<'a>>,
+}
+
+impl<'a> CstNode<'a> {
+    fn new(kind: SyntaxKind) -> Self {
+        Self {
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    /// All tokens of this node in source order.
+    pub fn tokens(&self) -> Vec<&CstToken<'a>> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'s>(&'s self, tokens: &mut Vec<&'s CstToken<'a>>) {
+        for child in &self.children {
+            match child {
+                CstElement::Node(node) => node.collect_tokens(tokens),
+                CstElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    pub fn first_token(&self) -> Option<&CstToken<'a>> {
+        self.children.iter().find_map(|child| match child {
+            CstElement::Node(node) => node.first_token(),
+            CstElement::Token(token) => Some(token),
+        })
+    }
+
+    pub fn last_token(&self) -> Option<&CstToken<'a>> {
+        self.children.iter().rev().find_map(|child| match child {
+            CstElement::Node(node) => node.last_token(),
+            CstElement::Token(token) => Some(token),
+        })
+    }
+
+    /// The span from the first to the last token, without trivia.
+    pub fn span(&self) -> Span {
+        match (self.first_token(), self.last_token()) {
+            (Some(first), Some(last)) => (first.span.0, last.span.1),
+            _ => (0, 0),
+        }
+    }
+
+    /// The span including the leading trivia of the first token and the trailing trivia of the last.
+    pub fn full_span(&self) -> Span {
+        match (self.first_token(), self.last_token()) {
+            (Some(first), Some(last)) => (first.full_span().0, last.full_span().1),
+            _ => (0, 0),
+        }
+    }
+
+    fn write_to(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                CstElement::Node(node) => node.write_to(out),
+                CstElement::Token(token) => token.write_to(out),
+            }
+        }
+    }
+}
+
+impl fmt::Display for CstNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.write_to(&mut out);
+        f.write_str(&out)
+    }
+}
+
+/// The CST of a whole file.
+///
+/// `to_string()` always returns exactly the source the tree was built from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile<'a> {
+    pub root: CstNode<'a>,
+    /// Trivia after the last token, or the whole file if it has no tokens.
+    pub eof_trivia: Vec<Trivia<'a>>,
+}
+
+impl<'a> SourceFile<'a> {
+    pub fn parse(source: &'a str) -> Self {
+        let pieces = split_pieces(source, &tokenize(source));
+        let (tokens, eof_trivia) = attach_trivia(pieces);
+        let mut root = TreeBuilder::build(tokens);
+        for child in &mut root.children {
+            if let CstElement::Node(item) = child
+                && item.kind == SyntaxKind::Item(ItemKind::Node)
+            {
+                for block in child_nodes_mut(item, SyntaxKind::Block) {
+                    group_statements(block);
+                }
+            }
+        }
+        Self { root, eof_trivia }
+    }
+
+    /// The top-level items in source order.
+    pub fn items(&self) -> impl Iterator<Item = Item<'_, 'a>> {
+        self.root.children.iter().filter_map(|child| match child {
+            CstElement::Node(node) => Item::cast(node),
+            CstElement::Token(_) => None,
+        })
+    }
+
+    /// The item whose text contains `offset`.
+    pub fn item_at(&self, offset: usize) -> Option<Item<'_, 'a>> {
+        self.items().find(|item| {
+            let (start, end) = item.span();
+            start <= offset && offset <= end
+        })
+    }
+
+    /// The token at `offset`; a token ending exactly at `offset` counts when no token starts there.
+    pub fn token_at(&self, offset: usize) -> Option<&CstToken<'a>> {
+        let tokens = self.root.tokens();
+        tokens
+            .iter()
+            .find(|token| token.span.0 <= offset && offset < token.span.1)
+            .or_else(|| tokens.iter().find(|token| token.span.1 == offset))
+            .copied()
+    }
+}
+
+impl fmt::Display for SourceFile<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.root.write_to(&mut out);
+        for trivia in &self.eof_trivia {
+            out.push_str(trivia.text);
+        }
+        f.write_str(&out)
+    }
+}
+
+/// A typed view over a top-level item node.
+#[derive(Debug, Clone, Copy)]
+pub struct Item<'c, 'a> {
+    node: &'c CstNode<'a>,
+}
+
+impl<'c, 'a> Item<'c, 'a> {
+    pub fn cast(node: &'c CstNode<'a>) -> Option<Self> {
+        matches!(node.kind, SyntaxKind::Item(_)).then_some(Self { node })
+    }
+
+    pub fn syntax(&self) -> &'c CstNode<'a> {
+        self.node
+    }
+
+    pub fn kind(&self) -> ItemKind {
+        match self.node.kind {
+            SyntaxKind::Item(kind) => kind,
+            _ => ItemKind::Unknown,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    /// The declared name: the first identifier directly inside the item.
+    pub fn name(&self) -> Option<&'c CstToken<'a>> {
+        self.node.children.iter().find_map(|child| match child {
+            CstElement::Token(token) if matches!(token.token, Token::Identifier(_)) => Some(token),
+            _ => None,
+        })
+    }
+
+    /// The comments directly above the item, without a blank line in between.
+    pub fn leading_comments(&self) -> Vec<&'c Trivia<'a>> {
+        let Some(first) = self.node.first_token() else {
+            return Vec::new();
+        };
+
+        let mut comments = Vec::new();
+        let mut newlines = 0;
+        for trivia in first.leading.iter().rev() {
+            match trivia.kind {
+                TriviaKind::Whitespace => {}
+                TriviaKind::Newline => {
+                    newlines += 1;
+                    if newlines > 1 {
+                        break;
+                    }
+                }
+                TriviaKind::LineComment | TriviaKind::BlockComment => {
+                    newlines = 0;
+                    comments.push(trivia);
+                }
+            }
+        }
+        comments.reverse();
+        comments
+    }
+
+    /// The AST declaration parsed from this item, if the item parsed.
+    pub fn ast<'p>(&self, program: &'p Program) -> Option<&'p TopLevel> {
+        let start = self.span().0;
+        program.body.iter().find(|item| item.span().0 == start)
+    }
+
+    /// The statements in the body of a node; other items have none.
+    pub fn statements(&self) -> impl Iterator<Item = Statement<'c, 'a>> {
+        block_statements(self.node)
+    }
+}
+
+/// A typed view over a statement node.
+#[derive(Debug, Clone, Copy)]
+pub struct Statement<'c, 'a> {
+    node: &'c CstNode<'a>,
+}
+
+impl<'c, 'a> Statement<'c, 'a> {
+    pub fn cast(node: &'c CstNode<'a>) -> Option<Self> {
+        matches!(node.kind, SyntaxKind::Statement(_)).then_some(Self { node })
+    }
+
+    pub fn syntax(&self) -> &'c CstNode<'a> {
+        self.node
+    }
+
+    pub fn kind(&self) -> StatementKind {
+        match self.node.kind {
+            SyntaxKind::Statement(kind) => kind,
+            _ => unreachable!("cast only accepts statements"),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    /// The statements of the `then` and `else` blocks of an `if`, in source order.
+    pub fn statements(&self) -> impl Iterator<Item = Statement<'c, 'a>> {
+        block_statements(self.node)
+    }
+
+    /// The options of a `choice` statement.
+    pub fn choice_entries(&self) -> impl Iterator<Item = ChoiceEntry<'c, 'a>> {
+        list_entries(self.node).filter_map(ChoiceEntry::cast)
+    }
+
+    /// The entries of a `with` statement's list or inline event, or its single event
+    /// reference.
+    pub fn event_entries(&self) -> impl Iterator<Item = EventEntry<'c, 'a>> {
+        child_nodes_any(self.node)
+            .chain(list_entries(self.node))
+            .chain(child_nodes(self.node, SyntaxKind::Block).flat_map(child_nodes_any))
+            .filter_map(EventEntry::cast)
+    }
+
+    /// The AST statement parsed from this statement, searched for in every node body.
+    pub fn ast<'p>(&self, program: &'p Program) -> Option<&'p NodeStmt> {
+        fn find(body: &[NodeStmt], start: usize) -> Option<&NodeStmt> {
+            body.iter().find_map(|stmt| {
+                if stmt.span().0 == start {
+                    return Some(stmt);
+                }
+                match stmt {
+                    NodeStmt::IfElse(if_else) => find(&if_else.then_body, start).or_else(|| {
+                        if_else
+                            .else_body
+                            .as_deref()
+                            .and_then(|body| find(body, start))
+                    }),
+                    _ => None,
+                }
+            })
+        }
+
+        let start = self.span().0;
+        program.body.iter().find_map(|item| match item {
+            TopLevel::NodeDef(node) => find(&node.body, start),
+            _ => None,
+        })
+    }
+}
+
+/// A typed view over an option of a `choice` list.
+#[derive(Debug, Clone, Copy)]
+pub struct ChoiceEntry<'c, 'a> {
+    node: &'c CstNode<'a>,
+}
+
+impl<'c, 'a> ChoiceEntry<'c, 'a> {
+    pub fn cast(node: &'c CstNode<'a>) -> Option<Self> {
+        (node.kind == SyntaxKind::ChoiceEntry).then_some(Self { node })
+    }
+
+    pub fn syntax(&self) -> &'c CstNode<'a> {
+        self.node
+    }
+
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    /// The options of the nested list this option leads to, if any.
+    pub fn entries(&self) -> impl Iterator<Item = ChoiceEntry<'c, 'a>> {
+        list_entries(self.node).filter_map(ChoiceEntry::cast)
+    }
+}
+
+/// A typed view over an entry of a `with` list.
+#[derive(Debug, Clone, Copy)]
+pub struct EventEntry<'c, 'a> {
+    node: &'c CstNode<'a>,
+}
+
+impl<'c, 'a> EventEntry<'c, 'a> {
+    pub fn cast(node: &'c CstNode<'a>) -> Option<Self> {
+        (node.kind == SyntaxKind::EventEntry).then_some(Self { node })
+    }
+
+    pub fn syntax(&self) -> &'c CstNode<'a> {
+        self.node
+    }
+
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    /// The entries of a nested list, if this entry is one.
+    pub fn entries(&self) -> impl Iterator<Item = EventEntry<'c, 'a>> {
+        list_entries(self.node).filter_map(EventEntry::cast)
+    }
+}
+
+/// The statements inside the blocks directly below `node`.
+fn block_statements<'c, 'a>(node: &'c CstNode<'a>) -> impl Iterator<Item = Statement<'c, 'a>> {
+    child_nodes(node, SyntaxKind::Block)
+        .flat_map(|block| child_nodes_any(block))
+        .filter_map(Statement::cast)
+}
+
+/// The entry nodes inside the lists directly below `node`.
+fn list_entries<'c, 'a>(node: &'c CstNode<'a>) -> impl Iterator<Item = &'c CstNode<'a>> {
+    child_nodes(node, SyntaxKind::List).flat_map(|list| child_nodes_any(list))
+}
+
+fn child_nodes_any<'c, 'a>(node: &'c CstNode<'a>) -> impl Iterator<Item = &'c CstNode<'a>> {
+    node.children.iter().filter_map(|child| match child {
+        CstElement::Node(node) => Some(node),
+        CstElement::Token(_) => None,
+    })
+}
+
+fn child_nodes<'c, 'a>(
+    node: &'c CstNode<'a>,
+    kind: SyntaxKind,
+) -> impl Iterator<Item = &'c CstNode<'a>> {
+    child_nodes_any(node).filter(move |child| child.kind == kind)
+}
+
+fn child_nodes_mut<'c, 'a>(
+    node: &'c mut CstNode<'a>,
+    kind: SyntaxKind,
+) -> impl Iterator<Item = &'c mut CstNode<'a>> {
+    node.children
+        .iter_mut()
+        .filter_map(move |child| match child {
+            CstElement::Node(node) if node.kind == kind => Some(node),
+            _ => None,
+        })
+}
+
+fn token_of<'e, 'a>(element: Option<&'e CstElement<'a>>) -> Option<&'e Token<'a>> {
+    match element {
+        Some(CstElement::Token(token)) => Some(&token.token),
+        _ => None,
+    }
+}
+
+/// Wrap runs of the children between the brackets of `node` into nodes, or of the
+/// children after the keyword of a statement.
+///
+/// `start` is given the children, an index, and the kind and children of the run that
+/// index would join; it returns the kind of node a new run starting there gets. Children
+/// before the first run, such as stray separators, stay where they are.
+fn group_children<'a>(
+    node: &mut CstNode<'a>,
+    start: impl Fn(
+        &[CstElement<'a>],
+        usize,
+        Option<(SyntaxKind, &[CstElement<'a>])>,
+    ) -> Option<SyntaxKind>,
+) {
+    let children = &node.children;
+    let has_closer = children.len() > 1
+        && matches!(
+            token_of(children.last()),
+            Some(Token::RightBrace | Token::RightBracket | Token::RightParen)
+        );
+    let inner_end = children.len() - usize::from(has_closer);
+
+    // Where each run starts, skipping the opening bracket or keyword
+    let mut runs: Vec<(usize, SyntaxKind)> = Vec::new();
+    for index in 1..inner_end {
+        let current = runs
+            .last()
+            .map(|&(run_start, kind)| (kind, &children[run_start..index]));
+        if let Some(kind) = start(children, index, current) {
+            runs.push((index, kind));
+        }
+    }
+    if runs.is_empty() {
+        return;
+    }
+
+    let mut elements = std::mem::take(&mut node.children).into_iter().enumerate();
+    let mut runs = runs.into_iter().peekable();
+    let mut current: Option<CstNode<'a>> = None;
+    for (index, element) in elements.by_ref() {
+        if index == inner_end {
+            node.children.extend(current.take().map(CstElement::Node));
+        }
+        if let Some((_, kind)) = runs.next_if(|&(run_start, _)| run_start == index) {
+            node.children
+                .extend(current.replace(CstNode::new(kind)).map(CstElement::Node));
+        }
+        match current.as_mut() {
+            Some(run) => run.children.push(element),
+            None => node.children.push(element),
+        }
+    }
+    node.children.extend(current.map(CstElement::Node));
+}
+
+/// Group a block into statements, then the blocks and lists inside them.
+fn group_statements(block: &mut CstNode) {
+    group_children(block, |children, index, run| {
+        let next = |by: usize| token_of(children.get(index + by));
+        let kind = match token_of(children.get(index))? {
+            Token::Text => StatementKind::Text,
+            Token::Choice => StatementKind::Choice,
+            Token::If => StatementKind::If,
+            // `with run Event` runs the event inside a `with` statement
+            Token::Run if index > 0 && token_of(children.get(index - 1)) == Some(&Token::With) => {
+                return None;
+            }
+            Token::Run => StatementKind::Run,
+            Token::Let => StatementKind::Variable,
+            // `run Event with index` overrides the index of the event it runs
+            Token::With
+                if run.is_some_and(|(_, elements)| {
+                    elements
+                        .iter()
+                        .rposition(|element| token_of(Some(element)) == Some(&Token::Run))
+                        .is_some_and(|run| {
+                            !elements[run..]
+                                .iter()
+                                .any(|element| token_of(Some(element)) == Some(&Token::With))
+                        })
+                }) =>
+            {
+                return None;
+            }
+            Token::With => StatementKind::WithEvents,
+            Token::Identifier(_) => match (next(1), next(2)) {
+                (Some(Token::Equals), _) => StatementKind::Assignment,
+                (Some(Token::Colon), Some(Token::Branch)) => StatementKind::Branch,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(SyntaxKind::Statement(kind))
+    });
+
+    for element in &mut block.children {
+        let CstElement::Node(statement) = element else {
+            continue;
+        };
+        match statement.kind {
+            SyntaxKind::Statement(StatementKind::If) => {
+                for block in child_nodes_mut(statement, SyntaxKind::Block) {
+                    group_statements(block);
+                }
+            }
+            SyntaxKind::Statement(StatementKind::Choice) => {
+                for list in child_nodes_mut(statement, SyntaxKind::List) {
+                    group_choice_entries(list);
+                }
+            }
+            // `with events: [...]`, `with event { ... }`, or a single reference as
+            // in `with Ding` and `with run Ding`
+            SyntaxKind::Statement(StatementKind::WithEvents) => {
+                let mut grouped = false;
+                for kind in [SyntaxKind::List, SyntaxKind::Block] {
+                    for list in child_nodes_mut(statement, kind) {
+                        group_event_entries(list);
+                        grouped = true;
+                    }
+                }
+                if !grouped {
+                    group_event_entries(statement);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Each option starts with its text, `"Go"` or `("Go")`; `.when(...)` and
+/// `when check()` continue it.
+fn group_choice_entries(list: &mut CstNode) {
+    group_children(list, |children, index, _| {
+        let starts = match &children[index] {
+            CstElement::Token(token) => matches!(token.token, Token::String(_)),
+            // Not the arguments of `.when(...)` or of a condition call
+            CstElement::Node(node) => {
+                let callee = token_of(children.get(index - 1));
+                let before = index
+                    .checked_sub(2)
+                    .and_then(|at| token_of(children.get(at)));
+                node.kind == SyntaxKind::Group
+                    && !(matches!(callee, Some(Token::When | Token::Identifier(_)))
+                        && matches!(before, Some(Token::Dot | Token::When)))
+            }
+        };
+        starts.then_some(SyntaxKind::ChoiceEntry)
+    });
+
+    for entry in child_nodes_mut(list, SyntaxKind::ChoiceEntry) {
+        for nested in child_nodes_mut(entry, SyntaxKind::List) {
+            group_choice_entries(nested);
+        }
+    }
+}
+
+/// Inline events start with their index and take the call after it; event references
+/// are names, optionally followed by `with` and an index.
+fn group_event_entries(list: &mut CstNode) {
+    group_children(list, |children, index, run| {
+        // `with` at index 0 is the statement's own keyword, any later one an override
+        let previous = token_of(children.get(index - 1));
+        let overrides = index > 1 && previous == Some(&Token::With);
+        let starts = match &children[index] {
+            CstElement::Token(token) => match token.token {
+                Token::Number(_) => !overrides,
+                Token::Identifier(_) => {
+                    let awaits_action = run.is_some_and(|(_, elements)| {
+                        matches!(token_of(elements.first()), Some(Token::Number(_)))
+                            && !elements.iter().any(|element| {
+                                matches!(token_of(Some(element)), Some(Token::Identifier(_)))
+                            })
+                    });
+                    previous != Some(&Token::Dot) && !overrides && !awaits_action
+                }
+                _ => false,
+            },
+            CstElement::Node(node) => node.kind == SyntaxKind::List,
+        };
+        starts.then_some(SyntaxKind::EventEntry)
+    });
+
+    for entry in child_nodes_mut(list, SyntaxKind::EventEntry) {
+        for nested in child_nodes_mut(entry, SyntaxKind::List) {
+            group_event_entries(nested);
+        }
+    }
+}
+
+enum Piece<'a> {
+    Trivia(Trivia<'a>),
+    Token(&'a str, Token<'a>, Span),
+}
+
+/// Turn the token stream into an unbroken sequence of pieces covering the whole source.
+fn split_pieces<'a>(source: &'a str, tokens: &[TokenInfo<'a>]) -> Vec<Piece<'a>> {
+    let mut pieces = Vec::new();
+    let mut pos = 0;
+
+    for info in tokens {
+        push_whitespace(source, pos, info.start, &mut pieces);
+        let span = (info.start, info.end);
+        pieces.push(match info.token {
+            Token::SingleLineComment(_) => Piece::Trivia(Trivia {
+                kind: TriviaKind::LineComment,
+                text: info.text,
+                span,
+            }),
+            Token::MultiLineComment(_) => Piece::Trivia(Trivia {
+                kind: TriviaKind::BlockComment,
+                text: info.text,
+                span,
+            }),
+            _ => Piece::Token(info.text, info.token.clone(), span),
+        });
+        pos = info.end;
+    }
+    push_whitespace(source, pos, source.len(), &mut pieces);
+
+    pieces
+}
+
+/// Split the gap between two tokens into whitespace runs and single newlines.
+fn push_whitespace<'a>(source: &'a str, start: usize, end: usize, pieces: &mut Vec<Piece<'a>>) {
+    let mut run_start = start;
+    let mut pos = start;
+    let bytes = source.as_bytes();
+
+    while pos < end {
+        let newline_len = match bytes[pos] {
+            b'\n' => 1,
+            b'\r' if bytes.get(pos + 1) == Some(&b'\n') => 2,
+            _ => 0,
+        };
+        if newline_len == 0 {
+            pos += 1;
+            continue;
+        }
+
+        if run_start < pos {
+            pieces.push(Piece::Trivia(Trivia {
+                kind: TriviaKind::Whitespace,
+                text: &source[run_start..pos],
+                span: (run_start, pos),
+            }));
+        }
+        pieces.push(Piece::Trivia(Trivia {
+            kind: TriviaKind::Newline,
+            text: &source[pos..pos + newline_len],
+            span: (pos, pos + newline_len),
+        }));
+        pos += newline_len;
+        run_start = pos;
+    }
+
+    if run_start < end {
+        pieces.push(Piece::Trivia(Trivia {
+            kind: TriviaKind::Whitespace,
+            text: &source[run_start..end],
+            span: (run_start, end),
+        }));
+    }
+}
+
+/// Attach every trivia piece to a token; returns the tokens and the trivia left at the end of the file.
+fn attach_trivia(pieces: Vec<Piece<'_>>) -> (Vec<CstToken<'_>>, Vec<Trivia<'_>>) {
+    let mut tokens: Vec<CstToken> = Vec::new();
+    let mut pending = Vec::new();
+    // Trivia goes to the previous token until the first newline after it
+    let mut on_token_line = false;
+
+    for piece in pieces {
+        match piece {
+            Piece::Token(text, token, span) => {
+                tokens.push(CstToken {
+                    token,
+                    text,
+                    span,
+                    leading: std::mem::take(&mut pending),
+                    trailing: Vec::new(),
+                });
+                on_token_line = true;
+            }
+            Piece::Trivia(trivia) => {
+                if trivia.kind == TriviaKind::Newline {
+                    on_token_line = false;
+                }
+                match tokens.last_mut() {
+                    Some(token) if on_token_line => token.trailing.push(trivia),
+                    _ => pending.push(trivia),
+                }
+            }
+        }
+    }
+
+    (tokens, pending)
+}
+
+struct TreeBuilder<'a> {
+    /// Open nodes; `stack[0]` is the source file.
+    stack: Vec<CstNode<'a>>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    fn build(tokens: Vec<CstToken<'a>>) -> CstNode<'a> {
+        let mut builder = Self {
+            stack: vec![CstNode::new(SyntaxKind::SourceFile)],
+        };
+
+        let mut tokens = tokens.into_iter().peekable();
+        let mut previous: Option<Token<'a>> = None;
+        while let Some(token) = tokens.next() {
+            let next = tokens.peek().map(|t| &t.token);
+            let starts_item = item_start(&token.token, previous.as_ref(), next)
+                // `let` is also a statement inside node bodies
+                .filter(|kind| *kind != ItemKind::Variable || builder.stack.len() <= 2);
+            if let Some(kind) = starts_item {
+                // Declarations never nest, so a missing closer ends at the next one
+                builder.close_to(1);
+                builder.stack.push(CstNode::new(SyntaxKind::Item(kind)));
+            } else if builder.stack.len() == 1 {
+                builder
+                    .stack
+                    .push(CstNode::new(SyntaxKind::Item(ItemKind::Unknown)));
+            }
+
+            previous = Some(token.token.clone());
+            builder.push_token(token);
+        }
+
+        builder.close_to(0);
+        builder
+            .stack
+            .pop()
+            .expect("the source file node is never closed")
+    }
+
+    fn push_token(&mut self, token: CstToken<'a>) {
+        match token.token {
+            Token::LeftBrace => self.open(SyntaxKind::Block, token),
+            Token::LeftBracket => self.open(SyntaxKind::List, token),
+            Token::LeftParen => self.open(SyntaxKind::Group, token),
+            Token::RightBrace => self.close(SyntaxKind::Block, token),
+            Token::RightBracket => self.close(SyntaxKind::List, token),
+            Token::RightParen => self.close(SyntaxKind::Group, token),
+            _ => self.push(CstElement::Token(token)),
+        }
+    }
+
+    fn push(&mut self, element: CstElement<'a>) {
+        self.stack
+            .last_mut()
+            .expect("the source file node is never closed")
+            .children
+            .push(element);
+    }
+
+    fn open(&mut self, kind: SyntaxKind, token: CstToken<'a>) {
+        let mut node = CstNode::new(kind);
+        node.children.push(CstElement::Token(token));
+        self.stack.push(node);
+    }
+
+    /// Close the innermost open node of `kind`; an unmatched closer stays a plain token.
+    fn close(&mut self, kind: SyntaxKind, token: CstToken<'a>) {
+        let matching = self
+            .stack
+            .iter()
+            .skip(2)
+            .rposition(|node| node.kind == kind)
+            .map(|index| index + 2);
+
+        match matching {
+            Some(depth) => {
+                self.close_to(depth + 1);
+                self.push(CstElement::Token(token));
+                self.close_to(depth);
+            }
+            None => self.push(CstElement::Token(token)),
+        }
+    }
+
+    /// Pop nodes until only `depth` remain, attaching each to its parent.
+    fn close_to(&mut self, depth: usize) {
+        while self.stack.len() > depth.max(1) {
+            let node = self.stack.pop().expect("checked above");
+            self.push(CstElement::Node(node));
+        }
+    }
+}
+
+/// The kind of declaration `token` starts, if any.
+fn item_start(token: &Token, previous: Option<&Token>, next: Option<&Token>) -> Option<ItemKind> {
+    match token {
+        Token::Node => Some(ItemKind::Node),
+        Token::Fn => Some(ItemKind::Function),
+        Token::Let => Some(ItemKind::Variable),
+        Token::Pub => Some(ItemKind::Constant),
+        Token::Const if previous != Some(&Token::Pub) => Some(ItemKind::Constant),
+        Token::Enum => Some(ItemKind::Enum),
+        Token::Timeline => Some(ItemKind::Timeline),
+        Token::Event if matches!(next, Some(Token::Identifier(_))) => Some(ItemKind::Event),
+        _ => None,
+    }
+}
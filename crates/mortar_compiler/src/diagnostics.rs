@@ -24,11 +24,18 @@
 
 use crate::Language;
 use crate::ast::{
-    Arg, ChoiceDest, ChoiceItem, Condition, EventAction, FuncCall, FunctionDecl,
-    InterpolatedString, NodeDef, NodeJump, NodeStmt, Program, StringPart, TimelineStmt, TopLevel,
+    Arg, AssignValue, BranchCase, BranchDef, BranchValue, ChoiceDest, ChoiceItem, Condition,
+    ConstDecl, EventAction, EventDef, FuncCall, FunctionDecl, IfCondition, IndexOverride,
+    InterpolatedString, NodeDef, NodeJump, NodeStmt, Param, Program, RunStmt, StringPart,
+    TimelineStmt, TopLevel, VarDecl, VarValue, WithEventItem,
 };
+use crate::codes;
+use crate::lint::{InlineLintAttribute, LintConfig, LintLevel};
+use crate::visit::{self, Visit};
 use owo_colors::OwoColorize;
+use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
 
 fn get_text(key: &str, language: Language) -> &'static str {
     match (key, language) {
@@ -61,6 +68,28 @@ fn get_text(key: &str, language: Language) -> &'static str {
         }
         ("function_declared_but_never_used", Language::Chinese) => "函数 '{}' 已声明但从未使用。",
 
+        // Unused symbol warnings
+        ("variable_declared_but_never_used", Language::English) => {
+            "Variable '{}' is declared but never used."
+        }
+        ("variable_declared_but_never_used", Language::Chinese) => "变量 '{}' 已声明但从未使用。",
+        ("constant_declared_but_never_used", Language::English) => {
+            "Constant '{}' is declared but never used."
+        }
+        ("constant_declared_but_never_used", Language::Chinese) => "常量 '{}' 已声明但从未使用。",
+        ("enum_defined_but_never_used", Language::English) => {
+            "Enum '{}' is defined but never used."
+        }
+        ("enum_defined_but_never_used", Language::Chinese) => "枚举 '{}' 已定义但从未使用。",
+        ("event_defined_but_never_run", Language::English) => {
+            "Event '{}' is defined but never run."
+        }
+        ("event_defined_but_never_run", Language::Chinese) => "事件 '{}' 已定义但从未运行。",
+        ("timeline_defined_but_never_run", Language::English) => {
+            "Timeline '{}' is defined but never run."
+        }
+        ("timeline_defined_but_never_run", Language::Chinese) => "时间线 '{}' 已定义但从未运行。",
+
         // Node not found errors
         ("node_not_defined", Language::English) => "Node '{}' is not defined.",
         ("node_not_defined", Language::Chinese) => "节点 '{}' 未定义",
@@ -93,6 +122,60 @@ fn get_text(key: &str, language: Language) -> &'static str {
             "条件函数 '{}' 必须返回布尔类型，但返回了 '{}'。"
         }
 
+        // Duplicate definition errors
+        ("node_defined_multiple_times", Language::English) => {
+            "Node '{}' is defined multiple times."
+        }
+        ("node_defined_multiple_times", Language::Chinese) => "节点 '{}' 被重复定义。",
+        ("function_declared_multiple_times", Language::English) => {
+            "Function '{}' is declared multiple times."
+        }
+        ("function_declared_multiple_times", Language::Chinese) => "函数 '{}' 被重复声明。",
+        ("variable_declared_multiple_times", Language::English) => {
+            "Variable '{}' is declared multiple times."
+        }
+        ("variable_declared_multiple_times", Language::Chinese) => "变量 '{}' 被重复声明。",
+        ("constant_declared_multiple_times", Language::English) => {
+            "Constant '{}' is declared multiple times."
+        }
+        ("constant_declared_multiple_times", Language::Chinese) => "常量 '{}' 被重复声明。",
+        ("enum_defined_multiple_times", Language::English) => {
+            "Enum '{}' is defined multiple times."
+        }
+        ("enum_defined_multiple_times", Language::Chinese) => "枚举 '{}' 被重复定义。",
+        ("enum_variant_defined_multiple_times", Language::English) => {
+            "Enum '{}' has variant '{}' defined multiple times."
+        }
+        ("enum_variant_defined_multiple_times", Language::Chinese) => {
+            "枚举 '{}' 的成员 '{}' 被重复定义。"
+        }
+        ("event_defined_multiple_times", Language::English) => {
+            "Event '{}' is defined multiple times."
+        }
+        ("event_defined_multiple_times", Language::Chinese) => "事件 '{}' 被重复定义。",
+        ("timeline_defined_multiple_times", Language::English) => {
+            "Timeline '{}' is defined multiple times."
+        }
+        ("timeline_defined_multiple_times", Language::Chinese) => "时间线 '{}' 被重复定义。",
+        ("first_defined_here", Language::English) => "first defined here",
+        ("first_defined_here", Language::Chinese) => "首次定义于此",
+        ("rename_to", Language::English) => "rename to '{}'",
+        ("rename_to", Language::Chinese) => "重命名为 '{}'",
+
+        // Event index warnings
+        ("event_index_out_of_bounds", Language::English) => {
+            "Event index {} is past the end of the preceding text ({} characters); the event will never fire."
+        }
+        ("event_index_out_of_bounds", Language::Chinese) => {
+            "事件索引 {} 超出了前一段文本的末尾（{} 个字符），该事件永远不会触发。"
+        }
+        ("event_index_out_of_order", Language::English) => {
+            "Event index {} is listed after index {}; events should be listed in ascending order."
+        }
+        ("event_index_out_of_order", Language::Chinese) => {
+            "事件索引 {} 排在索引 {} 之后，事件应按升序排列。"
+        }
+
         _ => "",
     }
 }
@@ -107,7 +190,7 @@ fn format_message(template: &str, args: &[&str]) -> String {
     result
 }
 
-fn get_line_col(source: &str, pos: usize) -> (usize, usize) {
+pub(crate) fn get_line_col(source: &str, pos: usize) -> (usize, usize) {
     let mut line = 1;
     let mut col = 1;
 
@@ -143,6 +226,8 @@ pub enum DiagnosticKind {
     },
     SyntaxError {
         message: String,
+        /// Code of the underlying parse error.
+        code: &'static str,
     },
     TypeError {
         message: String,
@@ -162,6 +247,39 @@ pub enum DiagnosticKind {
         expected: String,
         actual: String,
     },
+    DuplicateNode {
+        node_name: String,
+        first_span: Option<(usize, usize)>,
+    },
+    DuplicateFunction {
+        function_name: String,
+        first_span: Option<(usize, usize)>,
+    },
+    DuplicateVariable {
+        variable_name: String,
+        first_span: Option<(usize, usize)>,
+    },
+    DuplicateConstant {
+        constant_name: String,
+        first_span: Option<(usize, usize)>,
+    },
+    DuplicateEnum {
+        enum_name: String,
+        first_span: Option<(usize, usize)>,
+    },
+    DuplicateEnumVariant {
+        enum_name: String,
+        variant_name: String,
+        first_span: Option<(usize, usize)>,
+    },
+    DuplicateEvent {
+        event_name: String,
+        first_span: Option<(usize, usize)>,
+    },
+    DuplicateTimeline {
+        timeline_name: String,
+        first_span: Option<(usize, usize)>,
+    },
 
     // Warnings
     NonSnakeCaseFunction {
@@ -173,6 +291,123 @@ pub enum DiagnosticKind {
     UnusedFunction {
         function_name: String,
     },
+    UnusedVariable {
+        variable_name: String,
+    },
+    UnusedConstant {
+        constant_name: String,
+    },
+    UnusedEnum {
+        enum_name: String,
+    },
+    UnusedEvent {
+        event_name: String,
+    },
+    UnusedTimeline {
+        timeline_name: String,
+    },
+    EventIndexOutOfBounds {
+        index: f64,
+        text_length: usize,
+    },
+    EventIndexOutOfOrder {
+        index: f64,
+        previous_index: f64,
+    },
+}
+
+/// How the length of a `text:` line is measured when checking event indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextLengthUnit {
+    /// Unicode scalar values, i.e. Rust `char`s.
+    #[default]
+    Chars,
+    /// Extended grapheme clusters, i.e. user-perceived characters.
+    Graphemes,
+}
+
+/// Options for checking `with events` indices against the preceding text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventIndexOptions {
+    pub length_unit: TextLengthUnit,
+    /// Minimum number of characters each `{...}` in an interpolated string is assumed to expand to.
+    pub min_interpolation_length: usize,
+}
+
+impl Default for EventIndexOptions {
+    fn default() -> Self {
+        Self {
+            length_unit: TextLengthUnit::Chars,
+            min_interpolation_length: 1,
+        }
+    }
+}
+
+impl DiagnosticKind {
+    /// The stable lint name used by `mortar.toml` and inline `// mortar: allow(...)` comments.
+    /// Errors are not lints and return `None`.
+    pub fn lint_name(&self) -> Option<&'static str> {
+        match self {
+            DiagnosticKind::NonSnakeCaseFunction { .. } => Some("non_snake_case_function"),
+            DiagnosticKind::NonPascalCaseNode { .. } => Some("non_pascal_case_node"),
+            DiagnosticKind::UnusedFunction { .. } => Some("unused_function"),
+            DiagnosticKind::UnusedVariable { .. } => Some("unused_variable"),
+            DiagnosticKind::UnusedConstant { .. } => Some("unused_constant"),
+            DiagnosticKind::UnusedEnum { .. } => Some("unused_enum"),
+            DiagnosticKind::UnusedEvent { .. } => Some("unused_event"),
+            DiagnosticKind::UnusedTimeline { .. } => Some("unused_timeline"),
+            DiagnosticKind::EventIndexOutOfBounds { .. } => Some("event_index_out_of_bounds"),
+            DiagnosticKind::EventIndexOutOfOrder { .. } => Some("event_index_out_of_order"),
+            _ => None,
+        }
+    }
+
+    /// The stable code of this kind of diagnostic, e.g. `M0101`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DiagnosticKind::NodeNotFound { .. } => codes::NODE_NOT_FOUND,
+            DiagnosticKind::FunctionNotFound { .. } => codes::FUNCTION_NOT_FOUND,
+            DiagnosticKind::SyntaxError { code, .. } => code,
+            DiagnosticKind::TypeError { .. } => codes::TYPE_ERROR,
+            DiagnosticKind::ArgumentCountMismatch { .. } => codes::ARGUMENT_COUNT_MISMATCH,
+            DiagnosticKind::ArgumentTypeMismatch { .. } => codes::ARGUMENT_TYPE_MISMATCH,
+            DiagnosticKind::ConditionTypeMismatch { .. } => codes::CONDITION_TYPE_MISMATCH,
+            DiagnosticKind::DuplicateNode { .. } => codes::DUPLICATE_NODE,
+            DiagnosticKind::DuplicateFunction { .. } => codes::DUPLICATE_FUNCTION,
+            DiagnosticKind::DuplicateVariable { .. } => codes::DUPLICATE_VARIABLE,
+            DiagnosticKind::DuplicateConstant { .. } => codes::DUPLICATE_CONSTANT,
+            DiagnosticKind::DuplicateEnum { .. } => codes::DUPLICATE_ENUM,
+            DiagnosticKind::DuplicateEnumVariant { .. } => codes::DUPLICATE_ENUM_VARIANT,
+            DiagnosticKind::DuplicateEvent { .. } => codes::DUPLICATE_EVENT,
+            DiagnosticKind::DuplicateTimeline { .. } => codes::DUPLICATE_TIMELINE,
+            DiagnosticKind::NonSnakeCaseFunction { .. } => codes::NON_SNAKE_CASE_FUNCTION,
+            DiagnosticKind::NonPascalCaseNode { .. } => codes::NON_PASCAL_CASE_NODE,
+            DiagnosticKind::UnusedFunction { .. } => codes::UNUSED_FUNCTION,
+            DiagnosticKind::UnusedVariable { .. } => codes::UNUSED_VARIABLE,
+            DiagnosticKind::UnusedConstant { .. } => codes::UNUSED_CONSTANT,
+            DiagnosticKind::UnusedEnum { .. } => codes::UNUSED_ENUM,
+            DiagnosticKind::UnusedEvent { .. } => codes::UNUSED_EVENT,
+            DiagnosticKind::UnusedTimeline { .. } => codes::UNUSED_TIMELINE,
+            DiagnosticKind::EventIndexOutOfBounds { .. } => codes::EVENT_INDEX_OUT_OF_BOUNDS,
+            DiagnosticKind::EventIndexOutOfOrder { .. } => codes::EVENT_INDEX_OUT_OF_ORDER,
+        }
+    }
+
+    /// The span of the earlier definition, for diagnostics that point at two places.
+    pub fn first_span(&self) -> Option<(usize, usize)> {
+        match self {
+            DiagnosticKind::DuplicateNode { first_span, .. }
+            | DiagnosticKind::DuplicateFunction { first_span, .. }
+            | DiagnosticKind::DuplicateVariable { first_span, .. }
+            | DiagnosticKind::DuplicateConstant { first_span, .. }
+            | DiagnosticKind::DuplicateEnum { first_span, .. }
+            | DiagnosticKind::DuplicateEnumVariant { first_span, .. }
+            | DiagnosticKind::DuplicateEvent { first_span, .. }
+            | DiagnosticKind::DuplicateTimeline { first_span, .. } => *first_span,
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -183,19 +418,50 @@ pub struct Diagnostic {
     pub message: String,
 }
 
+/// A machine-applicable fix: replace the text at `span` with `replacement`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: (usize, usize),
+    pub replacement: String,
+}
+
+impl Diagnostic {
+    /// The stable code of this diagnostic, e.g. `M0101`.
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Fixes that can be applied automatically to resolve this diagnostic.
+    pub fn suggestions(&self, language: Language) -> Vec<Suggestion> {
+        let Some(span) = self.span else {
+            return Vec::new();
+        };
+
+        let replacement = match &self.kind {
+            DiagnosticKind::NonSnakeCaseFunction { function_name } => to_snake_case(function_name),
+            DiagnosticKind::NonPascalCaseNode { node_name } => to_pascal_case(node_name),
+            _ => return Vec::new(),
+        };
+
+        vec![Suggestion {
+            message: format_message(get_text("rename_to", language), &[&replacement]),
+            span,
+            replacement,
+        }]
+    }
+}
+
 pub struct DiagnosticCollector {
     diagnostics: Vec<Diagnostic>,
     file_name: String,
     language: Language,
+    event_index_options: EventIndexOptions,
 }
 
 impl DiagnosticCollector {
     pub fn new(file_name: String) -> Self {
-        Self {
-            diagnostics: Vec::new(),
-            file_name,
-            language: Language::English,
-        }
+        Self::new_with_language(file_name, Language::English)
     }
 
     pub fn new_with_language(file_name: String, language: Language) -> Self {
@@ -203,9 +469,22 @@ impl DiagnosticCollector {
             diagnostics: Vec::new(),
             file_name,
             language,
+            event_index_options: EventIndexOptions::default(),
         }
     }
 
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_event_index_options(&mut self, options: EventIndexOptions) {
+        self.event_index_options = options;
+    }
+
     pub fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
         self.diagnostics.push(diagnostic);
     }
@@ -250,8 +529,13 @@ impl DiagnosticCollector {
 
                 // Print colored error header
                 let header = format!(
-                    "{}: {}:{}:{}: {}",
-                    severity_str, self.file_name, line, col, diagnostic.message
+                    "{}[{}]: {}:{}:{}: {}",
+                    severity_str,
+                    diagnostic.code(),
+                    self.file_name,
+                    line,
+                    col,
+                    diagnostic.message
                 );
 
                 match diagnostic.severity {
@@ -295,10 +579,25 @@ impl DiagnosticCollector {
                     };
                     println!("{}", pointer);
                 }
+
+                if let Some((first_start, _)) = diagnostic.kind.first_span() {
+                    let (first_line, first_col) = get_line_col(source, first_start);
+                    println!(
+                        "    {} {}: {}:{}:{}",
+                        "=".bright_blue(),
+                        get_text("first_defined_here", self.language),
+                        self.file_name,
+                        first_line,
+                        first_col
+                    );
+                }
             } else {
                 let header = format!(
-                    "{}: {}: {}",
-                    severity_str, self.file_name, diagnostic.message
+                    "{}[{}]: {}: {}",
+                    severity_str,
+                    diagnostic.code(),
+                    self.file_name,
+                    diagnostic.message
                 );
                 match diagnostic.severity {
                     Severity::Error => println!("{}", header.red()),
@@ -309,11 +608,53 @@ impl DiagnosticCollector {
         }
     }
 
+    /// Apply project-wide lint levels and inline lint comments to the collected lints.
+    /// Allowed lints are removed and denied lints become errors.
+    pub fn apply_lint_levels(&mut self, config: &LintConfig, attributes: &[InlineLintAttribute]) {
+        self.diagnostics.retain_mut(|diagnostic| {
+            let Some(lint) = diagnostic.kind.lint_name() else {
+                return true;
+            };
+
+            // The innermost inline comment wins over outer ones and over the project config
+            let position = diagnostic.span.map(|(start, _)| start);
+            let level = position
+                .and_then(|position| {
+                    attributes
+                        .iter()
+                        .filter(|attribute| attribute.applies_to(lint, position))
+                        .min_by_key(|attribute| attribute.span.1 - attribute.span.0)
+                })
+                .map(|attribute| attribute.level)
+                .unwrap_or_else(|| config.level_of(lint));
+
+            match level {
+                LintLevel::Allow => false,
+                LintLevel::Warn => {
+                    diagnostic.severity = Severity::Warning;
+                    true
+                }
+                LintLevel::Deny => {
+                    diagnostic.severity = Severity::Error;
+                    true
+                }
+            }
+        });
+
+        if config.deny_warnings {
+            for diagnostic in &mut self.diagnostics {
+                diagnostic.severity = Severity::Error;
+            }
+        }
+    }
+
     pub fn get_diagnostics(&self) -> &Vec<Diagnostic> {
         &self.diagnostics
     }
 
     pub fn analyze_program(&mut self, program: &Program) {
+        self.check_duplicate_definitions(program);
+
         // Collect all function declarations and nodes
         let mut declared_functions = HashMap::new();
         let mut declared_nodes = HashMap::new();
@@ -339,7 +680,7 @@ impl DiagnosticCollector {
                         });
                     }
 
-                    declared_functions.insert(func.name.clone(), func);
+                    declared_functions.entry(func.name.clone()).or_insert(func);
                 }
                 TopLevel::NodeDef(node) => {
                     // Check node naming convention
@@ -357,7 +698,7 @@ impl DiagnosticCollector {
                         });
                     }
 
-                    declared_nodes.insert(node.name.clone(), node);
+                    declared_nodes.entry(node.name.clone()).or_insert(node);
                 }
                 TopLevel::VarDecl(_) | TopLevel::ConstDecl(_) | TopLevel::EnumDef(_) => {
                     // Variable, constant, and enum declarations don't need naming checks for now
@@ -427,6 +768,377 @@ impl DiagnosticCollector {
                 });
             }
         }
+
+        self.check_unused_symbols(program);
+        self.check_event_indices(program);
+    }
+
+    /// Warn about variables, constants, enums, events and timelines that nothing refers to.
+    /// Public constants are skipped because they are meant to be read by the host.
+    fn check_unused_symbols(&mut self, program: &Program) {
+        let mut usages = SymbolUsages::default();
+        usages.visit_program(program);
+
+        for item in &program.body {
+            let (kind, span, message_key, name) = match item {
+                TopLevel::VarDecl(var_decl) if !usages.values.contains(&var_decl.name) => (
+                    DiagnosticKind::UnusedVariable {
+                        variable_name: var_decl.name.clone(),
+                    },
+                    var_decl.name_span,
+                    "variable_declared_but_never_used",
+                    &var_decl.name,
+                ),
+                TopLevel::ConstDecl(const_decl)
+                    if !const_decl.is_public && !usages.values.contains(&const_decl.name) =>
+                {
+                    (
+                        DiagnosticKind::UnusedConstant {
+                            constant_name: const_decl.name.clone(),
+                        },
+                        const_decl.name_span,
+                        "constant_declared_but_never_used",
+                        &const_decl.name,
+                    )
+                }
+                TopLevel::EnumDef(enum_def) if !usages.types.contains(&enum_def.name) => (
+                    DiagnosticKind::UnusedEnum {
+                        enum_name: enum_def.name.clone(),
+                    },
+                    enum_def.name_span,
+                    "enum_defined_but_never_used",
+                    &enum_def.name,
+                ),
+                TopLevel::EventDef(event_def) if !usages.runs.contains(&event_def.name) => (
+                    DiagnosticKind::UnusedEvent {
+                        event_name: event_def.name.clone(),
+                    },
+                    event_def.name_span,
+                    "event_defined_but_never_run",
+                    &event_def.name,
+                ),
+                TopLevel::TimelineDef(timeline_def)
+                    if !usages.runs.contains(&timeline_def.name) =>
+                {
+                    (
+                        DiagnosticKind::UnusedTimeline {
+                            timeline_name: timeline_def.name.clone(),
+                        },
+                        timeline_def.name_span,
+                        "timeline_defined_but_never_run",
+                        &timeline_def.name,
+                    )
+                }
+                _ => continue,
+            };
+
+            self.add_diagnostic(Diagnostic {
+                kind,
+                severity: Severity::Warning,
+                span,
+                message: format_message(get_text(message_key, self.language), &[name]),
+            });
+        }
+    }
+
+    /// Report every top-level name (and enum variant) that is defined more than once.
+    fn check_duplicate_definitions(&mut self, program: &Program) {
+        let mut nodes = HashMap::new();
+        let mut functions = HashMap::new();
+        let mut values = HashMap::new();
+        let mut enums = HashMap::new();
+        let mut events = HashMap::new();
+        let mut timelines = HashMap::new();
+
+        for item in &program.body {
+            match item {
+                TopLevel::NodeDef(node) => {
+                    if let Some(&first_span) = nodes.get(&node.name) {
+                        self.report_duplicate(
+                            DiagnosticKind::DuplicateNode {
+                                node_name: node.name.clone(),
+                                first_span,
+                            },
+                            node.name_span,
+                            "node_defined_multiple_times",
+                            &[&node.name],
+                        );
+                    } else {
+                        nodes.insert(node.name.clone(), node.name_span);
+                    }
+                }
+                TopLevel::FunctionDecl(func) => {
+                    if let Some(&first_span) = functions.get(&func.name) {
+                        self.report_duplicate(
+                            DiagnosticKind::DuplicateFunction {
+                                function_name: func.name.clone(),
+                                first_span,
+                            },
+                            func.name_span,
+                            "function_declared_multiple_times",
+                            &[&func.name],
+                        );
+                    } else {
+                        functions.insert(func.name.clone(), func.name_span);
+                    }
+                }
+                TopLevel::VarDecl(var_decl) => {
+                    if let Some(&first_span) = values.get(&var_decl.name) {
+                        self.report_duplicate(
+                            DiagnosticKind::DuplicateVariable {
+                                variable_name: var_decl.name.clone(),
+                                first_span,
+                            },
+                            var_decl.name_span,
+                            "variable_declared_multiple_times",
+                            &[&var_decl.name],
+                        );
+                    } else {
+                        values.insert(var_decl.name.clone(), var_decl.name_span);
+                    }
+                }
+                TopLevel::ConstDecl(const_decl) => {
+                    if let Some(&first_span) = values.get(&const_decl.name) {
+                        self.report_duplicate(
+                            DiagnosticKind::DuplicateConstant {
+                                constant_name: const_decl.name.clone(),
+                                first_span,
+                            },
+                            const_decl.name_span,
+                            "constant_declared_multiple_times",
+                            &[&const_decl.name],
+                        );
+                    } else {
+                        values.insert(const_decl.name.clone(), const_decl.name_span);
+                    }
+                }
+                TopLevel::EnumDef(enum_def) => {
+                    if let Some(&first_span) = enums.get(&enum_def.name) {
+                        self.report_duplicate(
+                            DiagnosticKind::DuplicateEnum {
+                                enum_name: enum_def.name.clone(),
+                                first_span,
+                            },
+                            enum_def.name_span,
+                            "enum_defined_multiple_times",
+                            &[&enum_def.name],
+                        );
+                    } else {
+                        enums.insert(enum_def.name.clone(), enum_def.name_span);
+                    }
+
+                    let mut variants = HashMap::new();
+                    for (variant, span) in enum_def.variants.iter().zip(&enum_def.variant_spans) {
+                        if let Some(&first_span) = variants.get(variant) {
+                            self.report_duplicate(
+                                DiagnosticKind::DuplicateEnumVariant {
+                                    enum_name: enum_def.name.clone(),
+                                    variant_name: variant.clone(),
+                                    first_span: Some(first_span),
+                                },
+                                Some(*span),
+                                "enum_variant_defined_multiple_times",
+                                &[&enum_def.name, variant],
+                            );
+                        } else {
+                            variants.insert(variant, *span);
+                        }
+                    }
+                }
+                TopLevel::EventDef(event_def) => {
+                    if let Some(&first_span) = events.get(&event_def.name) {
+                        self.report_duplicate(
+                            DiagnosticKind::DuplicateEvent {
+                                event_name: event_def.name.clone(),
+                                first_span,
+                            },
+                            event_def.name_span,
+                            "event_defined_multiple_times",
+                            &[&event_def.name],
+                        );
+                    } else {
+                        events.insert(event_def.name.clone(), event_def.name_span);
+                    }
+                }
+                TopLevel::TimelineDef(timeline_def) => {
+                    if let Some(&first_span) = timelines.get(&timeline_def.name) {
+                        self.report_duplicate(
+                            DiagnosticKind::DuplicateTimeline {
+                                timeline_name: timeline_def.name.clone(),
+                                first_span,
+                            },
+                            timeline_def.name_span,
+                            "timeline_defined_multiple_times",
+                            &[&timeline_def.name],
+                        );
+                    } else {
+                        timelines.insert(timeline_def.name.clone(), timeline_def.name_span);
+                    }
+                }
+            }
+        }
+    }
+
+    fn report_duplicate(
+        &mut self,
+        kind: DiagnosticKind,
+        span: Option<(usize, usize)>,
+        message_key: &str,
+        args: &[&str],
+    ) {
+        self.add_diagnostic(Diagnostic {
+            kind,
+            severity: Severity::Error,
+            span,
+            message: format_message(get_text(message_key, self.language), args),
+        });
+    }
+
+    /// Check that event indices attached to text stay within the text and are listed in order.
+    fn check_event_indices(&mut self, program: &Program) {
+        let mut events = HashMap::new();
+        let mut constants = HashMap::new();
+
+        for item in &program.body {
+            match item {
+                TopLevel::EventDef(event_def) => {
+                    events.insert(event_def.name.as_str(), event_def);
+                }
+                TopLevel::ConstDecl(const_decl) => {
+                    if let VarValue::Number(value, _) = const_decl.value {
+                        constants.insert(const_decl.name.as_str(), value);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for item in &program.body {
+            match item {
+                TopLevel::NodeDef(node) => {
+                    self.check_body_event_indices(&node.body, &events, &constants);
+                }
+                TopLevel::VarDecl(var_decl) => {
+                    if let Some(VarValue::Branch(branch)) = &var_decl.value {
+                        self.check_branch_case_indices(&branch.cases);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_body_event_indices(
+        &mut self,
+        body: &[NodeStmt],
+        events: &HashMap<&str, &EventDef>,
+        constants: &HashMap<&str, f64>,
+    ) {
+        let mut previous_text_length = None;
+
+        for stmt in body {
+            match stmt {
+                NodeStmt::Text(text, _) => {
+                    previous_text_length = Some(self.text_length(text));
+                }
+                NodeStmt::InterpolatedText(interpolated, _) => {
+                    previous_text_length = Some(self.interpolated_text_length(interpolated));
+                }
+                NodeStmt::WithEvents(with_events) => {
+                    if let Some(text_length) = previous_text_length.take() {
+                        let mut indices = Vec::new();
+                        for item in &with_events.events {
+                            collect_with_event_indices(item, events, constants, &mut indices);
+                        }
+                        self.check_indices(&indices, text_length);
+                    }
+                }
+                NodeStmt::IfElse(if_else) => {
+                    previous_text_length = None;
+                    self.check_body_event_indices(&if_else.then_body, events, constants);
+                    if let Some(else_body) = &if_else.else_body {
+                        self.check_body_event_indices(else_body, events, constants);
+                    }
+                }
+                NodeStmt::Branch(branch) => {
+                    previous_text_length = None;
+                    self.check_branch_case_indices(&branch.cases);
+                }
+                _ => {
+                    previous_text_length = None;
+                }
+            }
+        }
+    }
+
+    fn check_branch_case_indices(&mut self, cases: &[BranchCase]) {
+        for case in cases {
+            if let Some(case_events) = &case.events {
+                let indices: Vec<_> = case_events
+                    .iter()
+                    .map(|event| (event.index, Some(event.index_span)))
+                    .collect();
+                let text_length = self.text_length(&case.text);
+                self.check_indices(&indices, text_length);
+            }
+        }
+    }
+
+    fn check_indices(&mut self, indices: &[(f64, Option<(usize, usize)>)], text_length: usize) {
+        let mut previous_index: Option<f64> = None;
+
+        for &(index, span) in indices {
+            if index > text_length as f64 {
+                self.add_diagnostic(Diagnostic {
+                    kind: DiagnosticKind::EventIndexOutOfBounds { index, text_length },
+                    severity: Severity::Warning,
+                    span,
+                    message: format_message(
+                        get_text("event_index_out_of_bounds", self.language),
+                        &[&index.to_string(), &text_length.to_string()],
+                    ),
+                });
+            }
+
+            if let Some(previous) = previous_index
+                && index < previous
+            {
+                self.add_diagnostic(Diagnostic {
+                    kind: DiagnosticKind::EventIndexOutOfOrder {
+                        index,
+                        previous_index: previous,
+                    },
+                    severity: Severity::Warning,
+                    span,
+                    message: format_message(
+                        get_text("event_index_out_of_order", self.language),
+                        &[&index.to_string(), &previous.to_string()],
+                    ),
+                });
+            }
+
+            previous_index = Some(previous_index.map_or(index, |previous| previous.max(index)));
+        }
+    }
+
+    fn text_length(&self, text: &str) -> usize {
+        match self.event_index_options.length_unit {
+            TextLengthUnit::Chars => text.chars().count(),
+            TextLengthUnit::Graphemes => text.graphemes(true).count(),
+        }
+    }
+
+    fn interpolated_text_length(&self, interpolated: &InterpolatedString) -> usize {
+        interpolated
+            .parts
+            .iter()
+            .map(|part| match part {
+                StringPart::Text(text, _) => self.text_length(text),
+                StringPart::Expression(_) | StringPart::Placeholder(_, _) => {
+                    self.event_index_options.min_interpolation_length
+                }
+            })
+            .sum()
     }
 
     fn analyze_var_value(
@@ -473,7 +1185,7 @@ impl DiagnosticCollector {
                     // Branch definitions don't need analysis here
                 }
 
-                NodeStmt::Choice(choices) => {
+                NodeStmt::Choice(choices, _) => {
                     self.analyze_choices(
                         choices,
                         declared_functions,
@@ -482,11 +1194,11 @@ impl DiagnosticCollector {
                         used_nodes,
                     );
                 }
-                NodeStmt::Text(text) => {
+                NodeStmt::Text(text, _) => {
                     // Check for function calls in text interpolation (old format)
                     self.analyze_text_interpolation(text, declared_functions, used_functions);
                 }
-                NodeStmt::InterpolatedText(interpolated) => {
+                NodeStmt::InterpolatedText(interpolated, _) => {
                     // Check function calls in interpolated string
                     self.analyze_interpolated_string(
                         interpolated,
@@ -531,7 +1243,7 @@ impl DiagnosticCollector {
             crate::ast::WithEventItem::InlineEvent(event) => {
                 self.analyze_event_action(&event.action, declared_functions, used_functions);
             }
-            crate::ast::WithEventItem::EventList(list) => {
+            crate::ast::WithEventItem::EventList(list, _) => {
                 for sub_item in list {
                     self.analyze_with_event_item(sub_item, declared_functions, used_functions);
                 }
@@ -558,7 +1270,7 @@ impl DiagnosticCollector {
                             node_name: node_name.clone(),
                         },
                         severity: Severity::Error,
-                        span: *span,
+                        span: Some(*span),
                         message: format_message(
                             get_text("node_not_defined", self.language),
                             &[node_name],
@@ -566,7 +1278,7 @@ impl DiagnosticCollector {
                     });
                 }
             }
-            NodeJump::Return | NodeJump::Break => {
+            NodeJump::Return(_) | NodeJump::Break(_) => {
                 // These are always valid
             }
         }
@@ -584,7 +1296,7 @@ impl DiagnosticCollector {
             // Analyze condition
             if let Some(condition) = &choice.condition {
                 match condition {
-                    Condition::Identifier(_) => {
+                    Condition::Identifier(_, _) => {
                         // TODO: Check if identifier is valid in scope
                         // For now, we assume identifiers are boolean
                     }
@@ -623,7 +1335,7 @@ impl DiagnosticCollector {
                                 node_name: node_name.clone(),
                             },
                             severity: Severity::Error,
-                            span: *span,
+                            span: Some(*span),
                             message: format_message(
                                 get_text("node_not_defined", self.language),
                                 &[node_name],
@@ -631,7 +1343,7 @@ impl DiagnosticCollector {
                         });
                     }
                 }
-                ChoiceDest::NestedChoices(nested) => {
+                ChoiceDest::NestedChoices(nested, _) => {
                     self.analyze_choices(
                         nested,
                         declared_functions,
@@ -640,7 +1352,7 @@ impl DiagnosticCollector {
                         used_nodes,
                     );
                 }
-                ChoiceDest::Return | ChoiceDest::Break => {
+                ChoiceDest::Return(_) | ChoiceDest::Break(_) => {
                     // These are always valid
                 }
             }
@@ -748,10 +1460,10 @@ impl DiagnosticCollector {
         declared_functions: &HashMap<String, &FunctionDecl>,
     ) -> String {
         match arg {
-            Arg::String(_) => "String".to_string(),
-            Arg::Number(_) => "Number".to_string(),
-            Arg::Boolean(_) => "Boolean".to_string(),
-            Arg::Identifier(_) => "Unknown".to_string(), // Could be enhanced with variable tracking
+            Arg::String(_, _) => "String".to_string(),
+            Arg::Number(_, _) => "Number".to_string(),
+            Arg::Boolean(_, _) => "Boolean".to_string(),
+            Arg::Identifier(_, _) => "Unknown".to_string(), // Could be enhanced with variable tracking
             Arg::FuncCall(func_call) => {
                 if let Some(func_decl) = declared_functions.get(&func_call.name) {
                     func_decl
@@ -834,34 +1546,227 @@ impl DiagnosticCollector {
     }
 }
 
-fn is_snake_case(s: &str) -> bool {
-    if s.is_empty() {
-        return false;
+/// Names referenced anywhere in a program, split by namespace.
+#[derive(Default)]
+struct SymbolUsages {
+    /// Variables and constants that are read: conditions, arguments, placeholders,
+    /// assigned values, index overrides and branch selectors. Assigning to a variable
+    /// does not count as using it.
+    values: HashSet<String>,
+    /// Enum names used as types or in `Enum.member` expressions.
+    types: HashSet<String>,
+    /// Events and timelines started with `run` or referenced from `with`.
+    runs: HashSet<String>,
+}
+
+impl SymbolUsages {
+    /// A branch selector names the enum variable it switches on; boolean branches
+    /// name a boolean variable in each case instead.
+    fn collect_branch_selector(&mut self, selector: Option<&str>, cases: &[BranchCase]) {
+        match selector {
+            Some(selector) => {
+                self.values.insert(selector.to_string());
+            }
+            None => {
+                for case in cases {
+                    self.values.insert(case.condition.clone());
+                }
+            }
+        }
     }
+}
 
-    // Should start with lowercase letter or underscore
-    let first_char = s.chars().next().unwrap();
-    if !first_char.is_ascii_lowercase() && first_char != '_' {
-        return false;
+impl Visit for SymbolUsages {
+    fn visit_function_decl(&mut self, func: &FunctionDecl) {
+        if let Some(return_type) = &func.return_type {
+            self.types.insert(return_type.clone());
+        }
+        visit::walk_function_decl(self, func);
+    }
+
+    fn visit_param(&mut self, param: &Param) {
+        self.types.insert(param.type_name.clone());
+    }
+
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) {
+        self.types.insert(var_decl.type_name.clone());
+        visit::walk_var_decl(self, var_decl);
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDecl) {
+        self.types.insert(const_decl.type_name.clone());
+        visit::walk_const_decl(self, const_decl);
+    }
+
+    fn visit_var_value(&mut self, value: &VarValue) {
+        if let VarValue::EnumMember(enum_name, _, _) = value {
+            self.types.insert(enum_name.clone());
+        }
+        visit::walk_var_value(self, value);
+    }
+
+    fn visit_branch_def(&mut self, branch: &BranchDef) {
+        self.collect_branch_selector(branch.enum_type.as_deref(), &branch.cases);
+        visit::walk_branch_def(self, branch);
+    }
+
+    fn visit_branch_value(&mut self, branch: &BranchValue) {
+        self.collect_branch_selector(branch.enum_type.as_deref(), &branch.cases);
+        visit::walk_branch_value(self, branch);
+    }
+
+    fn visit_string_part(&mut self, part: &StringPart) {
+        if let StringPart::Placeholder(name, _) = part {
+            self.values.insert(name.clone());
+        }
+        visit::walk_string_part(self, part);
+    }
+
+    fn visit_if_condition(&mut self, condition: &IfCondition) {
+        match condition {
+            IfCondition::Identifier(name, _) => {
+                self.values.insert(name.clone());
+            }
+            IfCondition::EnumMember(enum_name, _, _) => {
+                self.types.insert(enum_name.clone());
+            }
+            _ => {}
+        }
+        visit::walk_if_condition(self, condition);
+    }
+
+    fn visit_run_stmt(&mut self, run_stmt: &RunStmt) {
+        self.runs.insert(run_stmt.event_name.clone());
+        visit::walk_run_stmt(self, run_stmt);
     }
 
-    // Should contain only lowercase letters, digits, and underscores
-    s.chars()
-        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    fn visit_with_event_item(&mut self, item: &WithEventItem) {
+        if let WithEventItem::EventRef(name, _) | WithEventItem::EventRefWithOverride(name, _, _) =
+            item
+        {
+            self.runs.insert(name.clone());
+        }
+        visit::walk_with_event_item(self, item);
+    }
+
+    fn visit_index_override(&mut self, index_override: &IndexOverride) {
+        if let IndexOverride::Variable(name, _) = index_override {
+            self.values.insert(name.clone());
+        }
+    }
+
+    fn visit_assign_value(&mut self, value: &AssignValue) {
+        match value {
+            AssignValue::EnumMember(enum_name, _, _) => {
+                self.types.insert(enum_name.clone());
+            }
+            AssignValue::Identifier(name, _) => {
+                self.values.insert(name.clone());
+            }
+            AssignValue::Number(_, _) | AssignValue::Boolean(_, _) | AssignValue::String(_, _) => {}
+        }
+    }
+
+    fn visit_condition(&mut self, condition: &Condition) {
+        if let Condition::Identifier(name, _) = condition {
+            self.values.insert(name.clone());
+        }
+        visit::walk_condition(self, condition);
+    }
+
+    fn visit_arg(&mut self, arg: &Arg) {
+        if let Arg::Identifier(name, _) = arg {
+            self.values.insert(name.clone());
+        }
+        visit::walk_arg(self, arg);
+    }
 }
 
-fn is_pascal_case(s: &str) -> bool {
-    if s.is_empty() {
-        return false;
+/// Resolve the indices of a `with events` item, skipping events whose index is not known statically.
+fn collect_with_event_indices(
+    item: &WithEventItem,
+    events: &HashMap<&str, &EventDef>,
+    constants: &HashMap<&str, f64>,
+    indices: &mut Vec<(f64, Option<(usize, usize)>)>,
+) {
+    match item {
+        WithEventItem::InlineEvent(event) => {
+            indices.push((event.index, Some(event.index_span)));
+        }
+        WithEventItem::EventRef(name, span) => {
+            if let Some(index) = events.get(name.as_str()).and_then(|def| def.index) {
+                indices.push((index, Some(*span)));
+            }
+        }
+        WithEventItem::EventRefWithOverride(_, _, index_override) => {
+            let index = match index_override {
+                IndexOverride::Value(value, _) => Some(*value),
+                IndexOverride::Variable(name, _) => constants.get(name.as_str()).copied(),
+            };
+            if let Some(index) = index {
+                indices.push((index, Some(index_override.span())));
+            }
+        }
+        WithEventItem::EventList(list, _) => {
+            for sub_item in list {
+                collect_with_event_indices(sub_item, events, constants, indices);
+            }
+        }
     }
+}
+
+/// Whether a function name follows the `non_snake_case_function` lint.
+///
+/// Scripts without letter case, such as Chinese, count as lowercase.
+pub fn is_snake_case(s: &str) -> bool {
+    let Some(first_char) = s.chars().next() else {
+        return false;
+    };
+
+    // Should not start with a digit, and should contain no uppercase letters
+    !first_char.is_numeric() && !s.chars().any(char::is_uppercase)
+}
 
-    // Should start with uppercase letter
-    let first_char = s.chars().next().unwrap();
-    if !first_char.is_ascii_uppercase() {
+/// Whether a node name follows the `non_pascal_case_node` lint.
+///
+/// Scripts without letter case, such as Chinese, count as uppercase.
+pub fn is_pascal_case(s: &str) -> bool {
+    let Some(first_char) = s.chars().next() else {
         return false;
+    };
+
+    // Should start with an uppercase or case-less letter, and contain no underscores
+    !first_char.is_lowercase() && !first_char.is_numeric() && first_char != '_' && !s.contains('_')
+}
+
+fn to_snake_case(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut result = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // Split "playSound" and "HTTPRequest" before the new word
+            if prev != '_' && (prev.is_lowercase() || prev.is_ascii_digit() || next_is_lower) {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_lowercase());
     }
 
-    // Should contain only letters and digits
-    s.chars()
-        .all(|c| c.is_ascii_alphabetic() || c.is_ascii_digit())
+    result
+}
+
+fn to_pascal_case(s: &str) -> String {
+    s.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
 }
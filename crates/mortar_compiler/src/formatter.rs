@@ -0,0 +1,557 @@
+//! # formatter.rs
+//!
+//! # formatter.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The source formatter behind `mortar fmt` and LSP formatting.
+//!
+//! `mortar fmt` 与 LSP 格式化所使用的源码格式化器。
+//!
+//! The grammar lets authors write the same script in many ways: `nd` or `node`, optional commas and semicolons, single or double quotes and any indentation. The formatter rewrites a file into one canonical layout while keeping every comment.
+//!
+//! 语法允许作者用多种方式书写同一段脚本：`nd` 或 `node`、可省略的逗号和分号、单引号或双引号以及任意缩进。格式化器会将文件改写为统一的布局，同时保留所有注释。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! The formatter walks the lossless CST. Statement, choice, event and branch-case spans from the AST decide where lines start; everything else is joined with normalized spacing. Sources with syntax errors are left unchanged.
+//!
+//! 格式化器遍历无损 CST。AST 中语句、选项、事件和分支条目的范围决定了哪里换行，其余部分以规范化的空格连接。含有语法错误的源码保持不变。
+
+use crate::ast::*;
+use crate::cst::{CstElement, CstNode, CstToken, ItemKind, SourceFile, SyntaxKind, TriviaKind};
+use crate::parser::ParseHandler;
+use crate::token::Token;
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Spaces per indentation level when `use_tabs` is off.
+    pub indent_width: usize,
+    pub use_tabs: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            use_tabs: false,
+        }
+    }
+}
+
+/// Format a whole file. Sources that do not parse are returned unchanged.
+pub fn format_source(source: &str, options: FormatOptions) -> String {
+    match format_items(source, options) {
+        Some((output, _items)) => output,
+        None => source.to_string(),
+    }
+}
+
+/// Format the top-level items that overlap `range`.
+///
+/// Returns the replaced source span and its formatted text, or `None` if the source does not parse or no item overlaps.
+pub fn format_range(source: &str, range: Span, options: FormatOptions) -> Option<(Span, String)> {
+    let (output, formatted_items) = format_items(source, options)?;
+    let file = SourceFile::parse(source);
+
+    let overlapping: Vec<usize> = file
+        .items()
+        .enumerate()
+        .filter(|(_, item)| {
+            let (start, end) = item.span();
+            start <= range.1 && range.0 <= end
+        })
+        .map(|(index, _)| index)
+        .collect();
+    let (&first, &last) = (overlapping.first()?, overlapping.last()?);
+
+    let items: Vec<Span> = file.items().map(|item| item.span()).collect();
+    // Items always start at column zero, so take the indentation before the first one too
+    let line_start = source[..items[first].0].trim_end_matches([' ', '\t']).len();
+    let start = if line_start == 0 || source[..line_start].ends_with('\n') {
+        line_start
+    } else {
+        items[first].0
+    };
+
+    let text = output[formatted_items[first].0..formatted_items[last].1].to_string();
+    Some(((start, items[last].1), text))
+}
+
+/// Format the source and return the output span of every top-level item.
+fn format_items(source: &str, options: FormatOptions) -> Option<(String, Vec<Span>)> {
+    let program = ParseHandler::parse_source_code(source, false).ok()?;
+    let file = SourceFile::parse(source);
+
+    let mut starts = LineStarts::default();
+    starts.collect_program(&program);
+    starts.collect_event_fields(&file);
+
+    let mut layout = Layout {
+        starts: &starts,
+        entries: Vec::new(),
+        item: 0,
+    };
+    layout.root(&file.root);
+
+    let mut printer = Printer::new(options);
+    printer.print(&layout.entries, &file);
+    Some((printer.out, printer.items))
+}
+
+/// Byte offsets of tokens that begin a new line.
+#[derive(Default)]
+struct LineStarts {
+    /// Statements, enum variants and event fields, inside `{ ... }`.
+    block: HashSet<usize>,
+    /// Choices, events and branch cases, inside `[ ... ]`.
+    list: HashSet<usize>,
+}
+
+impl LineStarts {
+    fn collect_program(&mut self, program: &Program) {
+        for item in &program.body {
+            match item {
+                TopLevel::NodeDef(node) => self.collect_stmts(&node.body),
+                TopLevel::EnumDef(enum_def) => {
+                    self.block
+                        .extend(enum_def.variant_spans.iter().map(|span| span.0));
+                }
+                TopLevel::TimelineDef(timeline) => {
+                    self.block
+                        .extend(timeline.body.iter().map(|stmt| stmt.span().0));
+                }
+                TopLevel::VarDecl(VarDecl {
+                    value: Some(VarValue::Branch(branch)),
+                    ..
+                })
+                | TopLevel::ConstDecl(ConstDecl {
+                    value: VarValue::Branch(branch),
+                    ..
+                }) => self.collect_branch_cases(&branch.cases),
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_stmts(&mut self, stmts: &[NodeStmt]) {
+        for stmt in stmts {
+            self.block.insert(stmt.span().0);
+            match stmt {
+                NodeStmt::Choice(items, _) => self.collect_choices(items),
+                NodeStmt::IfElse(if_else) => {
+                    self.collect_stmts(&if_else.then_body);
+                    if let Some(else_body) = &if_else.else_body {
+                        self.collect_stmts(else_body);
+                    }
+                }
+                NodeStmt::WithEvents(with_events) => self.collect_with_events(&with_events.events),
+                NodeStmt::Branch(branch) => self.collect_branch_cases(&branch.cases),
+                NodeStmt::VarDecl(VarDecl {
+                    value: Some(VarValue::Branch(branch)),
+                    ..
+                }) => self.collect_branch_cases(&branch.cases),
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_choices(&mut self, items: &[ChoiceItem]) {
+        for item in items {
+            self.list.insert(item.span.0);
+            if let ChoiceDest::NestedChoices(nested, _) = &item.target {
+                self.collect_choices(nested);
+            }
+        }
+    }
+
+    fn collect_with_events(&mut self, items: &[WithEventItem]) {
+        for item in items {
+            self.list.insert(item.span().0);
+            if let WithEventItem::EventList(nested, _) = item {
+                self.collect_with_events(nested);
+            }
+        }
+    }
+
+    fn collect_branch_cases(&mut self, cases: &[BranchCase]) {
+        for case in cases {
+            self.list.insert(case.span.0);
+            for event in case.events.iter().flatten() {
+                self.list.insert(event.span.0);
+            }
+        }
+    }
+
+    /// `index`, `action` and `duration` inside event definitions.
+    fn collect_event_fields(&mut self, file: &SourceFile) {
+        for item in file.items().filter(|item| item.kind() == ItemKind::Event) {
+            for child in &item.syntax().children {
+                let CstElement::Node(block) = child else {
+                    continue;
+                };
+                for element in &block.children {
+                    if let CstElement::Token(token) = element
+                        && matches!(token.token, Token::Index | Token::Action | Token::Duration)
+                    {
+                        self.block.insert(token.span.0);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A token with its layout decided.
+struct Entry<'c, 'a> {
+    token: &'c CstToken<'a>,
+    starts_line: bool,
+    indent: usize,
+    /// Index of the top-level item the token belongs to.
+    item: usize,
+}
+
+struct Layout<'c, 'a, 's> {
+    starts: &'s LineStarts,
+    entries: Vec<Entry<'c, 'a>>,
+    item: usize,
+}
+
+impl<'c, 'a> Layout<'c, 'a, '_> {
+    fn root(&mut self, root: &'c CstNode<'a>) {
+        for child in &root.children {
+            match child {
+                CstElement::Node(node) => {
+                    self.node(node, true, 0);
+                    self.item += 1;
+                }
+                CstElement::Token(token) => self.push(token, true, 0),
+            }
+        }
+    }
+
+    fn push(&mut self, token: &'c CstToken<'a>, starts_line: bool, indent: usize) {
+        self.entries.push(Entry {
+            token,
+            starts_line,
+            indent,
+            item: self.item,
+        });
+    }
+
+    /// Lay out a node whose first token starts a line if `starts_line` is set.
+    fn node(&mut self, node: &'c CstNode<'a>, starts_line: bool, indent: usize) {
+        let children = &node.children;
+        let (inner, closer) = match (node.kind, children.last()) {
+            (
+                SyntaxKind::Block | SyntaxKind::List | SyntaxKind::Group,
+                Some(CstElement::Token(last)),
+            ) if children.len() > 1 && is_closer(&last.token) => {
+                (&children[1..children.len() - 1], Some(last))
+            }
+            (SyntaxKind::Block | SyntaxKind::List | SyntaxKind::Group, _) => (&children[1..], None),
+            _ => (&children[..], None),
+        };
+        // Statements and list entries are laid out by the tokens that start their lines
+        let mut flat = Vec::with_capacity(inner.len());
+        flatten_entries(inner, &mut flat);
+        let inner = flat;
+
+        let line_starts = match node.kind {
+            SyntaxKind::Block => Some(&self.starts.block),
+            SyntaxKind::List => Some(&self.starts.list),
+            _ => None,
+        };
+        // Blocks and lists holding statements or items get one per line; `{ 0, call() }` stays inline
+        let multi_line = inner
+            .iter()
+            .any(|element| line_starts.is_some_and(|set| set.contains(&first_offset(element))));
+
+        let inner_indent = match node.kind {
+            SyntaxKind::Block | SyntaxKind::List => indent + 1,
+            _ => indent,
+        };
+
+        if let Some(CstElement::Token(opener)) = children.first()
+            && matches!(
+                node.kind,
+                SyntaxKind::Block | SyntaxKind::List | SyntaxKind::Group
+            )
+        {
+            self.push(opener, starts_line, indent);
+        }
+
+        for (index, element) in inner.iter().enumerate() {
+            let element_starts_line = match node.kind {
+                SyntaxKind::Item(_) => index == 0 && starts_line,
+                _ => {
+                    multi_line
+                        && line_starts.is_some_and(|set| set.contains(&first_offset(element)))
+                }
+            };
+
+            match element {
+                CstElement::Node(child) => self.node(child, element_starts_line, inner_indent),
+                CstElement::Token(token) => self.push(token, element_starts_line, inner_indent),
+            }
+        }
+
+        if let Some(closer) = closer {
+            self.push(closer, multi_line, indent);
+        }
+    }
+}
+
+/// `elements` with the statement and entry nodes among them replaced by their children.
+fn flatten_entries<'c, 'a>(elements: &'c [CstElement<'a>], out: &mut Vec<&'c CstElement<'a>>) {
+    for element in elements {
+        match element {
+            CstElement::Node(node)
+                if matches!(
+                    node.kind,
+                    SyntaxKind::Statement(_) | SyntaxKind::ChoiceEntry | SyntaxKind::EventEntry
+                ) =>
+            {
+                flatten_entries(&node.children, out);
+            }
+            _ => out.push(element),
+        }
+    }
+}
+
+fn is_closer(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::RightBrace | Token::RightBracket | Token::RightParen
+    )
+}
+
+fn is_opener(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LeftBrace | Token::LeftBracket | Token::LeftParen
+    )
+}
+
+fn first_offset(element: &CstElement) -> usize {
+    match element {
+        CstElement::Node(node) => node.span().0,
+        CstElement::Token(token) => token.span.0,
+    }
+}
+
+struct Printer {
+    options: FormatOptions,
+    out: String,
+    /// Output span of every top-level item.
+    items: Vec<Span>,
+    /// A line comment was written, so the next token needs a new line.
+    force_break: bool,
+    /// Inside `branch<...>`, where no spaces are written.
+    in_generic: bool,
+}
+
+impl Printer {
+    fn new(options: FormatOptions) -> Self {
+        Self {
+            options,
+            out: String::new(),
+            items: Vec::new(),
+            force_break: false,
+            in_generic: false,
+        }
+    }
+
+    fn print(&mut self, entries: &[Entry], file: &SourceFile) {
+        for (index, entry) in entries.iter().enumerate() {
+            let previous = index.checked_sub(1).map(|i| &entries[i]);
+            let next = entries.get(index + 1);
+
+            let after_opener = previous.is_some_and(|p| is_opener(&p.token.token));
+            self.leading_trivia(entry, after_opener);
+
+            // Separators are dropped where the layout already ends the line
+            let drops_separator = matches!(entry.token.token, Token::Comma | Token::Semicolon)
+                && next.is_none_or(|n| n.starts_line);
+
+            if !drops_separator {
+                if entry.starts_line || self.force_break {
+                    let extra = usize::from(!entry.starts_line);
+                    self.new_line();
+                    self.write_indent(entry.indent + extra);
+                } else if let Some(previous) = previous
+                    && !self.at_line_start()
+                    && self.space_between(&previous.token.token, &entry.token.token, entries, index)
+                {
+                    self.out.push(' ');
+                }
+                self.force_break = false;
+
+                if previous.is_none_or(|p| p.item != entry.item) {
+                    self.items.push((self.out.len(), self.out.len()));
+                }
+                self.out.push_str(&canonical_text(entry.token));
+                if let Some(item) = self.items.last_mut() {
+                    item.1 = self.out.len();
+                }
+            }
+
+            for trivia in &entry.token.trailing {
+                if trivia.is_comment() {
+                    self.comment(trivia.text, trivia.kind == TriviaKind::LineComment);
+                }
+            }
+        }
+
+        // Comments after the last token
+        let mut newlines = 0;
+        for trivia in &file.eof_trivia {
+            match trivia.kind {
+                TriviaKind::Newline => newlines += 1,
+                TriviaKind::Whitespace => {}
+                _ => {
+                    self.own_line_comment(trivia.text, 0, newlines > 1);
+                    newlines = 0;
+                }
+            }
+        }
+
+        self.new_line();
+    }
+
+    /// Comments before a token, each on its own line unless it was inline in the source.
+    fn leading_trivia(&mut self, entry: &Entry, after_opener: bool) {
+        let mut newlines = 0;
+        let mut wrote_comment = false;
+        for trivia in &entry.token.leading {
+            match trivia.kind {
+                TriviaKind::Newline => newlines += 1,
+                TriviaKind::Whitespace => {}
+                TriviaKind::LineComment | TriviaKind::BlockComment => {
+                    if newlines > 0 || self.out.is_empty() {
+                        let blank = newlines > 1 && (wrote_comment || !after_opener);
+                        let indent = entry.indent + usize::from(is_closer(&entry.token.token));
+                        self.own_line_comment(trivia.text, indent, blank);
+                    } else {
+                        self.comment(trivia.text, trivia.kind == TriviaKind::LineComment);
+                    }
+                    wrote_comment = true;
+                    newlines = 0;
+                }
+            }
+        }
+
+        // Keep at most one blank line, except at the edges of a block
+        if entry.starts_line
+            && newlines > 1
+            && !is_closer(&entry.token.token)
+            && (wrote_comment || !after_opener)
+            && !self.out.is_empty()
+        {
+            self.new_line();
+            self.out.push('\n');
+        }
+    }
+
+    fn own_line_comment(&mut self, text: &str, indent: usize, blank_line: bool) {
+        self.new_line();
+        if blank_line && !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.write_indent(indent);
+        self.out.push_str(text.trim_end());
+        self.force_break = true;
+    }
+
+    /// A comment after a token on the same line, two spaces after it like in the examples.
+    fn comment(&mut self, text: &str, is_line_comment: bool) {
+        if !self.at_line_start() {
+            self.out.push_str(if is_line_comment { "  " } else { " " });
+        }
+        self.out.push_str(text.trim_end());
+        if is_line_comment {
+            self.force_break = true;
+        }
+    }
+
+    fn new_line(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn write_indent(&mut self, level: usize) {
+        if self.options.use_tabs {
+            self.out.push_str(&"\t".repeat(level));
+        } else {
+            self.out
+                .push_str(&" ".repeat(level * self.options.indent_width));
+        }
+    }
+
+    fn space_between(
+        &mut self,
+        previous: &Token,
+        current: &Token,
+        entries: &[Entry],
+        index: usize,
+    ) -> bool {
+        let before_previous = index.checked_sub(2).map(|i| &entries[i].token.token);
+
+        if matches!(previous, Token::Branch) && matches!(current, Token::Less) {
+            self.in_generic = true;
+            return false;
+        }
+        if self.in_generic {
+            if matches!(current, Token::Greater) {
+                self.in_generic = false;
+            }
+            return false;
+        }
+
+        match (previous, current) {
+            (
+                _,
+                Token::Comma
+                | Token::Semicolon
+                | Token::Colon
+                | Token::RightParen
+                | Token::RightBracket
+                | Token::Dot,
+            ) => false,
+            (Token::LeftParen | Token::LeftBracket | Token::Dot | Token::Not, _) => false,
+            (Token::LeftBrace, Token::RightBrace) => false,
+            (Token::Identifier(_), Token::LeftParen) => false,
+            // Chained calls may use keywords as names: `.when(cond)`, `.wait()`
+            (_, Token::LeftParen) => !matches!(before_previous, Some(Token::Dot)),
+            _ => true,
+        }
+    }
+}
+
+/// The token text with keyword aliases and quotes normalized.
+fn canonical_text<'a>(token: &CstToken<'a>) -> Cow<'a, str> {
+    match token.token {
+        Token::Node => Cow::Borrowed("node"),
+        Token::Fn => Cow::Borrowed("fn"),
+        Token::Pub => Cow::Borrowed("pub"),
+        Token::Timeline => Cow::Borrowed("timeline"),
+        Token::BooleanType => Cow::Borrowed("Bool"),
+        Token::String(content) if token.text.starts_with('\'') && !content.contains('"') => {
+            Cow::Owned(format!("\"{}\"", content.replace("\\'", "'")))
+        }
+        _ => Cow::Borrowed(token.text),
+    }
+}
@@ -22,12 +22,20 @@
 //!
 //! 此文件定义了 `Language` 枚举并暴露了编译器的公共 API。
 
+pub mod codes;
+pub mod config;
+pub mod cst;
 pub mod deserializer;
 pub mod diagnostics;
+pub mod formatter;
 pub mod handler;
+pub mod lint;
+pub mod message_format;
 pub mod parser;
+pub mod printer;
 pub mod serializer;
 pub mod token;
+pub mod visit;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Language {
@@ -43,16 +51,27 @@ pub use ast::NodeJump;
 pub use ast::NodeStmt;
 pub use ast::Program;
 pub use ast::TopLevel;
+pub use config::{ConfigError, ProjectConfig};
+pub use cst::SourceFile;
 pub use deserializer::{
     Action, BranchCase, BranchDef, Choice, Condition, Constant, ContentItem, Deserializer, Enum,
     Event, EventDef, Function, IfCondition, IndexOverride, Metadata, MortaredData, Node, Param,
     Statement, StringPart, TimelineDef, TimelineStmt, Variable,
 };
-pub use diagnostics::{Diagnostic, DiagnosticCollector, DiagnosticKind, Severity};
+pub use diagnostics::{
+    Diagnostic, DiagnosticCollector, DiagnosticKind, EventIndexOptions, Severity, Suggestion,
+    TextLengthUnit, is_pascal_case, is_snake_case,
+};
+pub use formatter::{FormatOptions, format_range, format_source};
 pub use handler::file_handler::{FileError, FileHandler};
+pub use lint::{InlineLintAttribute, LINT_NAMES, LintConfig, LintLevel};
+pub use message_format::MessageFormat;
 pub use parser::ParseHandler;
+pub use parser::incremental::IncrementalParse;
+pub use printer::{print_node_stmt, print_program, print_top_level};
 pub use serializer::Serializer;
-pub use token::{Token, TokenInfo, tokenize};
+pub use token::{Token, TokenInfo, is_identifier_continue, is_identifier_start, tokenize};
+pub use visit::{Visit, VisitMut};
 
 pub mod ast;
 #[cfg(test)]
@@ -0,0 +1,189 @@
+//! # lint.rs
+//!
+//! # lint.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Provides lint levels for the warnings produced by the diagnostic system.
+//!
+//! 为诊断系统产生的警告提供 lint 级别。
+//!
+//! Each lint can be allowed, kept as a warning, or denied (turned into an error), either project-wide or for a single item via an inline comment such as `// mortar: allow(unused_function)`.
+//!
+//! 每个 lint 都可以被允许、保留为警告或拒绝（变为错误），既可以在整个项目中设置，也可以通过 `// mortar: allow(unused_function)` 这样的行内注释只作用于单个条目。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! This file defines `LintLevel`, `LintConfig`, the list of stable lint names, and the scanner for inline lint comments.
+//!
+//! 此文件定义了 `LintLevel`、`LintConfig`、稳定的 lint 名称列表以及行内 lint 注释的扫描器。
+
+use crate::token::{Token, tokenize};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Stable names of every lint that can be configured.
+pub const LINT_NAMES: &[&str] = &[
+    "non_snake_case_function",
+    "non_pascal_case_node",
+    "unused_function",
+    "unused_variable",
+    "unused_constant",
+    "unused_enum",
+    "unused_event",
+    "unused_timeline",
+    "event_index_out_of_bounds",
+    "event_index_out_of_order",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// Project-wide lint levels.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    pub levels: HashMap<String, LintLevel>,
+    /// Turn every remaining warning into an error.
+    pub deny_warnings: bool,
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_level(&mut self, lint: &str, level: LintLevel) {
+        self.levels.insert(lint.to_string(), level);
+    }
+
+    /// The configured level of a lint; unconfigured lints warn.
+    pub fn level_of(&self, lint: &str) -> LintLevel {
+        self.levels.get(lint).copied().unwrap_or(LintLevel::Warn)
+    }
+}
+
+/// An inline `// mortar: <level>(<lint>, ...)` comment together with the item it applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineLintAttribute {
+    pub level: LintLevel,
+    pub lints: Vec<String>,
+    /// Byte range of the item following the comment.
+    pub span: (usize, usize),
+}
+
+impl InlineLintAttribute {
+    pub fn applies_to(&self, lint: &str, position: usize) -> bool {
+        self.span.0 <= position && position < self.span.1 && self.lints.iter().any(|l| l == lint)
+    }
+}
+
+/// Find every inline lint comment in `source` and work out which item each one is attached to.
+///
+/// The item starts at the first token after the comment and runs until a token at the same
+/// bracket depth begins a new line, so a comment above a node covers its whole body and
+/// a comment inside a node covers the next statement.
+pub fn collect_inline_attributes(source: &str) -> Vec<InlineLintAttribute> {
+    let tokens = tokenize(source);
+    let mut attributes = Vec::new();
+
+    for (i, token_info) in tokens.iter().enumerate() {
+        let Token::SingleLineComment(comment) = token_info.token else {
+            continue;
+        };
+        let Some((level, lints)) = parse_lint_comment(comment) else {
+            continue;
+        };
+
+        let mut items = tokens[i + 1..].iter().filter(|t| {
+            !matches!(
+                t.token,
+                Token::SingleLineComment(_) | Token::MultiLineComment(_)
+            )
+        });
+        let Some(first) = items.next() else {
+            continue;
+        };
+
+        let mut depth = bracket_delta(&first.token);
+        let mut end = first.end;
+        for token_info in items {
+            let starts_line = source[end..token_info.start].contains('\n');
+            if depth <= 0 && starts_line && !continues_item(&token_info.token) {
+                break;
+            }
+            depth += bracket_delta(&token_info.token);
+            end = token_info.end;
+            if depth < 0 {
+                break;
+            }
+        }
+
+        attributes.push(InlineLintAttribute {
+            level,
+            lints,
+            span: (first.start, end),
+        });
+    }
+
+    attributes
+}
+
+/// Parse `// mortar: allow(a, b)` into its level and lint names.
+fn parse_lint_comment(comment: &str) -> Option<(LintLevel, Vec<String>)> {
+    let rest = comment.trim_start_matches('/').trim();
+    let rest = rest.strip_prefix("mortar:")?.trim();
+    let open = rest.find('(')?;
+    let close = rest.rfind(')')?;
+    if close < open {
+        return None;
+    }
+
+    let level = LintLevel::from_name(rest[..open].trim())?;
+    let lints = rest[open + 1..close]
+        .split(',')
+        .map(|lint| lint.trim().to_string())
+        .filter(|lint| !lint.is_empty())
+        .collect::<Vec<_>>();
+
+    if lints.is_empty() {
+        None
+    } else {
+        Some((level, lints))
+    }
+}
+
+fn bracket_delta(token: &Token) -> i32 {
+    match token {
+        Token::LeftBrace | Token::LeftBracket | Token::LeftParen => 1,
+        Token::RightBrace | Token::RightBracket | Token::RightParen => -1,
+        _ => 0,
+    }
+}
+
+/// Tokens that may start a line without starting a new item, e.g. `} -> Next` or `} else {`.
+fn continues_item(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LeftBrace | Token::Arrow | Token::Else | Token::Dot
+    )
+}
@@ -0,0 +1,299 @@
+//! # message_format.rs
+//!
+//! # message_format.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Renders collected diagnostics in machine-readable formats.
+//!
+//! 以机器可读的格式输出收集到的诊断信息。
+//!
+//! Besides the colored terminal output, diagnostics can be printed as one-line `short` messages, as JSON objects (one per line) or as a SARIF 2.1.0 log that code-scanning dashboards can ingest.
+//!
+//! 除了彩色终端输出外，诊断还可以输出为单行的 `short` 消息、JSON 对象（每行一个），或可被代码扫描面板读取的 SARIF 2.1.0 日志。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! This file defines `MessageFormat` and the rendering methods on `DiagnosticCollector`.
+//!
+//! 此文件定义了 `MessageFormat` 以及 `DiagnosticCollector` 上的输出方法。
+
+use crate::Language;
+use crate::codes;
+use crate::diagnostics::{Diagnostic, DiagnosticCollector, Severity, get_line_col};
+use serde_json::{Value, json};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/Bli-AIk/mortar";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    /// Colored output with source snippets.
+    #[default]
+    Human,
+    /// One line per diagnostic: `file:line:col: severity[code]: message`.
+    Short,
+    /// One JSON object per diagnostic, one per line.
+    Json,
+    /// A SARIF 2.1.0 log.
+    Sarif,
+}
+
+impl MessageFormat {
+    pub const NAMES: &'static [&'static str] = &["human", "short", "json", "sarif"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(MessageFormat::Human),
+            "short" => Some(MessageFormat::Short),
+            "json" => Some(MessageFormat::Json),
+            "sarif" => Some(MessageFormat::Sarif),
+            _ => None,
+        }
+    }
+
+    /// Whether the output is meant for tools rather than people.
+    pub fn is_machine_readable(self) -> bool {
+        matches!(self, MessageFormat::Json | MessageFormat::Sarif)
+    }
+}
+
+impl DiagnosticCollector {
+    /// Print the diagnostics to stdout in the given format.
+    pub fn emit_diagnostics(&self, source: &str, format: MessageFormat) {
+        match format {
+            MessageFormat::Human => self.print_diagnostics(source),
+            _ => {
+                let output = self.format_diagnostics(source, format);
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
+            }
+        }
+    }
+
+    /// Render the diagnostics as text without colors.
+    ///
+    /// `Human` falls back to `Short` here, since the human format is only printed directly.
+    /// The SARIF log is always produced, even without results, so CI can record a clean run.
+    pub fn format_diagnostics(&self, source: &str, format: MessageFormat) -> String {
+        match format {
+            MessageFormat::Human | MessageFormat::Short => self
+                .get_diagnostics()
+                .iter()
+                .map(|diagnostic| self.short_message(source, diagnostic))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            MessageFormat::Json => self
+                .to_json(source)
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            MessageFormat::Sarif => {
+                serde_json::to_string_pretty(&self.to_sarif(source)).unwrap_or_default()
+            }
+        }
+    }
+
+    /// One JSON object per diagnostic.
+    pub fn to_json(&self, source: &str) -> Vec<Value> {
+        self.get_diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                let suggestions = diagnostic
+                    .suggestions(self.language())
+                    .into_iter()
+                    .map(|suggestion| {
+                        json!({
+                            "message": suggestion.message,
+                            "span": span_json(source, suggestion.span),
+                            "replacement": suggestion.replacement,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                json!({
+                    "file": self.file_name(),
+                    "code": diagnostic.code(),
+                    "lint": diagnostic.kind.lint_name(),
+                    "severity": severity_name(&diagnostic.severity),
+                    "message": diagnostic.message,
+                    "span": diagnostic.span.map(|span| span_json(source, span)),
+                    "related_span": diagnostic.kind.first_span().map(|span| span_json(source, span)),
+                    "suggestions": suggestions,
+                })
+            })
+            .collect()
+    }
+
+    /// A SARIF 2.1.0 log with one run and one rule per distinct code.
+    pub fn to_sarif(&self, source: &str) -> Value {
+        let mut rule_ids: Vec<&'static str> = Vec::new();
+        for diagnostic in self.get_diagnostics() {
+            if !rule_ids.contains(&diagnostic.code()) {
+                rule_ids.push(diagnostic.code());
+            }
+        }
+
+        let rules = rule_ids
+            .iter()
+            .map(|code| {
+                let explanation = codes::explain(code, Language::English).unwrap_or_default();
+                let summary = explanation.lines().next().unwrap_or_default();
+                json!({
+                    "id": code,
+                    "shortDescription": { "text": summary },
+                    "fullDescription": { "text": explanation },
+                    "help": { "text": format!("Run `mortar explain {}` for details.", code) },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let results = self
+            .get_diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                let rule_index = rule_ids
+                    .iter()
+                    .position(|code| *code == diagnostic.code())
+                    .unwrap_or_default();
+                let mut result = json!({
+                    "ruleId": diagnostic.code(),
+                    "ruleIndex": rule_index,
+                    "level": severity_name(&diagnostic.severity),
+                    "message": { "text": diagnostic.message },
+                    "locations": [self.sarif_location(source, diagnostic.span)],
+                });
+
+                if let Some(first_span) = diagnostic.kind.first_span() {
+                    result["relatedLocations"] = json!([{
+                        "id": 0,
+                        "physicalLocation": self.sarif_physical_location(source, first_span),
+                    }]);
+                }
+
+                let fixes = self.sarif_fixes(source, diagnostic);
+                if !fixes.is_empty() {
+                    result["fixes"] = Value::Array(fixes);
+                }
+
+                result
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "mortar",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": INFORMATION_URI,
+                        "rules": rules,
+                    }
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": results,
+            }],
+        })
+    }
+
+    /// SARIF artifact locations are URI references, so Windows separators become slashes.
+    fn artifact_uri(&self) -> String {
+        self.file_name().replace('\\', "/")
+    }
+
+    fn short_message(&self, source: &str, diagnostic: &Diagnostic) -> String {
+        let location = match diagnostic.span {
+            Some((start, _end)) => {
+                let (line, col) = get_line_col(source, start);
+                format!("{}:{}:{}", self.file_name(), line, col)
+            }
+            None => self.file_name().to_string(),
+        };
+
+        format!(
+            "{}: {}[{}]: {}",
+            location,
+            severity_name(&diagnostic.severity),
+            diagnostic.code(),
+            diagnostic.message
+        )
+    }
+
+    fn sarif_location(&self, source: &str, span: Option<(usize, usize)>) -> Value {
+        match span {
+            Some(span) => json!({ "physicalLocation": self.sarif_physical_location(source, span) }),
+            None => json!({
+                "physicalLocation": { "artifactLocation": { "uri": self.artifact_uri() } }
+            }),
+        }
+    }
+
+    fn sarif_physical_location(&self, source: &str, span: (usize, usize)) -> Value {
+        json!({
+            "artifactLocation": { "uri": self.artifact_uri() },
+            "region": sarif_region(source, span),
+        })
+    }
+
+    fn sarif_fixes(&self, source: &str, diagnostic: &Diagnostic) -> Vec<Value> {
+        diagnostic
+            .suggestions(self.language())
+            .into_iter()
+            .map(|suggestion| {
+                json!({
+                    "description": { "text": suggestion.message },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": self.artifact_uri() },
+                        "replacements": [{
+                            "deletedRegion": sarif_region(source, suggestion.span),
+                            "insertedContent": { "text": suggestion.replacement },
+                        }],
+                    }],
+                })
+            })
+            .collect()
+    }
+}
+
+fn severity_name(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// Byte offsets plus 1-based line and column (in characters) of both ends.
+fn span_json(source: &str, (start, end): (usize, usize)) -> Value {
+    let (line_start, column_start) = get_line_col(source, start);
+    let (line_end, column_end) = get_line_col(source, end);
+    json!({
+        "byte_start": start,
+        "byte_end": end,
+        "line_start": line_start,
+        "column_start": column_start,
+        "line_end": line_end,
+        "column_end": column_end,
+    })
+}
+
+/// A SARIF region; columns are code points, matching the run's `columnKind`.
+fn sarif_region(source: &str, (start, end): (usize, usize)) -> Value {
+    let (start_line, start_column) = get_line_col(source, start);
+    let (end_line, end_column) = get_line_col(source, end);
+    json!({
+        "startLine": start_line,
+        "startColumn": start_column,
+        "endLine": end_line,
+        "endColumn": end_column,
+        "byteOffset": start,
+        "byteLength": end.saturating_sub(start),
+    })
+}
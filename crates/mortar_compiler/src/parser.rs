@@ -24,15 +24,18 @@
 
 pub mod error;
 pub mod expression;
+pub mod incremental;
 pub mod statement;
 pub mod top_level;
 
+use incremental::IncrementalParse;
 use top_level::TopLevelParser;
 
 use crate::ast::Program;
+use crate::config::ProjectConfig;
 use crate::diagnostics::{Diagnostic, DiagnosticCollector, DiagnosticKind, Severity};
 use crate::token::{Token, TokenInfo};
-use error::ParseError;
+use error::{ParseError, Span};
 
 pub struct ParseHandler;
 
@@ -40,14 +43,6 @@ impl ParseHandler {
     pub fn parse_source_code(content: &str, verbose_lexer: bool) -> Result<Program, ParseError> {
         let tokens = if verbose_lexer {
             crate::token::lex_with_output(content)
-                .into_iter()
-                .map(|token| TokenInfo {
-                    token,
-                    start: 0, // We'll use better position tracking later
-                    end: 0,
-                    text: "",
-                })
-                .collect()
         } else {
             crate::token::tokenize(content)
         };
@@ -57,7 +52,7 @@ impl ParseHandler {
 
         // If there are accumulated errors, return the first one (since this API returns simple Result)
         if !parser.errors.is_empty() {
-            return Err(parser.errors[0].0.clone());
+            return Err(parser.errors[0].clone());
         }
 
         result
@@ -81,65 +76,109 @@ impl ParseHandler {
         file_name: String,
         verbose_lexer: bool,
         language: crate::Language,
+    ) -> (Result<Program, ParseError>, DiagnosticCollector) {
+        Self::parse_source_code_with_config(
+            content,
+            file_name,
+            verbose_lexer,
+            language,
+            &ProjectConfig::default(),
+        )
+    }
+
+    /// Parse and analyze with the settings of a `mortar.toml` project configuration.
+    pub fn parse_source_code_with_config(
+        content: &str,
+        file_name: String,
+        verbose_lexer: bool,
+        language: crate::Language,
+        config: &ProjectConfig,
     ) -> (Result<Program, ParseError>, DiagnosticCollector) {
         let tokens = if verbose_lexer {
             crate::token::lex_with_output(content)
-                .into_iter()
-                .map(|token| TokenInfo {
-                    token,
-                    start: 0, // We'll use better position tracking later
-                    end: 0,
-                    text: "",
-                })
-                .collect()
         } else {
             crate::token::tokenize(content)
         };
 
         let mut parser = Parser::new(tokens);
-        let mut diagnostics = DiagnosticCollector::new_with_language(file_name, language);
-
         let result = parser.parse_program();
 
-        // Handle accumulated errors (from recovery)
-        for (error, span) in &parser.errors {
-            diagnostics.add_diagnostic(Diagnostic {
-                kind: DiagnosticKind::SyntaxError {
-                    message: error.to_string(),
-                },
-                severity: Severity::Error,
-                span: Some(*span),
-                message: error.to_string(),
-            });
-        }
+        let diagnostics = diagnose(
+            content,
+            file_name,
+            language,
+            config,
+            parser.errors.iter().chain(result.as_ref().err()),
+            result.as_ref().ok(),
+        );
 
-        // If parsing failed fatally, add parse error to diagnostics
-        if let Err(ref parse_error) = result {
-            let current_span = parser.get_current_span();
-            diagnostics.add_diagnostic(Diagnostic {
-                kind: DiagnosticKind::SyntaxError {
-                    message: parse_error.to_string(),
-                },
-                severity: Severity::Error,
-                span: current_span,
-                message: parse_error.to_string(),
-            });
-        }
+        (result, diagnostics)
+    }
 
-        // If parsing succeeded (even partially), run semantic analysis
-        if let Ok(ref program) = result {
-            diagnostics.analyze_program(program);
-        }
+    /// The diagnostics `parse_source_code_with_config` would give for the source of `parse`.
+    pub fn diagnose_incremental(
+        parse: &IncrementalParse,
+        file_name: String,
+        language: crate::Language,
+        config: &ProjectConfig,
+    ) -> DiagnosticCollector {
+        diagnose(
+            parse.source(),
+            file_name,
+            language,
+            config,
+            parse.errors(),
+            Some(parse.program()),
+        )
+    }
+}
 
-        (result, diagnostics)
+/// Report syntax errors, then analyze the program that was recovered, if any.
+fn diagnose<'e>(
+    content: &str,
+    file_name: String,
+    language: crate::Language,
+    config: &ProjectConfig,
+    errors: impl Iterator<Item = &'e ParseError>,
+    program: Option<&Program>,
+) -> DiagnosticCollector {
+    let mut diagnostics = DiagnosticCollector::new_with_language(file_name, language);
+    diagnostics.set_event_index_options(config.event_index_options());
+
+    for error in errors {
+        diagnostics.add_diagnostic(syntax_error_diagnostic(error));
+    }
+
+    // If parsing succeeded (even partially), run semantic analysis
+    if let Some(program) = program {
+        diagnostics.analyze_program(program);
+    }
+
+    diagnostics.apply_lint_levels(
+        &config.lint_config(),
+        &crate::lint::collect_inline_attributes(content),
+    );
+
+    diagnostics
+}
+
+fn syntax_error_diagnostic(error: &ParseError) -> Diagnostic {
+    Diagnostic {
+        kind: DiagnosticKind::SyntaxError {
+            message: error.to_string(),
+            code: error.code(),
+        },
+        severity: Severity::Error,
+        span: Some(error.span()),
+        message: error.to_string(),
     }
 }
 
 pub struct Parser<'a> {
     pub(super) tokens: Vec<TokenInfo<'a>>,
     pub(super) current: usize,
-    /// Accumulated errors with their span (start, end)
-    pub(super) errors: Vec<(ParseError, (usize, usize))>,
+    /// Errors recovered from so far; parsing continues after each of them
+    pub(super) errors: Vec<ParseError>,
 }
 
 impl<'a> Parser<'a> {
@@ -160,22 +199,90 @@ impl<'a> Parser<'a> {
     }
 
     pub(super) fn advance(&mut self) -> Option<&TokenInfo<'_>> {
-        if !self.is_at_end() {
-            self.current += 1;
+        if self.is_at_end() {
+            return None;
         }
+        self.current += 1;
         self.tokens.get(self.current - 1)
     }
 
-    pub(super) fn get_current_span(&self) -> Option<(usize, usize)> {
-        if let Some(token_info) = self.peek() {
-            Some((token_info.start, token_info.end))
-        } else if self.current > 0 {
-            // If we're at the end, use the last token's position
-            self.tokens
-                .get(self.current - 1)
-                .map(|token_info| (token_info.start, token_info.end))
+    /// Span of the current token, or an empty span just past the last token at the end of input.
+    pub(super) fn current_span(&self) -> Span {
+        match self.peek() {
+            Some(token_info) => (token_info.start, token_info.end),
+            None => self.eof_span(),
+        }
+    }
+
+    /// Span of the most recently consumed token.
+    pub(super) fn previous_span(&self) -> Span {
+        self.current
+            .checked_sub(1)
+            .and_then(|index| self.tokens.get(index))
+            .map(|token_info| (token_info.start, token_info.end))
+            .unwrap_or((0, 0))
+    }
+
+    /// Span from the token at index `start` to the end of the last consumed token.
+    ///
+    /// Trailing comments are not part of the construct, so they are left out.
+    pub(super) fn span_from(&self, start: usize) -> Span {
+        let Some(first) = self.tokens.get(start) else {
+            return self.eof_span();
+        };
+
+        let last = self.tokens[start..self.current]
+            .iter()
+            .rev()
+            .find(|token_info| {
+                !matches!(
+                    token_info.token,
+                    Token::SingleLineComment(_) | Token::MultiLineComment(_)
+                )
+            });
+
+        match last {
+            Some(last) => (first.start, last.end),
+            None => (first.start, first.start),
+        }
+    }
+
+    fn eof_span(&self) -> Span {
+        self.tokens
+            .last()
+            .map(|token_info| (token_info.end, token_info.end))
+            .unwrap_or((0, 0))
+    }
+
+    /// Describe the current token for error messages.
+    pub(super) fn found(&self) -> String {
+        self.peek()
+            .map(|t| format!("{}", t.token))
+            .unwrap_or_else(|| "EOF".to_string())
+    }
+
+    /// An error at the current token, listing what would have been accepted instead.
+    pub(super) fn unexpected(&self, expected: &[&str]) -> ParseError {
+        let expected = expected.iter().map(|e| e.to_string()).collect();
+        if self.is_at_end() {
+            ParseError::UnexpectedEOF {
+                expected,
+                span: self.eof_span(),
+            }
         } else {
-            None
+            ParseError::UnexpectedToken {
+                expected,
+                found: self.found(),
+                span: self.current_span(),
+            }
+        }
+    }
+
+    /// A free-form error at the current token.
+    pub(super) fn error_here(&self, message: &str) -> ParseError {
+        ParseError::Custom {
+            message: message.to_string(),
+            span: self.current_span(),
         }
     }
 
@@ -195,42 +302,65 @@ impl<'a> Parser<'a> {
         if self.check(expected) {
             Ok(self.advance().unwrap())
         } else {
-            let found = self
-                .peek()
-                .map(|t| format!("{}", t.token))
-                .unwrap_or_else(|| "EOF".to_string());
-            Err(ParseError::UnexpectedToken {
-                expected: format!("{}", expected),
-                found,
-            })
+            Err(self.unexpected(&[&format!("'{}'", expected)]))
         }
     }
 
     pub(super) fn consume_identifier(&mut self, _error_msg: &str) -> Result<String, ParseError> {
-        if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                Ok(name.to_string())
-            } else {
-                Err(ParseError::ExpectedIdentifier {
-                    found: format!("{}", token_info.token),
-                })
-            }
-        } else {
-            Err(ParseError::UnexpectedEOF)
+        self.expect_identifier().map(|(name, _span)| name)
+    }
+
+    /// Consume an identifier and return it together with its span.
+    pub(super) fn expect_identifier(&mut self) -> Result<(String, Span), ParseError> {
+        match self.peek() {
+            Some(token_info) => match &token_info.token {
+                Token::Identifier(name) => {
+                    let result = (name.to_string(), (token_info.start, token_info.end));
+                    self.advance();
+                    Ok(result)
+                }
+                _ => Err(ParseError::ExpectedIdentifier {
+                    found: self.found(),
+                    span: self.current_span(),
+                }),
+            },
+            None => Err(self.unexpected(&["identifier"])),
         }
     }
 
+    /// Consume a string literal and decode it, see [`crate::token::string_value`].
     pub(super) fn consume_string(&mut self, _error_msg: &str) -> Result<String, ParseError> {
-        if let Some(token_info) = self.advance() {
-            if let Token::String(s) = &token_info.token {
-                Ok(s.to_string())
-            } else {
-                Err(ParseError::ExpectedString {
-                    found: format!("{}", token_info.token),
-                })
+        match self.peek() {
+            Some(token_info) => match &token_info.token {
+                Token::String(_) => {
+                    let start = token_info.start;
+                    let value = crate::token::string_value(token_info.text)
+                        .map_err(|error| ParseError::from(error.offset(start)))?
+                        .into_owned();
+                    self.advance();
+                    Ok(value)
+                }
+                _ => Err(ParseError::ExpectedString {
+                    found: self.found(),
+                    span: self.current_span(),
+                }),
+            },
+            None => Err(self.unexpected(&["string"])),
+        }
+    }
+
+    /// Consume a number literal.
+    pub(super) fn expect_number(&mut self) -> Result<f64, ParseError> {
+        match self.peek().map(|t| &t.token) {
+            Some(Token::Number(n)) => {
+                let value = n.parse::<f64>().map_err(|_| ParseError::InvalidNumber {
+                    value: n.to_string(),
+                    span: self.current_span(),
+                })?;
+                self.advance();
+                Ok(value)
             }
-        } else {
-            Err(ParseError::UnexpectedEOF)
+            _ => Err(self.unexpected(&["number"])),
         }
     }
 
@@ -279,10 +409,35 @@ impl<'a> Parser<'a> {
         }
     }
 
-    /// Synchronize parser state after an error.
-    /// Skips tokens until a semicolon or a keyword that starts a statement.
-    pub(super) fn synchronize(&mut self) {
-        self.advance(); // Consume the token that caused the error
+    /// Whether the current token starts a top-level declaration.
+    ///
+    /// `event` only counts when followed by a name, so `with event { ... }` is not mistaken for one.
+    pub(super) fn at_top_level_start(&self) -> bool {
+        match self.peek().map(|t| &t.token) {
+            Some(
+                Token::Node
+                | Token::Fn
+                | Token::Let
+                | Token::Const
+                | Token::Pub
+                | Token::Enum
+                | Token::Timeline,
+            ) => true,
+            Some(Token::Event) => matches!(
+                self.tokens.get(self.current + 1).map(|t| &t.token),
+                Some(Token::Identifier(_))
+            ),
+            _ => false,
+        }
+    }
+
+    /// Synchronize parser state after a top-level error.
+    /// Skips tokens until a semicolon or a keyword that starts a declaration.
+    pub(super) fn synchronize(&mut self, start: usize) {
+        // Always make progress, even if the declaration failed on its first token
+        if self.current == start {
+            self.advance();
+        }
 
         while !self.is_at_end() {
             // If previous token was semicolon, we are probably at a new statement
@@ -293,23 +448,41 @@ impl<'a> Parser<'a> {
                 return;
             }
 
-            // If current token is a keyword that starts a top-level declaration
-            if let Some(curr) = self.peek() {
-                match curr.token {
-                    Token::Node
-                    | Token::Fn
-                    | Token::Let
-                    | Token::Const
-                    | Token::Pub
-                    | Token::Enum
-                    | Token::Event
-                    | Token::Timeline => {
-                        return;
+            if self.at_top_level_start() {
+                return;
+            }
+
+            self.advance();
+        }
+    }
+
+    /// Skip the rest of a statement or list item that failed to parse.
+    ///
+    /// Stops before `close` or a `}` at the bracket depth where the error occurred,
+    /// before the start of the next item (as decided by `starts_item`),
+    /// or before a top-level keyword, so the enclosing construct can carry on.
+    pub(super) fn recover(&mut self, start: usize, close: &Token, starts_item: fn(&Self) -> bool) {
+        // Always make progress, so a token that cannot start anything is not retried forever
+        if self.current == start {
+            self.advance();
+        }
+
+        let mut depth = 0usize;
+        while let Some(token_info) = self.peek() {
+            match token_info.token {
+                Token::LeftBrace | Token::LeftBracket | Token::LeftParen => depth += 1,
+                Token::RightBrace | Token::RightBracket | Token::RightParen => {
+                    if depth == 0 {
+                        if self.check(close) || self.check(&Token::RightBrace) {
+                            return;
+                        }
+                    } else {
+                        depth -= 1;
                     }
-                    _ => {}
                 }
+                _ if depth == 0 && (self.at_top_level_start() || starts_item(self)) => return,
+                _ => {}
             }
-
             self.advance();
         }
     }
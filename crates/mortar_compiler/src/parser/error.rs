@@ -1,32 +1,151 @@
+use crate::codes;
+use crate::token::EscapeError;
 use std::fmt;
 
+pub use crate::ast::Span;
+
+/// A syntax error with the exact source range it refers to.
+///
+/// `expected` lists what the parser would have accepted at that point, e.g. `'}'` or `identifier`.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParseError {
-    UnexpectedToken { expected: String, found: String },
-    ExpectedIdentifier { found: String },
-    ExpectedString { found: String },
-    UnexpectedEOF,
-    InvalidNumber(String),
-    Custom(String),
+    UnexpectedToken {
+        expected: Vec<String>,
+        found: String,
+        span: Span,
+    },
+    ExpectedIdentifier {
+        found: String,
+        span: Span,
+    },
+    ExpectedString {
+        found: String,
+        span: Span,
+    },
+    UnexpectedEOF {
+        expected: Vec<String>,
+        span: Span,
+    },
+    InvalidNumber {
+        value: String,
+        span: Span,
+    },
+    InvalidEscape {
+        sequence: String,
+        span: Span,
+    },
+    Custom {
+        message: String,
+        span: Span,
+    },
+}
+
+impl ParseError {
+    /// The stable diagnostic code of this error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::UnexpectedToken { .. } => codes::UNEXPECTED_TOKEN,
+            ParseError::ExpectedIdentifier { .. } => codes::EXPECTED_IDENTIFIER,
+            ParseError::ExpectedString { .. } => codes::EXPECTED_STRING,
+            ParseError::UnexpectedEOF { .. } => codes::UNEXPECTED_EOF,
+            ParseError::InvalidNumber { .. } => codes::INVALID_NUMBER,
+            ParseError::InvalidEscape { .. } => codes::INVALID_ESCAPE,
+            ParseError::Custom { .. } => codes::SYNTAX_ERROR,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::ExpectedIdentifier { span, .. }
+            | ParseError::ExpectedString { span, .. }
+            | ParseError::UnexpectedEOF { span, .. }
+            | ParseError::InvalidNumber { span, .. }
+            | ParseError::InvalidEscape { span, .. }
+            | ParseError::Custom { span, .. } => *span,
+        }
+    }
+
+    pub(super) fn span_mut(&mut self) -> &mut Span {
+        match self {
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::ExpectedIdentifier { span, .. }
+            | ParseError::ExpectedString { span, .. }
+            | ParseError::UnexpectedEOF { span, .. }
+            | ParseError::InvalidNumber { span, .. }
+            | ParseError::InvalidEscape { span, .. }
+            | ParseError::Custom { span, .. } => span,
+        }
+    }
+
+    /// What the parser would have accepted where the error occurred.
+    pub fn expected(&self) -> Vec<String> {
+        match self {
+            ParseError::UnexpectedToken { expected, .. }
+            | ParseError::UnexpectedEOF { expected, .. } => expected.clone(),
+            ParseError::ExpectedIdentifier { .. } => vec!["identifier".to_string()],
+            ParseError::ExpectedString { .. } => vec!["string".to_string()],
+            ParseError::InvalidNumber { .. }
+            | ParseError::InvalidEscape { .. }
+            | ParseError::Custom { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Join expected items as `a`, `a or b`, or `a, b or c`.
+fn describe_expected(expected: &[String]) -> String {
+    match expected {
+        [] => "something else".to_string(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} or {}", rest.join(", "), last),
+    }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ParseError::UnexpectedToken { expected, found } => {
-                write!(f, "Expected {}, found {}", expected, found)
+            ParseError::UnexpectedToken {
+                expected, found, ..
+            } => {
+                write!(
+                    f,
+                    "Expected {}, found {}",
+                    describe_expected(expected),
+                    found
+                )
             }
-            ParseError::ExpectedIdentifier { found } => {
+            ParseError::ExpectedIdentifier { found, .. } => {
                 write!(f, "Expected identifier, found {}", found)
             }
-            ParseError::ExpectedString { found } => {
+            ParseError::ExpectedString { found, .. } => {
                 write!(f, "Expected string, found {}", found)
             }
-            ParseError::UnexpectedEOF => write!(f, "Unexpected end of input"),
-            ParseError::InvalidNumber(s) => write!(f, "Invalid number: {}", s),
-            ParseError::Custom(s) => write!(f, "{}", s),
+            ParseError::UnexpectedEOF { expected, .. } if expected.is_empty() => {
+                write!(f, "Unexpected end of input")
+            }
+            ParseError::UnexpectedEOF { expected, .. } => {
+                write!(
+                    f,
+                    "Unexpected end of input, expected {}",
+                    describe_expected(expected)
+                )
+            }
+            ParseError::InvalidNumber { value, .. } => write!(f, "Invalid number: {}", value),
+            ParseError::InvalidEscape { sequence, .. } => {
+                write!(f, "Invalid escape sequence: {}", sequence)
+            }
+            ParseError::Custom { message, .. } => write!(f, "{}", message),
         }
     }
 }
 
 impl std::error::Error for ParseError {}
+
+impl From<EscapeError> for ParseError {
+    fn from(error: EscapeError) -> Self {
+        ParseError::InvalidEscape {
+            sequence: error.sequence,
+            span: error.span,
+        }
+    }
+}
@@ -23,8 +23,13 @@ pub trait ExpressionParser {
     fn parse_type(&mut self) -> Result<String, ParseError>;
 
     fn parse_interpolated_string(&mut self, text: &str) -> Result<InterpolatedString, ParseError>;
-    fn parse_expression_from_string(&mut self, expr_text: &str) -> Result<FuncCall, ParseError>;
-    fn parse_simple_args(&mut self, args_text: &str) -> Result<Vec<Arg>, ParseError>;
+    fn parse_expression_from_string(
+        &mut self,
+        expr_text: &str,
+        offset: usize,
+    ) -> Result<FuncCall, ParseError>;
+    fn parse_simple_args(&mut self, args_text: &str, offset: usize)
+    -> Result<Vec<Arg>, ParseError>;
 }
 
 impl<'a> ExpressionParser for Parser<'a> {
@@ -37,11 +42,15 @@ impl<'a> ExpressionParser for Parser<'a> {
 
         while self.check(&Token::Or) {
             self.advance();
+            let operator_span = self.previous_span();
             let right = self.parse_and_expression()?;
+            let span = (left.span().0, right.span().1);
             left = IfCondition::Binary(Box::new(BinaryCondition {
                 left,
                 operator: ComparisonOp::Or,
+                operator_span,
                 right,
+                span,
             }));
         }
 
@@ -53,11 +62,15 @@ impl<'a> ExpressionParser for Parser<'a> {
 
         while self.check(&Token::And) {
             self.advance();
+            let operator_span = self.previous_span();
             let right = self.parse_comparison_expression()?;
+            let span = (left.span().0, right.span().1);
             left = IfCondition::Binary(Box::new(BinaryCondition {
                 left,
                 operator: ComparisonOp::And,
+                operator_span,
                 right,
+                span,
             }));
         }
 
@@ -69,11 +82,15 @@ impl<'a> ExpressionParser for Parser<'a> {
 
         while let Some(op) = self.peek_comparison_op() {
             self.advance(); // consume operator
+            let operator_span = self.previous_span();
             let right = self.parse_unary_expression()?;
+            let span = (left.span().0, right.span().1);
             left = IfCondition::Binary(Box::new(BinaryCondition {
                 left,
                 operator: op,
+                operator_span,
                 right,
+                span,
             }));
         }
 
@@ -83,10 +100,14 @@ impl<'a> ExpressionParser for Parser<'a> {
     fn parse_unary_expression(&mut self) -> Result<IfCondition, ParseError> {
         if self.check(&Token::Not) {
             self.advance();
+            let operator_span = self.previous_span();
             let operand = self.parse_unary_expression()?;
+            let span = (operator_span.0, operand.span().1);
             return Ok(IfCondition::Unary(Box::new(UnaryCondition {
                 operator: UnaryOp::Not,
+                operator_span,
                 operand,
+                span,
             })));
         }
 
@@ -105,12 +126,12 @@ impl<'a> ExpressionParser for Parser<'a> {
         // Handle boolean literals
         if self.check(&Token::True) {
             self.advance();
-            return Ok(IfCondition::Literal(true));
+            return Ok(IfCondition::Literal(true, self.previous_span()));
         }
 
         if self.check(&Token::False) {
             self.advance();
-            return Ok(IfCondition::Literal(false));
+            return Ok(IfCondition::Literal(false, self.previous_span()));
         }
 
         // Handle identifiers (variables, enum members) and numbers
@@ -118,28 +139,27 @@ impl<'a> ExpressionParser for Parser<'a> {
             match &token_info.token {
                 Token::Identifier(name) => {
                     let name = name.to_string();
+                    let start = self.current;
                     self.advance();
                     // Check for enum member access (EnumName.member)
                     if self.check(&Token::Dot) {
                         self.advance(); // consume '.'
                         let member =
                             self.consume_identifier("Expected enum member name after '.'")?;
-                        return Ok(IfCondition::EnumMember(name, member));
+                        return Ok(IfCondition::EnumMember(name, member, self.span_from(start)));
                     }
-                    return Ok(IfCondition::Identifier(name));
+                    return Ok(IfCondition::Identifier(name, self.previous_span()));
                 }
                 Token::Number(num) => {
                     let num = num.to_string();
                     self.advance();
-                    return Ok(IfCondition::Identifier(num));
+                    return Ok(IfCondition::Identifier(num, self.previous_span()));
                 }
                 _ => {}
             }
         }
 
-        Err(ParseError::Custom(
-            "Expected condition expression".to_string(),
-        ))
+        Err(self.unexpected(&["condition expression"]))
     }
 
     fn peek_comparison_op(&self) -> Option<ComparisonOp> {
@@ -155,33 +175,24 @@ impl<'a> ExpressionParser for Parser<'a> {
     }
 
     fn parse_func_call(&mut self) -> Result<FuncCall, ParseError> {
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            match &token_info.token {
-                Token::Identifier(name) => {
-                    (name.to_string(), Some((token_info.start, token_info.end)))
-                }
+        let start = self.current;
+        let (name, name_span) = if let Some(token_info) = self.peek() {
+            let name_span = Some((token_info.start, token_info.end));
+            let name = match &token_info.token {
+                Token::Identifier(name) => name.to_string(),
                 // Allow certain keywords as function names for backwards compatibility
-                Token::Wait => ("wait".to_string(), Some((token_info.start, token_info.end))),
-                Token::Run => ("run".to_string(), Some((token_info.start, token_info.end))),
-                Token::Action => (
-                    "action".to_string(),
-                    Some((token_info.start, token_info.end)),
-                ),
-                Token::Index => (
-                    "index".to_string(),
-                    Some((token_info.start, token_info.end)),
-                ),
-                Token::Duration => (
-                    "duration".to_string(),
-                    Some((token_info.start, token_info.end)),
-                ),
-                _ => {
-                    return Err(ParseError::Custom("Expected function name".to_string()));
-                }
-            }
+                Token::Wait => "wait".to_string(),
+                Token::Run => "run".to_string(),
+                Token::Action => "action".to_string(),
+                Token::Index => "index".to_string(),
+                Token::Duration => "duration".to_string(),
+                _ => return Err(self.unexpected(&["function name"])),
+            };
+            (name, name_span)
         } else {
-            return Err(ParseError::Custom("Expected function name".to_string()));
+            return Err(self.unexpected(&["function name"]));
         };
+        self.advance();
 
         self.consume(&Token::LeftParen, "Expected '('")?;
 
@@ -202,30 +213,27 @@ impl<'a> ExpressionParser for Parser<'a> {
             name,
             name_span,
             args,
+            span: self.span_from(start),
         })
     }
 
     fn parse_arg(&mut self) -> Result<Arg, ParseError> {
         match self.peek().map(|t| &t.token) {
-            Some(Token::String(s)) => {
-                let s = s.to_string();
-                self.advance();
-                Ok(Arg::String(s))
+            Some(Token::String(_)) => {
+                let s = self.consume_string("Expected string")?;
+                Ok(Arg::String(s, self.previous_span()))
             }
-            Some(Token::Number(n)) => {
-                let n = n
-                    .parse::<f64>()
-                    .map_err(|_| ParseError::InvalidNumber(n.to_string()))?;
-                self.advance();
-                Ok(Arg::Number(n))
+            Some(Token::Number(_)) => {
+                let value = self.expect_number()?;
+                Ok(Arg::Number(value, self.previous_span()))
             }
             Some(Token::True) => {
                 self.advance();
-                Ok(Arg::Boolean(true))
+                Ok(Arg::Boolean(true, self.previous_span()))
             }
             Some(Token::False) => {
                 self.advance();
-                Ok(Arg::Boolean(false))
+                Ok(Arg::Boolean(false, self.previous_span()))
             }
             Some(Token::Identifier(name)) => {
                 // Look ahead to see if it's a function call
@@ -234,131 +242,138 @@ impl<'a> ExpressionParser for Parser<'a> {
                 } else {
                     let name = name.to_string();
                     self.advance();
-                    Ok(Arg::Identifier(name))
+                    Ok(Arg::Identifier(name, self.previous_span()))
                 }
             }
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "argument".to_string(),
-                found: format!("{:?}", self.peek().map(|t| &t.token)),
-            }),
+            _ => Err(self.unexpected(&["argument"])),
         }
     }
 
     fn parse_assign_value(&mut self) -> Result<AssignValue, ParseError> {
         match self.peek().map(|t| &t.token) {
-            Some(Token::String(s)) => {
-                let value = s.to_string();
-                self.advance();
-                Ok(AssignValue::String(value))
+            Some(Token::String(_)) => {
+                let value = self.consume_string("Expected string")?;
+                Ok(AssignValue::String(value, self.previous_span()))
             }
-            Some(Token::Number(n)) => {
-                let value = n
-                    .parse::<f64>()
-                    .map_err(|_| ParseError::InvalidNumber(n.to_string()))?;
-                self.advance();
-                Ok(AssignValue::Number(value))
+            Some(Token::Number(_)) => {
+                let value = self.expect_number()?;
+                Ok(AssignValue::Number(value, self.previous_span()))
             }
             Some(Token::True) => {
                 self.advance();
-                Ok(AssignValue::Boolean(true))
+                Ok(AssignValue::Boolean(true, self.previous_span()))
             }
             Some(Token::False) => {
                 self.advance();
-                Ok(AssignValue::Boolean(false))
+                Ok(AssignValue::Boolean(false, self.previous_span()))
             }
             Some(Token::Identifier(name)) => {
                 let first_name = name.to_string();
+                let start = self.current;
                 self.advance();
                 // Check for enum member access (EnumName.member)
                 if self.check(&Token::Dot) {
                     self.advance(); // consume '.'
                     let member = self.consume_identifier("Expected enum member name after '.'")?;
-                    Ok(AssignValue::EnumMember(first_name, member))
+                    Ok(AssignValue::EnumMember(
+                        first_name,
+                        member,
+                        self.span_from(start),
+                    ))
                 } else {
-                    Ok(AssignValue::Identifier(first_name))
+                    Ok(AssignValue::Identifier(first_name, self.previous_span()))
                 }
             }
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "value (string, number, boolean, identifier, or enum member)".to_string(),
-                found: format!("{:?}", self.peek().map(|t| &t.token)),
-            }),
+            _ => {
+                Err(self.unexpected(&["string", "number", "boolean", "identifier", "enum member"]))
+            }
         }
     }
 
     fn parse_var_value(&mut self) -> Result<VarValue, ParseError> {
         match self.peek().map(|t| &t.token) {
-            Some(Token::String(s)) => {
-                let value = s.to_string();
-                self.advance();
-                Ok(VarValue::String(value))
+            Some(Token::String(_)) => {
+                let value = self.consume_string("Expected string")?;
+                Ok(VarValue::String(value, self.previous_span()))
             }
-            Some(Token::Number(n)) => {
-                let value = n
-                    .parse::<f64>()
-                    .map_err(|_| ParseError::InvalidNumber(n.to_string()))?;
-                self.advance();
-                Ok(VarValue::Number(value))
+            Some(Token::Number(_)) => {
+                let value = self.expect_number()?;
+                Ok(VarValue::Number(value, self.previous_span()))
             }
             Some(Token::True) => {
                 self.advance();
-                Ok(VarValue::Boolean(true))
+                Ok(VarValue::Boolean(true, self.previous_span()))
             }
             Some(Token::False) => {
                 self.advance();
-                Ok(VarValue::Boolean(false))
+                Ok(VarValue::Boolean(false, self.previous_span()))
             }
             Some(Token::Identifier(name)) => {
                 let enum_name = name.to_string();
+                let start = self.current;
                 self.advance();
                 // Check for enum member access (EnumName.member)
                 if self.check(&Token::Dot) {
                     self.advance(); // consume '.'
                     let member = self.consume_identifier("Expected enum member name after '.'")?;
-                    Ok(VarValue::EnumMember(enum_name, member))
+                    Ok(VarValue::EnumMember(
+                        enum_name,
+                        member,
+                        self.span_from(start),
+                    ))
                 } else {
-                    Err(ParseError::Custom(format!(
-                        "Unexpected identifier '{}' in variable value",
-                        enum_name
-                    )))
+                    Err(ParseError::Custom {
+                        message: format!("Unexpected identifier '{}' in variable value", enum_name),
+                        span: self.previous_span(),
+                    })
                 }
             }
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "value (string, number, boolean, or enum member)".to_string(),
-                found: format!("{:?}", self.peek().map(|t| &t.token)),
-            }),
+            _ => Err(self.unexpected(&["string", "number", "boolean", "enum member"])),
         }
     }
 
     fn parse_type(&mut self) -> Result<String, ParseError> {
-        match self.advance().map(|t| &t.token) {
-            Some(Token::Identifier(type_name)) => Ok(type_name.to_string()),
-            Some(Token::StringType) => Ok("String".to_string()),
-            Some(Token::NumberType) => Ok("Number".to_string()),
-            Some(Token::BooleanType) => Ok("Boolean".to_string()),
-            _ => Err(ParseError::Custom("Expected type".to_string())),
-        }
+        let type_name = match self.peek().map(|t| &t.token) {
+            Some(Token::Identifier(type_name)) => type_name.to_string(),
+            Some(Token::StringType) => "String".to_string(),
+            Some(Token::NumberType) => "Number".to_string(),
+            Some(Token::BooleanType) => "Boolean".to_string(),
+            _ => return Err(self.unexpected(&["type"])),
+        };
+        self.advance();
+        Ok(type_name)
     }
 
     fn parse_interpolated_string(&mut self, text: &str) -> Result<InterpolatedString, ParseError> {
+        // The string token was just consumed; its content starts right after the leading `$"`
+        let string_span = self.previous_span();
+        let base = string_span.0 + 2;
+
         let mut parts = Vec::new();
-        let mut chars = text.chars().peekable();
+        let mut chars = text.char_indices().peekable();
         let mut current_text = String::new();
+        let mut text_start = 0;
 
-        while let Some(ch) = chars.next() {
+        while let Some((pos, ch)) = chars.next() {
             if ch == '{' {
                 // Save any accumulated text
                 if !current_text.is_empty() {
-                    parts.push(StringPart::Text(current_text.clone()));
+                    parts.push(StringPart::Text(
+                        decode_text(&current_text, base + text_start)?,
+                        (base + text_start, base + pos),
+                    ));
                     current_text.clear();
                 }
 
                 // Parse expression until '}'
+                let expr_start = pos + 1;
+                let mut expr_end = text.len();
                 let mut expr_text = String::new();
                 let mut brace_count = 1;
                 let mut in_string = false;
                 let mut escape_next = false;
 
-                for expr_ch in chars.by_ref() {
+                for (expr_pos, expr_ch) in chars.by_ref() {
                     if escape_next {
                         expr_text.push(expr_ch);
                         escape_next = false;
@@ -384,6 +399,7 @@ impl<'a> ExpressionParser for Parser<'a> {
                         } else if expr_ch == '}' {
                             brace_count -= 1;
                             if brace_count == 0 {
+                                expr_end = expr_pos;
                                 break;
                             }
                             expr_text.push(expr_ch);
@@ -396,90 +412,139 @@ impl<'a> ExpressionParser for Parser<'a> {
                 }
 
                 if brace_count != 0 {
-                    eprintln!(
-                        "DEBUG: brace_count={}, in_string={}, expr_text={:?}",
-                        brace_count, in_string, expr_text
-                    );
-                    return Err(ParseError::Custom(
-                        "Unmatched '{' in interpolated string".to_string(),
-                    ));
+                    return Err(ParseError::Custom {
+                        message: "Unmatched '{' in interpolated string".to_string(),
+                        span: (base + pos, base + expr_end),
+                    });
                 }
 
                 // Check if this is a simple placeholder (identifier) or function call
                 let expr_trimmed = expr_text.trim();
                 if expr_trimmed.contains('(') {
                     // It's a function call
-                    let func_call = self.parse_expression_from_string(&expr_text)?;
+                    let func_call =
+                        self.parse_expression_from_string(&expr_text, base + expr_start)?;
                     parts.push(StringPart::Expression(func_call));
                 } else {
                     // It's a simple placeholder
-                    parts.push(StringPart::Placeholder(expr_trimmed.to_string()));
+                    let leading = expr_text.len() - expr_text.trim_start().len();
+                    let start = base + expr_start + leading;
+                    parts.push(StringPart::Placeholder(
+                        expr_trimmed.to_string(),
+                        (start, start + expr_trimmed.len()),
+                    ));
                 }
             } else {
+                if current_text.is_empty() {
+                    text_start = pos;
+                }
                 current_text.push(ch);
+                // An escaped brace is text, not the start of an expression
+                if ch == '\\'
+                    && let Some((_, escaped)) = chars.next()
+                {
+                    current_text.push(escaped);
+                }
             }
         }
 
         // Save any remaining text
         if !current_text.is_empty() {
-            parts.push(StringPart::Text(current_text));
+            parts.push(StringPart::Text(
+                decode_text(&current_text, base + text_start)?,
+                (base + text_start, base + text.len()),
+            ));
         }
 
-        Ok(InterpolatedString { parts })
+        Ok(InterpolatedString {
+            parts,
+            span: string_span,
+        })
     }
 
-    fn parse_expression_from_string(&mut self, expr_text: &str) -> Result<FuncCall, ParseError> {
+    fn parse_expression_from_string(
+        &mut self,
+        expr_text: &str,
+        offset: usize,
+    ) -> Result<FuncCall, ParseError> {
         // Simple parsing of "function_name()" or "function_name(args)"
+        // `offset` is the source position of `expr_text`, used to give every part its span
+        let offset = offset + expr_text.len() - expr_text.trim_start().len();
         let expr_text = expr_text.trim();
+        let span = (offset, offset + expr_text.len());
 
         if let Some(paren_pos) = expr_text.find('(') {
             let func_name = expr_text[..paren_pos].trim();
             let args_part = &expr_text[paren_pos + 1..];
 
             if !args_part.ends_with(')') {
-                return Err(ParseError::Custom(
-                    "Expected ')' at end of function call".to_string(),
-                ));
+                return Err(ParseError::Custom {
+                    message: "Expected ')' at end of function call".to_string(),
+                    span,
+                });
             }
 
-            let args_part = &args_part[..args_part.len() - 1].trim();
-            let args = if args_part.is_empty() {
+            let args_offset = offset + paren_pos + 1;
+            let args_part = &args_part[..args_part.len() - 1];
+            let args = if args_part.trim().is_empty() {
                 Vec::new()
             } else {
                 // For now, only support simple arguments (this could be expanded)
-                self.parse_simple_args(args_part)?
+                self.parse_simple_args(args_part, args_offset)?
             };
 
             Ok(FuncCall {
                 name: func_name.to_string(),
-                name_span: None, // We don't have precise span info from string parsing
+                name_span: Some((offset, offset + func_name.len())),
                 args,
+                span,
             })
         } else {
-            Err(ParseError::Custom(
-                "Expression in interpolated string must be a function call".to_string(),
-            ))
+            Err(ParseError::Custom {
+                message: "Expression in interpolated string must be a function call".to_string(),
+                span,
+            })
         }
     }
 
-    fn parse_simple_args(&mut self, args_text: &str) -> Result<Vec<Arg>, ParseError> {
+    fn parse_simple_args(
+        &mut self,
+        args_text: &str,
+        offset: usize,
+    ) -> Result<Vec<Arg>, ParseError> {
         let mut args = Vec::new();
+        let mut arg_offset = offset;
 
-        for arg in args_text.split(',') {
-            let arg = arg.trim();
-            if arg.starts_with('"') && arg.ends_with('"') {
-                args.push(Arg::String(arg[1..arg.len() - 1].to_string()));
+        for raw_arg in args_text.split(',') {
+            let arg = raw_arg.trim();
+            let start = arg_offset + raw_arg.len() - raw_arg.trim_start().len();
+            let span = (start, start + arg.len());
+            arg_offset += raw_arg.len() + 1; // skip the comma
+
+            if arg.len() >= 2 && arg.starts_with('"') && arg.ends_with('"') {
+                let value = decode_text(&arg[1..arg.len() - 1], start + 1)?;
+                args.push(Arg::String(value, span));
             } else if arg.chars().all(|c| c.is_ascii_digit() || c == '.') {
                 if let Ok(num) = arg.parse::<f64>() {
-                    args.push(Arg::Number(num));
+                    args.push(Arg::Number(num, span));
                 } else {
-                    return Err(ParseError::InvalidNumber(arg.to_string()));
+                    return Err(ParseError::InvalidNumber {
+                        value: arg.to_string(),
+                        span,
+                    });
                 }
             } else {
-                args.push(Arg::Identifier(arg.to_string()));
+                args.push(Arg::Identifier(arg.to_string(), span));
             }
         }
 
         Ok(args)
     }
 }
+
+/// Decode the escapes in a piece of string text that starts at `offset` in the source.
+fn decode_text(raw: &str, offset: usize) -> Result<String, ParseError> {
+    crate::token::unescape(raw)
+        .map(|value| value.into_owned())
+        .map_err(|error| error.offset(offset).into())
+}
@@ -0,0 +1,617 @@
+use std::ops::Range;
+
+use logos::Logos;
+
+use super::Parser;
+use super::error::ParseError;
+use super::top_level::TopLevelParser;
+use crate::ast::*;
+use crate::token::{Token, TokenInfo, tokenize};
+use crate::visit::{self, VisitMut};
+
+/// One attempt at parsing a top-level item.
+pub(super) struct Segment {
+    /// `None` when the attempt failed and its tokens were skipped.
+    pub(super) item: Option<TopLevel>,
+    /// Indices into the parser's errors of the errors reported during the attempt.
+    pub(super) errors: Range<usize>,
+    /// From the first token of the attempt to the end of the last token it consumed.
+    pub(super) span: Span,
+}
+
+impl Parser<'_> {
+    /// Parse every top-level item, keeping track of the source and errors of each attempt.
+    pub(super) fn parse_segments(&mut self) -> Vec<Segment> {
+        let mut segments = Vec::new();
+
+        while !self.is_at_end() {
+            self.skip_comments_and_separators();
+            if self.is_at_end() {
+                break;
+            }
+
+            let start = self.current;
+            let first_error = self.errors.len();
+            let item = match self.parse_top_level() {
+                Ok(item) => Some(item),
+                Err(err) => {
+                    self.errors.push(err);
+                    self.synchronize(start);
+                    None
+                }
+            };
+            segments.push(Segment {
+                item,
+                errors: first_error..self.errors.len(),
+                span: (self.tokens[start].start, self.previous_span().1),
+            });
+        }
+
+        segments
+    }
+}
+
+/// What is kept of a segment between edits; its item lives in the program.
+#[derive(Debug, Clone)]
+struct Parsed {
+    span: Span,
+    errors: Vec<ParseError>,
+    has_item: bool,
+    /// Whether a token of the segment failed to lex, as an unterminated string
+    /// does. An edit further on can close it, which changes every token up to there.
+    has_lex_error: bool,
+}
+
+/// A parsed source that can be updated after an edit, re-parsing only the
+/// top-level items the edit touches.
+///
+/// Updating always gives the same program and errors as parsing the new
+/// source from scratch. When that cannot be guaranteed, for example because an
+/// unterminated string now runs into the following items, the whole source is
+/// parsed again.
+#[derive(Debug, Clone)]
+pub struct IncrementalParse {
+    source: String,
+    program: Program,
+    segments: Vec<Parsed>,
+}
+
+impl IncrementalParse {
+    /// Parse `source` from scratch.
+    pub fn new(source: &str) -> Self {
+        let region = parse_tokens(tokenize(source));
+        Self {
+            source: source.to_string(),
+            program: Program {
+                body: region.items,
+                span: (0, region.program_end.unwrap_or(0)),
+            },
+            segments: region.segments,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// The syntax errors of the source, in order.
+    pub fn errors(&self) -> impl Iterator<Item = &ParseError> {
+        self.segments.iter().flat_map(|segment| &segment.errors)
+    }
+
+    /// Bring the parse up to date with `source`, the previous source after an edit.
+    ///
+    /// Returns `true` when items outside the edit were reused, and `false` when
+    /// everything was parsed again.
+    pub fn update(&mut self, source: &str) -> bool {
+        if source == self.source {
+            return true;
+        }
+        let changed = changed_range(&self.source, source);
+        self.edit(source, changed)
+    }
+
+    /// Bring the parse up to date with `source`, the previous source with the
+    /// bytes in `changed` replaced, without comparing the two sources.
+    ///
+    /// The text outside `changed` must be the same in both. Returns `true` when
+    /// items outside the edit were reused, and `false` when everything was parsed again.
+    pub fn edit(&mut self, source: &str, changed: Range<usize>) -> bool {
+        let in_bounds = changed.start <= changed.end
+            && changed.end <= self.source.len()
+            && self.source.len() - changed.len() <= source.len();
+        if in_bounds {
+            debug_assert!(
+                source.starts_with(&self.source[..changed.start])
+                    && source.ends_with(&self.source[changed.end..]),
+                "the source differs outside the changed range"
+            );
+            if self.reparse(source, changed) {
+                return true;
+            }
+        }
+        *self = Self::new(source);
+        false
+    }
+
+    /// Re-parse the part of `source` that changed, leaving `self` untouched if that is not possible.
+    fn reparse(&mut self, source: &str, changed: Range<usize>) -> bool {
+        let (edit_start, old_edit_end) = (changed.start, changed.end);
+        let delta = source.len() as isize - self.source.len() as isize;
+
+        // Re-parse from one item before the edit, since the parser may look at
+        // the first tokens of the next item when deciding where one ends. That
+        // item must be complete, so it has the two tokens that lookahead needs.
+        let touched = self
+            .segments
+            .iter()
+            .position(|segment| segment.span.1 >= edit_start)
+            .unwrap_or(self.segments.len());
+        // A string left open before the edit may be closed by it
+        let relexed = self.segments[..touched]
+            .iter()
+            .position(|segment| segment.has_lex_error)
+            .unwrap_or(touched);
+        let mut first = relexed.saturating_sub(1);
+        while first > 0 && !self.segments[first].has_item {
+            first -= 1;
+        }
+
+        // Resume reusing at the first complete item after the edit
+        let after = (touched..self.segments.len())
+            .find(|&i| self.segments[i].span.0 > old_edit_end && self.segments[i].has_item)
+            .unwrap_or(self.segments.len());
+
+        let region_start = if first == 0 {
+            0
+        } else {
+            self.segments[first].span.0
+        };
+        let region_end = match self.segments.get(after) {
+            Some(segment) => shift(segment.span.0, delta),
+            None => source.len(),
+        };
+
+        let Some(region) = parse_region(source, region_start, region_end) else {
+            return false;
+        };
+        let program_end = if after < self.segments.len() {
+            // An error at the end of the region would be reported at the next item instead
+            let last_token_end = region.last_token_end.unwrap_or(region_start);
+            if region
+                .segments
+                .iter()
+                .flat_map(|segment| &segment.errors)
+                .any(|error| error.span().0 >= last_token_end)
+            {
+                return false;
+            }
+            shift(self.program.span.1, delta)
+        } else {
+            match region.program_end {
+                Some(end) => end,
+                None => return false,
+            }
+        };
+
+        let items_before = self.items_in(0..first);
+        let items_replaced = self.items_in(first..after);
+
+        let mut shifter = SpanShift { delta };
+        for item in &mut self.program.body[items_before + items_replaced..] {
+            shifter.visit_top_level_mut(item);
+        }
+        for segment in &mut self.segments[after..] {
+            segment.span = shift_span(segment.span, delta);
+            for error in &mut segment.errors {
+                let span = error.span_mut();
+                *span = shift_span(*span, delta);
+            }
+        }
+
+        self.program
+            .body
+            .splice(items_before..items_before + items_replaced, region.items);
+        self.program.span = (0, program_end);
+        self.segments.splice(first..after, region.segments);
+        self.source = source.to_string();
+        true
+    }
+
+    fn items_in(&self, segments: Range<usize>) -> usize {
+        self.segments[segments]
+            .iter()
+            .filter(|segment| segment.has_item)
+            .count()
+    }
+}
+
+/// The result of parsing part of a source.
+struct Region {
+    segments: Vec<Parsed>,
+    items: Vec<TopLevel>,
+    /// End of the last token, where errors at the end of input point.
+    last_token_end: Option<usize>,
+    /// End of the last token that is not a comment, where a program ends.
+    program_end: Option<usize>,
+}
+
+/// Parse the top-level items of `source[start..end]`, which must start on a token.
+///
+/// Gives `None` when a token runs past `end`, as the text there then lexes
+/// differently from the whole source.
+fn parse_region(source: &str, start: usize, end: usize) -> Option<Region> {
+    let mut tokens = Vec::new();
+    let mut lexer = Token::lexer(&source[start..]);
+    while let Some(result) = lexer.next() {
+        let span = lexer.span();
+        let (token_start, token_end) = (start + span.start, start + span.end);
+        if token_start >= end {
+            break;
+        }
+        if token_end > end {
+            return None;
+        }
+        tokens.push(TokenInfo {
+            token: result.unwrap_or(Token::Error),
+            start: token_start,
+            end: token_end,
+            text: &source[token_start..token_end],
+        });
+    }
+    Some(parse_tokens(tokens))
+}
+
+/// Parse the top-level items of `tokens`.
+fn parse_tokens(tokens: Vec<TokenInfo>) -> Region {
+    let lex_errors: Vec<usize> = tokens
+        .iter()
+        .filter(|token| token.token == Token::Error)
+        .map(|token| token.start)
+        .collect();
+    let last_token_end = tokens.last().map(|token| token.end);
+    let program_end = match tokens.iter().rev().find(|token| {
+        !matches!(
+            token.token,
+            Token::SingleLineComment(_) | Token::MultiLineComment(_)
+        )
+    }) {
+        Some(token) => Some(token.end),
+        None => tokens.first().map(|token| token.start),
+    };
+
+    let mut parser = Parser::new(tokens);
+    let mut items = Vec::new();
+    let segments = parser
+        .parse_segments()
+        .into_iter()
+        .map(|segment| {
+            let has_item = segment.item.is_some();
+            items.extend(segment.item);
+            let (start, end) = segment.span;
+            Parsed {
+                span: segment.span,
+                errors: parser.errors[segment.errors].to_vec(),
+                has_item,
+                has_lex_error: lex_errors.iter().any(|&at| start <= at && at < end),
+            }
+        })
+        .collect();
+
+    Region {
+        segments,
+        items,
+        last_token_end,
+        program_end,
+    }
+}
+
+/// The byte range of `old` that differs from `new`, found by trimming their
+/// common prefix and suffix.
+fn changed_range(old: &str, new: &str) -> Range<usize> {
+    let mut prefix = common_prefix(old.as_bytes(), new.as_bytes());
+    while !old.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = common_suffix(old.as_bytes(), new.as_bytes(), max_suffix);
+    while !old.is_char_boundary(old.len() - suffix) {
+        suffix -= 1;
+    }
+
+    prefix..old.len() - suffix
+}
+
+/// Bytes compared at once while trimming; slice comparison stays fast even without optimizations.
+const CHUNK: usize = 256;
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    let len = a.len().min(b.len());
+    let mut prefix = 0;
+    while prefix + CHUNK <= len && a[prefix..prefix + CHUNK] == b[prefix..prefix + CHUNK] {
+        prefix += CHUNK;
+    }
+    while prefix < len && a[prefix] == b[prefix] {
+        prefix += 1;
+    }
+    prefix
+}
+
+fn common_suffix(a: &[u8], b: &[u8], max: usize) -> usize {
+    let (a_len, b_len) = (a.len(), b.len());
+    let mut suffix = 0;
+    while suffix + CHUNK <= max
+        && a[a_len - suffix - CHUNK..a_len - suffix] == b[b_len - suffix - CHUNK..b_len - suffix]
+    {
+        suffix += CHUNK;
+    }
+    while suffix < max && a[a_len - suffix - 1] == b[b_len - suffix - 1] {
+        suffix += 1;
+    }
+    suffix
+}
+
+fn shift(offset: usize, delta: isize) -> usize {
+    offset.saturating_add_signed(delta)
+}
+
+fn shift_span(span: Span, delta: isize) -> Span {
+    (shift(span.0, delta), shift(span.1, delta))
+}
+
+fn shift_option(span: &mut Option<Span>, delta: isize) {
+    if let Some(span) = span {
+        *span = shift_span(*span, delta);
+    }
+}
+
+/// Moves every span of the items it visits by `delta` bytes.
+struct SpanShift {
+    delta: isize,
+}
+
+impl SpanShift {
+    fn shift(&self, span: &mut Span) {
+        *span = shift_span(*span, self.delta);
+    }
+}
+
+impl VisitMut for SpanShift {
+    fn visit_node_def_mut(&mut self, node: &mut NodeDef) {
+        shift_option(&mut node.name_span, self.delta);
+        self.shift(&mut node.span);
+        visit::walk_node_def_mut(self, node);
+    }
+
+    fn visit_node_stmt_mut(&mut self, stmt: &mut NodeStmt) {
+        match stmt {
+            NodeStmt::Text(_, span)
+            | NodeStmt::InterpolatedText(_, span)
+            | NodeStmt::Choice(_, span) => self.shift(span),
+            _ => {}
+        }
+        visit::walk_node_stmt_mut(self, stmt);
+    }
+
+    fn visit_node_jump_mut(&mut self, jump: &mut NodeJump) {
+        match jump {
+            NodeJump::Identifier(_, span) | NodeJump::Return(span) | NodeJump::Break(span) => {
+                self.shift(span)
+            }
+        }
+    }
+
+    fn visit_if_else_mut(&mut self, if_else: &mut IfElseStmt) {
+        self.shift(&mut if_else.span);
+        visit::walk_if_else_mut(self, if_else);
+    }
+
+    fn visit_if_condition_mut(&mut self, condition: &mut IfCondition) {
+        match condition {
+            IfCondition::Identifier(_, span)
+            | IfCondition::EnumMember(_, _, span)
+            | IfCondition::Literal(_, span) => self.shift(span),
+            IfCondition::Binary(_) | IfCondition::Unary(_) => {}
+        }
+        visit::walk_if_condition_mut(self, condition);
+    }
+
+    fn visit_binary_condition_mut(&mut self, binary: &mut BinaryCondition) {
+        self.shift(&mut binary.operator_span);
+        self.shift(&mut binary.span);
+        visit::walk_binary_condition_mut(self, binary);
+    }
+
+    fn visit_unary_condition_mut(&mut self, unary: &mut UnaryCondition) {
+        self.shift(&mut unary.operator_span);
+        self.shift(&mut unary.span);
+        visit::walk_unary_condition_mut(self, unary);
+    }
+
+    fn visit_interpolated_string_mut(&mut self, interpolated: &mut InterpolatedString) {
+        self.shift(&mut interpolated.span);
+        visit::walk_interpolated_string_mut(self, interpolated);
+    }
+
+    fn visit_string_part_mut(&mut self, part: &mut StringPart) {
+        match part {
+            StringPart::Text(_, span) | StringPart::Placeholder(_, span) => self.shift(span),
+            StringPart::Expression(_) => {}
+        }
+        visit::walk_string_part_mut(self, part);
+    }
+
+    fn visit_branch_def_mut(&mut self, branch: &mut BranchDef) {
+        shift_option(&mut branch.name_span, self.delta);
+        self.shift(&mut branch.span);
+        visit::walk_branch_def_mut(self, branch);
+    }
+
+    fn visit_branch_case_mut(&mut self, case: &mut BranchCase) {
+        self.shift(&mut case.span);
+        visit::walk_branch_case_mut(self, case);
+    }
+
+    fn visit_event_mut(&mut self, event: &mut Event) {
+        self.shift(&mut event.index_span);
+        self.shift(&mut event.span);
+        visit::walk_event_mut(self, event);
+    }
+
+    fn visit_event_action_mut(&mut self, action: &mut EventAction) {
+        self.shift(&mut action.span);
+        visit::walk_event_action_mut(self, action);
+    }
+
+    fn visit_event_def_mut(&mut self, event_def: &mut EventDef) {
+        shift_option(&mut event_def.name_span, self.delta);
+        self.shift(&mut event_def.span);
+        visit::walk_event_def_mut(self, event_def);
+    }
+
+    fn visit_timeline_def_mut(&mut self, timeline: &mut TimelineDef) {
+        shift_option(&mut timeline.name_span, self.delta);
+        self.shift(&mut timeline.span);
+        visit::walk_timeline_def_mut(self, timeline);
+    }
+
+    fn visit_timeline_stmt_mut(&mut self, stmt: &mut TimelineStmt) {
+        if let TimelineStmt::Wait(_, span) = stmt {
+            self.shift(span);
+        }
+        visit::walk_timeline_stmt_mut(self, stmt);
+    }
+
+    fn visit_run_stmt_mut(&mut self, run: &mut RunStmt) {
+        shift_option(&mut run.event_name_span, self.delta);
+        self.shift(&mut run.span);
+        visit::walk_run_stmt_mut(self, run);
+    }
+
+    fn visit_index_override_mut(&mut self, index_override: &mut IndexOverride) {
+        match index_override {
+            IndexOverride::Value(_, span) | IndexOverride::Variable(_, span) => self.shift(span),
+        }
+    }
+
+    fn visit_with_events_mut(&mut self, with_events: &mut WithEventsStmt) {
+        self.shift(&mut with_events.span);
+        visit::walk_with_events_mut(self, with_events);
+    }
+
+    fn visit_with_event_item_mut(&mut self, item: &mut WithEventItem) {
+        match item {
+            WithEventItem::EventRef(_, span)
+            | WithEventItem::EventRefWithOverride(_, span, _)
+            | WithEventItem::EventList(_, span) => self.shift(span),
+            WithEventItem::InlineEvent(_) => {}
+        }
+        visit::walk_with_event_item_mut(self, item);
+    }
+
+    fn visit_choice_item_mut(&mut self, item: &mut ChoiceItem) {
+        self.shift(&mut item.span);
+        visit::walk_choice_item_mut(self, item);
+    }
+
+    fn visit_condition_mut(&mut self, condition: &mut Condition) {
+        if let Condition::Identifier(_, span) = condition {
+            self.shift(span);
+        }
+        visit::walk_condition_mut(self, condition);
+    }
+
+    fn visit_choice_dest_mut(&mut self, dest: &mut ChoiceDest) {
+        match dest {
+            ChoiceDest::Identifier(_, span)
+            | ChoiceDest::Return(span)
+            | ChoiceDest::Break(span)
+            | ChoiceDest::NestedChoices(_, span) => self.shift(span),
+        }
+        visit::walk_choice_dest_mut(self, dest);
+    }
+
+    fn visit_function_decl_mut(&mut self, function: &mut FunctionDecl) {
+        shift_option(&mut function.name_span, self.delta);
+        self.shift(&mut function.span);
+        visit::walk_function_decl_mut(self, function);
+    }
+
+    fn visit_param_mut(&mut self, param: &mut Param) {
+        self.shift(&mut param.span);
+    }
+
+    fn visit_func_call_mut(&mut self, call: &mut FuncCall) {
+        shift_option(&mut call.name_span, self.delta);
+        self.shift(&mut call.span);
+        visit::walk_func_call_mut(self, call);
+    }
+
+    fn visit_arg_mut(&mut self, arg: &mut Arg) {
+        match arg {
+            Arg::String(_, span)
+            | Arg::Number(_, span)
+            | Arg::Boolean(_, span)
+            | Arg::Identifier(_, span) => self.shift(span),
+            Arg::FuncCall(_) => {}
+        }
+        visit::walk_arg_mut(self, arg);
+    }
+
+    fn visit_var_decl_mut(&mut self, var_decl: &mut VarDecl) {
+        shift_option(&mut var_decl.name_span, self.delta);
+        self.shift(&mut var_decl.span);
+        visit::walk_var_decl_mut(self, var_decl);
+    }
+
+    fn visit_const_decl_mut(&mut self, const_decl: &mut ConstDecl) {
+        shift_option(&mut const_decl.name_span, self.delta);
+        self.shift(&mut const_decl.span);
+        visit::walk_const_decl_mut(self, const_decl);
+    }
+
+    fn visit_enum_def_mut(&mut self, enum_def: &mut EnumDef) {
+        shift_option(&mut enum_def.name_span, self.delta);
+        for span in &mut enum_def.variant_spans {
+            self.shift(span);
+        }
+        self.shift(&mut enum_def.span);
+    }
+
+    fn visit_var_value_mut(&mut self, value: &mut VarValue) {
+        match value {
+            VarValue::String(_, span)
+            | VarValue::Number(_, span)
+            | VarValue::Boolean(_, span)
+            | VarValue::EnumMember(_, _, span) => self.shift(span),
+            VarValue::Branch(_) => {}
+        }
+        visit::walk_var_value_mut(self, value);
+    }
+
+    fn visit_branch_value_mut(&mut self, branch: &mut BranchValue) {
+        self.shift(&mut branch.span);
+        visit::walk_branch_value_mut(self, branch);
+    }
+
+    fn visit_assignment_mut(&mut self, assignment: &mut Assignment) {
+        shift_option(&mut assignment.var_name_span, self.delta);
+        self.shift(&mut assignment.span);
+        visit::walk_assignment_mut(self, assignment);
+    }
+
+    fn visit_assign_value_mut(&mut self, value: &mut AssignValue) {
+        match value {
+            AssignValue::EnumMember(_, _, span)
+            | AssignValue::Identifier(_, span)
+            | AssignValue::Number(_, span)
+            | AssignValue::Boolean(_, span)
+            | AssignValue::String(_, span) => self.shift(span),
+        }
+    }
+}
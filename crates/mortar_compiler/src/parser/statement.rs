@@ -8,9 +8,11 @@ use crate::parser::expression::ExpressionParser;
 use crate::token::Token;
 
 pub trait StatementParser {
+    fn parse_node_body(&mut self) -> Vec<NodeStmt>;
     fn parse_node_stmt(&mut self) -> Result<NodeStmt, ParseError>;
     fn parse_text_stmt(&mut self) -> Result<NodeStmt, ParseError>;
     fn parse_choice_stmt(&mut self) -> Result<Vec<ChoiceItem>, ParseError>;
+    fn parse_choice_list(&mut self) -> Result<Vec<ChoiceItem>, ParseError>;
     fn parse_choice_item(&mut self) -> Result<ChoiceItem, ParseError>;
     fn parse_choice_cond(&mut self) -> Result<Condition, ParseError>;
     fn parse_choice_dest(&mut self) -> Result<ChoiceDest, ParseError>;
@@ -32,15 +34,48 @@ pub trait StatementParser {
 }
 
 impl<'a> StatementParser for Parser<'a> {
+    /// Parse statements up to the closing `}` of a node or block, which is left for the caller.
+    ///
+    /// A statement that fails to parse is recorded and skipped, so later mistakes in the same node are reported too.
+    fn parse_node_body(&mut self) -> Vec<NodeStmt> {
+        let mut body = Vec::new();
+        loop {
+            self.skip_comments_and_separators();
+
+            // A top-level keyword means the closing brace is missing; `let` gets its own error below
+            if self.check(&Token::RightBrace)
+                || self.is_at_end()
+                || (self.at_top_level_start() && !self.check(&Token::Let))
+            {
+                break;
+            }
+
+            let start = self.current;
+            match self.parse_node_stmt() {
+                Ok(stmt) => body.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.recover(start, &Token::RightBrace, starts_node_stmt);
+                }
+            }
+            self.skip_optional_separators();
+        }
+        body
+    }
+
     fn parse_node_stmt(&mut self) -> Result<NodeStmt, ParseError> {
         match self.peek().map(|t| &t.token) {
             Some(Token::If) => Ok(NodeStmt::IfElse(self.parse_if_else()?)),
             Some(Token::Text) => Ok(self.parse_text_stmt()?),
-            Some(Token::Events) => Err(ParseError::Custom("Standalone 'events:' is deprecated. Use 'with events:' after a text statement instead.".to_string())),
-            Some(Token::Choice) => Ok(NodeStmt::Choice(self.parse_choice_stmt()?)),
+            Some(Token::Events) => Err(self.error_here("Standalone 'events:' is deprecated. Use 'with events:' after a text statement instead.")),
+            Some(Token::Choice) => {
+                let start = self.current;
+                let items = self.parse_choice_stmt()?;
+                Ok(NodeStmt::Choice(items, self.span_from(start)))
+            }
             Some(Token::Run) => Ok(NodeStmt::Run(self.parse_run_stmt()?)),
             Some(Token::With) => Ok(NodeStmt::WithEvents(self.parse_with_events_stmt()?)),
-            Some(Token::Let) => Err(ParseError::Custom("Variable declarations with 'let' are not allowed inside nodes. Please define variables at the top level (outside of nodes).".to_string())),
+            Some(Token::Let) => Err(self.error_here("Variable declarations with 'let' are not allowed inside nodes. Please define variables at the top level (outside of nodes).")),
             Some(Token::Identifier(_)) => {
                 // Could be:
                 // 1. Assignment (name = value)
@@ -50,60 +85,79 @@ impl<'a> StatementParser for Parser<'a> {
                         Token::Equals => {
                             return Ok(NodeStmt::Assignment(self.parse_assignment()?));
                         }
-                        Token::Colon => {
+                        Token::Colon
                             if self.current + 2 < self.tokens.len()
-                                && matches!(self.tokens[self.current + 2].token, Token::Branch) {
-                                    return Ok(NodeStmt::Branch(self.parse_branch_def()?));
-                                }
-
+                                && matches!(self.tokens[self.current + 2].token, Token::Branch) =>
+                        {
+                            return Ok(NodeStmt::Branch(self.parse_branch_def()?));
                         }
                         _ => {}
                     }
                 }
-                Err(ParseError::Custom("Unexpected identifier in node body. Expected 'text', 'choice', 'run', 'with', assignment, or branch definition".to_string()))
+                Err(self.error_here("Unexpected identifier in node body. Expected 'text', 'choice', 'run', 'with', assignment, or branch definition"))
             }
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "'text', 'choice', 'run', 'with', assignment, or branch definition".to_string(),
-                found: self.peek().map(|t| format!("{}", t.token)).unwrap_or_else(|| "EOF".to_string())
-            }),
+            _ => Err(self.unexpected(&["'text'", "'choice'", "'run'", "'with'", "'if'", "assignment", "branch definition"])),
         }
     }
 
     fn parse_text_stmt(&mut self) -> Result<NodeStmt, ParseError> {
+        let start = self.current;
         self.consume(&Token::Text, "Expected 'text'")?;
         self.consume(&Token::Colon, "Expected ':'")?;
 
-        if let Some(token_info) = self.advance() {
-            match &token_info.token {
-                Token::String(text) => Ok(NodeStmt::Text(text.to_string())),
-                Token::InterpolatedString(text) => {
-                    let text_copy = text.to_string(); // Make a copy to avoid borrow issues
-                    let interpolated = self.parse_interpolated_string(&text_copy)?;
-                    Ok(NodeStmt::InterpolatedText(interpolated))
-                }
-                _ => Err(ParseError::ExpectedString {
-                    found: format!("{}", token_info.token),
-                }),
+        match self.peek().map(|t| &t.token) {
+            Some(Token::String(_)) => {
+                let text = self.consume_string("Expected text")?;
+                Ok(NodeStmt::Text(text, self.span_from(start)))
             }
-        } else {
-            Err(ParseError::UnexpectedEOF)
+            Some(Token::InterpolatedString(text)) => {
+                let text_copy = text.to_string(); // Make a copy to avoid borrow issues
+                self.advance();
+                let interpolated = self.parse_interpolated_string(&text_copy)?;
+                Ok(NodeStmt::InterpolatedText(
+                    interpolated,
+                    self.span_from(start),
+                ))
+            }
+            Some(_) => Err(ParseError::ExpectedString {
+                found: self.found(),
+                span: self.current_span(),
+            }),
+            None => Err(self.unexpected(&["string"])),
         }
     }
 
     fn parse_choice_stmt(&mut self) -> Result<Vec<ChoiceItem>, ParseError> {
         self.consume(&Token::Choice, "Expected 'choice'")?;
         self.consume(&Token::Colon, "Expected ':'")?;
+        self.parse_choice_list()
+    }
+
+    /// Parse `[ item, ... ]`, skipping over items that fail to parse.
+    fn parse_choice_list(&mut self) -> Result<Vec<ChoiceItem>, ParseError> {
         self.consume(&Token::LeftBracket, "Expected '['")?;
 
         let mut items = Vec::new();
-
-        while !self.check(&Token::RightBracket) && !self.is_at_end() {
+        loop {
             self.skip_comments_and_separators();
 
-            if !self.check(&Token::RightBracket) && !self.is_at_end() {
-                items.push(self.parse_choice_item()?);
-                self.skip_optional_separators();
+            if self.check(&Token::RightBracket)
+                || self.check(&Token::RightBrace)
+                || self.is_at_end()
+                || self.at_top_level_start()
+            {
+                break;
+            }
+
+            let start = self.current;
+            match self.parse_choice_item() {
+                Ok(item) => items.push(item),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.recover(start, &Token::RightBracket, starts_choice_item);
+                }
             }
+            self.skip_optional_separators();
         }
 
         self.consume(&Token::RightBracket, "Expected ']'")?;
@@ -111,32 +165,16 @@ impl<'a> StatementParser for Parser<'a> {
     }
 
     fn parse_choice_item(&mut self) -> Result<ChoiceItem, ParseError> {
+        let start = self.current;
+
         // Parse choice text
         let text = if self.check(&Token::LeftParen) {
             self.advance(); // consume '('
-            let text = if let Some(token_info) = self.advance() {
-                if let Token::String(s) = &token_info.token {
-                    s.to_string()
-                } else {
-                    return Err(ParseError::Custom(
-                        "Expected string in parentheses".to_string(),
-                    ));
-                }
-            } else {
-                return Err(ParseError::Custom(
-                    "Expected string in parentheses".to_string(),
-                ));
-            };
+            let text = self.consume_string("Expected string in parentheses")?;
             self.consume(&Token::RightParen, "Expected ')'")?;
             text
-        } else if let Some(token_info) = self.advance() {
-            if let Token::String(s) = &token_info.token {
-                s.to_string()
-            } else {
-                return Err(ParseError::Custom("Expected choice text".to_string()));
-            }
         } else {
-            return Err(ParseError::Custom("Expected choice text".to_string()));
+            self.consume_string("Expected choice text")?
         };
 
         // Parse optional condition
@@ -157,6 +195,7 @@ impl<'a> StatementParser for Parser<'a> {
             text,
             condition,
             target,
+            span: self.span_from(start),
         })
     }
 
@@ -183,41 +222,23 @@ impl<'a> StatementParser for Parser<'a> {
                 } else {
                     unreachable!()
                 };
-                let span = Some((token_info.start, token_info.end));
+                let span = (token_info.start, token_info.end);
                 Ok(ChoiceDest::Identifier(name, span))
             }
             Some(Token::Return) => {
                 self.advance();
-                Ok(ChoiceDest::Return)
+                Ok(ChoiceDest::Return(self.previous_span()))
             }
             Some(Token::Break) => {
                 self.advance();
-                Ok(ChoiceDest::Break)
+                Ok(ChoiceDest::Break(self.previous_span()))
             }
             Some(Token::LeftBracket) => {
-                self.advance(); // consume '['
-
-                let mut items = Vec::new();
-
-                while !self.check(&Token::RightBracket) && !self.is_at_end() {
-                    self.skip_comments_and_separators();
-
-                    if !self.check(&Token::RightBracket) && !self.is_at_end() {
-                        items.push(self.parse_choice_item()?);
-                        self.skip_optional_separators();
-                    }
-                }
-
-                self.consume(&Token::RightBracket, "Expected ']'")?;
-                Ok(ChoiceDest::NestedChoices(items))
+                let start = self.current;
+                let items = self.parse_choice_list()?;
+                Ok(ChoiceDest::NestedChoices(items, self.span_from(start)))
             }
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "choice destination".to_string(),
-                found: self
-                    .peek()
-                    .map(|t| format!("{}", t.token))
-                    .unwrap_or_else(|| "EOF".to_string()),
-            }),
+            _ => Err(self.unexpected(&["node name", "'return'", "'break'", "'['"])),
         }
     }
 
@@ -230,24 +251,20 @@ impl<'a> StatementParser for Parser<'a> {
                 } else {
                     let name = name.to_string();
                     self.advance();
-                    Ok(Condition::Identifier(name))
+                    Ok(Condition::Identifier(name, self.previous_span()))
                 }
             } else {
-                Err(ParseError::Custom(
-                    "Expected identifier or function call in condition".to_string(),
-                ))
+                Err(self.unexpected(&["identifier", "function call"]))
             }
         } else {
-            Err(ParseError::Custom(
-                "Expected identifier or function call in condition".to_string(),
-            ))
+            Err(self.unexpected(&["identifier", "function call"]))
         }
     }
 
     fn parse_branch_def(&mut self) -> Result<BranchDef, ParseError> {
-        let name_token = self.consume_identifier("Expected branch name")?;
-        let name = name_token.clone();
-        let name_span = Some((0, name.len())); // Approximate
+        let start = self.current;
+        let (name, name_span) = self.expect_identifier()?;
+        let name_span = Some(name_span);
 
         self.consume(&Token::Colon, "Expected ':' after branch name")?;
         self.consume(&Token::Branch, "Expected 'branch' keyword")?;
@@ -266,14 +283,25 @@ impl<'a> StatementParser for Parser<'a> {
         self.consume(&Token::LeftBracket, "Expected '[' to start branch cases")?;
 
         let mut cases = Vec::new();
+        loop {
+            // Cases can be separated by newlines or commas (optional)
+            self.skip_comments_and_separators();
 
-        while !self.check(&Token::RightBracket) && !self.is_at_end() {
-            let case = self.parse_branch_case()?;
-            cases.push(case);
+            if self.check(&Token::RightBracket)
+                || self.check(&Token::RightBrace)
+                || self.is_at_end()
+                || self.at_top_level_start()
+            {
+                break;
+            }
 
-            // Cases can be separated by newlines or commas (optional)
-            if self.check(&Token::Comma) {
-                self.advance();
+            let start = self.current;
+            match self.parse_branch_case() {
+                Ok(case) => cases.push(case),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.recover(start, &Token::RightBracket, starts_branch_case);
+                }
             }
         }
 
@@ -284,14 +312,18 @@ impl<'a> StatementParser for Parser<'a> {
             name_span,
             enum_type,
             cases,
+            span: self.span_from(start),
         })
     }
 
     fn parse_branch_case(&mut self) -> Result<BranchCase, ParseError> {
+        let start = self.current;
         let condition = self.consume_identifier("Expected condition or variant")?;
         self.consume(&Token::Comma, "Expected ',' after condition")?;
 
         let text = self.consume_string("Expected text for branch case")?;
+        // A trailing comma only separates cases, so it is not part of this one
+        let mut span = self.span_from(start);
 
         // Check for optional events
         let events = if self.check(&Token::Comma) {
@@ -300,7 +332,9 @@ impl<'a> StatementParser for Parser<'a> {
             if self.check(&Token::Events) {
                 self.advance(); // consume 'events'
                 self.consume(&Token::Colon, "Expected ':' after 'events'")?;
-                Some(self.parse_event_list()?)
+                let events = self.parse_event_list()?;
+                span = self.span_from(start);
+                Some(events)
             } else {
                 None
             }
@@ -312,6 +346,7 @@ impl<'a> StatementParser for Parser<'a> {
             condition,
             text,
             events,
+            span,
         })
     }
 
@@ -319,13 +354,24 @@ impl<'a> StatementParser for Parser<'a> {
         self.consume(&Token::LeftBracket, "Expected '[' to start events")?;
 
         let mut events = Vec::new();
+        loop {
+            self.skip_comments_and_separators();
 
-        while !self.check(&Token::RightBracket) && !self.is_at_end() {
-            let event = self.parse_event()?;
-            events.push(event);
+            if self.check(&Token::RightBracket)
+                || self.check(&Token::RightBrace)
+                || self.is_at_end()
+                || self.at_top_level_start()
+            {
+                break;
+            }
 
-            if self.check(&Token::Comma) {
-                self.advance();
+            let start = self.current;
+            match self.parse_event() {
+                Ok(event) => events.push(event),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.recover(start, &Token::RightBracket, starts_event);
+                }
             }
         }
 
@@ -335,30 +381,25 @@ impl<'a> StatementParser for Parser<'a> {
     }
 
     fn parse_event(&mut self) -> Result<Event, ParseError> {
-        let index = if let Some(token_info) = self.advance() {
-            if let Token::Number(n) = &token_info.token {
-                n.parse::<f64>()
-                    .map_err(|_| ParseError::InvalidNumber(n.to_string()))?
-            } else {
-                return Err(ParseError::Custom(
-                    "Expected number for event index".to_string(),
-                ));
-            }
-        } else {
-            return Err(ParseError::Custom(
-                "Expected number for event index".to_string(),
-            ));
-        };
+        let start = self.current;
+        let index = self.expect_number()?;
+        let index_span = self.previous_span();
 
         // Skip optional comma or semicolon after event index
         self.skip_optional_separators();
 
         let action = self.parse_event_action()?;
 
-        Ok(Event { index, action })
+        Ok(Event {
+            index,
+            index_span,
+            action,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_event_action(&mut self) -> Result<EventAction, ParseError> {
+        let start = self.current;
         let call = self.parse_func_call()?;
         let mut chains = Vec::new();
 
@@ -367,10 +408,15 @@ impl<'a> StatementParser for Parser<'a> {
             chains.push(self.parse_func_call()?);
         }
 
-        Ok(EventAction { call, chains })
+        Ok(EventAction {
+            call,
+            chains,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_if_else(&mut self) -> Result<IfElseStmt, ParseError> {
+        let start = self.current;
         self.consume(&Token::If, "Expected 'if'")?;
 
         // Parse condition
@@ -379,14 +425,7 @@ impl<'a> StatementParser for Parser<'a> {
         // Parse then body
         self.consume(&Token::LeftBrace, "Expected '{' after if condition")?;
 
-        let mut then_body = Vec::new();
-        while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            self.skip_comments_and_separators();
-            if self.check(&Token::RightBrace) {
-                break;
-            }
-            then_body.push(self.parse_node_stmt()?);
-        }
+        let then_body = self.parse_node_body();
 
         self.consume(&Token::RightBrace, "Expected '}' to end if body")?;
 
@@ -395,14 +434,7 @@ impl<'a> StatementParser for Parser<'a> {
             self.advance(); // consume 'else'
             self.consume(&Token::LeftBrace, "Expected '{' after else")?;
 
-            let mut body = Vec::new();
-            while !self.check(&Token::RightBrace) && !self.is_at_end() {
-                self.skip_comments_and_separators();
-                if self.check(&Token::RightBrace) {
-                    break;
-                }
-                body.push(self.parse_node_stmt()?);
-            }
+            let body = self.parse_node_body();
 
             self.consume(&Token::RightBrace, "Expected '}' to end else body")?;
             Some(body)
@@ -414,23 +446,16 @@ impl<'a> StatementParser for Parser<'a> {
             condition,
             then_body,
             else_body,
+            span: self.span_from(start),
         })
     }
 
     fn parse_run_stmt(&mut self) -> Result<RunStmt, ParseError> {
+        let start = self.current;
         self.consume(&Token::Run, "Expected 'run'")?;
 
-        let (event_name, event_name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::ExpectedIdentifier {
-                    found: format!("{}", token_info.token),
-                });
-            }
-        } else {
-            return Err(ParseError::UnexpectedEOF);
-        };
+        let (event_name, event_name_span) = self.expect_identifier()?;
+        let event_name_span = Some(event_name_span);
 
         let mut args = Vec::new();
 
@@ -454,20 +479,15 @@ impl<'a> StatementParser for Parser<'a> {
         let index_override = if self.check(&Token::With) {
             self.advance();
 
-            if let Some(Token::Number(n)) = self.peek().map(|t| &t.token) {
-                let value = n
-                    .parse::<f64>()
-                    .map_err(|_| ParseError::InvalidNumber(n.to_string()))?;
-                self.advance();
-                Some(IndexOverride::Value(value))
+            if self.check(&Token::Number("")) {
+                let value = self.expect_number()?;
+                Some(IndexOverride::Value(value, self.previous_span()))
             } else if let Some(Token::Identifier(name)) = self.peek().map(|t| &t.token) {
                 let name = name.to_string();
                 self.advance();
-                Some(IndexOverride::Variable(name))
+                Some(IndexOverride::Variable(name, self.previous_span()))
             } else {
-                return Err(ParseError::Custom(
-                    "Expected number or identifier after 'with'".to_string(),
-                ));
+                return Err(self.unexpected(&["number", "identifier"]));
             }
         } else {
             None
@@ -479,10 +499,12 @@ impl<'a> StatementParser for Parser<'a> {
             args,
             index_override,
             ignore_duration: false,
+            span: self.span_from(start),
         })
     }
 
     fn parse_with_events_stmt(&mut self) -> Result<WithEventsStmt, ParseError> {
+        let start = self.current;
         self.consume(&Token::With, "Expected 'with'")?;
 
         let mut events = Vec::new();
@@ -492,7 +514,7 @@ impl<'a> StatementParser for Parser<'a> {
             let run_stmt = self.parse_run_stmt()?;
 
             let name = run_stmt.event_name.clone();
-            let span = run_stmt.event_name_span;
+            let span = run_stmt.event_name_span.unwrap_or(run_stmt.span);
 
             if let Some(override_val) = run_stmt.index_override {
                 events.push(WithEventItem::EventRefWithOverride(
@@ -514,57 +536,57 @@ impl<'a> StatementParser for Parser<'a> {
             self.consume(&Token::Colon, "Expected ':' after 'events'")?;
             self.consume(&Token::LeftBracket, "Expected '['")?;
 
-            while !self.check(&Token::RightBracket) && !self.is_at_end() {
+            loop {
                 self.skip_comments_and_separators();
 
-                if self.check(&Token::RightBracket) {
+                if self.check(&Token::RightBracket)
+                    || self.check(&Token::RightBrace)
+                    || self.is_at_end()
+                    || self.at_top_level_start()
+                {
                     break;
                 }
 
-                if let Some(Token::Number(_)) = self.peek().map(|t| &t.token) {
-                    let event = self.parse_event()?;
-                    events.push(WithEventItem::InlineEvent(event));
+                let item_start = self.current;
+                let item = if self.check(&Token::Number("")) {
+                    self.parse_event().map(WithEventItem::InlineEvent)
                 } else if let Some(Token::Identifier(name)) = self.peek().map(|t| &t.token) {
                     let name = name.to_string();
-                    let span = self.peek().map(|t| (t.start, t.end));
                     self.advance();
-                    events.push(WithEventItem::EventRef(name, span));
+                    Ok(WithEventItem::EventRef(name, self.previous_span()))
                 } else {
-                    return Err(ParseError::Custom(
-                        "Expected event index or event name in 'with events' list".to_string(),
-                    ));
-                }
+                    Err(self.unexpected(&["event index", "event name"]))
+                };
 
+                match item {
+                    Ok(item) => events.push(item),
+                    Err(error) => {
+                        self.errors.push(error);
+                        self.recover(item_start, &Token::RightBracket, starts_with_event_item);
+                    }
+                }
                 self.skip_optional_separators();
             }
 
             self.consume(&Token::RightBracket, "Expected ']'")?;
         } else if let Some(Token::Identifier(name)) = self.peek().map(|t| &t.token) {
             let name = name.to_string();
-            let span = self.peek().map(|t| (t.start, t.end));
             self.advance();
-            events.push(WithEventItem::EventRef(name, span));
+            events.push(WithEventItem::EventRef(name, self.previous_span()));
         } else {
-            return Err(ParseError::Custom(
-                "Expected 'events', 'event', or event name after 'with'".to_string(),
-            ));
+            return Err(self.unexpected(&["'events'", "'event'", "'run'", "event name"]));
         }
 
-        Ok(WithEventsStmt { events })
+        Ok(WithEventsStmt {
+            events,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_assignment(&mut self) -> Result<Assignment, ParseError> {
-        let (var_name, var_name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::ExpectedIdentifier {
-                    found: format!("{}", token_info.token),
-                });
-            }
-        } else {
-            return Err(ParseError::UnexpectedEOF);
-        };
+        let start = self.current;
+        let (var_name, var_name_span) = self.expect_identifier()?;
+        let var_name_span = Some(var_name_span);
 
         self.consume(&Token::Equals, "Expected '=' after variable name")?;
 
@@ -574,6 +596,51 @@ impl<'a> StatementParser for Parser<'a> {
             var_name,
             var_name_span,
             value,
+            span: self.span_from(start),
         })
     }
 }
+
+fn token_at<'p>(parser: &'p Parser, offset: usize) -> Option<&'p Token<'p>> {
+    parser.tokens.get(parser.current + offset).map(|t| &t.token)
+}
+
+/// Whether the current token can begin a statement inside a node.
+///
+/// `with` is left out, since it also continues `run` statements and would cause a second error.
+fn starts_node_stmt(parser: &Parser) -> bool {
+    match token_at(parser, 0) {
+        Some(Token::Text | Token::Choice | Token::Run | Token::If | Token::Let | Token::Events) => {
+            true
+        }
+        Some(Token::Identifier(_)) => match token_at(parser, 1) {
+            Some(Token::Equals) => true,
+            Some(Token::Colon) => matches!(token_at(parser, 2), Some(Token::Branch)),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn starts_choice_item(parser: &Parser) -> bool {
+    matches!(token_at(parser, 0), Some(Token::String(_)))
+}
+
+fn starts_branch_case(parser: &Parser) -> bool {
+    matches!(token_at(parser, 0), Some(Token::Identifier(_)))
+        && matches!(token_at(parser, 1), Some(Token::Comma))
+        && matches!(token_at(parser, 2), Some(Token::String(_)))
+}
+
+fn starts_event(parser: &Parser) -> bool {
+    matches!(token_at(parser, 0), Some(Token::Number(_)))
+}
+
+/// Event references are bare names; names followed by `(` are calls inside an inline event.
+fn starts_with_event_item(parser: &Parser) -> bool {
+    match token_at(parser, 0) {
+        Some(Token::Number(_)) => true,
+        Some(Token::Identifier(_)) => !matches!(token_at(parser, 1), Some(Token::LeftParen)),
+        _ => false,
+    }
+}
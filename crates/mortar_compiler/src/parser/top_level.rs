@@ -30,25 +30,17 @@ pub trait TopLevelParser {
 
 impl<'a> TopLevelParser for Parser<'a> {
     fn parse_program(&mut self) -> Result<Program, ParseError> {
-        let mut body = Vec::new();
-
-        while !self.is_at_end() {
-            self.skip_comments_and_separators();
-
-            if !self.is_at_end() {
-                match self.parse_top_level() {
-                    Ok(stmt) => body.push(stmt),
-                    Err(err) => {
-                        // Capture the span of the error token (approximately)
-                        let span = self.get_current_span().unwrap_or((0, 0));
-                        self.errors.push((err, span));
-                        self.synchronize();
-                    }
-                }
-            }
-        }
-
-        Ok(Program { body })
+        let body = self
+            .parse_segments()
+            .into_iter()
+            .filter_map(|segment| segment.item)
+            .collect();
+
+        // The program covers everything from the start of the file to its last token
+        Ok(Program {
+            body,
+            span: (0, self.span_from(0).1),
+        })
     }
 
     fn parse_top_level(&mut self) -> Result<TopLevel, ParseError> {
@@ -64,48 +56,41 @@ impl<'a> TopLevelParser for Parser<'a> {
             Some(Token::Enum) => Ok(TopLevel::EnumDef(self.parse_enum_def()?)),
             Some(Token::Event) => Ok(TopLevel::EventDef(self.parse_event_def()?)),
             Some(Token::Timeline) => Ok(TopLevel::TimelineDef(self.parse_timeline_def()?)),
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "'node', 'fn', 'let', 'const', 'pub', 'enum', 'event', or 'timeline'"
-                    .to_string(),
-                found: self
-                    .peek()
-                    .map(|t| format!("{}", t.token))
-                    .unwrap_or_else(|| "EOF".to_string()),
-            }),
+            _ => Err(self.unexpected(&[
+                "'node'",
+                "'fn'",
+                "'let'",
+                "'const'",
+                "'pub'",
+                "'enum'",
+                "'event'",
+                "'timeline'",
+            ])),
         }
     }
 
     fn parse_node_def(&mut self) -> Result<NodeDef, ParseError> {
+        let start = self.current;
         self.consume(&Token::Node, "Expected 'node'")?;
 
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::Custom(
-                    "Expected identifier after 'node'".to_string(),
-                ));
-            }
-        } else {
-            return Err(ParseError::Custom(
-                "Expected identifier after 'node'".to_string(),
-            ));
-        };
+        let (name, name_span) = self.expect_identifier()?;
 
         self.consume(&Token::LeftBrace, "Expected '{'")?;
 
-        let mut body = Vec::new();
-        while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            self.skip_comments_and_separators();
+        let body = self.parse_node_body();
 
-            if !self.check(&Token::RightBrace) && !self.is_at_end() {
-                body.push(self.parse_node_stmt()?);
-                self.skip_optional_separators();
-            }
+        // Keep the node even if its closing brace is missing, so jumps to it still resolve
+        if let Err(error) = self.consume(&Token::RightBrace, "Expected '}'") {
+            self.errors.push(error);
+            return Ok(NodeDef {
+                name,
+                name_span: Some(name_span),
+                body,
+                jump: None,
+                span: self.span_from(start),
+            });
         }
 
-        self.consume(&Token::RightBrace, "Expected '}'")?;
-
         let jump = if self.check(&Token::Arrow) {
             Some(self.parse_node_jump()?)
         } else {
@@ -114,9 +99,10 @@ impl<'a> TopLevelParser for Parser<'a> {
 
         Ok(NodeDef {
             name,
-            name_span,
+            name_span: Some(name_span),
             body,
             jump,
+            span: self.span_from(start),
         })
     }
 
@@ -131,39 +117,26 @@ impl<'a> TopLevelParser for Parser<'a> {
                 } else {
                     unreachable!()
                 };
-                let span = Some((token_info.start, token_info.end));
+                let span = (token_info.start, token_info.end);
                 Ok(NodeJump::Identifier(name, span))
             }
             Some(Token::Return) => {
                 self.advance();
-                Ok(NodeJump::Return)
+                Ok(NodeJump::Return(self.previous_span()))
             }
             Some(Token::Break) => {
                 self.advance();
-                Ok(NodeJump::Break)
+                Ok(NodeJump::Break(self.previous_span()))
             }
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "identifier, 'return', or 'break'".to_string(),
-                found: self
-                    .peek()
-                    .map(|t| format!("{}", t.token))
-                    .unwrap_or_else(|| "EOF".to_string()),
-            }),
+            _ => Err(self.unexpected(&["node name", "'return'", "'break'"])),
         }
     }
 
     fn parse_function_decl(&mut self) -> Result<FunctionDecl, ParseError> {
+        let start = self.current;
         self.consume(&Token::Fn, "Expected 'fn'")?;
 
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::Custom("Expected function name".to_string()));
-            }
-        } else {
-            return Err(ParseError::Custom("Expected function name".to_string()));
-        };
+        let (name, name_span) = self.expect_identifier()?;
 
         self.consume(&Token::LeftParen, "Expected '('")?;
 
@@ -189,44 +162,33 @@ impl<'a> TopLevelParser for Parser<'a> {
 
         Ok(FunctionDecl {
             name,
-            name_span,
+            name_span: Some(name_span),
             params,
             return_type,
+            span: self.span_from(start),
         })
     }
 
     fn parse_param(&mut self) -> Result<Param, ParseError> {
-        let name = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                name.to_string()
-            } else {
-                return Err(ParseError::Custom("Expected parameter name".to_string()));
-            }
-        } else {
-            return Err(ParseError::Custom("Expected parameter name".to_string()));
-        };
+        let start = self.current;
+        let (name, _name_span) = self.expect_identifier()?;
 
         self.consume(&Token::Colon, "Expected ':'")?;
 
         let type_name = self.parse_type()?;
 
-        Ok(Param { name, type_name })
+        Ok(Param {
+            name,
+            type_name,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_var_decl(&mut self) -> Result<VarDecl, ParseError> {
+        let start = self.current;
         self.consume(&Token::Let, "Expected 'let'")?;
 
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::ExpectedIdentifier {
-                    found: format!("{}", token_info.token),
-                });
-            }
-        } else {
-            return Err(ParseError::UnexpectedEOF);
-        };
+        let (name, name_span) = self.expect_identifier()?;
 
         self.consume(&Token::Colon, "Expected ':' after variable name")?;
 
@@ -245,6 +207,7 @@ impl<'a> TopLevelParser for Parser<'a> {
             };
 
             // Parse branch cases in brackets: [condition, text, ...]
+            let branch_start = self.current;
             self.consume(&Token::LeftBracket, "Expected '[' to start branch cases")?;
 
             let mut cases = Vec::new();
@@ -263,9 +226,14 @@ impl<'a> TopLevelParser for Parser<'a> {
 
             Ok(VarDecl {
                 name,
-                name_span,
+                name_span: Some(name_span),
                 type_name: "Branch".to_string(),
-                value: Some(VarValue::Branch(BranchValue { enum_type, cases })),
+                value: Some(VarValue::Branch(BranchValue {
+                    enum_type,
+                    cases,
+                    span: self.span_from(branch_start),
+                })),
+                span: self.span_from(start),
             })
         } else {
             // Regular variable declaration
@@ -280,14 +248,16 @@ impl<'a> TopLevelParser for Parser<'a> {
 
             Ok(VarDecl {
                 name,
-                name_span,
+                name_span: Some(name_span),
                 type_name,
                 value,
+                span: self.span_from(start),
             })
         }
     }
 
     fn parse_const_decl(&mut self) -> Result<ConstDecl, ParseError> {
+        let start = self.current;
         let is_public = if self.check(&Token::Pub) {
             self.advance();
             true
@@ -297,15 +267,7 @@ impl<'a> TopLevelParser for Parser<'a> {
 
         self.consume(&Token::Const, "Expected 'const'")?;
 
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::Custom("Expected constant name".to_string()));
-            }
-        } else {
-            return Err(ParseError::Custom("Expected constant name".to_string()));
-        };
+        let (name, name_span) = self.expect_identifier()?;
 
         self.consume(&Token::Colon, "Expected ':' after constant name")?;
 
@@ -318,28 +280,23 @@ impl<'a> TopLevelParser for Parser<'a> {
         Ok(ConstDecl {
             is_public,
             name,
-            name_span,
+            name_span: Some(name_span),
             type_name,
             value,
+            span: self.span_from(start),
         })
     }
 
     fn parse_enum_def(&mut self) -> Result<EnumDef, ParseError> {
+        let start = self.current;
         self.consume(&Token::Enum, "Expected 'enum'")?;
 
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::Custom("Expected enum name".to_string()));
-            }
-        } else {
-            return Err(ParseError::Custom("Expected enum name".to_string()));
-        };
+        let (name, name_span) = self.expect_identifier()?;
 
         self.consume(&Token::LeftBrace, "Expected '{'")?;
 
         let mut variants = Vec::new();
+        let mut variant_spans = Vec::new();
 
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
             self.skip_comments_and_separators();
@@ -348,15 +305,9 @@ impl<'a> TopLevelParser for Parser<'a> {
                 break;
             }
 
-            if let Some(token_info) = self.advance() {
-                if let Token::Identifier(variant) = &token_info.token {
-                    variants.push(variant.to_string());
-                } else {
-                    return Err(ParseError::Custom("Expected enum variant name".to_string()));
-                }
-            } else {
-                return Err(ParseError::Custom("Expected enum variant name".to_string()));
-            }
+            let (variant, variant_span) = self.expect_identifier()?;
+            variants.push(variant);
+            variant_spans.push(variant_span);
 
             self.skip_optional_separators();
         }
@@ -365,27 +316,18 @@ impl<'a> TopLevelParser for Parser<'a> {
 
         Ok(EnumDef {
             name,
-            name_span,
+            name_span: Some(name_span),
             variants,
+            variant_spans,
+            span: self.span_from(start),
         })
     }
 
     fn parse_event_def(&mut self) -> Result<EventDef, ParseError> {
+        let start = self.current;
         self.consume(&Token::Event, "Expected 'event'")?;
 
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::Custom(
-                    "Expected identifier after 'event'".to_string(),
-                ));
-            }
-        } else {
-            return Err(ParseError::Custom(
-                "Expected identifier after 'event'".to_string(),
-            ));
-        };
+        let (name, name_span) = self.expect_identifier()?;
 
         self.consume(&Token::LeftBrace, "Expected '{'")?;
 
@@ -404,17 +346,7 @@ impl<'a> TopLevelParser for Parser<'a> {
                 Some(Token::Index) => {
                     self.advance();
                     self.consume(&Token::Colon, "Expected ':' after 'index'")?;
-                    if let Some(Token::Number(n)) = self.peek().map(|t| &t.token) {
-                        index = Some(
-                            n.parse::<f64>()
-                                .map_err(|_| ParseError::InvalidNumber(n.to_string()))?,
-                        );
-                        self.advance();
-                    } else {
-                        return Err(ParseError::Custom(
-                            "Expected number after 'index:'".to_string(),
-                        ));
-                    }
+                    index = Some(self.expect_number()?);
                 }
                 Some(Token::Action) => {
                     self.advance();
@@ -424,26 +356,10 @@ impl<'a> TopLevelParser for Parser<'a> {
                 Some(Token::Duration) => {
                     self.advance();
                     self.consume(&Token::Colon, "Expected ':' after 'duration'")?;
-                    if let Some(Token::Number(n)) = self.peek().map(|t| &t.token) {
-                        duration = Some(
-                            n.parse::<f64>()
-                                .map_err(|_| ParseError::InvalidNumber(n.to_string()))?,
-                        );
-                        self.advance();
-                    } else {
-                        return Err(ParseError::Custom(
-                            "Expected number after 'duration:'".to_string(),
-                        ));
-                    }
+                    duration = Some(self.expect_number()?);
                 }
                 _ => {
-                    return Err(ParseError::UnexpectedToken {
-                        expected: "'index', 'action', or 'duration'".to_string(),
-                        found: self
-                            .peek()
-                            .map(|t| format!("{}", t.token))
-                            .unwrap_or_else(|| "EOF".to_string()),
-                    });
+                    return Err(self.unexpected(&["'index'", "'action'", "'duration'"]));
                 }
             }
 
@@ -452,35 +368,26 @@ impl<'a> TopLevelParser for Parser<'a> {
 
         self.consume(&Token::RightBrace, "Expected '}'")?;
 
-        let action = action.ok_or(ParseError::Custom(
-            "Event definition must have an 'action' field".to_string(),
-        ))?;
+        let action = action.ok_or(ParseError::Custom {
+            message: "Event definition must have an 'action' field".to_string(),
+            span: name_span,
+        })?;
 
         Ok(EventDef {
             name,
-            name_span,
+            name_span: Some(name_span),
             index,
             action,
             duration,
+            span: self.span_from(start),
         })
     }
 
     fn parse_timeline_def(&mut self) -> Result<TimelineDef, ParseError> {
+        let start = self.current;
         self.consume(&Token::Timeline, "Expected 'timeline'")?;
 
-        let (name, name_span) = if let Some(token_info) = self.advance() {
-            if let Token::Identifier(name) = &token_info.token {
-                (name.to_string(), Some((token_info.start, token_info.end)))
-            } else {
-                return Err(ParseError::Custom(
-                    "Expected identifier after 'timeline'".to_string(),
-                ));
-            }
-        } else {
-            return Err(ParseError::Custom(
-                "Expected identifier after 'timeline'".to_string(),
-            ));
-        };
+        let (name, name_span) = self.expect_identifier()?;
 
         self.consume(&Token::LeftBrace, "Expected '{'")?;
 
@@ -488,18 +395,31 @@ impl<'a> TopLevelParser for Parser<'a> {
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
             self.skip_comments_and_separators();
 
-            if !self.check(&Token::RightBrace) && !self.is_at_end() {
-                body.push(self.parse_timeline_stmt()?);
-                self.skip_optional_separators();
+            if self.check(&Token::RightBrace) || self.is_at_end() || self.at_top_level_start() {
+                break;
+            }
+
+            let stmt_start = self.current;
+            match self.parse_timeline_stmt() {
+                Ok(stmt) => body.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.recover(stmt_start, &Token::RightBrace, starts_timeline_stmt);
+                }
             }
+            self.skip_optional_separators();
         }
 
-        self.consume(&Token::RightBrace, "Expected '}'")?;
+        // Like nodes, keep the timeline even if its closing brace is missing
+        if let Err(error) = self.consume(&Token::RightBrace, "Expected '}'") {
+            self.errors.push(error);
+        }
 
         Ok(TimelineDef {
             name,
-            name_span,
+            name_span: Some(name_span),
             body,
+            span: self.span_from(start),
         })
     }
 
@@ -508,35 +428,30 @@ impl<'a> TopLevelParser for Parser<'a> {
             Some(Token::Run) => Ok(TimelineStmt::Run(self.parse_run_stmt()?)),
             Some(Token::Now) => {
                 // Parse "now run EventName" - ignores duration
+                let start = self.current;
                 self.advance(); // consume "now"
                 if !self.check(&Token::Run) {
-                    return Err(ParseError::Custom("Expected 'run' after 'now'".to_string()));
+                    return Err(self.unexpected(&["'run'"]));
                 }
                 let mut run_stmt = self.parse_run_stmt()?;
                 run_stmt.ignore_duration = true;
+                run_stmt.span = self.span_from(start);
                 Ok(TimelineStmt::Run(run_stmt))
             }
             Some(Token::Wait) => {
+                let start = self.current;
                 self.advance();
-                if let Some(Token::Number(n)) = self.peek().map(|t| &t.token) {
-                    let duration = n
-                        .parse::<f64>()
-                        .map_err(|_| ParseError::InvalidNumber(n.to_string()))?;
-                    self.advance();
-                    Ok(TimelineStmt::Wait(duration))
-                } else {
-                    Err(ParseError::Custom(
-                        "Expected number after 'wait'".to_string(),
-                    ))
-                }
+                let duration = self.expect_number()?;
+                Ok(TimelineStmt::Wait(duration, self.span_from(start)))
             }
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "'run', 'now', or 'wait'".to_string(),
-                found: self
-                    .peek()
-                    .map(|t| format!("{}", t.token))
-                    .unwrap_or_else(|| "EOF".to_string()),
-            }),
+            _ => Err(self.unexpected(&["'run'", "'now'", "'wait'"])),
         }
     }
 }
+
+fn starts_timeline_stmt(parser: &Parser) -> bool {
+    matches!(
+        parser.peek().map(|t| &t.token),
+        Some(Token::Run | Token::Now | Token::Wait)
+    )
+}
@@ -0,0 +1,550 @@
+//! # printer.rs
+//!
+//! # printer.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Turns an AST back into Mortar source.
+//!
+//! 将 AST 还原为 Mortar 源码。
+//!
+//! Tools that generate or transform scripts build a `Program` and print it here. Unlike the formatter, the printer only sees the AST, so comments and the author's blank lines are not kept.
+//!
+//! 生成或改写脚本的工具可以构建 `Program` 并在此打印。与格式化器不同，打印器只能看到 AST，因此不会保留注释和作者留下的空行。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Provides `print_program`, `print_top_level` and `print_node_stmt`, plus `Display` for `Program`, `TopLevel` and `NodeStmt`. The output uses the same canonical layout as `mortar fmt`, and parsing it gives back the same AST apart from spans.
+//!
+//! 提供 `print_program`、`print_top_level` 与 `print_node_stmt`，并为 `Program`、`TopLevel` 和 `NodeStmt` 实现 `Display`。输出与 `mortar fmt` 使用相同的规范布局，再次解析后得到的 AST 除范围外完全相同。
+
+use crate::ast::*;
+use std::fmt;
+
+/// Print a whole program, ending with a single newline.
+pub fn print_program(program: &Program) -> String {
+    let mut printer = Printer::default();
+    let mut previous: Option<&TopLevel> = None;
+
+    for item in &program.body {
+        if let Some(previous) = previous
+            && !is_same_declaration_group(previous, item)
+        {
+            printer.out.push('\n');
+        }
+        printer.top_level(item);
+        previous = Some(item);
+    }
+
+    printer.out
+}
+
+/// Print one top-level item without a trailing newline.
+pub fn print_top_level(item: &TopLevel) -> String {
+    let mut printer = Printer::default();
+    printer.top_level(item);
+    printer.finish()
+}
+
+/// Print one node statement, unindented and without a trailing newline.
+pub fn print_node_stmt(stmt: &NodeStmt) -> String {
+    let mut printer = Printer::default();
+    printer.node_stmt(stmt);
+    printer.finish()
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&print_program(self))
+    }
+}
+
+impl fmt::Display for TopLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&print_top_level(self))
+    }
+}
+
+impl fmt::Display for NodeStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&print_node_stmt(self))
+    }
+}
+
+/// Whether two consecutive one-line declarations of the same kind can go without a blank line between them.
+fn is_same_declaration_group(previous: &TopLevel, next: &TopLevel) -> bool {
+    match (previous, next) {
+        (TopLevel::FunctionDecl(_), TopLevel::FunctionDecl(_))
+        | (TopLevel::ConstDecl(_), TopLevel::ConstDecl(_)) => true,
+        (TopLevel::VarDecl(previous), TopLevel::VarDecl(next)) => {
+            !matches!(previous.value, Some(VarValue::Branch(_)))
+                && !matches!(next.value, Some(VarValue::Branch(_)))
+        }
+        _ => false,
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+}
+
+impl Printer {
+    fn finish(mut self) -> String {
+        if self.out.ends_with('\n') {
+            self.out.pop();
+        }
+        self.out
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Write a line ending with an opening bracket and indent what follows.
+    fn open(&mut self, text: &str) {
+        self.line(text);
+        self.indent += 1;
+    }
+
+    fn close(&mut self, text: &str) {
+        self.indent -= 1;
+        self.line(text);
+    }
+
+    fn top_level(&mut self, item: &TopLevel) {
+        match item {
+            TopLevel::NodeDef(node) => self.node_def(node),
+            TopLevel::FunctionDecl(function) => self.line(&function_decl(function)),
+            TopLevel::VarDecl(var) => self.var_decl(var),
+            TopLevel::ConstDecl(constant) => {
+                let visibility = if constant.is_public { "pub " } else { "" };
+                let header = format!(
+                    "{}const {}: {} =",
+                    visibility,
+                    constant.name,
+                    type_name(&constant.type_name)
+                );
+                self.value(&header, &constant.value);
+            }
+            TopLevel::EnumDef(enum_def) => {
+                if enum_def.variants.is_empty() {
+                    self.line(&format!("enum {} {{}}", enum_def.name));
+                    return;
+                }
+                self.open(&format!("enum {} {{", enum_def.name));
+                for variant in &enum_def.variants {
+                    self.line(variant);
+                }
+                self.close("}");
+            }
+            TopLevel::EventDef(event) => {
+                self.open(&format!("event {} {{", event.name));
+                if let Some(index) = event.index {
+                    self.line(&format!("index: {}", number(index)));
+                }
+                self.line(&format!("action: {}", event_action(&event.action)));
+                if let Some(duration) = event.duration {
+                    self.line(&format!("duration: {}", number(duration)));
+                }
+                self.close("}");
+            }
+            TopLevel::TimelineDef(timeline) => {
+                if timeline.body.is_empty() {
+                    self.line(&format!("timeline {} {{}}", timeline.name));
+                    return;
+                }
+                self.open(&format!("timeline {} {{", timeline.name));
+                for stmt in &timeline.body {
+                    match stmt {
+                        TimelineStmt::Run(run) => self.line(&run_stmt(run)),
+                        TimelineStmt::Wait(duration, _) => {
+                            self.line(&format!("wait {}", number(*duration)))
+                        }
+                    }
+                }
+                self.close("}");
+            }
+        }
+    }
+
+    fn node_def(&mut self, node: &NodeDef) {
+        let jump = match &node.jump {
+            Some(NodeJump::Identifier(target, _)) => format!(" -> {}", target),
+            Some(NodeJump::Return(_)) => " -> return".to_string(),
+            Some(NodeJump::Break(_)) => " -> break".to_string(),
+            None => String::new(),
+        };
+
+        if node.body.is_empty() {
+            self.line(&format!("node {} {{}}{}", node.name, jump));
+            return;
+        }
+        self.open(&format!("node {} {{", node.name));
+        self.node_stmts(&node.body);
+        self.close(&format!("}}{}", jump));
+    }
+
+    fn node_stmts(&mut self, stmts: &[NodeStmt]) {
+        for stmt in stmts {
+            self.node_stmt(stmt);
+        }
+    }
+
+    fn node_stmt(&mut self, stmt: &NodeStmt) {
+        match stmt {
+            NodeStmt::Text(text, _) => self.line(&format!("text: {}", string_literal(text))),
+            NodeStmt::InterpolatedText(interpolated, _) => {
+                self.line(&format!("text: {}", interpolated_string(interpolated)))
+            }
+            NodeStmt::Choice(items, _) => self.choice_list("choice: ", items),
+            NodeStmt::Branch(branch) => {
+                let header = format!("{}: {}", branch.name, branch_type(&branch.enum_type));
+                self.branch_cases(&header, &branch.cases);
+            }
+            NodeStmt::IfElse(if_else) => {
+                self.open(&format!("if {} {{", if_condition(&if_else.condition)));
+                self.node_stmts(&if_else.then_body);
+                if let Some(else_body) = &if_else.else_body {
+                    self.close("} else {");
+                    self.indent += 1;
+                    self.node_stmts(else_body);
+                }
+                self.close("}");
+            }
+            NodeStmt::Run(run) => self.line(&run_stmt(run)),
+            NodeStmt::WithEvents(with_events) => self.with_events(&with_events.events),
+            NodeStmt::VarDecl(var) => self.var_decl(var),
+            NodeStmt::Assignment(assignment) => self.line(&format!(
+                "{} = {}",
+                assignment.var_name,
+                assign_value(&assignment.value)
+            )),
+        }
+    }
+
+    /// Print `<prefix>[ ... ]` with one choice per line.
+    fn choice_list(&mut self, prefix: &str, items: &[ChoiceItem]) {
+        if items.is_empty() {
+            self.line(&format!("{}[]", prefix));
+            return;
+        }
+        self.open(&format!("{}[", prefix));
+        for item in items {
+            let mut head = string_literal(&item.text);
+            if let Some(condition) = &item.condition {
+                head.push_str(" when ");
+                head.push_str(&choice_condition(condition));
+            }
+            match &item.target {
+                ChoiceDest::Identifier(target, _) => self.line(&format!("{} -> {}", head, target)),
+                ChoiceDest::Return(_) => self.line(&format!("{} -> return", head)),
+                ChoiceDest::Break(_) => self.line(&format!("{} -> break", head)),
+                ChoiceDest::NestedChoices(nested, _) => {
+                    self.choice_list(&format!("{} -> ", head), nested)
+                }
+            }
+        }
+        self.close("]");
+    }
+
+    /// Print `<header> [ ... ]` with one branch case per line.
+    fn branch_cases(&mut self, header: &str, cases: &[BranchCase]) {
+        if cases.is_empty() {
+            self.line(&format!("{} []", header));
+            return;
+        }
+        self.open(&format!("{} [", header));
+        for case in cases {
+            let head = format!("{}, {}", case.condition, string_literal(&case.text));
+            match &case.events {
+                Some(events) => {
+                    self.open(&format!("{}, events: [", head));
+                    for event in events {
+                        self.line(&inline_event(event));
+                    }
+                    self.close("]");
+                }
+                None => self.line(&head),
+            }
+        }
+        self.close("]");
+    }
+
+    fn with_events(&mut self, items: &[WithEventItem]) {
+        match items {
+            [WithEventItem::EventRef(name, _)] => self.line(&format!("with {}", name)),
+            [WithEventItem::EventRefWithOverride(name, _, index)] => {
+                self.line(&format!("with run {} with {}", name, index_override(index)))
+            }
+            _ => self.with_event_list("with events: ", items),
+        }
+    }
+
+    /// Event references with an index override and nested lists have no list syntax, so they are written the way a single item would be.
+    fn with_event_list(&mut self, prefix: &str, items: &[WithEventItem]) {
+        if items.is_empty() {
+            self.line(&format!("{}[]", prefix));
+            return;
+        }
+        self.open(&format!("{}[", prefix));
+        for item in items {
+            match item {
+                WithEventItem::EventRef(name, _) => self.line(name),
+                WithEventItem::EventRefWithOverride(name, _, index) => {
+                    self.line(&format!("run {} with {}", name, index_override(index)))
+                }
+                WithEventItem::InlineEvent(event) => self.line(&inline_event(event)),
+                WithEventItem::EventList(nested, _) => self.with_event_list("", nested),
+            }
+        }
+        self.close("]");
+    }
+
+    fn var_decl(&mut self, var: &VarDecl) {
+        match &var.value {
+            Some(VarValue::Branch(branch)) => {
+                let header = format!("let {}: {}", var.name, branch_type(&branch.enum_type));
+                self.branch_cases(&header, &branch.cases);
+            }
+            Some(value) => {
+                let header = format!("let {}: {} =", var.name, type_name(&var.type_name));
+                self.value(&header, value);
+            }
+            None => self.line(&format!("let {}: {}", var.name, type_name(&var.type_name))),
+        }
+    }
+
+    /// Print `<header> <value>`, where the header ends with `=`.
+    fn value(&mut self, header: &str, value: &VarValue) {
+        if let VarValue::Branch(branch) = value {
+            let header = format!("{} {}", header, branch_type(&branch.enum_type));
+            self.branch_cases(&header, &branch.cases);
+            return;
+        }
+
+        let value = match value {
+            VarValue::String(text, _) => string_literal(text),
+            VarValue::Number(value, _) => number(*value),
+            VarValue::Boolean(value, _) => value.to_string(),
+            VarValue::EnumMember(enum_name, member, _) => format!("{}.{}", enum_name, member),
+            VarValue::Branch(_) => unreachable!("branch values are printed above"),
+        };
+        self.line(&format!("{} {}", header, value));
+    }
+}
+
+fn function_decl(function: &FunctionDecl) -> String {
+    let params: Vec<String> = function
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, type_name(&param.type_name)))
+        .collect();
+    let mut text = format!("fn {}({})", function.name, params.join(", "));
+    if let Some(return_type) = &function.return_type {
+        text.push_str(" -> ");
+        text.push_str(type_name(return_type));
+    }
+    text
+}
+
+/// The parser stores both `Bool` and `Boolean` as `Boolean`; print the short form.
+pub fn type_name(name: &str) -> &str {
+    match name {
+        "Boolean" => "Bool",
+        _ => name,
+    }
+}
+
+fn branch_type(enum_type: &Option<String>) -> String {
+    match enum_type {
+        Some(enum_type) => format!("branch<{}>", enum_type),
+        None => "branch".to_string(),
+    }
+}
+
+fn run_stmt(run: &RunStmt) -> String {
+    let mut text = String::new();
+    if run.ignore_duration {
+        text.push_str("now ");
+    }
+    text.push_str("run ");
+    text.push_str(&run.event_name);
+    if !run.args.is_empty() {
+        text.push_str(&format!("({})", args(&run.args)));
+    }
+    if let Some(index) = &run.index_override {
+        text.push_str(" with ");
+        text.push_str(&index_override(index));
+    }
+    text
+}
+
+fn index_override(index: &IndexOverride) -> String {
+    match index {
+        IndexOverride::Value(value, _) => number(*value),
+        IndexOverride::Variable(name, _) => name.clone(),
+    }
+}
+
+fn inline_event(event: &Event) -> String {
+    format!("{}, {}", number(event.index), event_action(&event.action))
+}
+
+fn event_action(action: &EventAction) -> String {
+    let mut text = func_call(&action.call);
+    for chain in &action.chains {
+        text.push('.');
+        text.push_str(&func_call(chain));
+    }
+    text
+}
+
+fn func_call(call: &FuncCall) -> String {
+    format!("{}({})", call.name, args(&call.args))
+}
+
+fn args(args: &[Arg]) -> String {
+    args.iter().map(arg).collect::<Vec<_>>().join(", ")
+}
+
+fn arg(arg: &Arg) -> String {
+    match arg {
+        Arg::String(text, _) => string_literal(text),
+        Arg::Number(value, _) => number(*value),
+        Arg::Boolean(value, _) => value.to_string(),
+        Arg::Identifier(name, _) => name.clone(),
+        Arg::FuncCall(call) => func_call(call),
+    }
+}
+
+fn assign_value(value: &AssignValue) -> String {
+    match value {
+        AssignValue::EnumMember(enum_name, member, _) => format!("{}.{}", enum_name, member),
+        AssignValue::Identifier(name, _) => name.clone(),
+        AssignValue::Number(value, _) => number(*value),
+        AssignValue::Boolean(value, _) => value.to_string(),
+        AssignValue::String(text, _) => string_literal(text),
+    }
+}
+
+fn choice_condition(condition: &Condition) -> String {
+    match condition {
+        Condition::Identifier(name, _) => name.clone(),
+        Condition::FuncCall(call) => func_call(call),
+    }
+}
+
+/// Binding strength of a condition, matching the parser: `||` < `&&` < comparisons < `!` and atoms.
+fn precedence(condition: &IfCondition) -> u8 {
+    match condition {
+        IfCondition::Binary(binary) => match binary.operator {
+            ComparisonOp::Or => 1,
+            ComparisonOp::And => 2,
+            _ => 3,
+        },
+        _ => 4,
+    }
+}
+
+fn if_condition(condition: &IfCondition) -> String {
+    match condition {
+        IfCondition::Binary(binary) => {
+            let own = precedence(condition);
+            // Operators are left-associative, so a right operand of the same strength needs parentheses
+            let left = parenthesize(&binary.left, precedence(&binary.left) < own);
+            let right = parenthesize(&binary.right, precedence(&binary.right) <= own);
+            format!("{} {} {}", left, comparison_op(&binary.operator), right)
+        }
+        IfCondition::Unary(unary) => {
+            let operand = parenthesize(&unary.operand, precedence(&unary.operand) < 4);
+            match unary.operator {
+                UnaryOp::Not => format!("!{}", operand),
+            }
+        }
+        IfCondition::Identifier(name, _) => name.clone(),
+        IfCondition::EnumMember(enum_name, member, _) => format!("{}.{}", enum_name, member),
+        IfCondition::Literal(value, _) => value.to_string(),
+    }
+}
+
+fn parenthesize(condition: &IfCondition, needed: bool) -> String {
+    if needed {
+        format!("({})", if_condition(condition))
+    } else {
+        if_condition(condition)
+    }
+}
+
+fn comparison_op(operator: &ComparisonOp) -> &'static str {
+    match operator {
+        ComparisonOp::Greater => ">",
+        ComparisonOp::Less => "<",
+        ComparisonOp::GreaterEqual => ">=",
+        ComparisonOp::LessEqual => "<=",
+        ComparisonOp::Equal => "==",
+        ComparisonOp::NotEqual => "!=",
+        ComparisonOp::And => "&&",
+        ComparisonOp::Or => "||",
+    }
+}
+
+fn interpolated_string(interpolated: &InterpolatedString) -> String {
+    let mut text = String::from("$\"");
+    for part in &interpolated.parts {
+        match part {
+            StringPart::Text(part, _) => push_escaped(&mut text, part, &['"', '{', '}']),
+            StringPart::Expression(call) => text.push_str(&format!("{{{}}}", func_call(call))),
+            StringPart::Placeholder(name, _) => text.push_str(&format!("{{{}}}", name)),
+        }
+    }
+    text.push('"');
+    text
+}
+
+/// Quote a decoded string, escaping what the lexer would otherwise read differently.
+///
+/// Text with a `"` but no `'` goes into single quotes, so the `"` can stay bare.
+fn string_literal(content: &str) -> String {
+    let quote = if content.contains('"') && !content.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+
+    let mut literal = String::from(quote);
+    push_escaped(&mut literal, content, &[quote]);
+    literal.push(quote);
+    literal
+}
+
+/// Append `text` with backslashes, control characters and the given characters escaped.
+fn push_escaped(out: &mut String, text: &str, special: &[char]) {
+    for ch in text.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\0' => out.push_str("\\0"),
+            _ if special.contains(&ch) => {
+                out.push('\\');
+                out.push(ch);
+            }
+            _ => out.push(ch),
+        }
+    }
+}
+
+fn number(value: f64) -> String {
+    value.to_string()
+}
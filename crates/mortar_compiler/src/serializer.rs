@@ -29,6 +29,7 @@ use crate::ast::{
     InterpolatedString, NodeDef, NodeJump, NodeStmt, Program, StringPart, TimelineDef,
     TimelineStmt, TopLevel, VarDecl, VarValue, WithEventItem, WithEventsStmt,
 };
+use crate::token::tokenize;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -214,6 +215,14 @@ fn is_false(v: &bool) -> bool {
     !*v
 }
 
+fn to_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> Result<String, String> {
+    if pretty {
+        serde_json::to_string_pretty(value).map_err(|e| format!("Serialization error: {}", e))
+    } else {
+        serde_json::to_string(value).map_err(|e| format!("Serialization error: {}", e))
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct JsonFunction {
     name: String,
@@ -290,12 +299,17 @@ pub struct Serializer;
 impl Serializer {
     pub fn serialize_to_json(program: &Program, pretty: bool) -> Result<String, String> {
         let mortared = Self::convert_program_to_mortared(program)?;
-        if pretty {
-            serde_json::to_string_pretty(&mortared)
-                .map_err(|e| format!("Serialization error: {}", e))
-        } else {
-            serde_json::to_string(&mortared).map_err(|e| format!("Serialization error: {}", e))
-        }
+        to_json(&mortared, pretty)
+    }
+
+    /// The parsed AST as JSON, spans included, for `mortar --emit ast`.
+    pub fn ast_to_json(program: &Program, pretty: bool) -> Result<String, String> {
+        to_json(program, pretty)
+    }
+
+    /// Every token of `source` with its byte span as JSON, for `mortar --emit tokens`.
+    pub fn tokens_to_json(source: &str, pretty: bool) -> Result<String, String> {
+        to_json(&tokenize(source), pretty)
     }
 
     pub fn save_to_file(program: &Program, input_path: &str, pretty: bool) -> Result<(), String> {
@@ -396,7 +410,7 @@ impl Serializer {
 
         while let Some(stmt) = body_iter.next() {
             match stmt {
-                NodeStmt::Text(text) => {
+                NodeStmt::Text(text, _) => {
                     let mut events = Vec::new();
                     if let Some(NodeStmt::WithEvents(with_events)) = body_iter.peek() {
                         Self::process_with_events(with_events, &mut events, event_map)?;
@@ -414,7 +428,7 @@ impl Serializer {
                         },
                     });
                 }
-                NodeStmt::InterpolatedText(interpolated) => {
+                NodeStmt::InterpolatedText(interpolated, _) => {
                     let (rendered_text, parts) = Self::convert_interpolated_string(interpolated)?;
                     let mut events = Vec::new();
                     if let Some(NodeStmt::WithEvents(with_events)) = body_iter.peek() {
@@ -443,10 +457,10 @@ impl Serializer {
                         .args
                         .iter()
                         .map(|arg| match arg {
-                            Arg::String(s) => format!("\"{}\"", s),
-                            Arg::Number(n) => n.to_string(),
-                            Arg::Boolean(b) => b.to_string(),
-                            Arg::Identifier(id) => id.clone(),
+                            Arg::String(s, _) => format!("\"{}\"", s),
+                            Arg::Number(n, _) => n.to_string(),
+                            Arg::Boolean(b, _) => b.to_string(),
+                            Arg::Identifier(id, _) => id.clone(),
                             Arg::FuncCall(func_call) => {
                                 format!("{}(...)", func_call.name)
                             }
@@ -458,11 +472,11 @@ impl Serializer {
                             .index_override
                             .as_ref()
                             .map(|override_val| match override_val {
-                                IndexOverride::Value(v) => JsonIndexOverride {
+                                IndexOverride::Value(v, _) => JsonIndexOverride {
                                     override_type: "value".to_string(),
                                     value: v.to_string(),
                                 },
-                                IndexOverride::Variable(var) => JsonIndexOverride {
+                                IndexOverride::Variable(var, _) => JsonIndexOverride {
                                     override_type: "variable".to_string(),
                                     value: var.clone(),
                                 },
@@ -475,7 +489,7 @@ impl Serializer {
                         ignore_duration: run_stmt.ignore_duration,
                     });
                 }
-                NodeStmt::Choice(choice_items) => {
+                NodeStmt::Choice(choice_items, _) => {
                     let mut json_choices = Vec::new();
                     for item in choice_items {
                         json_choices.push(Self::convert_choice_item(item)?);
@@ -495,13 +509,13 @@ impl Serializer {
                 }
                 NodeStmt::Assignment(assignment) => {
                     let value_str = match &assignment.value {
-                        AssignValue::EnumMember(enum_name, member) => {
+                        AssignValue::EnumMember(enum_name, member, _) => {
                             format!("{}.{}", enum_name, member)
                         }
-                        AssignValue::Identifier(id) => id.clone(),
-                        AssignValue::Number(n) => n.to_string(),
-                        AssignValue::Boolean(b) => b.to_string(),
-                        AssignValue::String(s) => s.clone(),
+                        AssignValue::Identifier(id, _) => id.clone(),
+                        AssignValue::Number(n, _) => n.to_string(),
+                        AssignValue::Boolean(b, _) => b.to_string(),
+                        AssignValue::String(s, _) => s.clone(),
                     };
 
                     pending_statements.push(JsonStatement {
@@ -582,7 +596,9 @@ impl Serializer {
                     if let Some(event_def) = event_map.get(name) {
                         let event = Event {
                             index: event_def.index.unwrap_or(0.0),
+                            index_span: event_def.span,
                             action: event_def.action.clone(),
+                            span: event_def.span,
                         };
                         events.push(Self::convert_event(&event)?);
                     } else {
@@ -592,8 +608,8 @@ impl Serializer {
                 WithEventItem::EventRefWithOverride(name, _span, override_val) => {
                     if let Some(event_def) = event_map.get(name) {
                         let (index, index_variable) = match override_val {
-                            IndexOverride::Value(v) => (*v, None),
-                            IndexOverride::Variable(var_name) => (0.0, Some(var_name.clone())),
+                            IndexOverride::Value(v, _) => (*v, None),
+                            IndexOverride::Variable(var_name, _) => (0.0, Some(var_name.clone())),
                         };
 
                         let mut actions =
@@ -611,7 +627,7 @@ impl Serializer {
                         return Err(format!("Event '{}' not found", name));
                     }
                 }
-                WithEventItem::EventList(_) => {
+                WithEventItem::EventList(_, _) => {
                     // TODO: Handle nested event lists if needed
                 }
             }
@@ -658,7 +674,7 @@ impl Serializer {
 
         for stmt in body {
             match stmt {
-                NodeStmt::Text(text) => {
+                NodeStmt::Text(text, _) => {
                     has_text_in_block = true;
                     content.push(ContentItem::Text {
                         value: text.clone(),
@@ -668,7 +684,7 @@ impl Serializer {
                         pre_statements: std::mem::take(&mut pending_stmts),
                     });
                 }
-                NodeStmt::InterpolatedText(interp) => {
+                NodeStmt::InterpolatedText(interp, _) => {
                     has_text_in_block = true;
                     let (rendered, parts) = Self::convert_interpolated_string(interp)?;
                     content.push(ContentItem::Text {
@@ -681,13 +697,13 @@ impl Serializer {
                 }
                 NodeStmt::Assignment(assignment) => {
                     let value_str = match &assignment.value {
-                        AssignValue::EnumMember(enum_name, member) => {
+                        AssignValue::EnumMember(enum_name, member, _) => {
                             format!("{}.{}", enum_name, member)
                         }
-                        AssignValue::Identifier(id) => id.clone(),
-                        AssignValue::Number(n) => n.to_string(),
-                        AssignValue::Boolean(b) => b.to_string(),
-                        AssignValue::String(s) => s.clone(),
+                        AssignValue::Identifier(id, _) => id.clone(),
+                        AssignValue::Number(n, _) => n.to_string(),
+                        AssignValue::Boolean(b, _) => b.to_string(),
+                        AssignValue::String(s, _) => s.clone(),
                     };
                     pending_stmts.push(JsonStatement {
                         stmt_type: "assignment".to_string(),
@@ -786,7 +802,7 @@ impl Serializer {
                 operand: Some(Box::new(Self::convert_if_condition(&unary.operand)?)),
                 value: None,
             }),
-            IfCondition::Identifier(name) => Ok(JsonIfCondition {
+            IfCondition::Identifier(name, _) => Ok(JsonIfCondition {
                 cond_type: "identifier".to_string(),
                 operator: None,
                 left: None,
@@ -794,7 +810,7 @@ impl Serializer {
                 operand: None,
                 value: Some(name.clone()),
             }),
-            IfCondition::EnumMember(enum_name, member) => Ok(JsonIfCondition {
+            IfCondition::EnumMember(enum_name, member, _) => Ok(JsonIfCondition {
                 cond_type: "enum_member".to_string(),
                 operator: None,
                 left: None,
@@ -802,7 +818,7 @@ impl Serializer {
                 operand: None,
                 value: Some(format!("{}.{}", enum_name, member)),
             }),
-            IfCondition::Literal(val) => Ok(JsonIfCondition {
+            IfCondition::Literal(val, _) => Ok(JsonIfCondition {
                 cond_type: "literal".to_string(),
                 operator: None,
                 left: None,
@@ -833,10 +849,10 @@ impl Serializer {
 
         for arg in &func_call.args {
             match arg {
-                Arg::String(s) => args.push(s.clone()),
-                Arg::Number(n) => args.push(n.to_string()),
-                Arg::Boolean(b) => args.push(b.to_string()),
-                Arg::Identifier(id) => args.push(id.clone()),
+                Arg::String(s, _) => args.push(s.clone()),
+                Arg::Number(n, _) => args.push(n.to_string()),
+                Arg::Boolean(b, _) => args.push(b.to_string()),
+                Arg::Identifier(id, _) => args.push(id.clone()),
                 Arg::FuncCall(_) => {
                     return Err(
                         "Nested function calls in arguments not supported in JSON output"
@@ -854,7 +870,7 @@ impl Serializer {
 
     fn convert_choice_item(choice_item: &ChoiceItem) -> Result<JsonChoice, String> {
         let condition = match &choice_item.condition {
-            Some(Condition::Identifier(id)) => Some(JsonCondition {
+            Some(Condition::Identifier(id, _)) => Some(JsonCondition {
                 condition_type: id.clone(),
                 args: Vec::new(),
             }),
@@ -864,10 +880,10 @@ impl Serializer {
                     .args
                     .iter()
                     .map(|arg| match arg {
-                        Arg::String(s) => s.clone(),
-                        Arg::Number(n) => n.to_string(),
-                        Arg::Boolean(b) => b.to_string(),
-                        Arg::Identifier(id) => id.clone(),
+                        Arg::String(s, _) => s.clone(),
+                        Arg::Number(n, _) => n.to_string(),
+                        Arg::Boolean(b, _) => b.to_string(),
+                        Arg::Identifier(id, _) => id.clone(),
                         Arg::FuncCall(_) => "nested_call".to_string(), // Simplified
                     })
                     .collect(),
@@ -877,9 +893,9 @@ impl Serializer {
 
         let (next, action, nested_choice) = match &choice_item.target {
             ChoiceDest::Identifier(name, _) => (Some(name.clone()), None, None),
-            ChoiceDest::Return => (None, Some("return".to_string()), None),
-            ChoiceDest::Break => (None, Some("break".to_string()), None),
-            ChoiceDest::NestedChoices(nested_items) => {
+            ChoiceDest::Return(_) => (None, Some("return".to_string()), None),
+            ChoiceDest::Break(_) => (None, Some("break".to_string()), None),
+            ChoiceDest::NestedChoices(nested_items, _) => {
                 let mut nested_choices = Vec::new();
                 for item in nested_items {
                     nested_choices.push(Self::convert_choice_item(item)?);
@@ -940,10 +956,10 @@ impl Serializer {
 
     fn convert_var_value(value: &VarValue) -> serde_json::Value {
         match value {
-            VarValue::String(s) => serde_json::Value::String(s.clone()),
-            VarValue::Number(n) => serde_json::json!(n),
-            VarValue::Boolean(b) => serde_json::Value::Bool(*b),
-            VarValue::EnumMember(enum_name, member) => {
+            VarValue::String(s, _) => serde_json::Value::String(s.clone()),
+            VarValue::Number(n, _) => serde_json::json!(n),
+            VarValue::Boolean(b, _) => serde_json::Value::Bool(*b),
+            VarValue::EnumMember(enum_name, member, _) => {
                 serde_json::Value::String(format!("{}.{}", enum_name, member))
             }
             VarValue::Branch(branch_value) => {
@@ -981,7 +997,7 @@ impl Serializer {
 
         for part in &interpolated.parts {
             match part {
-                StringPart::Text(text) => {
+                StringPart::Text(text, _) => {
                     rendered_text.push_str(text);
                     parts.push(JsonStringPart {
                         part_type: "text".to_string(),
@@ -1003,10 +1019,10 @@ impl Serializer {
                         .iter()
                         .map(|arg| {
                             match arg {
-                                Arg::String(s) => format!("\"{}\"", s),
-                                Arg::Number(n) => n.to_string(),
-                                Arg::Boolean(b) => b.to_string(),
-                                Arg::Identifier(id) => id.clone(),
+                                Arg::String(s, _) => format!("\"{}\"", s),
+                                Arg::Number(n, _) => n.to_string(),
+                                Arg::Boolean(b, _) => b.to_string(),
+                                Arg::Identifier(id, _) => id.clone(),
                                 Arg::FuncCall(nested) => format!("{}()", nested.name), // Simplified
                             }
                         })
@@ -1021,7 +1037,7 @@ impl Serializer {
                         branches: None,
                     });
                 }
-                StringPart::Placeholder(name) => {
+                StringPart::Placeholder(name, _) => {
                     // Placeholder will be resolved by branch definitions
                     let placeholder = format!("{{{}}}", name);
                     rendered_text.push_str(&placeholder);
@@ -1053,10 +1069,10 @@ impl Serializer {
                     .args
                     .iter()
                     .map(|arg| match arg {
-                        Arg::String(s) => format!("\"{}\"", s),
-                        Arg::Number(n) => n.to_string(),
-                        Arg::Boolean(b) => b.to_string(),
-                        Arg::Identifier(id) => id.clone(),
+                        Arg::String(s, _) => format!("\"{}\"", s),
+                        Arg::Number(n, _) => n.to_string(),
+                        Arg::Boolean(b, _) => b.to_string(),
+                        Arg::Identifier(id, _) => id.clone(),
                         Arg::FuncCall(fc) => format!("{}()", fc.name),
                     })
                     .collect(),
@@ -1077,17 +1093,17 @@ impl Serializer {
                         .args
                         .iter()
                         .map(|arg| match arg {
-                            Arg::String(s) => format!("\"{}\"", s),
-                            Arg::Number(n) => n.to_string(),
-                            Arg::Boolean(b) => b.to_string(),
-                            Arg::Identifier(id) => id.clone(),
+                            Arg::String(s, _) => format!("\"{}\"", s),
+                            Arg::Number(n, _) => n.to_string(),
+                            Arg::Boolean(b, _) => b.to_string(),
+                            Arg::Identifier(id, _) => id.clone(),
                             Arg::FuncCall(fc) => format!("{}()", fc.name),
                         })
                         .collect(),
                     duration: None,
                     ignore_duration: run_stmt.ignore_duration,
                 },
-                TimelineStmt::Wait(duration) => JsonTimelineStmt {
+                TimelineStmt::Wait(duration, _) => JsonTimelineStmt {
                     stmt_type: "wait".to_string(),
                     event_name: None,
                     args: Vec::new(),
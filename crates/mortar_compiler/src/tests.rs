@@ -20,17 +20,28 @@
 
 // Test modules
 mod branch_test;
+mod codes_test;
 mod control_flow_test;
+mod cst_test;
 mod deserializer_test;
 mod diagnostics_test;
 #[cfg(test)]
 mod file_handler_tests;
+mod formatter_test;
+mod incremental_test;
+mod lint_test;
+mod message_format_test;
+mod parse_error_test;
 mod parser_test;
 mod performance_serialization_test;
 mod performance_test;
+mod printer_test;
 mod serializer_test;
+mod span_test;
+mod string_test;
 mod token_test;
 mod variable_test;
+mod visit_test;
 // Placeholder for future tests
 // mod interpolation_test;
 // mod separator_test;
@@ -35,13 +35,13 @@ fn test_parse_placeholder_in_text() {
     let program = result.unwrap();
     match &program.body[0] {
         TopLevel::NodeDef(node) => match &node.body[0] {
-            NodeStmt::InterpolatedText(interp) => {
+            NodeStmt::InterpolatedText(interp, _) => {
                 assert_eq!(interp.parts.len(), 3);
-                assert!(matches!(&interp.parts[0], StringPart::Text(_)));
-                assert!(matches!(&interp.parts[1], StringPart::Placeholder(_)));
-                assert!(matches!(&interp.parts[2], StringPart::Text(_)));
+                assert!(matches!(&interp.parts[0], StringPart::Text(_, _)));
+                assert!(matches!(&interp.parts[1], StringPart::Placeholder(_, _)));
+                assert!(matches!(&interp.parts[2], StringPart::Text(_, _)));
 
-                if let StringPart::Placeholder(name) = &interp.parts[1] {
+                if let StringPart::Placeholder(name, _) = &interp.parts[1] {
                     assert_eq!(name, "name");
                 }
             }
@@ -199,12 +199,12 @@ fn test_multiple_placeholders() {
             assert_eq!(node.body.len(), 3);
 
             match &node.body[0] {
-                NodeStmt::InterpolatedText(interp) => {
+                NodeStmt::InterpolatedText(interp, _) => {
                     // Count placeholders
                     let placeholder_count = interp
                         .parts
                         .iter()
-                        .filter(|p| matches!(p, StringPart::Placeholder(_)))
+                        .filter(|p| matches!(p, StringPart::Placeholder(_, _)))
                         .count();
                     assert_eq!(placeholder_count, 2);
                 }
@@ -0,0 +1,80 @@
+//! # codes_test.rs
+//!
+//! # codes_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for stable diagnostic codes.
+//!
+//! 稳定诊断代码的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks that diagnostics carry their codes and that every code has an explanation.
+//!
+//! 检查诊断是否带有代码，以及每个代码是否都有说明。
+
+use crate::{Language, ParseHandler, codes};
+use std::collections::HashSet;
+
+fn diagnostic_codes(source: &str) -> Vec<&'static str> {
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+    diagnostics
+        .get_diagnostics()
+        .iter()
+        .map(|d| d.code())
+        .collect()
+}
+
+#[test]
+fn test_codes_are_unique_and_explained() {
+    let all: Vec<_> = codes::all_codes().collect();
+    let unique: HashSet<_> = all.iter().collect();
+    assert_eq!(all.len(), unique.len());
+
+    for code in all {
+        assert!(code.starts_with('M') && code.len() == 5, "{}", code);
+        assert!(codes::explain(code, Language::English).is_some());
+        assert!(codes::explain(code, Language::Chinese).is_some());
+    }
+}
+
+#[test]
+fn test_explain_is_case_insensitive() {
+    assert_eq!(
+        codes::explain("m0101", Language::English),
+        codes::explain("M0101", Language::English)
+    );
+    assert!(codes::explain("M9999", Language::English).is_none());
+}
+
+#[test]
+fn test_semantic_diagnostics_carry_codes() {
+    let source = r#"
+        node Start {
+            text: "Hello"
+        } -> Missing
+
+        node Start {
+            text: "Again"
+        }
+
+        fn unused_helper()
+    "#;
+
+    let found = diagnostic_codes(source);
+    assert!(found.contains(&codes::NODE_NOT_FOUND));
+    assert!(found.contains(&codes::DUPLICATE_NODE));
+    assert!(found.contains(&codes::UNUSED_FUNCTION));
+}
+
+#[test]
+fn test_syntax_errors_carry_parse_error_codes() {
+    assert!(diagnostic_codes(r#"let "score": Number = 0"#).contains(&codes::EXPECTED_IDENTIFIER));
+    assert!(diagnostic_codes("node Start {\n    text: Hello\n}").contains(&codes::EXPECTED_STRING));
+}
@@ -102,7 +102,7 @@ fn test_parse_boolean_condition() {
     match &program.body[1] {
         TopLevel::NodeDef(node) => match &node.body[0] {
             NodeStmt::IfElse(if_else) => match &if_else.condition {
-                IfCondition::Identifier(name) => {
+                IfCondition::Identifier(name, _) => {
                     assert_eq!(name, "is_winner");
                 }
                 _ => panic!("Expected Identifier condition"),
@@ -244,7 +244,7 @@ fn test_parse_not_operator() {
                         IfCondition::Unary(unary) => {
                             // Check operand
                             match &unary.operand {
-                                IfCondition::Identifier(name) => {
+                                IfCondition::Identifier(name, _) => {
                                     assert_eq!(name, "flag");
                                 }
                                 _ => panic!("Expected Identifier operand"),
@@ -0,0 +1,387 @@
+//! # cst_test.rs
+//!
+//! # cst_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for the lossless concrete syntax tree.
+//!
+//! 无损具体语法树的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks that the CST prints back the exact source, where comments are attached, and how tokens are grouped into items, statements and list entries.
+//!
+//! 检查 CST 能否原样输出源码、注释附着的位置，以及记号如何分组为条目、语句和列表条目。
+
+use crate::ParseHandler;
+use crate::ast::{ChoiceDest, NodeStmt, WithEventItem};
+use crate::cst::{
+    ChoiceEntry, CstElement, EventEntry, ItemKind, SourceFile, Statement, StatementKind,
+    SyntaxKind, TriviaKind,
+};
+use crate::token::Token;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_round_trip_examples() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut checked = 0;
+
+    for language in fs::read_dir(examples).unwrap() {
+        for entry in fs::read_dir(language.unwrap().path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "mortar") {
+                let source = fs::read_to_string(&path).unwrap();
+                assert_eq!(
+                    SourceFile::parse(&source).to_string(),
+                    source,
+                    "{}",
+                    path.display()
+                );
+                checked += 1;
+            }
+        }
+    }
+
+    assert!(checked > 0);
+}
+
+#[test]
+fn test_round_trip_unusual_sources() {
+    let sources = [
+        "",
+        "   \n\n",
+        "// only a comment",
+        "/* block */\r\nnode A {\r\n    text: \"CRLF\"\r\n}\r\n",
+        "node A { text: \"unclosed\"",
+        "node A {\n    text: \"a\" } } ]\n",
+        "node 名字 { text: \"你好\" } @ # \"unterminated",
+        "\tnode\tA{text:'single'}// trailing",
+    ];
+
+    for source in sources {
+        assert_eq!(SourceFile::parse(source).to_string(), source);
+    }
+}
+
+#[test]
+fn test_comment_attachment() {
+    let source = "// About Start\n// Second line\nnode Start { // opening\n    text: \"Hi\"\n}\n";
+    let file = SourceFile::parse(source);
+    let item = file.items().next().unwrap();
+
+    let comments: Vec<&str> = item.leading_comments().iter().map(|t| t.text).collect();
+    assert_eq!(comments, vec!["// About Start", "// Second line"]);
+
+    let brace = file.token_at(source.find('{').unwrap()).unwrap();
+    assert_eq!(brace.token, Token::LeftBrace);
+    assert_eq!(brace.trailing.len(), 2);
+    assert_eq!(brace.trailing[1].kind, TriviaKind::LineComment);
+    assert_eq!(brace.trailing[1].text, "// opening");
+
+    let text_keyword = file.token_at(source.find("text").unwrap()).unwrap();
+    assert_eq!(text_keyword.leading[0].kind, TriviaKind::Newline);
+}
+
+#[test]
+fn test_blank_line_separates_leading_comments() {
+    let source = "// File header\n\n// About Start\nnode Start {}\n";
+    let file = SourceFile::parse(source);
+    let item = file.items().next().unwrap();
+
+    let comments: Vec<&str> = item.leading_comments().iter().map(|t| t.text).collect();
+    assert_eq!(comments, vec!["// About Start"]);
+    assert!(
+        file.eof_trivia
+            .iter()
+            .all(|t| t.kind == TriviaKind::Newline)
+    );
+}
+
+#[test]
+fn test_items_and_names() {
+    let source = r#"
+pub const Title: String = "Game"
+let score: Number = 0
+fn play(name: String)
+enum Mood { happy sad }
+event Ding { index: 0 action: play("ding") }
+timeline Intro { run Ding }
+node Start {
+    let inner: Number = 1
+    text: "Hi"
+} -> Next
+"#;
+    let file = SourceFile::parse(source);
+
+    let items: Vec<(ItemKind, &str)> = file
+        .items()
+        .map(|item| (item.kind(), item.name().unwrap().text))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (ItemKind::Constant, "Title"),
+            (ItemKind::Variable, "score"),
+            (ItemKind::Function, "play"),
+            (ItemKind::Enum, "Mood"),
+            (ItemKind::Event, "Ding"),
+            (ItemKind::Timeline, "Intro"),
+            (ItemKind::Node, "Start"),
+        ]
+    );
+
+    let node = file.items().last().unwrap();
+    assert!(node.syntax().to_string().ends_with("} -> Next"));
+    assert!(
+        node.syntax().children.iter().any(
+            |child| matches!(child, CstElement::Node(block) if block.kind == SyntaxKind::Block)
+        )
+    );
+}
+
+#[test]
+fn test_missing_brace_ends_at_next_declaration() {
+    let source = "node Start {\n    text: \"Hello\"\n\nnode Next {\n    text: \"World\"\n}\n";
+    let file = SourceFile::parse(source);
+
+    let names: Vec<&str> = file.items().map(|item| item.name().unwrap().text).collect();
+    assert_eq!(names, vec!["Start", "Next"]);
+    assert_eq!(file.to_string(), source);
+}
+
+#[test]
+fn test_item_links_to_ast() {
+    let source = "// greeting\nnode Start {\n    text: \"Hi\"\n}\n\nfn greet()\n";
+    let program = ParseHandler::parse_source_code(source, false).unwrap();
+    let file = SourceFile::parse(source);
+
+    for (item, top_level) in file.items().zip(&program.body) {
+        assert_eq!(item.ast(&program), Some(top_level));
+        assert_eq!(item.span(), top_level.span());
+    }
+
+    let item = file.item_at(source.find("Hi").unwrap()).unwrap();
+    assert_eq!(item.kind(), ItemKind::Node);
+}
+
+#[test]
+fn test_node_statements() {
+    let source = r#"node Start {
+    text: "Hi"
+    with events: [
+        0, play("a")
+    ]
+    mood: branch<feeling> [
+        happy, "Glad"
+    ]
+    score = 1
+    run Ding with beat
+    with Ding
+    if score > 0 {
+        text: "Again"
+    } else {
+        choice: [
+            "Go" -> Next
+        ]
+    }
+    let inner: Number = 1
+}
+"#;
+    let file = SourceFile::parse(source);
+    let node = file.items().next().unwrap();
+
+    let statements: Vec<(StatementKind, String)> = node
+        .statements()
+        .map(|statement| (statement.kind(), statement.syntax().to_string()))
+        .collect();
+    let kinds: Vec<StatementKind> = statements.iter().map(|(kind, _)| *kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            StatementKind::Text,
+            StatementKind::WithEvents,
+            StatementKind::Branch,
+            StatementKind::Assignment,
+            StatementKind::Run,
+            StatementKind::WithEvents,
+            StatementKind::If,
+            StatementKind::Variable,
+        ]
+    );
+    assert_eq!(statements[4].1.trim(), "run Ding with beat");
+    assert_eq!(statements[5].1.trim(), "with Ding");
+
+    let if_else = node.statements().nth(6).unwrap();
+    let nested: Vec<StatementKind> = if_else.statements().map(|s| s.kind()).collect();
+    assert_eq!(nested, vec![StatementKind::Text, StatementKind::Choice]);
+    assert_eq!(file.to_string(), source);
+}
+
+#[test]
+fn test_choice_and_event_entries() {
+    let source = r#"node Start {
+    text: "Hi"
+    with events: [
+        0, play("a")
+        3, play("b").then(flash())
+        Ding
+        [
+            1, play("c")
+            Ding
+        ]
+    ]
+    choice: [
+        "Go" -> Next,
+        ("Wait").when(ready) -> return
+        "More" -> [
+            "A" -> A
+            "B" -> B
+        ]
+    ]
+}
+"#;
+    let file = SourceFile::parse(source);
+    let node = file.items().next().unwrap();
+    let entry_texts =
+        |node: &crate::cst::CstNode| node.to_string().trim().trim_end_matches(',').to_string();
+
+    let with_events = node.statements().nth(1).unwrap();
+    let events: Vec<String> = with_events
+        .event_entries()
+        .map(|entry| entry_texts(entry.syntax()))
+        .collect();
+    assert_eq!(events[0], "0, play(\"a\")");
+    assert_eq!(events[1], "3, play(\"b\").then(flash())");
+    assert_eq!(events[2], "Ding");
+    assert_eq!(events.len(), 4);
+    let nested: Vec<String> = with_events
+        .event_entries()
+        .last()
+        .unwrap()
+        .entries()
+        .map(|entry| entry_texts(entry.syntax()))
+        .collect();
+    assert_eq!(nested, vec!["1, play(\"c\")", "Ding"]);
+
+    let choice = node.statements().nth(2).unwrap();
+    let options: Vec<String> = choice
+        .choice_entries()
+        .map(|entry| entry_texts(entry.syntax()))
+        .collect();
+    assert_eq!(options[0], "\"Go\" -> Next");
+    assert_eq!(options[1], "(\"Wait\").when(ready) -> return");
+    assert_eq!(options.len(), 3);
+    let more = choice.choice_entries().last().unwrap();
+    assert_eq!(more.entries().count(), 2);
+    assert_eq!(file.to_string(), source);
+}
+
+#[test]
+fn test_with_statement_forms() {
+    let source = "node Start {\n    text: \"Hi\"\n    with run Ding with beat\n    with event { 0, play(\"a\") }\n    with Ding\n}\n";
+    let file = SourceFile::parse(source);
+    let node = file.items().next().unwrap();
+
+    let entries: Vec<Vec<String>> = node
+        .statements()
+        .skip(1)
+        .map(|statement| {
+            assert_eq!(statement.kind(), StatementKind::WithEvents);
+            statement
+                .event_entries()
+                .map(|entry| entry.syntax().to_string().trim().to_string())
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            vec!["Ding with beat".to_string()],
+            vec!["0, play(\"a\")".to_string()],
+            vec!["Ding".to_string()],
+        ]
+    );
+}
+
+/// Compare the CST statements of a body with the parsed ones, down to list entries.
+fn check_statements(statements: Vec<Statement>, body: &[NodeStmt], program: &crate::ast::Program) {
+    assert_eq!(statements.len(), body.len());
+
+    for (statement, stmt) in statements.iter().zip(body) {
+        assert_eq!(statement.ast(program), Some(stmt));
+        // A statement keeps its trailing `;`, which the parsed span leaves out
+        assert_eq!(statement.span().0, stmt.span().0);
+        assert!(statement.span().1 >= stmt.span().1);
+        match stmt {
+            NodeStmt::IfElse(if_else) => {
+                let mut parsed = if_else.then_body.clone();
+                parsed.extend(if_else.else_body.clone().unwrap_or_default());
+                check_statements(statement.statements().collect(), &parsed, program);
+            }
+            NodeStmt::Choice(items, _) => {
+                check_choices(statement.choice_entries().collect(), items)
+            }
+            NodeStmt::WithEvents(with_events) => {
+                check_events(statement.event_entries().collect(), &with_events.events)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_choices(entries: Vec<ChoiceEntry>, items: &[crate::ast::ChoiceItem]) {
+    assert_eq!(entries.len(), items.len());
+    for (entry, item) in entries.iter().zip(items) {
+        assert_eq!(entry.span().0, item.span.0);
+        if let ChoiceDest::NestedChoices(nested, _) = &item.target {
+            check_choices(entry.entries().collect(), nested);
+        }
+    }
+}
+
+fn check_events(entries: Vec<EventEntry>, items: &[WithEventItem]) {
+    assert_eq!(entries.len(), items.len());
+    for (entry, item) in entries.iter().zip(items) {
+        assert_eq!(entry.span().0, item.span().0);
+        if let WithEventItem::EventList(nested, _) = item {
+            check_events(entry.entries().collect(), nested);
+        }
+    }
+}
+
+#[test]
+fn test_statements_match_ast_in_examples() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut checked = 0;
+
+    for language in fs::read_dir(examples).unwrap() {
+        for entry in fs::read_dir(language.unwrap().path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "mortar") {
+                continue;
+            }
+            let source = fs::read_to_string(&path).unwrap();
+            let Ok(program) = ParseHandler::parse_source_code(&source, false) else {
+                continue;
+            };
+            let file = SourceFile::parse(&source);
+
+            for item in file.items().filter(|item| item.kind() == ItemKind::Node) {
+                let Some(crate::ast::TopLevel::NodeDef(node)) = item.ast(&program) else {
+                    panic!("{}: node without AST", path.display());
+                };
+                check_statements(item.statements().collect(), &node.body, &program);
+                checked += 1;
+            }
+        }
+    }
+
+    assert!(checked > 0);
+}
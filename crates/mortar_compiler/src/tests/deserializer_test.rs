@@ -364,16 +364,19 @@ fn test_roundtrip_serialization() {
             TopLevel::NodeDef(NodeDef {
                 name: "TestNode".to_string(),
                 name_span: Some((0, 8)),
-                body: vec![NodeStmt::Text("Test content".to_string())],
+                body: vec![NodeStmt::Text("Test content".to_string(), (0, 0))],
                 jump: None,
+                span: (0, 0),
             }),
             TopLevel::FunctionDecl(FunctionDecl {
                 name: "test_func".to_string(),
                 name_span: Some((0, 9)),
                 params: vec![],
                 return_type: None,
+                span: (0, 0),
             }),
         ],
+        span: (0, 0),
     };
 
     // Serialize
@@ -18,7 +18,9 @@
 //!
 //! 验证错误报告、警告生成和诊断消息格式化。
 
-use crate::{DiagnosticCollector, Language, ParseHandler};
+use crate::{
+    DiagnosticCollector, DiagnosticKind, EventIndexOptions, Language, ParseHandler, TextLengthUnit,
+};
 
 #[test]
 fn test_diagnostic_collector_creation() {
@@ -144,6 +146,37 @@ fn test_analyze_program_naming_conventions() {
     }
 }
 
+#[test]
+fn test_naming_conventions_accept_caseless_scripts() {
+    let source = r#"
+let 好感度: Number = 0
+node 开始 {
+    text: "你好"
+    好感度 = 1
+} -> 结束Scene
+node 结束Scene {}
+node lower_node {}
+fn 播放音效()
+fn PlaySound()
+"#;
+
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+
+    let mut flagged: Vec<&str> = diagnostics
+        .get_diagnostics()
+        .iter()
+        .filter_map(|d| match &d.kind {
+            DiagnosticKind::NonPascalCaseNode { node_name } => Some(node_name.as_str()),
+            DiagnosticKind::NonSnakeCaseFunction { function_name } => Some(function_name.as_str()),
+            _ => None,
+        })
+        .collect();
+    flagged.sort();
+
+    assert_eq!(flagged, vec!["PlaySound", "lower_node"]);
+}
+
 #[test]
 fn test_analyze_program_type_mismatch() {
     let source = r#"
@@ -182,14 +215,70 @@ fn test_analyze_program_duplicate_definitions() {
         fn test()
     "#;
 
-    let (result, _diagnostics) =
+    let (result, diagnostics) =
         ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
 
-    if let Ok(program) = result {
-        let mut diag = DiagnosticCollector::new("test.mortar".to_string());
-        diag.analyze_program(&program);
-        // Should detect duplicates
-    }
+    assert!(result.is_ok());
+    assert!(diagnostics.has_errors());
+
+    let duplicates: Vec<_> = diagnostics
+        .get_diagnostics()
+        .iter()
+        .filter(|d| {
+            matches!(
+                d.kind,
+                DiagnosticKind::DuplicateNode { .. } | DiagnosticKind::DuplicateFunction { .. }
+            )
+        })
+        .collect();
+    assert_eq!(duplicates.len(), 2);
+
+    // Both the duplicate and the first definition are located
+    let node_duplicate = &duplicates[0];
+    let (second_start, _) = node_duplicate.span.unwrap();
+    let (first_start, _) = node_duplicate.kind.first_span().unwrap();
+    assert!(first_start < second_start);
+    assert_eq!(&source[first_start..first_start + 4], "Test");
+}
+
+#[test]
+fn test_duplicate_definitions_for_all_top_level_kinds() {
+    let source = r#"
+        let flag: Boolean = false
+        let flag: Boolean = true
+        const title: String = "A"
+        const title: String = "B"
+        enum Mood { happy, sad, happy }
+        enum Mood { calm }
+        event Flash { action: flash() }
+        event Flash { action: flash() }
+        timeline Intro { run Flash }
+        timeline Intro { wait 1 }
+
+        node Start { text: "Hi" }
+
+        fn flash()
+    "#;
+
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+
+    let kinds: Vec<_> = diagnostics
+        .get_diagnostics()
+        .iter()
+        .map(|d| &d.kind)
+        .collect();
+
+    assert!(kinds.iter().any(|k| matches!(k, DiagnosticKind::DuplicateVariable { variable_name, .. } if variable_name == "flag")));
+    assert!(kinds.iter().any(|k| matches!(k, DiagnosticKind::DuplicateConstant { constant_name, .. } if constant_name == "title")));
+    assert!(kinds.iter().any(
+        |k| matches!(k, DiagnosticKind::DuplicateEnum { enum_name, .. } if enum_name == "Mood")
+    ));
+    assert!(kinds.iter().any(|k| matches!(k, DiagnosticKind::DuplicateEnumVariant { variant_name, .. } if variant_name == "happy")));
+    assert!(kinds.iter().any(
+        |k| matches!(k, DiagnosticKind::DuplicateEvent { event_name, .. } if event_name == "Flash")
+    ));
+    assert!(kinds.iter().any(|k| matches!(k, DiagnosticKind::DuplicateTimeline { timeline_name, .. } if timeline_name == "Intro")));
 }
 
 #[test]
@@ -257,3 +346,246 @@ fn test_parse_error_generates_diagnostic() {
         "Expected diagnostics to have errors"
     );
 }
+
+fn event_index_warnings(source: &str, options: EventIndexOptions) -> Vec<DiagnosticKind> {
+    let program = ParseHandler::parse_source_code(source, false).expect("source should parse");
+    let mut diag = DiagnosticCollector::new("test.mortar".to_string());
+    diag.set_event_index_options(options);
+    diag.analyze_program(&program);
+    diag.get_diagnostics()
+        .iter()
+        .filter(|d| {
+            matches!(
+                d.kind,
+                DiagnosticKind::EventIndexOutOfBounds { .. }
+                    | DiagnosticKind::EventIndexOutOfOrder { .. }
+            )
+        })
+        .map(|d| d.kind.clone())
+        .collect()
+}
+
+#[test]
+fn test_event_index_past_end_of_text() {
+    let source = r#"
+        node Test {
+            text: "0123456789"
+            with events: [
+                10, set_color("red")
+                17, set_color("blue")
+            ]
+        }
+
+        fn set_color(color: String)
+    "#;
+
+    let warnings = event_index_warnings(source, EventIndexOptions::default());
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(
+        warnings[0],
+        DiagnosticKind::EventIndexOutOfBounds {
+            index: 17.0,
+            text_length: 10
+        }
+    ));
+}
+
+#[test]
+fn test_event_index_out_of_order() {
+    let source = r#"
+        const late: Number = 8
+
+        event Early {
+            index: 2
+            action: set_color("red")
+        }
+
+        node Test {
+            text: "0123456789"
+            with events: [
+                5, set_color("blue")
+                Early
+            ]
+            text: "0123456789"
+            with run Early with late
+        }
+
+        fn set_color(color: String)
+    "#;
+
+    let warnings = event_index_warnings(source, EventIndexOptions::default());
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(
+        warnings[0],
+        DiagnosticKind::EventIndexOutOfOrder {
+            index: 2.0,
+            previous_index: 5.0
+        }
+    ));
+}
+
+#[test]
+fn test_event_index_resolves_constant_override() {
+    let source = r#"
+        const late: Number = 30
+
+        event Sound {
+            action: play_sound("a.wav")
+        }
+
+        node Test {
+            text: "short"
+            with run Sound with late
+        }
+
+        fn play_sound(file: String)
+    "#;
+
+    let warnings = event_index_warnings(source, EventIndexOptions::default());
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(
+        warnings[0],
+        DiagnosticKind::EventIndexOutOfBounds {
+            index: 30.0,
+            text_length: 5
+        }
+    ));
+}
+
+#[test]
+fn test_event_index_length_units() {
+    // "e\u{301}" is two scalar values but a single grapheme cluster.
+    let source = "node Test {\n text: \"e\u{301}e\u{301}\"\n with events: [ 4, f() ]\n}\nfn f()";
+
+    let chars = event_index_warnings(source, EventIndexOptions::default());
+    assert!(chars.is_empty());
+
+    let graphemes = event_index_warnings(
+        source,
+        EventIndexOptions {
+            length_unit: TextLengthUnit::Graphemes,
+            ..EventIndexOptions::default()
+        },
+    );
+    assert_eq!(graphemes.len(), 1);
+}
+
+#[test]
+fn test_event_index_interpolated_minimum_length() {
+    let source = r#"
+        node Test {
+            text: $"Hi {name}"
+            with events: [ 5, f() ]
+        }
+
+        fn f()
+    "#;
+
+    let default = event_index_warnings(source, EventIndexOptions::default());
+    assert_eq!(default.len(), 1);
+
+    let lenient = event_index_warnings(
+        source,
+        EventIndexOptions {
+            min_interpolation_length: 2,
+            ..EventIndexOptions::default()
+        },
+    );
+    assert!(lenient.is_empty());
+}
+
+fn unused_symbol_names(source: &str) -> Vec<String> {
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+
+    diagnostics
+        .get_diagnostics()
+        .iter()
+        .filter_map(|d| match &d.kind {
+            DiagnosticKind::UnusedVariable { variable_name } => Some(variable_name.clone()),
+            DiagnosticKind::UnusedConstant { constant_name } => Some(constant_name.clone()),
+            DiagnosticKind::UnusedEnum { enum_name } => Some(enum_name.clone()),
+            DiagnosticKind::UnusedEvent { event_name } => Some(event_name.clone()),
+            DiagnosticKind::UnusedTimeline { timeline_name } => Some(timeline_name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_unused_symbols_reported() {
+    let source = r#"
+        let dead_flag: Boolean = false
+        const secret: Number = 3
+        pub const exported: Number = 4
+        enum Unused { a, b }
+        event Never { action: flash() }
+        timeline NeverStarted { wait 1 }
+
+        node Start { text: "Hi" }
+
+        fn flash()
+    "#;
+
+    let names = unused_symbol_names(source);
+    assert_eq!(
+        names,
+        vec!["dead_flag", "secret", "Unused", "Never", "NeverStarted"]
+    );
+}
+
+#[test]
+fn test_symbols_used_across_nodes_timelines_and_branches() {
+    let source = r#"
+        enum Place { forest, city }
+        let current_place: Place
+        let is_day: Boolean = true
+        let score: Number = 0
+        const late: Number = 5
+
+        let place_name: branch<current_place> [
+            forest, "the forest"
+            city, "the city"
+        ]
+
+        let greeting: branch [
+            is_day, "Good day"
+        ]
+
+        event Flash { index: 1, action: flash() }
+        event Sound { action: flash() }
+        timeline Intro { run Sound }
+
+        node Start {
+            text: $"{greeting}, welcome to {place_name}."
+            with events: [ Flash ]
+            run Intro with late
+            score = 1
+            if score > 0 {
+                text: "Again"
+            }
+        }
+
+        fn flash()
+    "#;
+
+    assert!(unused_symbol_names(source).is_empty());
+}
+
+#[test]
+fn test_write_only_variable_reported() {
+    let source = r#"
+        let flag: Boolean = false
+        let seen: Boolean = false
+        let copied: Boolean = false
+
+        node Start {
+            text: "Hi"
+            flag = true
+            copied = seen
+        }
+    "#;
+
+    // Assigning to a variable is not a use; reading one in an assigned value is
+    assert_eq!(unused_symbol_names(source), vec!["flag", "copied"]);
+}
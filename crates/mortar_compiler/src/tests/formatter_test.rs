@@ -0,0 +1,193 @@
+//! # formatter_test.rs
+//!
+//! # formatter_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for the source formatter.
+//!
+//! 源码格式化器的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks the canonical layout, that comments survive formatting, and that formatting the examples is stable and does not change the compiled output.
+//!
+//! 检查规范布局、格式化后注释是否保留，以及格式化示例文件是否稳定且不改变编译结果。
+
+use crate::formatter::{FormatOptions, format_range, format_source};
+use crate::{ParseHandler, Serializer};
+use std::fs;
+use std::path::Path;
+
+fn format(source: &str) -> String {
+    format_source(source, FormatOptions::default())
+}
+
+/// The compiled JSON without the generation timestamp.
+fn compiled(source: &str) -> String {
+    let program = ParseHandler::parse_source_code(source, false).unwrap();
+    Serializer::serialize_to_json(&program, true)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.contains("generated_at"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_examples_are_stable_and_keep_their_meaning() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+
+    for language in fs::read_dir(examples).unwrap() {
+        for entry in fs::read_dir(language.unwrap().path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "mortar") {
+                continue;
+            }
+
+            let source = fs::read_to_string(&path).unwrap();
+            let formatted = format(&source);
+
+            assert_eq!(format(&formatted), formatted, "{}", path.display());
+            assert_eq!(
+                compiled(&formatted),
+                compiled(&source),
+                "{}",
+                path.display()
+            );
+            assert_eq!(
+                source.matches("//").count(),
+                formatted.matches("//").count(),
+                "comments lost in {}",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_normalizes_aliases_quotes_and_separators() {
+    let source = "public const Title: String = 'Game';\nlet ready:Boolean\nfunction check()->Bool;\ntl Intro{run Ding wait 1}\nevent Ding{index:0 action:check()}\nnd Start{text:'Hi';ready=true;}->return\n";
+
+    assert_eq!(
+        format(source),
+        r#"pub const Title: String = "Game"
+let ready: Bool
+fn check() -> Bool
+timeline Intro {
+    run Ding
+    wait 1
+}
+event Ding {
+    index: 0
+    action: check()
+}
+node Start {
+    text: "Hi"
+    ready = true
+} -> return
+"#
+    );
+}
+
+#[test]
+fn test_choice_and_event_layout() {
+    let source = r#"node Start {
+text: "Hi"
+with events: [0, play("a.wav") 4.5,play("b.wav"),]
+choice: ["A" -> Next, "B" when ready() -> ["C" -> return; "D" -> break], ("E").when(ready()) -> Next]
+}
+node Next {}
+fn play(file: String)
+fn ready() -> Bool
+"#;
+
+    assert_eq!(
+        format(source),
+        r#"node Start {
+    text: "Hi"
+    with events: [
+        0, play("a.wav")
+        4.5, play("b.wav")
+    ]
+    choice: [
+        "A" -> Next
+        "B" when ready() -> [
+            "C" -> return
+            "D" -> break
+        ]
+        ("E").when(ready()) -> Next
+    ]
+}
+node Next {}
+fn play(file: String)
+fn ready() -> Bool
+"#
+    );
+}
+
+#[test]
+fn test_comments_are_preserved() {
+    let source = "node A {   // after brace\n\n\n    text: \"a\" /* inline */ ; // after semi\n    choice: [ // open\n        \"x\" -> B, // first\n        // between\n        \"y\" -> B\n        // before close\n    ]\n    /* end */\n}\n// tail\n\n\nnode B {}\n";
+
+    assert_eq!(
+        format(source),
+        "node A {  // after brace\n    text: \"a\" /* inline */  // after semi\n    choice: [  // open\n        \"x\" -> B  // first\n        // between\n        \"y\" -> B\n        // before close\n    ]\n    /* end */\n}\n// tail\n\nnode B {}\n"
+    );
+}
+
+#[test]
+fn test_blank_lines_are_collapsed_and_trimmed_at_block_edges() {
+    let source =
+        "let a: Number = 1\n\n\n\nnode Start {\n\n    text: \"a\"\n\n\n    text: \"b\"\n\n}\n";
+
+    assert_eq!(
+        format(source),
+        "let a: Number = 1\n\nnode Start {\n    text: \"a\"\n\n    text: \"b\"\n}\n"
+    );
+}
+
+#[test]
+fn test_inline_event_and_if_else_layout() {
+    let source = "let ready: Bool\nnode Start {\n    text: \"Hi\"\n    with event {0, play()}\n    if (ready)\n    {\n        text: \"Yes\"\n    }\n    else\n    {\n        ready = true\n    }\n}\nfn play()\n";
+
+    assert_eq!(
+        format(source),
+        "let ready: Bool\nnode Start {\n    text: \"Hi\"\n    with event { 0, play() }\n    if (ready) {\n        text: \"Yes\"\n    } else {\n        ready = true\n    }\n}\nfn play()\n"
+    );
+}
+
+#[test]
+fn test_sources_with_syntax_errors_are_unchanged() {
+    let source = "nd Start {\n text: 42\n}\n";
+    assert_eq!(format(source), source);
+}
+
+#[test]
+fn test_indentation_options() {
+    let source = "node Start { text: \"Hi\" }";
+    let options = FormatOptions {
+        indent_width: 2,
+        use_tabs: false,
+    };
+
+    assert_eq!(
+        format_source(source, options),
+        "node Start {\n  text: \"Hi\"\n}\n"
+    );
+}
+
+#[test]
+fn test_format_range_only_covers_overlapping_items() {
+    let source = "nd First{text:'a'}\n  nd Second{text:'b'}\nnd Third{text:'c'}\n";
+    let offset = source.find("Second").unwrap();
+
+    let (span, text) = format_range(source, (offset, offset), FormatOptions::default()).unwrap();
+
+    assert_eq!(&source[span.0..span.1], "  nd Second{text:'b'}");
+    assert_eq!(text, "node Second {\n    text: \"b\"\n}");
+}
@@ -0,0 +1,260 @@
+//! # incremental_test.rs
+//!
+//! # incremental_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for `IncrementalParse`, which reparses only the top-level items an edit touches.
+//!
+//! `IncrementalParse` 的测试，它只重新解析编辑所涉及的顶层项。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks that every update, whether it reuses items or falls back to a full parse, yields the same program and errors as parsing the new text from scratch, across the examples and edits that open strings, comments and blocks.
+//!
+//! 检查每次更新（无论复用顶层项还是退回完整解析）得到的程序和错误是否与从头解析新文本一致，覆盖示例文件以及打开字符串、注释和代码块的编辑。
+
+use std::fs;
+use std::path::Path;
+
+use crate::ParseHandler;
+use crate::parser::error::ParseError;
+use crate::parser::incremental::IncrementalParse;
+
+const SOURCE: &str = r#"// Covers every kind of top-level item
+let score: Number = 0
+let mood: Mood
+let mood_ok: Bool
+const title: String = "Hero"
+
+enum Mood {
+    happy
+    sad
+}
+
+fn play_sound(file: String)
+fn get_name() -> String
+
+event Ding {
+    index: 0
+    action: play_sound("ding.wav")
+}
+
+timeline Intro {
+    run Ding
+    wait 1.5
+}
+
+node Start {
+    text: $"Hello {get_name()}, {title}!"
+    with events: [
+        0, play_sound("a.wav")
+        2, play_sound("b.wav")
+    ]
+    score = 10
+    if score > 5 && !(score == 7) {
+        text: "High"
+    } else {
+        text: "Low"
+    }
+    choice: [
+        "Go" -> Next,
+        ("Stay").when(mood_ok) -> [
+            "Really" -> return,
+            "No" -> break
+        ]
+    ]
+}
+
+node Next {
+    run Intro
+    text: "Bye"
+} -> Start
+"#;
+
+fn errors(parse: &IncrementalParse) -> Vec<ParseError> {
+    parse.errors().cloned().collect()
+}
+
+/// Updates `parse` to `source` and checks it against a fresh parse of the same text.
+fn update_and_compare(parse: &mut IncrementalParse, source: &str) -> bool {
+    let reused = parse.update(source);
+    let fresh = IncrementalParse::new(source);
+    assert_eq!(parse.source(), source);
+    assert_eq!(
+        parse.program(),
+        fresh.program(),
+        "program differs after update to:\n{}",
+        source
+    );
+    assert_eq!(
+        errors(parse),
+        errors(&fresh),
+        "errors differ after update to:\n{}",
+        source
+    );
+    reused
+}
+
+/// `source` with `replacement` in place of the bytes `start..end`.
+fn edit(source: &str, start: usize, end: usize, replacement: &str) -> String {
+    format!("{}{}{}", &source[..start], replacement, &source[end..])
+}
+
+fn char_boundaries(source: &str, step: usize) -> Vec<usize> {
+    source
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([source.len()])
+        .step_by(step)
+        .collect()
+}
+
+fn examples() -> Vec<String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut sources = Vec::new();
+    for language in ["en", "zh"] {
+        let mut paths: Vec<_> = fs::read_dir(root.join(language))
+            .expect("examples should exist")
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "mortar"))
+            .collect();
+        paths.sort();
+        sources.extend(paths.iter().map(|path| fs::read_to_string(path).unwrap()));
+    }
+    sources
+}
+
+#[test]
+fn test_new_matches_full_parse() {
+    let parse = IncrementalParse::new(SOURCE);
+    let program = ParseHandler::parse_source_code(SOURCE, false).expect("source should parse");
+    assert_eq!(parse.program(), &program);
+    assert_eq!(parse.errors().count(), 0);
+}
+
+#[test]
+fn test_edit_inside_node_reuses_other_items() {
+    let mut parse = IncrementalParse::new(SOURCE);
+    let at = SOURCE.find("\"High\"").unwrap() + 1;
+    let source = edit(SOURCE, at, at + 4, "Very high");
+    assert!(update_and_compare(&mut parse, &source));
+    assert!(update_and_compare(&mut parse, SOURCE));
+}
+
+#[test]
+fn test_edit_shifts_spans_of_later_items() {
+    let mut parse = IncrementalParse::new(SOURCE);
+    // Growing the first item moves every span that follows it
+    let at = SOURCE.find("= 0").unwrap() + 2;
+    let source = edit(SOURCE, at, at + 1, "12345");
+    assert!(update_and_compare(&mut parse, &source));
+}
+
+#[test]
+fn test_unchanged_source() {
+    let mut parse = IncrementalParse::new(SOURCE);
+    update_and_compare(&mut parse, SOURCE);
+}
+
+#[test]
+fn test_edits_at_the_ends() {
+    let mut parse = IncrementalParse::new(SOURCE);
+    update_and_compare(&mut parse, &format!("let extra: Bool\n{}", SOURCE));
+    update_and_compare(&mut parse, SOURCE);
+    update_and_compare(&mut parse, &format!("{}node Last {{}}\n", SOURCE));
+    update_and_compare(&mut parse, SOURCE);
+    update_and_compare(&mut parse, "");
+    update_and_compare(&mut parse, SOURCE);
+}
+
+#[test]
+fn test_edits_that_open_constructs() {
+    let at = SOURCE.find("node Start").unwrap();
+    for opening in ["\"", "/*", "node X {", "$\"{", "[", "}", "fn", "event"] {
+        let mut parse = IncrementalParse::new(SOURCE);
+        let source = edit(SOURCE, at, at, opening);
+        update_and_compare(&mut parse, &source);
+        update_and_compare(&mut parse, SOURCE);
+    }
+}
+
+#[test]
+fn test_deleting_a_closing_brace() {
+    let mut parse = IncrementalParse::new(SOURCE);
+    let at = SOURCE.find("} -> Start").unwrap();
+    update_and_compare(&mut parse, &edit(SOURCE, at, at + 1, ""));
+
+    let at = SOURCE.find("}\n\nfn").unwrap();
+    update_and_compare(&mut parse, &edit(SOURCE, at, at + 1, ""));
+    update_and_compare(&mut parse, SOURCE);
+}
+
+#[test]
+fn test_single_character_edits_everywhere() {
+    for source in examples().iter().chain([&SOURCE.to_string()]) {
+        let mut parse = IncrementalParse::new(source);
+        for at in char_boundaries(source, 7) {
+            update_and_compare(&mut parse, &edit(source, at, at, "x"));
+            update_and_compare(&mut parse, source);
+            update_and_compare(&mut parse, &edit(source, at, at, " "));
+            update_and_compare(&mut parse, source);
+        }
+    }
+}
+
+#[test]
+fn test_deletions_everywhere() {
+    for source in examples().iter().chain([&SOURCE.to_string()]) {
+        let mut parse = IncrementalParse::new(source);
+        let boundaries = char_boundaries(source, 11);
+        for pair in boundaries.windows(2) {
+            update_and_compare(&mut parse, &edit(source, pair[0], pair[1], ""));
+            update_and_compare(&mut parse, source);
+        }
+    }
+}
+
+#[test]
+fn test_successive_edits_without_reset() {
+    let mut parse = IncrementalParse::new(SOURCE);
+    let mut source = SOURCE.to_string();
+    for (i, at) in char_boundaries(SOURCE, 13).into_iter().enumerate().rev() {
+        let text = ["a", "\n", "{", "\"", "1", "}"][i % 6];
+        source = edit(&source, at, at, text);
+        update_and_compare(&mut parse, &source);
+    }
+}
+
+#[test]
+fn test_closing_a_string_left_open_in_an_earlier_item() {
+    let source = "node A {\n    text: \"\"\"\n    hello\n}\n\nnode B {\n    text: \"hi\"\n}\n";
+    let mut parse = IncrementalParse::new(source);
+    update_and_compare(&mut parse, &format!("{}\"\"\"\n", source));
+    update_and_compare(&mut parse, source);
+
+    for opening in ["r\"", "\"", "$\""] {
+        let open = source.replace("\"\"\"", opening);
+        let mut parse = IncrementalParse::new(&open);
+        update_and_compare(&mut parse, &format!("{}\"\n", open));
+    }
+}
+
+#[test]
+fn test_edit_with_changed_range() {
+    let mut parse = IncrementalParse::new(SOURCE);
+    let at = SOURCE.find("\"High\"").unwrap() + 1;
+    let source = edit(SOURCE, at, at + 4, "Very high");
+    assert!(parse.edit(&source, at..at + 4));
+    let fresh = IncrementalParse::new(&source);
+    assert_eq!(parse.program(), fresh.program());
+    assert_eq!(errors(&parse), errors(&fresh));
+
+    // A range outside the previous source cannot be an edit of it
+    assert!(!parse.edit(SOURCE, 0..source.len() + 1));
+    assert_eq!(parse.program(), IncrementalParse::new(SOURCE).program());
+}
@@ -0,0 +1,171 @@
+//! # lint_test.rs
+//!
+//! # lint_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for configurable lint levels.
+//!
+//! 可配置 lint 级别的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Validates `mortar.toml` parsing, inline lint comments and `deny_warnings`.
+//!
+//! 验证 `mortar.toml` 解析、行内 lint 注释以及 `deny_warnings`。
+
+use crate::lint::collect_inline_attributes;
+use crate::{
+    ConfigError, DiagnosticKind, Language, LintLevel, ParseHandler, ProjectConfig, Severity,
+    TextLengthUnit,
+};
+
+fn lints_with_config(source: &str, config: &ProjectConfig) -> Vec<(String, Severity)> {
+    let (_result, diagnostics) = ParseHandler::parse_source_code_with_config(
+        source,
+        "test.mortar".to_string(),
+        false,
+        Language::English,
+        config,
+    );
+
+    diagnostics
+        .get_diagnostics()
+        .iter()
+        .filter_map(|d| {
+            d.kind
+                .lint_name()
+                .map(|lint| (lint.to_string(), d.severity.clone()))
+        })
+        .collect()
+}
+
+#[test]
+fn test_project_config_from_toml() {
+    let config = ProjectConfig::from_toml(
+        r#"
+        [lints]
+        unused_function = "allow"
+        non_pascal_case_node = "deny"
+
+        [diagnostics]
+        deny_warnings = true
+        text_length_unit = "graphemes"
+        min_interpolation_length = 3
+        "#,
+    )
+    .unwrap();
+
+    let lints = config.lint_config();
+    assert_eq!(lints.level_of("unused_function"), LintLevel::Allow);
+    assert_eq!(lints.level_of("non_pascal_case_node"), LintLevel::Deny);
+    assert_eq!(lints.level_of("unused_variable"), LintLevel::Warn);
+    assert!(lints.deny_warnings);
+
+    let options = config.event_index_options();
+    assert_eq!(options.length_unit, TextLengthUnit::Graphemes);
+    assert_eq!(options.min_interpolation_length, 3);
+}
+
+#[test]
+fn test_project_config_rejects_unknown_lint() {
+    let result = ProjectConfig::from_toml("[lints]\nunused_fucntion = \"allow\"\n");
+    assert!(matches!(result, Err(ConfigError::UnknownLint(name)) if name == "unused_fucntion"));
+
+    let result = ProjectConfig::from_toml("[lints]\nunused_function = \"ignore\"\n");
+    assert!(matches!(result, Err(ConfigError::InvalidToml(_))));
+}
+
+#[test]
+fn test_config_levels_allow_and_deny() {
+    let source = r#"
+        node lower_node { text: "Hi" }
+        fn unused_helper()
+    "#;
+
+    let config = ProjectConfig::from_toml(
+        "[lints]\nunused_function = \"allow\"\nnon_pascal_case_node = \"deny\"\n",
+    )
+    .unwrap();
+
+    let lints = lints_with_config(source, &config);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].0, "non_pascal_case_node");
+    assert!(matches!(lints[0].1, Severity::Error));
+}
+
+#[test]
+fn test_deny_warnings() {
+    let source = r#"
+        node Start { text: "Hi" }
+        fn unused_helper()
+    "#;
+
+    let mut config = ProjectConfig::default();
+    config.diagnostics.deny_warnings = true;
+
+    let lints = lints_with_config(source, &config);
+    assert_eq!(lints.len(), 1);
+    assert!(matches!(lints[0].1, Severity::Error));
+}
+
+#[test]
+fn test_inline_allow_applies_to_next_item_only() {
+    let source = r#"
+// mortar: allow(unused_function, non_snake_case_function)
+fn OldHelper()
+
+fn AnotherHelper()
+
+node Start {
+    text: "Hi"
+}
+"#;
+
+    let lints = lints_with_config(source, &ProjectConfig::default());
+    assert_eq!(lints.len(), 2);
+    assert!(
+        lints
+            .iter()
+            .all(|(_, severity)| matches!(severity, Severity::Warning))
+    );
+
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+    assert!(diagnostics.get_diagnostics().iter().all(|d| !matches!(
+        &d.kind,
+        DiagnosticKind::UnusedFunction { function_name } if function_name == "OldHelper"
+    )));
+}
+
+#[test]
+fn test_inline_attribute_covers_node_body() {
+    let source = r#"
+// mortar: allow(event_index_out_of_bounds)
+node Start {
+    text: "Hi"
+    with events: [ 9, flash() ]
+} -> End
+
+node End {
+    text: "Bye"
+    // mortar: deny(event_index_out_of_bounds)
+    with events: [ 9, flash() ]
+}
+
+fn flash()
+"#;
+
+    let attributes = collect_inline_attributes(source);
+    assert_eq!(attributes.len(), 2);
+    assert!(source[attributes[0].span.0..attributes[0].span.1].ends_with("-> End"));
+    assert!(source[attributes[1].span.0..attributes[1].span.1].starts_with("with events"));
+
+    let lints = lints_with_config(source, &ProjectConfig::default());
+    assert_eq!(lints.len(), 1);
+    assert!(matches!(lints[0].1, Severity::Error));
+}
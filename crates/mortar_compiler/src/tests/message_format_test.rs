@@ -0,0 +1,125 @@
+//! # message_format_test.rs
+//!
+//! # message_format_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for machine-readable diagnostic output.
+//!
+//! 机器可读诊断输出的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Covers the `short`, `json` and `sarif` message formats and suggested fixes.
+//!
+//! 覆盖 `short`、`json` 和 `sarif` 消息格式以及建议的修复。
+
+use crate::{DiagnosticCollector, Language, MessageFormat, ParseHandler};
+
+const SOURCE: &str = "fn PlaySound()\nnode Start { text: \"Hi\" } -> Missing\n";
+
+fn collect(source: &str) -> DiagnosticCollector {
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "story.mortar".to_string(), false);
+    diagnostics
+}
+
+#[test]
+fn test_message_format_from_name() {
+    for name in MessageFormat::NAMES {
+        assert!(MessageFormat::from_name(name).is_some());
+    }
+    assert_eq!(MessageFormat::from_name("xml"), None);
+    assert!(MessageFormat::Sarif.is_machine_readable());
+    assert!(!MessageFormat::Short.is_machine_readable());
+}
+
+#[test]
+fn test_short_format() {
+    let output = collect(SOURCE).format_diagnostics(SOURCE, MessageFormat::Short);
+    let lines: Vec<_> = output.lines().collect();
+
+    assert!(lines.contains(
+        &"story.mortar:1:4: warning[M0301]: Function 'PlaySound' should use snake_case naming."
+    ));
+    assert!(lines.contains(&"story.mortar:2:30: error[M0101]: Node 'Missing' is not defined."));
+}
+
+#[test]
+fn test_json_format() {
+    let diagnostics = collect(SOURCE);
+    let output = diagnostics.format_diagnostics(SOURCE, MessageFormat::Json);
+    let values: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(values.len(), diagnostics.get_diagnostics().len());
+
+    let naming = values.iter().find(|v| v["code"] == "M0301").unwrap();
+    assert_eq!(naming["file"], "story.mortar");
+    assert_eq!(naming["severity"], "warning");
+    assert_eq!(naming["lint"], "non_snake_case_function");
+    assert_eq!(naming["span"]["byte_start"], 3);
+    assert_eq!(naming["span"]["byte_end"], 12);
+    assert_eq!(naming["span"]["line_start"], 1);
+    assert_eq!(naming["span"]["column_start"], 4);
+    assert_eq!(naming["suggestions"][0]["replacement"], "play_sound");
+
+    let missing = values.iter().find(|v| v["code"] == "M0101").unwrap();
+    assert_eq!(missing["severity"], "error");
+    assert_eq!(missing["suggestions"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_sarif_format() {
+    let diagnostics = collect(SOURCE);
+    let sarif = diagnostics.to_sarif(SOURCE);
+
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "mortar");
+
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), diagnostics.get_diagnostics().len());
+
+    for result in results {
+        let index = result["ruleIndex"].as_u64().unwrap() as usize;
+        assert_eq!(rules[index]["id"], result["ruleId"]);
+        assert!(result["locations"][0]["physicalLocation"]["region"]["startLine"].is_u64());
+    }
+
+    let naming = results.iter().find(|r| r["ruleId"] == "M0301").unwrap();
+    let replacement = &naming["fixes"][0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["insertedContent"]["text"], "play_sound");
+    assert_eq!(replacement["deletedRegion"]["startColumn"], 4);
+}
+
+#[test]
+fn test_sarif_without_results_is_still_a_log() {
+    let source = "node Start { text: \"Hi\" }\n";
+    let output = collect(source).format_diagnostics(source, MessageFormat::Sarif);
+    let sarif: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(sarif["runs"][0]["results"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_naming_suggestions() {
+    let source = "fn playSound()\nfn HTTPRequest()\nnode start_scene { text: \"Hi\" }\n";
+    let diagnostics = collect(source);
+
+    let replacements: Vec<_> = diagnostics
+        .get_diagnostics()
+        .iter()
+        .flat_map(|d| d.suggestions(Language::English))
+        .map(|s| s.replacement)
+        .collect();
+    assert_eq!(
+        replacements,
+        vec!["play_sound", "http_request", "StartScene"]
+    );
+}
@@ -0,0 +1,281 @@
+//! # parse_error_test.rs
+//!
+//! # parse_error_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for syntax error locations and error recovery.
+//!
+//! 语法错误位置与错误恢复的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks that parse errors carry exact spans and expected tokens, and that one compile reports every syntax error in a file.
+//!
+//! 检查解析错误是否携带精确的范围和期望的记号，以及一次编译能否报告文件中的所有语法错误。
+
+use crate::parser::error::ParseError;
+use crate::{DiagnosticKind, ParseHandler, TopLevel};
+
+/// `(code, spanned source text, message)` of every syntax error.
+fn syntax_errors(source: &str) -> Vec<(&'static str, String, String)> {
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+
+    diagnostics
+        .get_diagnostics()
+        .iter()
+        .filter_map(|d| match &d.kind {
+            DiagnosticKind::SyntaxError { code, .. } => {
+                let (start, end) = d.span.expect("syntax errors always have a span");
+                Some((*code, source[start..end].to_string(), d.message.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn node_names(source: &str) -> Vec<String> {
+    let (result, _diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+
+    result
+        .expect("recovery should still produce a program")
+        .body
+        .iter()
+        .filter_map(|item| match item {
+            TopLevel::NodeDef(node) => Some(node.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_error_carries_span_and_expected_tokens() {
+    let source = "node Start {\n    text: \"Hi\"\n    choice: [\n        \"Go\" -> 42\n    ]\n}\n";
+
+    let error = ParseHandler::parse_source_code(source, false).unwrap_err();
+
+    let (start, end) = error.span();
+    assert_eq!(&source[start..end], "42");
+    assert_eq!(
+        error.expected(),
+        vec!["node name", "'return'", "'break'", "'['"]
+    );
+    assert_eq!(
+        error.to_string(),
+        "Expected node name, 'return', 'break' or '[', found 42"
+    );
+}
+
+#[test]
+fn test_unexpected_eof() {
+    let error = ParseHandler::parse_source_code("node Start {\n    text:", false).unwrap_err();
+
+    assert!(matches!(error, ParseError::UnexpectedEOF { .. }));
+    assert_eq!(error.code(), "M0004");
+    assert_eq!(error.expected(), vec!["string"]);
+}
+
+#[test]
+fn test_multiple_errors_in_one_node_are_all_reported() {
+    let source = r#"
+node Start {
+    text: 123
+    text: "Still parsed"
+    run
+    choice: [
+        "A" -> Next
+    ]
+    text: Oops
+}
+
+node Next {
+    text: "Done"
+}
+"#;
+
+    let errors = syntax_errors(source);
+
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "M0003",
+                "123".to_string(),
+                "Expected string, found 123".to_string()
+            ),
+            (
+                "M0002",
+                "choice".to_string(),
+                "Expected identifier, found choice".to_string()
+            ),
+            (
+                "M0003",
+                "Oops".to_string(),
+                "Expected string, found Oops".to_string()
+            ),
+        ]
+    );
+    assert_eq!(node_names(source), vec!["Start", "Next"]);
+}
+
+#[test]
+fn test_recovery_inside_choice_list() {
+    let source = r#"
+node Start {
+    choice: [
+        "A" -> ,
+        "B" when -> Next,
+        "C" -> Next
+    ]
+}
+
+node Next {}
+"#;
+
+    let errors = syntax_errors(source);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].1, ",");
+    assert_eq!(errors[1].1, "->");
+
+    let (result, _diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+    let program = result.unwrap();
+    let TopLevel::NodeDef(node) = &program.body[0] else {
+        panic!("expected a node");
+    };
+    let crate::NodeStmt::Choice(items, _) = &node.body[0] else {
+        panic!("expected a choice statement");
+    };
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].text, "C");
+}
+
+#[test]
+fn test_recovery_inside_with_events_list() {
+    let source = r#"
+node Start {
+    text: "Hello"
+    with events: [
+        0, play_sound("a.wav")
+        "oops"
+        1, play_sound(
+        2, play_sound("b.wav")
+    ]
+    text: "After"
+}
+"#;
+
+    let errors = syntax_errors(source);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert_eq!(errors[0].0, "M0001");
+    assert_eq!(errors[0].1, "\"oops\"");
+
+    let (result, _diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+    let program = result.unwrap();
+    let TopLevel::NodeDef(node) = &program.body[0] else {
+        panic!("expected a node");
+    };
+    assert_eq!(node.body.len(), 3);
+}
+
+#[test]
+fn test_recovery_inside_timeline() {
+    let source = r#"
+timeline Intro {
+    wait "long"
+    run ShowTitle
+    now 3
+    wait 1
+}
+"#;
+
+    let errors = syntax_errors(source);
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "M0001",
+                "\"long\"".to_string(),
+                "Expected number, found \"long\"".to_string()
+            ),
+            (
+                "M0001",
+                "3".to_string(),
+                "Expected 'run', found 3".to_string()
+            ),
+        ]
+    );
+
+    let (result, _diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+    let program = result.unwrap();
+    let TopLevel::TimelineDef(timeline) = &program.body[0] else {
+        panic!("expected a timeline");
+    };
+    assert_eq!(timeline.body.len(), 2);
+}
+
+#[test]
+fn test_missing_closing_brace_keeps_following_node() {
+    let source = r#"
+node Start {
+    text: "Hello"
+
+node Next {
+    text: "World"
+}
+"#;
+
+    let errors = syntax_errors(source);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].1, "node");
+    assert_eq!(errors[0].2, "Expected '}', found node");
+    assert_eq!(node_names(source), vec!["Start", "Next"]);
+}
+
+#[test]
+fn test_let_inside_node_does_not_hide_later_errors() {
+    let source = r#"
+node Start {
+    let score: Number = 0
+    text: "Hello"
+    text: 5
+}
+"#;
+
+    let errors = syntax_errors(source);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].0, "M0006");
+    assert_eq!(errors[0].1, "let");
+    assert_eq!(errors[1].1, "5");
+}
+
+#[test]
+fn test_errors_in_if_body_are_recovered() {
+    let source = r#"
+let ready: Bool = true
+
+node Start {
+    if ready {
+        text: 1
+        text: "ok"
+    } else {
+        text: 2
+    }
+    text: "After"
+}
+"#;
+
+    let errors = syntax_errors(source);
+    assert_eq!(
+        errors.iter().map(|e| e.1.as_str()).collect::<Vec<_>>(),
+        vec!["1", "2"]
+    );
+}
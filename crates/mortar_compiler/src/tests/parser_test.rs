@@ -47,12 +47,11 @@ fn test_parse_node_def() {
         body: vec![TopLevel::NodeDef(NodeDef {
             name: "start_node".to_string(),
             name_span: Some((14, 24)), // Approximate span for "start_node"
-            body: vec![NodeStmt::Text("Hello, world!".to_string())],
-            jump: Some(NodeJump::Identifier(
-                "next_node".to_string(),
-                Some((74, 83)),
-            )), // Updated to actual span
+            body: vec![NodeStmt::Text("Hello, world!".to_string(), (39, 60))],
+            jump: Some(NodeJump::Identifier("next_node".to_string(), (74, 83))), // Updated to actual span
+            span: (9, 83),
         })],
+        span: (0, 83),
     };
     check_parsing(source, expected);
 }
@@ -70,14 +69,18 @@ fn test_parse_function_decl() {
                 Param {
                     name: "param1".to_string(),
                     type_name: "String".to_string(),
+                    span: (24, 38),
                 },
                 Param {
                     name: "param2".to_string(),
                     type_name: "Number".to_string(),
+                    span: (40, 54),
                 },
             ],
             return_type: Some("String".to_string()),
+            span: (9, 65),
         })],
+        span: (0, 65),
     };
     check_parsing(source, expected);
 }
@@ -98,40 +101,52 @@ fn test_parse_node_with_events() {
             name: "event_node".to_string(),
             name_span: Some((14, 24)), // Approximate span
             body: vec![
-                NodeStmt::Text("Hello".to_string()),
+                NodeStmt::Text("Hello".to_string(), (39, 52)),
                 NodeStmt::WithEvents(WithEventsStmt {
                     events: vec![
                         WithEventItem::InlineEvent(Event {
                             index: 0.0,
+                            index_span: (96, 97),
                             action: EventAction {
                                 call: FuncCall {
                                     name: "say".to_string(),
                                     name_span: Some((99, 102)),
-                                    args: vec![Arg::String("hello".to_string())],
+                                    args: vec![Arg::String("hello".to_string(), (103, 110))],
+                                    span: (99, 111),
                                 },
                                 chains: vec![],
+                                span: (99, 111),
                             },
+                            span: (96, 111),
                         }),
                         WithEventItem::InlineEvent(Event {
                             index: 1.5,
+                            index_span: (128, 131),
                             action: EventAction {
                                 call: FuncCall {
                                     name: "say".to_string(),
                                     name_span: Some((133, 136)),
-                                    args: vec![Arg::String("world".to_string())],
+                                    args: vec![Arg::String("world".to_string(), (137, 144))],
+                                    span: (133, 145),
                                 },
                                 chains: vec![FuncCall {
                                     name: "wait".to_string(),
                                     name_span: Some((146, 150)),
-                                    args: vec![Arg::Number(1.0)],
+                                    args: vec![Arg::Number(1.0, (151, 152))],
+                                    span: (146, 153),
                                 }],
+                                span: (133, 153),
                             },
+                            span: (128, 153),
                         }),
                     ],
+                    span: (65, 167),
                 }),
             ],
             jump: None,
+            span: (9, 177),
         })],
+        span: (0, 177),
     };
     check_parsing(source, expected);
 }
@@ -154,38 +169,55 @@ fn test_parse_node_with_choices() {
         body: vec![TopLevel::NodeDef(NodeDef {
             name: "choice_node".to_string(),
             name_span: Some((14, 25)), // Approximate span
-            body: vec![NodeStmt::Choice(vec![
-                ChoiceItem {
-                    text: "Choice 1".to_string(),
-                    condition: None,
-                    target: ChoiceDest::Identifier("next_node".to_string(), Some((80, 89))),
-                },
-                ChoiceItem {
-                    text: "Choice 2".to_string(),
-                    condition: Some(Condition::Identifier("is_ready".to_string())),
-                    target: ChoiceDest::Return,
-                },
-                ChoiceItem {
-                    text: "Choice 3".to_string(),
-                    condition: Some(Condition::FuncCall(FuncCall {
-                        name: "check".to_string(),
-                        name_span: Some((178, 183)), // Updated to actual span
-                        args: vec![Arg::Identifier("arg1".to_string())],
-                    })),
-                    target: ChoiceDest::Break,
-                },
-                ChoiceItem {
-                    text: "Choice 4".to_string(),
-                    condition: None,
-                    target: ChoiceDest::NestedChoices(vec![ChoiceItem {
-                        text: "Nested 1".to_string(),
+            body: vec![NodeStmt::Choice(
+                vec![
+                    ChoiceItem {
+                        text: "Choice 1".to_string(),
                         condition: None,
-                        target: ChoiceDest::Identifier("nested_node".to_string(), Some((267, 278))),
-                    }]),
-                },
-            ])],
+                        target: ChoiceDest::Identifier("next_node".to_string(), (80, 89)),
+                        span: (66, 89),
+                    },
+                    ChoiceItem {
+                        text: "Choice 2".to_string(),
+                        condition: Some(Condition::Identifier("is_ready".to_string(), (123, 131))),
+                        target: ChoiceDest::Return((136, 142)),
+                        span: (107, 142),
+                    },
+                    ChoiceItem {
+                        text: "Choice 3".to_string(),
+                        condition: Some(Condition::FuncCall(FuncCall {
+                            name: "check".to_string(),
+                            name_span: Some((178, 183)), // Updated to actual span
+                            args: vec![Arg::Identifier("arg1".to_string(), (184, 188))],
+                            span: (178, 189),
+                        })),
+                        target: ChoiceDest::Break((194, 199)),
+                        span: (160, 199),
+                    },
+                    ChoiceItem {
+                        text: "Choice 4".to_string(),
+                        condition: None,
+                        target: ChoiceDest::NestedChoices(
+                            vec![ChoiceItem {
+                                text: "Nested 1".to_string(),
+                                condition: None,
+                                target: ChoiceDest::Identifier(
+                                    "nested_node".to_string(),
+                                    (267, 278),
+                                ),
+                                span: (253, 278),
+                            }],
+                            (231, 296),
+                        ),
+                        span: (217, 296),
+                    },
+                ],
+                (40, 310),
+            )],
             jump: None,
+            span: (9, 320),
         })],
+        span: (0, 320),
     };
     check_parsing(source, expected);
 }
@@ -111,7 +111,7 @@ fn test_parse_timeline_def() {
             }
 
             match &timeline_def.body[1] {
-                TimelineStmt::Wait(duration) => {
+                TimelineStmt::Wait(duration, _) => {
                     assert_eq!(*duration, 2.0);
                 }
                 _ => panic!("Expected Wait statement"),
@@ -162,7 +162,7 @@ fn test_parse_run_with_index() {
             NodeStmt::Run(run_stmt) => {
                 assert_eq!(run_stmt.event_name, "Basic");
                 match &run_stmt.index_override {
-                    Some(IndexOverride::Value(v)) => assert_eq!(*v, 1.5),
+                    Some(IndexOverride::Value(v, _)) => assert_eq!(*v, 1.5),
                     _ => panic!("Expected Value index override"),
                 }
             }
@@ -187,7 +187,7 @@ fn test_parse_run_with_variable_index() {
             NodeStmt::Run(run_stmt) => {
                 assert_eq!(run_stmt.event_name, "Basic");
                 match &run_stmt.index_override {
-                    Some(IndexOverride::Variable(name)) => assert_eq!(name, "test_index"),
+                    Some(IndexOverride::Variable(name, _)) => assert_eq!(name, "test_index"),
                     _ => panic!("Expected Variable index override"),
                 }
             }
@@ -268,7 +268,7 @@ fn test_parse_run_with_args() {
                 assert_eq!(run_stmt.event_name, "DialogueNode");
                 assert_eq!(run_stmt.args.len(), 1);
                 match &run_stmt.args[0] {
-                    Arg::String(s) => assert_eq!(s, "Start"),
+                    Arg::String(s, _) => assert_eq!(s, "Start"),
                     _ => panic!("Expected String argument"),
                 }
             }
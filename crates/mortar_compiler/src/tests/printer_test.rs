@@ -0,0 +1,223 @@
+//! # printer_test.rs
+//!
+//! # printer_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for the AST pretty-printer.
+//!
+//! AST 美化打印器的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks that printing the examples and parsing the result gives back the same program, and covers the layout of each statement kind, condition parentheses and string quoting.
+//!
+//! 检查打印示例文件后重新解析能否得到相同的程序，并覆盖各类语句的布局、条件括号和字符串引号。
+
+use crate::ast::*;
+use crate::printer::{print_node_stmt, print_program};
+use crate::{ParseHandler, Serializer};
+use std::fs;
+use std::path::Path;
+
+fn parse(source: &str) -> Program {
+    ParseHandler::parse_source_code(source, false).expect("source should parse")
+}
+
+/// The compiled JSON without the generation timestamp, which compares programs while ignoring spans.
+fn compiled(program: &Program) -> String {
+    Serializer::serialize_to_json(program, true)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.contains("generated_at"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn round_trip(source: &str) -> String {
+    let program = parse(source);
+    let printed = print_program(&program);
+    let reparsed = parse(&printed);
+
+    assert_eq!(compiled(&reparsed), compiled(&program), "{}", printed);
+    assert_eq!(print_program(&reparsed), printed);
+    printed
+}
+
+#[test]
+fn test_examples_round_trip() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut checked = 0;
+
+    for language in fs::read_dir(examples).unwrap() {
+        for entry in fs::read_dir(language.unwrap().path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "mortar") {
+                continue;
+            }
+
+            let program = parse(&fs::read_to_string(&path).unwrap());
+            let printed = print_program(&program);
+            let reparsed = ParseHandler::parse_source_code(&printed, false)
+                .unwrap_or_else(|error| panic!("{}: {:?}\n{}", path.display(), error, printed));
+
+            assert_eq!(
+                compiled(&reparsed),
+                compiled(&program),
+                "{}",
+                path.display()
+            );
+            assert_eq!(print_program(&reparsed), printed, "{}", path.display());
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0);
+}
+
+#[test]
+fn test_top_level_layout() {
+    let source = "public const Title: String = 'Game'\npub const Max: Number = 3\nlet ready: Boolean\nlet mood: Mood = Mood.happy\nfunction play(file: String, loud: Bool) -> Bool\nfn stop()\nenum Mood { happy sad }\nevent Ding { index: 0.5 action: play(\"a.wav\", true).wait() duration: 2 }\ntl Intro { run Ding now run Ding(1) with 2 wait 1 }\nnd Start {} -> return\n";
+
+    assert_eq!(
+        round_trip(source),
+        r#"pub const Title: String = "Game"
+pub const Max: Number = 3
+
+let ready: Bool
+let mood: Mood = Mood.happy
+
+fn play(file: String, loud: Bool) -> Bool
+fn stop()
+
+enum Mood {
+    happy
+    sad
+}
+
+event Ding {
+    index: 0.5
+    action: play("a.wav", true).wait()
+    duration: 2
+}
+
+timeline Intro {
+    run Ding
+    now run Ding(1) with 2
+    wait 1
+}
+
+node Start {} -> return
+"#
+    );
+}
+
+#[test]
+fn test_node_statement_layout() {
+    let source = r#"
+let place: branch [
+    is_forest, "forest", is_city, "city"
+]
+node Start {
+    text: $"Hi {name}, you are in {place} with {count(items, 2)}!"
+    with events: [0, play("a.wav") 1.5, stop()]
+    text: "Ding"
+    with Ding
+    text: "Ding again"
+    with run Ding with 3
+    run Ding(2) with slot
+    object: branch<Mood> [
+        happy, "smile", events: [0, play("b.wav")]
+        sad, "frown"
+    ]
+    choice: ["A" -> Next, "B" when ready() -> ["C" -> return; "D" when open -> break]]
+    score = 1
+    mood = Mood.sad
+} -> Next
+event Ding { action: stop() }
+"#;
+
+    assert_eq!(
+        round_trip(source),
+        r#"let place: branch [
+    is_forest, "forest"
+    is_city, "city"
+]
+
+node Start {
+    text: $"Hi {name}, you are in {place} with {count(items, 2)}!"
+    with events: [
+        0, play("a.wav")
+        1.5, stop()
+    ]
+    text: "Ding"
+    with Ding
+    text: "Ding again"
+    with run Ding with 3
+    run Ding(2) with slot
+    object: branch<Mood> [
+        happy, "smile", events: [
+            0, play("b.wav")
+        ]
+        sad, "frown"
+    ]
+    choice: [
+        "A" -> Next
+        "B" when ready() -> [
+            "C" -> return
+            "D" when open -> break
+        ]
+    ]
+    score = 1
+    mood = Mood.sad
+} -> Next
+
+event Ding {
+    action: stop()
+}
+"#
+    );
+}
+
+#[test]
+fn test_if_conditions_keep_their_grouping() {
+    let source = "node Start {\n    if (a || b) && !(c > 1) && !d && (e == f || g) {\n        text: \"yes\"\n    } else {\n        if x < (y < z) {\n            text: \"nested\"\n        }\n    }\n}\n";
+
+    assert_eq!(
+        round_trip(source),
+        "node Start {\n    if (a || b) && !(c > 1) && !d && (e == f || g) {\n        text: \"yes\"\n    } else {\n        if x < (y < z) {\n            text: \"nested\"\n        }\n    }\n}\n"
+    );
+}
+
+#[test]
+fn test_strings_keep_escapes_and_quotes() {
+    let source = "node Start {\n    text: 'She said \"hi\"'\n    text: \"It's \\\"fine\\\"\"\n    text: 'plain'\n}\n";
+
+    assert_eq!(
+        round_trip(source),
+        "node Start {\n    text: 'She said \"hi\"'\n    text: \"It's \\\"fine\\\"\"\n    text: \"plain\"\n}\n"
+    );
+}
+
+#[test]
+fn test_display_prints_single_items() {
+    let program = parse("node Start {\n    if ready {\n        text: \"Hi\"\n    }\n}\n");
+    let TopLevel::NodeDef(node) = &program.body[0] else {
+        panic!("expected a node");
+    };
+
+    assert_eq!(
+        print_node_stmt(&node.body[0]),
+        "if ready {\n    text: \"Hi\"\n}"
+    );
+    assert_eq!(node.body[0].to_string(), print_node_stmt(&node.body[0]));
+    assert_eq!(
+        program.body[0].to_string(),
+        "node Start {\n    if ready {\n        text: \"Hi\"\n    }\n}"
+    );
+    assert_eq!(program.to_string(), print_program(&program));
+}
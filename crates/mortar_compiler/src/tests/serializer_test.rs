@@ -35,47 +35,61 @@ fn create_test_program() -> Program {
                 params: vec![Param {
                     name: "p1".to_string(),
                     type_name: "String".to_string(),
+                    span: (0, 0),
                 }],
                 return_type: Some("Number".to_string()),
+                span: (0, 0),
             }),
             TopLevel::NodeDef(NodeDef {
                 name: "start_node".to_string(),
                 name_span: Some((0, 10)), // Approximate span
                 body: vec![
-                    NodeStmt::Text("This is the first line.".to_string()),
+                    NodeStmt::Text("This is the first line.".to_string(), (0, 0)),
                     NodeStmt::WithEvents(WithEventsStmt {
                         events: vec![WithEventItem::InlineEvent(Event {
                             index: 0.5,
+                            index_span: (0, 0),
                             action: EventAction {
                                 call: FuncCall {
                                     name: "play_sound".to_string(),
                                     name_span: Some((0, 10)), // Approximate span
-                                    args: vec![Arg::String("music.mp3".to_string())],
+                                    args: vec![Arg::String("music.mp3".to_string(), (0, 0))],
+                                    span: (0, 0),
                                 },
                                 chains: vec![],
+                                span: (0, 0),
                             },
+                            span: (0, 0),
                         })],
+                        span: (0, 0),
                     }),
-                    NodeStmt::Text("This is the second line.".to_string()),
-                    NodeStmt::Choice(vec![
-                        ChoiceItem {
-                            text: "Go to next".to_string(),
-                            condition: None,
-                            target: ChoiceDest::Identifier("next_node".to_string(), Some((0, 9))),
-                        },
-                        ChoiceItem {
-                            text: "Stay here".to_string(),
-                            condition: Some(Condition::Identifier("has_item".to_string())),
-                            target: ChoiceDest::Break,
-                        },
-                    ]),
+                    NodeStmt::Text("This is the second line.".to_string(), (0, 0)),
+                    NodeStmt::Choice(
+                        vec![
+                            ChoiceItem {
+                                text: "Go to next".to_string(),
+                                condition: None,
+                                target: ChoiceDest::Identifier("next_node".to_string(), (0, 9)),
+                                span: (0, 0),
+                            },
+                            ChoiceItem {
+                                text: "Stay here".to_string(),
+                                condition: Some(Condition::Identifier(
+                                    "has_item".to_string(),
+                                    (0, 0),
+                                )),
+                                target: ChoiceDest::Break((0, 0)),
+                                span: (0, 0),
+                            },
+                        ],
+                        (0, 0),
+                    ),
                 ],
-                jump: Some(NodeJump::Identifier(
-                    "default_next".to_string(),
-                    Some((0, 12)),
-                )),
+                jump: Some(NodeJump::Identifier("default_next".to_string(), (0, 12))),
+                span: (0, 0),
             }),
         ],
+        span: (0, 0),
     }
 }
 
@@ -171,7 +185,10 @@ fn test_serialize_choices() {
 fn test_serialize_empty_program() {
     use crate::ast::Program;
 
-    let program = Program { body: vec![] };
+    let program = Program {
+        body: vec![],
+        span: (0, 0),
+    };
     let result = Serializer::serialize_to_json(&program, false);
     assert!(result.is_ok());
 
@@ -185,7 +202,10 @@ fn test_serialize_empty_program() {
 fn test_serialize_to_json_pretty() {
     use crate::ast::Program;
 
-    let program = Program { body: vec![] };
+    let program = Program {
+        body: vec![],
+        span: (0, 0),
+    };
     let result = Serializer::serialize_to_json(&program, true);
     assert!(result.is_ok());
 
@@ -199,7 +219,10 @@ fn test_save_to_file_basic() {
     use crate::ast::Program;
     use tempfile::TempDir;
 
-    let program = Program { body: vec![] };
+    let program = Program {
+        body: vec![],
+        span: (0, 0),
+    };
     let temp_dir = TempDir::new().unwrap();
     let output_file = temp_dir.path().join("output.mortared");
 
@@ -214,7 +237,10 @@ fn test_save_to_file_with_language() {
     use crate::Language;
     use tempfile::TempDir;
 
-    let program = Program { body: vec![] };
+    let program = Program {
+        body: vec![],
+        span: (0, 0),
+    };
     let temp_dir = TempDir::new().unwrap();
     let output_file = temp_dir.path().join("output.mortared");
 
@@ -238,14 +264,17 @@ fn test_serialize_variable_declarations() {
                 name_span: Some((0, 11)),
                 type_name: "String".to_string(),
                 value: None,
+                span: (0, 0),
             }),
             TopLevel::VarDecl(VarDecl {
                 name: "score".to_string(),
                 name_span: Some((0, 5)),
                 type_name: "Number".to_string(),
-                value: Some(VarValue::Number(100.0)),
+                value: Some(VarValue::Number(100.0, (0, 0))),
+                span: (0, 0),
             }),
         ],
+        span: (0, 0),
     };
 
     let json_string = Serializer::serialize_to_json(&program, false).unwrap();
@@ -270,16 +299,19 @@ fn test_serialize_constant_declarations() {
                 name: "game_title".to_string(),
                 name_span: Some((0, 10)),
                 type_name: "String".to_string(),
-                value: VarValue::String("My Game".to_string()),
+                value: VarValue::String("My Game".to_string(), (0, 0)),
+                span: (0, 0),
             }),
             TopLevel::ConstDecl(ConstDecl {
                 is_public: false,
                 name: "max_level".to_string(),
                 name_span: Some((0, 9)),
                 type_name: "Number".to_string(),
-                value: VarValue::Number(99.0),
+                value: VarValue::Number(99.0, (0, 0)),
+                span: (0, 0),
             }),
         ],
+        span: (0, 0),
     };
 
     let json_string = Serializer::serialize_to_json(&program, false).unwrap();
@@ -307,7 +339,10 @@ fn test_serialize_enum_definitions() {
                 "playing".to_string(),
                 "paused".to_string(),
             ],
+            variant_spans: vec![(0, 0); 3],
+            span: (0, 0),
         })],
+        span: (0, 0),
     };
 
     let json_string = Serializer::serialize_to_json(&program, false).unwrap();
@@ -320,3 +355,32 @@ fn test_serialize_enum_definitions() {
     assert_eq!(json["enums"][0]["variants"][1], "playing");
     assert_eq!(json["enums"][0]["variants"][2], "paused");
 }
+
+#[test]
+fn test_ast_json_round_trips_through_deserialize() {
+    let source = "let ready: Bool\nnode Start {\n    text: $\"Hi {name}\"\n    if !ready {\n        choice: [\"Go\" when ready -> return]\n    }\n} -> Start\n";
+    let program = crate::ParseHandler::parse_source_code(source, false).unwrap();
+
+    let json = Serializer::ast_to_json(&program, false).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["span"], serde_json::json!([0, source.len() - 1]));
+    assert_eq!(value["body"][1]["NodeDef"]["name"], "Start");
+
+    let restored: Program = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, program);
+}
+
+#[test]
+fn test_tokens_json_keeps_spans() {
+    let source = "node Start { // hi\n}";
+    let json = Serializer::tokens_to_json(source, false).unwrap();
+    let tokens: Value = serde_json::from_str(&json).unwrap();
+    let tokens = tokens.as_array().unwrap();
+
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(tokens[0]["token"], "Node");
+    assert_eq!(tokens[1]["token"]["Identifier"], "Start");
+    assert_eq!(tokens[1]["start"], 5);
+    assert_eq!(tokens[1]["end"], 10);
+    assert_eq!(tokens[3]["text"], "// hi");
+}
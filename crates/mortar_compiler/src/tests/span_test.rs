@@ -0,0 +1,175 @@
+//! # span_test.rs
+//!
+//! # span_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for the source spans recorded on AST nodes.
+//!
+//! 针对 AST 节点上记录的源码范围的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Parses small scripts and checks that statements, choices, conditions, events and interpolated parts point at the right source text.
+//!
+//! 解析简短的脚本，检查语句、选项、条件、事件和插值片段是否指向正确的源码文本。
+
+use crate::ast::*;
+use crate::{DiagnosticKind, ParseHandler};
+
+fn parse(source: &str) -> Program {
+    ParseHandler::parse_source_code(source, false).expect("source should parse")
+}
+
+fn text(source: &str, (start, end): Span) -> &str {
+    &source[start..end]
+}
+
+fn first_node(program: &Program) -> &NodeDef {
+    program
+        .body
+        .iter()
+        .find_map(|item| match item {
+            TopLevel::NodeDef(node) => Some(node),
+            _ => None,
+        })
+        .expect("expected a node")
+}
+
+#[test]
+fn test_node_and_statement_spans() {
+    let source = "node Start {\n    text: \"Hello\"\n    score = 1\n} -> Next\n";
+    let program = parse(source);
+    let node = first_node(&program);
+
+    assert_eq!(text(source, node.span), &source[..source.len() - 1]);
+    assert_eq!(text(source, node.body[0].span()), "text: \"Hello\"");
+    assert_eq!(text(source, node.body[1].span()), "score = 1");
+    assert_eq!(text(source, node.jump.as_ref().unwrap().span()), "Next");
+    assert_eq!(program.span, (0, source.len() - 1));
+}
+
+#[test]
+fn test_choice_and_condition_spans() {
+    let source = r#"node Start {
+    choice: [
+        "Left" when has_map -> Next,
+        "Right" when can_leave() -> return
+    ]
+}
+"#;
+    let program = parse(source);
+    let node = first_node(&program);
+    let NodeStmt::Choice(items, span) = &node.body[0] else {
+        panic!("expected a choice statement");
+    };
+
+    assert!(text(source, *span).starts_with("choice: ["));
+    assert!(text(source, *span).ends_with(']'));
+    assert_eq!(text(source, items[0].target.span()), "Next");
+    assert_eq!(text(source, items[1].target.span()), "return");
+    assert_eq!(
+        text(source, items[0].condition.as_ref().unwrap().span()),
+        "has_map"
+    );
+    assert_eq!(
+        text(source, items[1].condition.as_ref().unwrap().span()),
+        "can_leave()"
+    );
+    assert_eq!(
+        text(source, items[1].span),
+        "\"Right\" when can_leave() -> return"
+    );
+}
+
+#[test]
+fn test_if_condition_spans() {
+    let source = "let a: Bool = true\nlet b: Bool = false\nnode Start {\n    if a && !b {\n        text: \"Yes\"\n    }\n}\n";
+    let program = parse(source);
+    let node = first_node(&program);
+    let NodeStmt::IfElse(if_else) = &node.body[0] else {
+        panic!("expected an if statement");
+    };
+
+    assert!(text(source, if_else.span).starts_with("if a && !b {"));
+    assert_eq!(text(source, if_else.condition.span()), "a && !b");
+    let IfCondition::Binary(binary) = &if_else.condition else {
+        panic!("expected a binary condition");
+    };
+    assert_eq!(text(source, binary.operator_span), "&&");
+    assert_eq!(text(source, binary.right.span()), "!b");
+}
+
+#[test]
+fn test_event_spans() {
+    let source = r#"node Start {
+    text: "Hello"
+    with events: [
+        0.5, play_sound("a.wav").wait()
+    ]
+}
+"#;
+    let program = parse(source);
+    let node = first_node(&program);
+    let NodeStmt::WithEvents(with_events) = &node.body[1] else {
+        panic!("expected a with-events statement");
+    };
+    let WithEventItem::InlineEvent(event) = &with_events.events[0] else {
+        panic!("expected an inline event");
+    };
+
+    assert_eq!(text(source, event.index_span), "0.5");
+    assert_eq!(
+        text(source, event.span),
+        "0.5, play_sound(\"a.wav\").wait()"
+    );
+    assert_eq!(
+        text(source, event.action.call.span),
+        "play_sound(\"a.wav\")"
+    );
+    assert_eq!(text(source, event.action.call.args[0].span()), "\"a.wav\"");
+}
+
+#[test]
+fn test_interpolated_part_spans() {
+    let source = "node Start {\n    text: $\"Hi {name}, {greet(name)}!\"\n}\n";
+    let program = parse(source);
+    let node = first_node(&program);
+    let NodeStmt::InterpolatedText(interpolated, span) = &node.body[0] else {
+        panic!("expected interpolated text");
+    };
+
+    assert_eq!(text(source, *span), "text: $\"Hi {name}, {greet(name)}!\"");
+    let parts: Vec<&str> = interpolated
+        .parts
+        .iter()
+        .map(|part| text(source, part.span()))
+        .collect();
+    assert_eq!(parts, vec!["Hi ", "name", ", ", "greet(name)", "!"]);
+}
+
+#[test]
+fn test_duplicate_enum_variant_points_at_variants() {
+    let source = "enum Mood {\n    happy\n    sad\n    happy\n}\n";
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+
+    let diagnostic = diagnostics
+        .get_diagnostics()
+        .iter()
+        .find(|d| matches!(d.kind, DiagnosticKind::DuplicateEnumVariant { .. }))
+        .expect("expected a duplicate variant diagnostic");
+
+    let (start, end) = diagnostic.span.unwrap();
+    assert_eq!(&source[start..end], "happy");
+    assert_eq!(start, source.rfind("happy").unwrap());
+    assert_eq!(
+        diagnostic.kind.first_span(),
+        Some((16, 21)),
+        "the first definition of the variant"
+    );
+}
@@ -0,0 +1,303 @@
+//! # string_test.rs
+//!
+//! # string_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for string literals: escape sequences, raw strings and multi-line strings.
+//!
+//! 字符串字面量的测试：转义序列、原始字符串和多行字符串。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks that every kind of string is lexed as one token and decoded to the right value, that invalid escapes are reported with their exact location, and that printed programs keep the decoded text.
+//!
+//! 检查每种字符串都被识别为单个记号并解码为正确的值，无效转义能以精确位置报告，且打印出的程序保留解码后的文本。
+
+use crate::ast::*;
+use crate::printer::print_program;
+use crate::token::{
+    EscapeError, Token, dedent, string_chars, string_value, tokenize, unescape, unescape_chars,
+};
+use crate::{DiagnosticKind, ParseHandler};
+use std::borrow::Cow;
+
+fn texts(source: &str) -> Vec<String> {
+    let program = ParseHandler::parse_source_code(source, false).expect("source should parse");
+    let TopLevel::NodeDef(node) = &program.body[0] else {
+        panic!("expected a node");
+    };
+
+    node.body
+        .iter()
+        .map(|stmt| match stmt {
+            NodeStmt::Text(text, _) => text.clone(),
+            NodeStmt::InterpolatedText(interpolated, _) => interpolated
+                .parts
+                .iter()
+                .map(|part| match part {
+                    StringPart::Text(text, _) => text.clone(),
+                    StringPart::Placeholder(name, _) => format!("<{}>", name),
+                    StringPart::Expression(call) => format!("<{}()>", call.name),
+                })
+                .collect(),
+            other => panic!("expected text, found {:?}", other),
+        })
+        .collect()
+}
+
+/// `(spanned source text, message)` of every invalid escape.
+fn escape_errors(source: &str) -> Vec<(String, String)> {
+    let (_result, diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(source, "test.mortar".to_string(), false);
+
+    diagnostics
+        .get_diagnostics()
+        .iter()
+        .filter(|d| matches!(d.kind, DiagnosticKind::SyntaxError { code: "M0007", .. }))
+        .map(|d| {
+            let (start, end) = d.span.unwrap();
+            (source[start..end].to_string(), d.message.clone())
+        })
+        .collect()
+}
+
+#[test]
+fn test_unescape_decodes_every_sequence() {
+    assert_eq!(
+        unescape(r#"a\nb\tc\rd\0e\\f\"g\'h\{i\}j"#).unwrap(),
+        "a\nb\tc\rd\0e\\f\"g'h{i}j"
+    );
+    assert_eq!(unescape(r"\u{41}\u{597D}\u{1F600}").unwrap(), "A好😀");
+}
+
+#[test]
+fn test_unescape_borrows_text_without_escapes() {
+    assert!(matches!(
+        unescape("plain 文本"),
+        Ok(Cow::Borrowed("plain 文本"))
+    ));
+}
+
+#[test]
+fn test_unescape_reports_the_invalid_sequence() {
+    let error = |raw| unescape(raw).unwrap_err();
+
+    assert_eq!(
+        error(r"ok \q"),
+        EscapeError {
+            sequence: r"\q".to_string(),
+            span: (3, 5),
+        }
+    );
+    assert_eq!(error(r"好\x").span, (3, 5));
+    assert_eq!(error(r"end\").sequence, r"\");
+    assert_eq!(error(r"\u41").sequence, r"\u");
+    assert_eq!(error(r"\u{}").sequence, r"\u{}");
+    assert_eq!(error(r"\u{1234567}").sequence, r"\u{1234567}");
+    assert_eq!(error(r"\u{D800}").sequence, r"\u{D800}");
+    assert_eq!(error(r"\u{zz} after").span, (0, 6));
+}
+
+#[test]
+fn test_dedent_strips_common_indentation() {
+    assert_eq!(
+        dedent("\n        First\n          Second\n\n        Third\n        "),
+        "First\n  Second\n\nThird"
+    );
+    assert_eq!(dedent("\r\n    a\r\n    b\r\n    "), "a\nb");
+    assert_eq!(dedent("inline"), "inline");
+    assert_eq!(dedent("\tkeep\n\t\tnested\n"), "keep\n\tnested");
+}
+
+#[test]
+fn test_unescape_chars_keeps_source_offsets() {
+    assert_eq!(
+        unescape_chars(r"a\n好\u{41}b").unwrap(),
+        vec![(0, 'a'), (1, '\n'), (3, '好'), (6, 'A'), (12, 'b')]
+    );
+}
+
+#[test]
+fn test_string_chars_map_back_to_the_literal() {
+    let literals = [
+        r#""plain""#,
+        r#"'a\tb'"#,
+        r#"r"C:\path""#,
+        "\"\"\"\n        ab\n          cd\\n\n        \"\"\"",
+        "\"\"\"\r\n    one\r\n\r\n    two\r\n    \"\"\"",
+        "r\"\"\"\n\t\\raw\n\t\"\"\"",
+    ];
+
+    for literal in literals {
+        let chars = string_chars(literal).unwrap();
+        let value: String = chars.iter().map(|(_, ch)| ch).collect();
+        assert_eq!(value, string_value(literal).unwrap(), "{}", literal);
+
+        for (at, ch) in chars {
+            let source = &literal[at..];
+            let expected = if source.starts_with('\\') && !literal.starts_with('r') {
+                unescape(&source[..2]).map_or(true, |decoded| decoded.starts_with(ch))
+            } else {
+                source.starts_with(ch)
+            };
+            assert!(expected, "{:?} at {} in {:?}", ch, at, literal);
+        }
+    }
+
+    let chars = string_chars("\"\"\"\n        ab\n          cd\n        \"\"\"").unwrap();
+    assert_eq!(chars[3], (23, ' '));
+    assert_eq!(chars[5], (25, 'c'));
+}
+
+#[test]
+fn test_every_string_kind_is_one_token() {
+    let source = r#"'single' r"raw\n" r'raw "quoted"' """
+    block "with" quotes \"""
+""" r"""
+    raw \block
+""""#;
+    let tokens = tokenize(source);
+
+    let contents: Vec<&str> = tokens
+        .iter()
+        .map(|t| match t.token {
+            Token::String(content) => content,
+            ref other => panic!("expected a string, found {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        contents,
+        vec![
+            "single",
+            r"raw\n",
+            r#"raw "quoted""#,
+            "\n    block \"with\" quotes \\\"\"\"\n",
+            "\n    raw \\block\n",
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_multi_line_string_is_an_error() {
+    let tokens = tokenize("text: \"\"\"\n    never closed\n");
+    assert!(tokens.iter().any(|t| t.token == Token::Error));
+}
+
+#[test]
+fn test_string_value_by_kind() {
+    assert_eq!(string_value(r#""a\tb""#).unwrap(), "a\tb");
+    assert_eq!(string_value(r#"'it\'s'"#).unwrap(), "it's");
+    assert_eq!(string_value(r#"r"C:\games\n""#).unwrap(), r"C:\games\n");
+    assert_eq!(
+        string_value("\"\"\"\n    Hi\\tthere\n      \\u{597D}\n    \"\"\"").unwrap(),
+        "Hi\tthere\n  好"
+    );
+    assert_eq!(
+        string_value("r\"\"\"\n    C:\\saves\n    \"\"\"").unwrap(),
+        r"C:\saves"
+    );
+    assert_eq!(string_value(r#""\q""#).unwrap_err().span, (1, 3));
+    assert_eq!(
+        string_value("\"\"\"\n        \\q\n    \"\"\"")
+            .unwrap_err()
+            .span,
+        (12, 14)
+    );
+}
+
+#[test]
+fn test_parser_stores_decoded_text() {
+    let source = "node Start {
+    text: \"She said \\\"hi\\\"\\n\"
+    text: r\"C:\\games\"
+    text: \"\"\"
+        A long
+        monologue.
+        \"\"\"
+    text: $\"Gold: \\{{gold}\\} \\\"{name()}\\\"\"
+}
+let gold: Number = 0
+fn name() -> String
+";
+
+    assert_eq!(
+        texts(source),
+        vec![
+            "She said \"hi\"\n",
+            r"C:\games",
+            "A long\nmonologue.",
+            "Gold: {<gold>} \"<name()>\"",
+        ]
+    );
+}
+
+#[test]
+fn test_escapes_are_decoded_in_every_string_position() {
+    let source = r#"
+let greeting: String = "a\tb"
+fn play(file: String)
+node Start {
+    text: $"{play("x\ty")}"
+    with events: [0, play("c\\d")]
+    greeting = 'e\'f'
+    choice: ["g\"h" -> return]
+}
+"#;
+    let program = ParseHandler::parse_source_code(source, false).unwrap();
+    let printed = print_program(&program);
+
+    assert!(printed.contains(r#"let greeting: String = "a\tb""#));
+    assert!(printed.contains(r#"text: $"{play("x\ty")}""#));
+    assert!(printed.contains(r#"0, play("c\\d")"#));
+    assert!(printed.contains(r#"greeting = "e'f""#));
+    assert!(printed.contains(r#"'g"h' -> return"#));
+}
+
+#[test]
+fn test_invalid_escapes_are_reported() {
+    let source = r#"
+node Start {
+    text: "C:\games"
+    text: $"Hi \x {name}"
+    text: "fine"
+}
+"#;
+
+    assert_eq!(
+        escape_errors(source),
+        vec![
+            (
+                r"\g".to_string(),
+                r"Invalid escape sequence: \g".to_string()
+            ),
+            (
+                r"\x".to_string(),
+                r"Invalid escape sequence: \x".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_printed_strings_parse_back_to_the_same_text() {
+    let source = "node Start {
+    text: \"\"\"
+        Line one
+        \tTabbed \\\\ \"quoted\"
+        \"\"\"
+    text: $\"\\{literal\\} {name}\\n\"
+}
+let name: String = \"x\"
+";
+    let program = ParseHandler::parse_source_code(source, false).unwrap();
+    let printed = print_program(&program);
+
+    assert!(printed.contains(r#"text: 'Line one\n\tTabbed \\ "quoted"'"#));
+    assert!(printed.contains(r#"text: $"\{literal\} {name}\n""#));
+    assert_eq!(texts(&printed), texts(source));
+}
@@ -290,3 +290,31 @@ fn test_tokenize_complex_program() {
     // Should have multiple tokens
     assert!(tokens.len() > 20);
 }
+
+#[test]
+fn test_tokenize_unicode_identifiers() {
+    let input = "node 开始 { 好感度_2 = Énergie }";
+    let tokens = tokenize(input);
+
+    let identifiers: Vec<(&str, usize, usize)> = tokens
+        .iter()
+        .filter(|t| matches!(t.token, Token::Identifier(_)))
+        .map(|t| (t.text, t.start, t.end))
+        .collect();
+    assert_eq!(
+        identifiers,
+        vec![("开始", 5, 11), ("好感度_2", 14, 25), ("Énergie", 28, 36)]
+    );
+    assert!(!tokens.iter().any(|t| matches!(t.token, Token::Error)));
+}
+
+#[test]
+fn test_identifiers_must_start_with_xid_start() {
+    let tokens = tokenize("_名字 1名字 ·x");
+
+    assert!(matches!(tokens[0].token, Token::Identifier("_名字")));
+    assert!(matches!(tokens[1].token, Token::Number("1")));
+    assert!(matches!(tokens[2].token, Token::Identifier("名字")));
+    // U+00B7 continues identifiers but cannot start one
+    assert!(matches!(tokens[3].token, Token::Error));
+}
@@ -55,7 +55,7 @@ fn test_parse_variable_declaration_with_string_value() {
             assert_eq!(var.type_name, "String");
             assert!(var.value.is_some());
             match &var.value {
-                Some(VarValue::String(s)) => assert_eq!(s, "My Game"),
+                Some(VarValue::String(s, _)) => assert_eq!(s, "My Game"),
                 _ => panic!("Expected String value"),
             }
         }
@@ -75,7 +75,7 @@ fn test_parse_variable_declaration_with_number_value() {
             assert_eq!(var.name, "score");
             assert_eq!(var.type_name, "Number");
             match &var.value {
-                Some(VarValue::Number(n)) => assert_eq!(*n, 100.0),
+                Some(VarValue::Number(n, _)) => assert_eq!(*n, 100.0),
                 _ => panic!("Expected Number value"),
             }
         }
@@ -94,7 +94,7 @@ fn test_parse_variable_declaration_with_float_value() {
         TopLevel::VarDecl(var) => {
             assert_eq!(var.name, "health");
             match &var.value {
-                Some(VarValue::Number(n)) => assert_eq!(*n, 50.5),
+                Some(VarValue::Number(n, _)) => assert_eq!(*n, 50.5),
                 _ => panic!("Expected Number value"),
             }
         }
@@ -114,7 +114,7 @@ fn test_parse_variable_declaration_with_bool_true() {
             assert_eq!(var.name, "is_active");
             assert_eq!(var.type_name, "Boolean");
             match &var.value {
-                Some(VarValue::Boolean(b)) => assert!(*b),
+                Some(VarValue::Boolean(b, _)) => assert!(*b),
                 _ => panic!("Expected Boolean value"),
             }
         }
@@ -134,7 +134,7 @@ fn test_parse_variable_declaration_with_bool_false() {
             assert_eq!(var.name, "debug_mode");
             assert_eq!(var.type_name, "Boolean");
             match &var.value {
-                Some(VarValue::Boolean(b)) => assert!(!(*b)),
+                Some(VarValue::Boolean(b, _)) => assert!(!(*b)),
                 _ => panic!("Expected Boolean value"),
             }
         }
@@ -155,7 +155,7 @@ fn test_parse_public_constant() {
             assert_eq!(const_decl.type_name, "String");
             assert!(const_decl.is_public);
             match &const_decl.value {
-                VarValue::String(s) => assert_eq!(s, "Hello"),
+                VarValue::String(s, _) => assert_eq!(s, "Hello"),
                 _ => panic!("Expected String value"),
             }
         }
@@ -176,7 +176,7 @@ fn test_parse_private_constant() {
             assert_eq!(const_decl.type_name, "Number");
             assert!(!const_decl.is_public);
             match &const_decl.value {
-                VarValue::Number(n) => assert_eq!(*n, 99.0),
+                VarValue::Number(n, _) => assert_eq!(*n, 99.0),
                 _ => panic!("Expected Number value"),
             }
         }
@@ -0,0 +1,170 @@
+//! # visit_test.rs
+//!
+//! # visit_test.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tests for the `Visit` and `VisitMut` traversal traits.
+//!
+//! `Visit` 与 `VisitMut` 遍历 trait 的测试。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Checks that the default walks reach nested choices, `else` bodies, nested event lists and branch values, that an override can stop the descent, and that `VisitMut` can rewrite a program in place.
+//!
+//! 检查默认遍历能否到达嵌套选项、`else` 分支体、嵌套事件列表和分支值，重写方法能否停止向下遍历，以及 `VisitMut` 能否原地改写程序。
+
+use crate::ParseHandler;
+use crate::ast::*;
+use crate::printer::print_program;
+use crate::visit::{self, Visit, VisitMut};
+
+fn parse(source: &str) -> Program {
+    ParseHandler::parse_source_code(source, false).expect("source should parse")
+}
+
+/// Records the name of every function call it reaches.
+#[derive(Default)]
+struct Calls(Vec<String>);
+
+impl Visit for Calls {
+    fn visit_func_call(&mut self, call: &FuncCall) {
+        self.0.push(call.name.clone());
+        visit::walk_func_call(self, call);
+    }
+}
+
+const SOURCE: &str = r#"
+let place: branch [
+    is_forest, "forest", events: [0, in_branch_value()]
+]
+event Ding { action: in_event_def(arg_call()) }
+timeline Intro { run Ding(in_timeline()) }
+node Start {
+    text: $"Hi {in_text()}"
+    with events: [0, in_inline_event().in_chain()]
+    choice: [
+        "A" -> Next,
+        "B" -> ["C" when in_nested_choice() -> return]
+    ]
+    if ready {
+        text: "yes"
+    } else {
+        object: branch [
+            is_open, "open", events: [0, in_else_branch()]
+        ]
+    }
+}
+"#;
+
+#[test]
+fn test_visit_reaches_every_nested_call() {
+    let mut calls = Calls::default();
+    calls.visit_program(&parse(SOURCE));
+
+    assert_eq!(
+        calls.0,
+        vec![
+            "in_branch_value",
+            "in_event_def",
+            "arg_call",
+            "in_timeline",
+            "in_text",
+            "in_inline_event",
+            "in_chain",
+            "in_nested_choice",
+            "in_else_branch",
+        ]
+    );
+}
+
+#[test]
+fn test_visit_reaches_nested_event_lists() {
+    let program = parse(SOURCE);
+    let TopLevel::NodeDef(node) = &program.body[3] else {
+        panic!("expected a node");
+    };
+    let NodeStmt::WithEvents(with_events) = &node.body[1] else {
+        panic!("expected a with-events statement");
+    };
+    let nested = WithEventsStmt {
+        events: vec![WithEventItem::EventList(
+            with_events.events.clone(),
+            with_events.span,
+        )],
+        span: with_events.span,
+    };
+
+    let mut calls = Calls::default();
+    calls.visit_with_events(&nested);
+
+    assert_eq!(calls.0, vec!["in_inline_event", "in_chain"]);
+}
+
+#[test]
+fn test_override_without_walk_stops_descent() {
+    /// Skips everything inside nodes.
+    #[derive(Default)]
+    struct TopLevelCalls(Calls);
+
+    impl Visit for TopLevelCalls {
+        fn visit_node_def(&mut self, _node: &NodeDef) {}
+
+        fn visit_func_call(&mut self, call: &FuncCall) {
+            self.0.visit_func_call(call);
+        }
+    }
+
+    let mut calls = TopLevelCalls::default();
+    calls.visit_program(&parse(SOURCE));
+
+    assert_eq!(
+        calls.0.0,
+        vec!["in_branch_value", "in_event_def", "arg_call", "in_timeline"]
+    );
+}
+
+#[test]
+fn test_visit_mut_rewrites_in_place() {
+    /// Renames a function at every call site.
+    struct RenameCall;
+
+    impl VisitMut for RenameCall {
+        fn visit_func_call_mut(&mut self, call: &mut FuncCall) {
+            if call.name == "ready" {
+                call.name = "is_ready".to_string();
+            }
+            visit::walk_func_call_mut(self, call);
+        }
+    }
+
+    let mut program = parse(
+        r#"node Start {
+    text: $"{ready()}"
+    with events: [0, play(ready())]
+    choice: ["Go" -> ["Now" when ready() -> return]]
+}
+"#,
+    );
+    RenameCall.visit_program_mut(&mut program);
+
+    assert_eq!(
+        print_program(&program),
+        r#"node Start {
+    text: $"{is_ready()}"
+    with events: [
+        0, play(is_ready())
+    ]
+    choice: [
+        "Go" -> [
+            "Now" when is_ready() -> return
+        ]
+    ]
+}
+"#
+    );
+}
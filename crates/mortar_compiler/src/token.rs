@@ -22,11 +22,14 @@
 //!
 //! 此文件包含 `Token` 枚举定义、token 显示格式化以及解析器和 LSP 使用的 `tokenize` 函数。
 
+use crate::ast::Span;
 use logos::Logos;
 use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::fmt;
 
-#[derive(Logos, Debug, PartialEq, Clone)]
+#[derive(Logos, Debug, PartialEq, Clone, Serialize)]
 // Ignore whitespace
 #[logos(skip r"[ \t\r\n]+")]
 pub enum Token<'a> {
@@ -165,6 +168,7 @@ pub enum Token<'a> {
     // endregion
 
     // region Literals
+    // The payload is the raw text between the quotes; `string_value` decodes it
     #[regex(r#""([^"\\]|\\.)*""#, |lex| {
         let s = lex.slice();
         &s[1..s.len()-1]
@@ -173,6 +177,18 @@ pub enum Token<'a> {
         let s = lex.slice();
         &s[1..s.len()-1]
     })]
+    // Raw strings: r"C:\path" keeps every backslash
+    #[regex(r#"r"[^"]*""#, |lex| {
+        let s = lex.slice();
+        &s[2..s.len()-1]
+    })]
+    #[regex(r#"r'[^']*'"#, |lex| {
+        let s = lex.slice();
+        &s[2..s.len()-1]
+    })]
+    // Multi-line strings: """ ... """, optionally raw as r""" ... """
+    #[token("\"\"\"", |lex| lex_text_block(lex, false))]
+    #[token("r\"\"\"", |lex| lex_text_block(lex, true))]
     String(&'a str),
 
     // Interpolated string: $"text {expression} more text"
@@ -183,13 +199,24 @@ pub enum Token<'a> {
     #[regex(r"[0-9]+(\.[0-9]+)?")]
     Number(&'a str),
 
-    #[regex(r"[A-Za-z_][A-Za-z0-9_]*")]
+    // Unicode identifiers, so names like `开始` or `好感度` work
+    #[regex(r"[\p{XID_Start}_]\p{XID_Continue}*")]
     Identifier(&'a str),
     // endregion
 }
 
+/// Whether `ch` can start an identifier: `_` or a Unicode `XID_Start` character, as in the lexer.
+pub fn is_identifier_start(ch: char) -> bool {
+    ch == '_' || unicode_ident::is_xid_start(ch)
+}
+
+/// Whether `ch` can continue an identifier: a Unicode `XID_Continue` character, as in the lexer.
+pub fn is_identifier_continue(ch: char) -> bool {
+    unicode_ident::is_xid_continue(ch)
+}
+
 /// Lexical analysis result containing token information and position
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TokenInfo<'a> {
     pub token: Token<'a>,
     pub start: usize,
@@ -349,24 +376,272 @@ fn lex_interpolated_string<'a>(lex: &mut logos::Lexer<'a, Token<'a>>) -> Option<
     None
 }
 
-pub(crate) fn lex_with_output(input: &str) -> Vec<Token<'_>> {
-    let lex = Token::lexer(input);
+fn lex_text_block<'a>(lex: &mut logos::Lexer<'a, Token<'a>>, raw: bool) -> Option<&'a str> {
+    let start = lex.span().end;
+    let rest = &lex.source()[start..];
+    let bytes = rest.as_bytes();
+
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos] == b'\\' && !raw {
+            pos += 2;
+            continue;
+        }
+        if bytes[pos..].starts_with(b"\"\"\"") {
+            lex.bump(pos + 3);
+            return Some(&rest[..pos]);
+        }
+        pos += 1;
+    }
+
+    None
+}
+
+/// An escape sequence that could not be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscapeError {
+    /// The sequence as written, e.g. `\q` or `\u{110000}`.
+    pub sequence: String,
+    /// Where the sequence is, relative to the start of the text that was decoded.
+    pub span: Span,
+}
+
+/// Decode a string literal, given its full source text with quotes and prefix.
+///
+/// - `"..."` and `'...'` have their escape sequences decoded, see [`unescape`].
+/// - `r"..."` and `r'...'` are raw: the text is taken as written.
+/// - `"""..."""` spans several lines and is dedented, see [`dedent`], before escapes are decoded.
+/// - `r"""..."""` is dedented but otherwise raw.
+///
+/// Error spans are relative to the start of `literal`.
+pub fn string_value(literal: &str) -> Result<Cow<'_, str>, EscapeError> {
+    if let Some(content) = literal
+        .strip_prefix("r\"\"\"")
+        .and_then(|rest| rest.strip_suffix("\"\"\""))
+    {
+        return Ok(Cow::Owned(dedent(content)));
+    }
+    if let Some(content) = literal
+        .strip_prefix("\"\"\"")
+        .and_then(|rest| rest.strip_suffix("\"\"\""))
+    {
+        // Report errors against the source before dedenting moves them
+        unescape(content).map_err(|error| error.offset(3))?;
+        let dedented = dedent(content);
+        return Ok(Cow::Owned(
+            unescape(&dedented)
+                .map_err(|error| error.offset(3))?
+                .into_owned(),
+        ));
+    }
+    if let Some(content) = literal.strip_prefix('r') {
+        return Ok(Cow::Borrowed(strip_quotes(content)));
+    }
+
+    unescape(strip_quotes(literal)).map_err(|error| error.offset(1))
+}
+
+fn strip_quotes(literal: &str) -> &str {
+    literal
+        .get(1..literal.len().saturating_sub(1))
+        .unwrap_or("")
+}
+
+impl EscapeError {
+    /// Move the span by `by` bytes, e.g. from string-relative to file positions.
+    pub fn offset(self, by: usize) -> Self {
+        Self {
+            span: (self.span.0 + by, self.span.1 + by),
+            ..self
+        }
+    }
+}
+
+/// Decode the escape sequences in the text of a string.
+///
+/// Supported sequences are `\n`, `\r`, `\t`, `\0`, `\\`, `\"`, `\'`, `\{`, `\}` and `\u{...}` with one to six hex digits.
+/// Text without a backslash is returned as is, without copying.
+pub fn unescape(raw: &str) -> Result<Cow<'_, str>, EscapeError> {
+    if !raw.contains('\\') {
+        return Ok(Cow::Borrowed(raw));
+    }
+    let chars = unescape_chars(raw)?;
+    Ok(Cow::Owned(chars.into_iter().map(|(_, ch)| ch).collect()))
+}
+
+/// Decode the escape sequences in the text of a string like [`unescape`], giving
+/// each char along with the offset in `raw` of the char or sequence it came from.
+pub fn unescape_chars(raw: &str) -> Result<Vec<(usize, char)>, EscapeError> {
+    let mut value = Vec::with_capacity(raw.len());
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch != '\\' {
+            value.push((start, ch));
+            continue;
+        }
+
+        let invalid = |end: usize| EscapeError {
+            sequence: raw[start..end].to_string(),
+            span: (start, end),
+        };
+
+        let Some((pos, escaped)) = chars.next() else {
+            return Err(invalid(raw.len()));
+        };
+        let after = pos + escaped.len_utf8();
+
+        let decoded = match escaped {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            '\\' | '"' | '\'' | '{' | '}' => escaped,
+            'u' => {
+                let digits_start = after + 1;
+                let end = match (raw[after..].starts_with('{'), raw[after..].find('}')) {
+                    (true, Some(close)) => after + close,
+                    _ => return Err(invalid(after)),
+                };
+                while chars.peek().is_some_and(|&(pos, _)| pos <= end) {
+                    chars.next();
+                }
+
+                let digits = &raw[digits_start..end];
+                let code = (1..=6)
+                    .contains(&digits.len())
+                    .then(|| u32::from_str_radix(digits, 16).ok())
+                    .flatten()
+                    .and_then(char::from_u32);
+                match code {
+                    Some(ch) => ch,
+                    None => return Err(invalid(end + 1)),
+                }
+            }
+            _ => return Err(invalid(after)),
+        };
+        value.push((start, decoded));
+    }
+
+    Ok(value)
+}
+
+/// Strip the layout of a multi-line string so it can be indented along with the code around it.
+///
+/// A line break right after the opening quotes and a last line holding only the indentation of the closing quotes are dropped, then the longest run of leading spaces and tabs shared by every non-blank line is removed.
+/// Lines holding only whitespace come out empty.
+pub fn dedent(text: &str) -> String {
+    dedent_pieces(text)
+        .into_iter()
+        .map(|(_, piece)| piece)
+        .collect()
+}
+
+/// The pieces of `text` that [`dedent`] keeps, in order, each with its offset in `text`.
+///
+/// Every line break of the result is a `"\n"` piece at the offset of the `\n` it stands for.
+fn dedent_pieces(text: &str) -> Vec<(usize, &str)> {
+    // Each line with its offset, without its line break
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (at, _) in text.match_indices('\n') {
+        let line = &text[start..at];
+        lines.push((start, line.strip_suffix('\r').unwrap_or(line)));
+        start = at + 1;
+    }
+    lines.push((start, &text[start..]));
+
+    let is_blank = |line: &str| line.trim_matches([' ', '\t']).is_empty();
+    if lines.len() > 1 && is_blank(lines[0].1) {
+        lines.remove(0);
+    }
+    if lines.len() > 1 && is_blank(lines[lines.len() - 1].1) {
+        lines.pop();
+    }
+
+    let indent = lines
+        .iter()
+        .filter(|(_, line)| !is_blank(line))
+        .map(|(_, line)| line.len() - line.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+
+    let mut pieces = Vec::with_capacity(lines.len() * 2);
+    for (i, &(offset, line)) in lines.iter().enumerate() {
+        if i > 0 {
+            pieces.push((offset - 1, "\n"));
+        }
+        if !is_blank(line) {
+            pieces.push((offset + indent, &line[indent..]));
+        }
+    }
+    pieces
+}
+
+/// Decode a string literal like [`string_value`], giving each char of the value
+/// along with the offset in `literal` of the char or escape sequence it came from.
+pub fn string_chars(literal: &str) -> Result<Vec<(usize, char)>, EscapeError> {
+    let raw = literal.starts_with('r');
+    let quotes = if raw { "r\"\"\"" } else { "\"\"\"" };
+    if let Some(content) = literal
+        .strip_prefix(quotes)
+        .and_then(|rest| rest.strip_suffix("\"\"\""))
+    {
+        let prefix = quotes.len();
+        // Report errors against the source before dedenting moves them
+        if !raw {
+            unescape_chars(content).map_err(|error| error.offset(prefix))?;
+        }
+
+        // Decode the dedented text, then map each offset in it back through its piece
+        let pieces = dedent_pieces(content);
+        let mut dedented = String::new();
+        let mut starts = Vec::with_capacity(pieces.len());
+        for (offset, piece) in &pieces {
+            starts.push((dedented.len(), prefix + offset));
+            dedented.push_str(piece);
+        }
+        let chars = if raw {
+            dedented.char_indices().collect()
+        } else {
+            unescape_chars(&dedented).map_err(|error| error.offset(prefix))?
+        };
+        return Ok(chars
+            .into_iter()
+            .map(|(at, ch)| {
+                let piece = starts.partition_point(|&(start, _)| start <= at) - 1;
+                let (piece_start, source_start) = starts[piece];
+                (source_start + at - piece_start, ch)
+            })
+            .collect());
+    }
+    if raw {
+        return Ok(strip_quotes(&literal[1..])
+            .char_indices()
+            .map(|(at, ch)| (at + 2, ch))
+            .collect());
+    }
+
+    let chars = unescape_chars(strip_quotes(literal)).map_err(|error| error.offset(1))?;
+    Ok(chars.into_iter().map(|(at, ch)| (at + 1, ch)).collect())
+}
+
+/// Tokenize like [`tokenize`], printing each token as it goes.
+///
+/// Stops at the first lexer error, as the parser cannot use anything past it.
+pub(crate) fn lex_with_output(input: &str) -> Vec<TokenInfo<'_>> {
     let mut tokens = Vec::new();
 
     println!();
     println!("{}", "(Mortar) Lexer output:".green());
 
-    for result in lex {
-        match result {
-            Ok(token) => {
-                print!("{:?} ", token);
-                tokens.push(token);
-            }
-            Err(_) => {
-                println!("{}", "Lexer error encountered!".red());
-                break;
-            }
+    for token_info in tokenize(input) {
+        if token_info.token == Token::Error {
+            println!("{}", "Lexer error encountered!".red());
+            break;
         }
+        print!("{:?} ", token_info.token);
+        tokens.push(token_info);
     }
 
     println!("\n");
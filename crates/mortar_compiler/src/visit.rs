@@ -0,0 +1,824 @@
+//! # visit.rs
+//!
+//! # visit.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Traversal traits for the AST.
+//!
+//! AST 的遍历 trait。
+//!
+//! `Visit` walks a `Program` by shared reference and `VisitMut` by mutable reference. Every AST type has a `visit_*` method whose default implementation calls the matching `walk_*` function, which visits the children. Override only the methods you care about, and call the `walk_*` function from an override to keep descending.
+//!
+//! `Visit` 通过共享引用遍历 `Program`，`VisitMut` 通过可变引用遍历。每种 AST 类型都有一个 `visit_*` 方法，其默认实现调用对应的 `walk_*` 函数来访问子节点。只需重写关心的方法，并在重写中调用 `walk_*` 函数即可继续向下遍历。
+//!
+//! ## Source File Overview
+//!
+//! ## 源文件概述
+//!
+//! Defines `Visit` with its `walk_*` functions, then `VisitMut` with its `walk_*_mut` functions. Both cover nested choices, `if`/`else` bodies, nested event lists and branch values.
+//!
+//! 先定义 `Visit` 及其 `walk_*` 函数，再定义 `VisitMut` 及其 `walk_*_mut` 函数。两者都覆盖嵌套选项、`if`/`else` 分支体、嵌套事件列表和分支值。
+
+use crate::ast::*;
+
+/// Read-only traversal of the AST.
+///
+/// ```
+/// use mortar_compiler::ast::FuncCall;
+/// use mortar_compiler::visit::{self, Visit};
+/// use mortar_compiler::ParseHandler;
+///
+/// #[derive(Default)]
+/// struct Calls(Vec<String>);
+///
+/// impl Visit for Calls {
+///     fn visit_func_call(&mut self, call: &FuncCall) {
+///         self.0.push(call.name.clone());
+///         visit::walk_func_call(self, call);
+///     }
+/// }
+///
+/// let program = ParseHandler::parse_source_code(
+///     "node A {\n    choice: [\"Go\" when ready() -> B]\n}\nnode B {}\nfn ready() -> Bool\n",
+///     false,
+/// )
+/// .unwrap();
+/// let mut calls = Calls::default();
+/// calls.visit_program(&program);
+/// assert_eq!(calls.0, vec!["ready"]);
+/// ```
+pub trait Visit {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_top_level(&mut self, item: &TopLevel) {
+        walk_top_level(self, item);
+    }
+
+    fn visit_node_def(&mut self, node: &NodeDef) {
+        walk_node_def(self, node);
+    }
+
+    fn visit_node_stmt(&mut self, stmt: &NodeStmt) {
+        walk_node_stmt(self, stmt);
+    }
+
+    fn visit_node_jump(&mut self, _jump: &NodeJump) {}
+
+    fn visit_if_else(&mut self, if_else: &IfElseStmt) {
+        walk_if_else(self, if_else);
+    }
+
+    fn visit_if_condition(&mut self, condition: &IfCondition) {
+        walk_if_condition(self, condition);
+    }
+
+    fn visit_binary_condition(&mut self, binary: &BinaryCondition) {
+        walk_binary_condition(self, binary);
+    }
+
+    fn visit_unary_condition(&mut self, unary: &UnaryCondition) {
+        walk_unary_condition(self, unary);
+    }
+
+    fn visit_interpolated_string(&mut self, interpolated: &InterpolatedString) {
+        walk_interpolated_string(self, interpolated);
+    }
+
+    fn visit_string_part(&mut self, part: &StringPart) {
+        walk_string_part(self, part);
+    }
+
+    fn visit_branch_def(&mut self, branch: &BranchDef) {
+        walk_branch_def(self, branch);
+    }
+
+    fn visit_branch_case(&mut self, case: &BranchCase) {
+        walk_branch_case(self, case);
+    }
+
+    fn visit_event(&mut self, event: &Event) {
+        walk_event(self, event);
+    }
+
+    fn visit_event_action(&mut self, action: &EventAction) {
+        walk_event_action(self, action);
+    }
+
+    fn visit_event_def(&mut self, event_def: &EventDef) {
+        walk_event_def(self, event_def);
+    }
+
+    fn visit_timeline_def(&mut self, timeline: &TimelineDef) {
+        walk_timeline_def(self, timeline);
+    }
+
+    fn visit_timeline_stmt(&mut self, stmt: &TimelineStmt) {
+        walk_timeline_stmt(self, stmt);
+    }
+
+    fn visit_run_stmt(&mut self, run: &RunStmt) {
+        walk_run_stmt(self, run);
+    }
+
+    fn visit_index_override(&mut self, _index_override: &IndexOverride) {}
+
+    fn visit_with_events(&mut self, with_events: &WithEventsStmt) {
+        walk_with_events(self, with_events);
+    }
+
+    fn visit_with_event_item(&mut self, item: &WithEventItem) {
+        walk_with_event_item(self, item);
+    }
+
+    fn visit_choice_item(&mut self, item: &ChoiceItem) {
+        walk_choice_item(self, item);
+    }
+
+    fn visit_condition(&mut self, condition: &Condition) {
+        walk_condition(self, condition);
+    }
+
+    fn visit_choice_dest(&mut self, dest: &ChoiceDest) {
+        walk_choice_dest(self, dest);
+    }
+
+    fn visit_function_decl(&mut self, function: &FunctionDecl) {
+        walk_function_decl(self, function);
+    }
+
+    fn visit_param(&mut self, _param: &Param) {}
+
+    fn visit_func_call(&mut self, call: &FuncCall) {
+        walk_func_call(self, call);
+    }
+
+    fn visit_arg(&mut self, arg: &Arg) {
+        walk_arg(self, arg);
+    }
+
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) {
+        walk_var_decl(self, var_decl);
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDecl) {
+        walk_const_decl(self, const_decl);
+    }
+
+    fn visit_enum_def(&mut self, _enum_def: &EnumDef) {}
+
+    fn visit_var_value(&mut self, value: &VarValue) {
+        walk_var_value(self, value);
+    }
+
+    fn visit_branch_value(&mut self, branch: &BranchValue) {
+        walk_branch_value(self, branch);
+    }
+
+    fn visit_assignment(&mut self, assignment: &Assignment) {
+        walk_assignment(self, assignment);
+    }
+
+    fn visit_assign_value(&mut self, _value: &AssignValue) {}
+}
+
+pub fn walk_program<V: Visit + ?Sized>(visitor: &mut V, program: &Program) {
+    for item in &program.body {
+        visitor.visit_top_level(item);
+    }
+}
+
+pub fn walk_top_level<V: Visit + ?Sized>(visitor: &mut V, item: &TopLevel) {
+    match item {
+        TopLevel::NodeDef(node) => visitor.visit_node_def(node),
+        TopLevel::FunctionDecl(function) => visitor.visit_function_decl(function),
+        TopLevel::VarDecl(var_decl) => visitor.visit_var_decl(var_decl),
+        TopLevel::ConstDecl(const_decl) => visitor.visit_const_decl(const_decl),
+        TopLevel::EnumDef(enum_def) => visitor.visit_enum_def(enum_def),
+        TopLevel::EventDef(event_def) => visitor.visit_event_def(event_def),
+        TopLevel::TimelineDef(timeline) => visitor.visit_timeline_def(timeline),
+    }
+}
+
+pub fn walk_node_def<V: Visit + ?Sized>(visitor: &mut V, node: &NodeDef) {
+    for stmt in &node.body {
+        visitor.visit_node_stmt(stmt);
+    }
+    if let Some(jump) = &node.jump {
+        visitor.visit_node_jump(jump);
+    }
+}
+
+pub fn walk_node_stmt<V: Visit + ?Sized>(visitor: &mut V, stmt: &NodeStmt) {
+    match stmt {
+        NodeStmt::Text(_, _) => {}
+        NodeStmt::InterpolatedText(interpolated, _) => {
+            visitor.visit_interpolated_string(interpolated)
+        }
+        NodeStmt::Choice(items, _) => {
+            for item in items {
+                visitor.visit_choice_item(item);
+            }
+        }
+        NodeStmt::Branch(branch) => visitor.visit_branch_def(branch),
+        NodeStmt::IfElse(if_else) => visitor.visit_if_else(if_else),
+        NodeStmt::Run(run) => visitor.visit_run_stmt(run),
+        NodeStmt::WithEvents(with_events) => visitor.visit_with_events(with_events),
+        NodeStmt::VarDecl(var_decl) => visitor.visit_var_decl(var_decl),
+        NodeStmt::Assignment(assignment) => visitor.visit_assignment(assignment),
+    }
+}
+
+pub fn walk_if_else<V: Visit + ?Sized>(visitor: &mut V, if_else: &IfElseStmt) {
+    visitor.visit_if_condition(&if_else.condition);
+    for stmt in &if_else.then_body {
+        visitor.visit_node_stmt(stmt);
+    }
+    for stmt in if_else.else_body.iter().flatten() {
+        visitor.visit_node_stmt(stmt);
+    }
+}
+
+pub fn walk_if_condition<V: Visit + ?Sized>(visitor: &mut V, condition: &IfCondition) {
+    match condition {
+        IfCondition::Binary(binary) => visitor.visit_binary_condition(binary),
+        IfCondition::Unary(unary) => visitor.visit_unary_condition(unary),
+        IfCondition::Identifier(_, _)
+        | IfCondition::EnumMember(_, _, _)
+        | IfCondition::Literal(_, _) => {}
+    }
+}
+
+pub fn walk_binary_condition<V: Visit + ?Sized>(visitor: &mut V, binary: &BinaryCondition) {
+    visitor.visit_if_condition(&binary.left);
+    visitor.visit_if_condition(&binary.right);
+}
+
+pub fn walk_unary_condition<V: Visit + ?Sized>(visitor: &mut V, unary: &UnaryCondition) {
+    visitor.visit_if_condition(&unary.operand);
+}
+
+pub fn walk_interpolated_string<V: Visit + ?Sized>(
+    visitor: &mut V,
+    interpolated: &InterpolatedString,
+) {
+    for part in &interpolated.parts {
+        visitor.visit_string_part(part);
+    }
+}
+
+pub fn walk_string_part<V: Visit + ?Sized>(visitor: &mut V, part: &StringPart) {
+    match part {
+        StringPart::Expression(call) => visitor.visit_func_call(call),
+        StringPart::Text(_, _) | StringPart::Placeholder(_, _) => {}
+    }
+}
+
+pub fn walk_branch_def<V: Visit + ?Sized>(visitor: &mut V, branch: &BranchDef) {
+    for case in &branch.cases {
+        visitor.visit_branch_case(case);
+    }
+}
+
+pub fn walk_branch_case<V: Visit + ?Sized>(visitor: &mut V, case: &BranchCase) {
+    for event in case.events.iter().flatten() {
+        visitor.visit_event(event);
+    }
+}
+
+pub fn walk_event<V: Visit + ?Sized>(visitor: &mut V, event: &Event) {
+    visitor.visit_event_action(&event.action);
+}
+
+pub fn walk_event_action<V: Visit + ?Sized>(visitor: &mut V, action: &EventAction) {
+    visitor.visit_func_call(&action.call);
+    for chain in &action.chains {
+        visitor.visit_func_call(chain);
+    }
+}
+
+pub fn walk_event_def<V: Visit + ?Sized>(visitor: &mut V, event_def: &EventDef) {
+    visitor.visit_event_action(&event_def.action);
+}
+
+pub fn walk_timeline_def<V: Visit + ?Sized>(visitor: &mut V, timeline: &TimelineDef) {
+    for stmt in &timeline.body {
+        visitor.visit_timeline_stmt(stmt);
+    }
+}
+
+pub fn walk_timeline_stmt<V: Visit + ?Sized>(visitor: &mut V, stmt: &TimelineStmt) {
+    match stmt {
+        TimelineStmt::Run(run) => visitor.visit_run_stmt(run),
+        TimelineStmt::Wait(_, _) => {}
+    }
+}
+
+pub fn walk_run_stmt<V: Visit + ?Sized>(visitor: &mut V, run: &RunStmt) {
+    for arg in &run.args {
+        visitor.visit_arg(arg);
+    }
+    if let Some(index_override) = &run.index_override {
+        visitor.visit_index_override(index_override);
+    }
+}
+
+pub fn walk_with_events<V: Visit + ?Sized>(visitor: &mut V, with_events: &WithEventsStmt) {
+    for item in &with_events.events {
+        visitor.visit_with_event_item(item);
+    }
+}
+
+pub fn walk_with_event_item<V: Visit + ?Sized>(visitor: &mut V, item: &WithEventItem) {
+    match item {
+        WithEventItem::EventRef(_, _) => {}
+        WithEventItem::EventRefWithOverride(_, _, index_override) => {
+            visitor.visit_index_override(index_override)
+        }
+        WithEventItem::InlineEvent(event) => visitor.visit_event(event),
+        WithEventItem::EventList(items, _) => {
+            for item in items {
+                visitor.visit_with_event_item(item);
+            }
+        }
+    }
+}
+
+pub fn walk_choice_item<V: Visit + ?Sized>(visitor: &mut V, item: &ChoiceItem) {
+    if let Some(condition) = &item.condition {
+        visitor.visit_condition(condition);
+    }
+    visitor.visit_choice_dest(&item.target);
+}
+
+pub fn walk_condition<V: Visit + ?Sized>(visitor: &mut V, condition: &Condition) {
+    match condition {
+        Condition::Identifier(_, _) => {}
+        Condition::FuncCall(call) => visitor.visit_func_call(call),
+    }
+}
+
+pub fn walk_choice_dest<V: Visit + ?Sized>(visitor: &mut V, dest: &ChoiceDest) {
+    match dest {
+        ChoiceDest::NestedChoices(items, _) => {
+            for item in items {
+                visitor.visit_choice_item(item);
+            }
+        }
+        ChoiceDest::Identifier(_, _) | ChoiceDest::Return(_) | ChoiceDest::Break(_) => {}
+    }
+}
+
+pub fn walk_function_decl<V: Visit + ?Sized>(visitor: &mut V, function: &FunctionDecl) {
+    for param in &function.params {
+        visitor.visit_param(param);
+    }
+}
+
+pub fn walk_func_call<V: Visit + ?Sized>(visitor: &mut V, call: &FuncCall) {
+    for arg in &call.args {
+        visitor.visit_arg(arg);
+    }
+}
+
+pub fn walk_arg<V: Visit + ?Sized>(visitor: &mut V, arg: &Arg) {
+    match arg {
+        Arg::FuncCall(call) => visitor.visit_func_call(call),
+        Arg::String(_, _) | Arg::Number(_, _) | Arg::Boolean(_, _) | Arg::Identifier(_, _) => {}
+    }
+}
+
+pub fn walk_var_decl<V: Visit + ?Sized>(visitor: &mut V, var_decl: &VarDecl) {
+    if let Some(value) = &var_decl.value {
+        visitor.visit_var_value(value);
+    }
+}
+
+pub fn walk_const_decl<V: Visit + ?Sized>(visitor: &mut V, const_decl: &ConstDecl) {
+    visitor.visit_var_value(&const_decl.value);
+}
+
+pub fn walk_var_value<V: Visit + ?Sized>(visitor: &mut V, value: &VarValue) {
+    match value {
+        VarValue::Branch(branch) => visitor.visit_branch_value(branch),
+        VarValue::String(_, _)
+        | VarValue::Number(_, _)
+        | VarValue::Boolean(_, _)
+        | VarValue::EnumMember(_, _, _) => {}
+    }
+}
+
+pub fn walk_branch_value<V: Visit + ?Sized>(visitor: &mut V, branch: &BranchValue) {
+    for case in &branch.cases {
+        visitor.visit_branch_case(case);
+    }
+}
+
+pub fn walk_assignment<V: Visit + ?Sized>(visitor: &mut V, assignment: &Assignment) {
+    visitor.visit_assign_value(&assignment.value);
+}
+
+/// Mutable traversal of the AST, for tools that rewrite a program in place.
+///
+/// ```
+/// use mortar_compiler::ast::NodeJump;
+/// use mortar_compiler::visit::VisitMut;
+/// use mortar_compiler::{ParseHandler, print_program};
+///
+/// struct RenameNode;
+///
+/// impl VisitMut for RenameNode {
+///     fn visit_node_jump_mut(&mut self, jump: &mut NodeJump) {
+///         if let NodeJump::Identifier(target, _) = jump
+///             && target == "Old"
+///         {
+///             *target = "New".to_string();
+///         }
+///     }
+/// }
+///
+/// let mut program = ParseHandler::parse_source_code("node Start {} -> Old\n", false).unwrap();
+/// RenameNode.visit_program_mut(&mut program);
+/// assert_eq!(print_program(&program), "node Start {} -> New\n");
+/// ```
+pub trait VisitMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    fn visit_top_level_mut(&mut self, item: &mut TopLevel) {
+        walk_top_level_mut(self, item);
+    }
+
+    fn visit_node_def_mut(&mut self, node: &mut NodeDef) {
+        walk_node_def_mut(self, node);
+    }
+
+    fn visit_node_stmt_mut(&mut self, stmt: &mut NodeStmt) {
+        walk_node_stmt_mut(self, stmt);
+    }
+
+    fn visit_node_jump_mut(&mut self, _jump: &mut NodeJump) {}
+
+    fn visit_if_else_mut(&mut self, if_else: &mut IfElseStmt) {
+        walk_if_else_mut(self, if_else);
+    }
+
+    fn visit_if_condition_mut(&mut self, condition: &mut IfCondition) {
+        walk_if_condition_mut(self, condition);
+    }
+
+    fn visit_binary_condition_mut(&mut self, binary: &mut BinaryCondition) {
+        walk_binary_condition_mut(self, binary);
+    }
+
+    fn visit_unary_condition_mut(&mut self, unary: &mut UnaryCondition) {
+        walk_unary_condition_mut(self, unary);
+    }
+
+    fn visit_interpolated_string_mut(&mut self, interpolated: &mut InterpolatedString) {
+        walk_interpolated_string_mut(self, interpolated);
+    }
+
+    fn visit_string_part_mut(&mut self, part: &mut StringPart) {
+        walk_string_part_mut(self, part);
+    }
+
+    fn visit_branch_def_mut(&mut self, branch: &mut BranchDef) {
+        walk_branch_def_mut(self, branch);
+    }
+
+    fn visit_branch_case_mut(&mut self, case: &mut BranchCase) {
+        walk_branch_case_mut(self, case);
+    }
+
+    fn visit_event_mut(&mut self, event: &mut Event) {
+        walk_event_mut(self, event);
+    }
+
+    fn visit_event_action_mut(&mut self, action: &mut EventAction) {
+        walk_event_action_mut(self, action);
+    }
+
+    fn visit_event_def_mut(&mut self, event_def: &mut EventDef) {
+        walk_event_def_mut(self, event_def);
+    }
+
+    fn visit_timeline_def_mut(&mut self, timeline: &mut TimelineDef) {
+        walk_timeline_def_mut(self, timeline);
+    }
+
+    fn visit_timeline_stmt_mut(&mut self, stmt: &mut TimelineStmt) {
+        walk_timeline_stmt_mut(self, stmt);
+    }
+
+    fn visit_run_stmt_mut(&mut self, run: &mut RunStmt) {
+        walk_run_stmt_mut(self, run);
+    }
+
+    fn visit_index_override_mut(&mut self, _index_override: &mut IndexOverride) {}
+
+    fn visit_with_events_mut(&mut self, with_events: &mut WithEventsStmt) {
+        walk_with_events_mut(self, with_events);
+    }
+
+    fn visit_with_event_item_mut(&mut self, item: &mut WithEventItem) {
+        walk_with_event_item_mut(self, item);
+    }
+
+    fn visit_choice_item_mut(&mut self, item: &mut ChoiceItem) {
+        walk_choice_item_mut(self, item);
+    }
+
+    fn visit_condition_mut(&mut self, condition: &mut Condition) {
+        walk_condition_mut(self, condition);
+    }
+
+    fn visit_choice_dest_mut(&mut self, dest: &mut ChoiceDest) {
+        walk_choice_dest_mut(self, dest);
+    }
+
+    fn visit_function_decl_mut(&mut self, function: &mut FunctionDecl) {
+        walk_function_decl_mut(self, function);
+    }
+
+    fn visit_param_mut(&mut self, _param: &mut Param) {}
+
+    fn visit_func_call_mut(&mut self, call: &mut FuncCall) {
+        walk_func_call_mut(self, call);
+    }
+
+    fn visit_arg_mut(&mut self, arg: &mut Arg) {
+        walk_arg_mut(self, arg);
+    }
+
+    fn visit_var_decl_mut(&mut self, var_decl: &mut VarDecl) {
+        walk_var_decl_mut(self, var_decl);
+    }
+
+    fn visit_const_decl_mut(&mut self, const_decl: &mut ConstDecl) {
+        walk_const_decl_mut(self, const_decl);
+    }
+
+    fn visit_enum_def_mut(&mut self, _enum_def: &mut EnumDef) {}
+
+    fn visit_var_value_mut(&mut self, value: &mut VarValue) {
+        walk_var_value_mut(self, value);
+    }
+
+    fn visit_branch_value_mut(&mut self, branch: &mut BranchValue) {
+        walk_branch_value_mut(self, branch);
+    }
+
+    fn visit_assignment_mut(&mut self, assignment: &mut Assignment) {
+        walk_assignment_mut(self, assignment);
+    }
+
+    fn visit_assign_value_mut(&mut self, _value: &mut AssignValue) {}
+}
+
+pub fn walk_program_mut<V: VisitMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for item in &mut program.body {
+        visitor.visit_top_level_mut(item);
+    }
+}
+
+pub fn walk_top_level_mut<V: VisitMut + ?Sized>(visitor: &mut V, item: &mut TopLevel) {
+    match item {
+        TopLevel::NodeDef(node) => visitor.visit_node_def_mut(node),
+        TopLevel::FunctionDecl(function) => visitor.visit_function_decl_mut(function),
+        TopLevel::VarDecl(var_decl) => visitor.visit_var_decl_mut(var_decl),
+        TopLevel::ConstDecl(const_decl) => visitor.visit_const_decl_mut(const_decl),
+        TopLevel::EnumDef(enum_def) => visitor.visit_enum_def_mut(enum_def),
+        TopLevel::EventDef(event_def) => visitor.visit_event_def_mut(event_def),
+        TopLevel::TimelineDef(timeline) => visitor.visit_timeline_def_mut(timeline),
+    }
+}
+
+pub fn walk_node_def_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut NodeDef) {
+    for stmt in &mut node.body {
+        visitor.visit_node_stmt_mut(stmt);
+    }
+    if let Some(jump) = &mut node.jump {
+        visitor.visit_node_jump_mut(jump);
+    }
+}
+
+pub fn walk_node_stmt_mut<V: VisitMut + ?Sized>(visitor: &mut V, stmt: &mut NodeStmt) {
+    match stmt {
+        NodeStmt::Text(_, _) => {}
+        NodeStmt::InterpolatedText(interpolated, _) => {
+            visitor.visit_interpolated_string_mut(interpolated)
+        }
+        NodeStmt::Choice(items, _) => {
+            for item in items {
+                visitor.visit_choice_item_mut(item);
+            }
+        }
+        NodeStmt::Branch(branch) => visitor.visit_branch_def_mut(branch),
+        NodeStmt::IfElse(if_else) => visitor.visit_if_else_mut(if_else),
+        NodeStmt::Run(run) => visitor.visit_run_stmt_mut(run),
+        NodeStmt::WithEvents(with_events) => visitor.visit_with_events_mut(with_events),
+        NodeStmt::VarDecl(var_decl) => visitor.visit_var_decl_mut(var_decl),
+        NodeStmt::Assignment(assignment) => visitor.visit_assignment_mut(assignment),
+    }
+}
+
+pub fn walk_if_else_mut<V: VisitMut + ?Sized>(visitor: &mut V, if_else: &mut IfElseStmt) {
+    visitor.visit_if_condition_mut(&mut if_else.condition);
+    for stmt in &mut if_else.then_body {
+        visitor.visit_node_stmt_mut(stmt);
+    }
+    for stmt in if_else.else_body.iter_mut().flatten() {
+        visitor.visit_node_stmt_mut(stmt);
+    }
+}
+
+pub fn walk_if_condition_mut<V: VisitMut + ?Sized>(visitor: &mut V, condition: &mut IfCondition) {
+    match condition {
+        IfCondition::Binary(binary) => visitor.visit_binary_condition_mut(binary),
+        IfCondition::Unary(unary) => visitor.visit_unary_condition_mut(unary),
+        IfCondition::Identifier(_, _)
+        | IfCondition::EnumMember(_, _, _)
+        | IfCondition::Literal(_, _) => {}
+    }
+}
+
+pub fn walk_binary_condition_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    binary: &mut BinaryCondition,
+) {
+    visitor.visit_if_condition_mut(&mut binary.left);
+    visitor.visit_if_condition_mut(&mut binary.right);
+}
+
+pub fn walk_unary_condition_mut<V: VisitMut + ?Sized>(visitor: &mut V, unary: &mut UnaryCondition) {
+    visitor.visit_if_condition_mut(&mut unary.operand);
+}
+
+pub fn walk_interpolated_string_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    interpolated: &mut InterpolatedString,
+) {
+    for part in &mut interpolated.parts {
+        visitor.visit_string_part_mut(part);
+    }
+}
+
+pub fn walk_string_part_mut<V: VisitMut + ?Sized>(visitor: &mut V, part: &mut StringPart) {
+    match part {
+        StringPart::Expression(call) => visitor.visit_func_call_mut(call),
+        StringPart::Text(_, _) | StringPart::Placeholder(_, _) => {}
+    }
+}
+
+pub fn walk_branch_def_mut<V: VisitMut + ?Sized>(visitor: &mut V, branch: &mut BranchDef) {
+    for case in &mut branch.cases {
+        visitor.visit_branch_case_mut(case);
+    }
+}
+
+pub fn walk_branch_case_mut<V: VisitMut + ?Sized>(visitor: &mut V, case: &mut BranchCase) {
+    for event in case.events.iter_mut().flatten() {
+        visitor.visit_event_mut(event);
+    }
+}
+
+pub fn walk_event_mut<V: VisitMut + ?Sized>(visitor: &mut V, event: &mut Event) {
+    visitor.visit_event_action_mut(&mut event.action);
+}
+
+pub fn walk_event_action_mut<V: VisitMut + ?Sized>(visitor: &mut V, action: &mut EventAction) {
+    visitor.visit_func_call_mut(&mut action.call);
+    for chain in &mut action.chains {
+        visitor.visit_func_call_mut(chain);
+    }
+}
+
+pub fn walk_event_def_mut<V: VisitMut + ?Sized>(visitor: &mut V, event_def: &mut EventDef) {
+    visitor.visit_event_action_mut(&mut event_def.action);
+}
+
+pub fn walk_timeline_def_mut<V: VisitMut + ?Sized>(visitor: &mut V, timeline: &mut TimelineDef) {
+    for stmt in &mut timeline.body {
+        visitor.visit_timeline_stmt_mut(stmt);
+    }
+}
+
+pub fn walk_timeline_stmt_mut<V: VisitMut + ?Sized>(visitor: &mut V, stmt: &mut TimelineStmt) {
+    match stmt {
+        TimelineStmt::Run(run) => visitor.visit_run_stmt_mut(run),
+        TimelineStmt::Wait(_, _) => {}
+    }
+}
+
+pub fn walk_run_stmt_mut<V: VisitMut + ?Sized>(visitor: &mut V, run: &mut RunStmt) {
+    for arg in &mut run.args {
+        visitor.visit_arg_mut(arg);
+    }
+    if let Some(index_override) = &mut run.index_override {
+        visitor.visit_index_override_mut(index_override);
+    }
+}
+
+pub fn walk_with_events_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    with_events: &mut WithEventsStmt,
+) {
+    for item in &mut with_events.events {
+        visitor.visit_with_event_item_mut(item);
+    }
+}
+
+pub fn walk_with_event_item_mut<V: VisitMut + ?Sized>(visitor: &mut V, item: &mut WithEventItem) {
+    match item {
+        WithEventItem::EventRef(_, _) => {}
+        WithEventItem::EventRefWithOverride(_, _, index_override) => {
+            visitor.visit_index_override_mut(index_override)
+        }
+        WithEventItem::InlineEvent(event) => visitor.visit_event_mut(event),
+        WithEventItem::EventList(items, _) => {
+            for item in items {
+                visitor.visit_with_event_item_mut(item);
+            }
+        }
+    }
+}
+
+pub fn walk_choice_item_mut<V: VisitMut + ?Sized>(visitor: &mut V, item: &mut ChoiceItem) {
+    if let Some(condition) = &mut item.condition {
+        visitor.visit_condition_mut(condition);
+    }
+    visitor.visit_choice_dest_mut(&mut item.target);
+}
+
+pub fn walk_condition_mut<V: VisitMut + ?Sized>(visitor: &mut V, condition: &mut Condition) {
+    match condition {
+        Condition::Identifier(_, _) => {}
+        Condition::FuncCall(call) => visitor.visit_func_call_mut(call),
+    }
+}
+
+pub fn walk_choice_dest_mut<V: VisitMut + ?Sized>(visitor: &mut V, dest: &mut ChoiceDest) {
+    match dest {
+        ChoiceDest::NestedChoices(items, _) => {
+            for item in items {
+                visitor.visit_choice_item_mut(item);
+            }
+        }
+        ChoiceDest::Identifier(_, _) | ChoiceDest::Return(_) | ChoiceDest::Break(_) => {}
+    }
+}
+
+pub fn walk_function_decl_mut<V: VisitMut + ?Sized>(visitor: &mut V, function: &mut FunctionDecl) {
+    for param in &mut function.params {
+        visitor.visit_param_mut(param);
+    }
+}
+
+pub fn walk_func_call_mut<V: VisitMut + ?Sized>(visitor: &mut V, call: &mut FuncCall) {
+    for arg in &mut call.args {
+        visitor.visit_arg_mut(arg);
+    }
+}
+
+pub fn walk_arg_mut<V: VisitMut + ?Sized>(visitor: &mut V, arg: &mut Arg) {
+    match arg {
+        Arg::FuncCall(call) => visitor.visit_func_call_mut(call),
+        Arg::String(_, _) | Arg::Number(_, _) | Arg::Boolean(_, _) | Arg::Identifier(_, _) => {}
+    }
+}
+
+pub fn walk_var_decl_mut<V: VisitMut + ?Sized>(visitor: &mut V, var_decl: &mut VarDecl) {
+    if let Some(value) = &mut var_decl.value {
+        visitor.visit_var_value_mut(value);
+    }
+}
+
+pub fn walk_const_decl_mut<V: VisitMut + ?Sized>(visitor: &mut V, const_decl: &mut ConstDecl) {
+    visitor.visit_var_value_mut(&mut const_decl.value);
+}
+
+pub fn walk_var_value_mut<V: VisitMut + ?Sized>(visitor: &mut V, value: &mut VarValue) {
+    match value {
+        VarValue::Branch(branch) => visitor.visit_branch_value_mut(branch),
+        VarValue::String(_, _)
+        | VarValue::Number(_, _)
+        | VarValue::Boolean(_, _)
+        | VarValue::EnumMember(_, _, _) => {}
+    }
+}
+
+pub fn walk_branch_value_mut<V: VisitMut + ?Sized>(visitor: &mut V, branch: &mut BranchValue) {
+    for case in &mut branch.cases {
+        visitor.visit_branch_case_mut(case);
+    }
+}
+
+pub fn walk_assignment_mut<V: VisitMut + ?Sized>(visitor: &mut V, assignment: &mut Assignment) {
+    visitor.visit_assign_value_mut(&mut assignment.value);
+}
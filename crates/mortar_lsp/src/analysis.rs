@@ -1,14 +1,13 @@
 use std::collections::HashSet;
 
-use mortar_compiler::ast::{
-    ChoiceDest, FunctionDecl, NodeDef, NodeJump, NodeStmt, Program, TopLevel,
-};
+use mortar_compiler::ast::{EventDef, FunctionDecl, Program, TopLevel};
 
 #[derive(Debug, Clone, Default)]
 pub struct SymbolTable {
     pub nodes: Vec<String>,
     pub functions: Vec<FunctionDecl>,
     pub variables: Vec<String>,
+    pub events: Vec<EventDef>,
 }
 
 impl SymbolTable {
@@ -17,124 +16,50 @@ impl SymbolTable {
     }
 }
 
-/// Analyze program and generate symbol table
-pub fn analyze_program(program: &Program) -> Result<SymbolTable, Vec<(String, usize)>> {
+/// Analyze program and generate symbol table.
+///
+/// Duplicate definitions are reported by the compiler's `DiagnosticCollector`;
+/// here only the first definition of each name is recorded.
+pub fn analyze_program(program: &Program) -> SymbolTable {
     let mut symbol_table = SymbolTable::new();
-    let mut errors = Vec::new();
 
     let mut node_names = HashSet::new();
     let mut function_names = HashSet::new();
+    let mut variable_names = HashSet::new();
+    let mut event_names = HashSet::new();
 
     for item in &program.body {
         match item {
             TopLevel::NodeDef(node) => {
-                if node_names.contains(&node.name) {
-                    errors.push((format!("Node '{}' is defined repeatedly", node.name), 1));
-                } else {
-                    node_names.insert(node.name.clone());
+                if node_names.insert(node.name.clone()) {
                     symbol_table.nodes.push(node.name.clone());
                 }
-
-                // Verify node content
-                analyze_node(node, &mut errors);
             }
             TopLevel::FunctionDecl(func) => {
-                if function_names.contains(&func.name) {
-                    errors.push((
-                        format!("Duplicate definition of function '{}'", func.name),
-                        1,
-                    ));
-                } else {
-                    function_names.insert(func.name.clone());
+                if function_names.insert(func.name.clone()) {
                     symbol_table.functions.push(func.clone());
                 }
             }
-            TopLevel::VarDecl(_) | TopLevel::ConstDecl(_) | TopLevel::EnumDef(_) => {
-                // Variable, constant, and enum declarations - no analysis needed yet
+            TopLevel::VarDecl(var_decl) => {
+                if variable_names.insert(var_decl.name.clone()) {
+                    symbol_table.variables.push(var_decl.name.clone());
+                }
             }
-            TopLevel::EventDef(_) | TopLevel::TimelineDef(_) => {
-                // Event and timeline definitions - no analysis needed yet
+            TopLevel::ConstDecl(const_decl) => {
+                if variable_names.insert(const_decl.name.clone()) {
+                    symbol_table.variables.push(const_decl.name.clone());
+                }
             }
-        }
-    }
-
-    // Verify whether the node jump target exists
-    for item in &program.body {
-        if let TopLevel::NodeDef(node) = item {
-            validate_node_references(node, &node_names, &mut errors);
-        }
-    }
-
-    if errors.is_empty() {
-        Ok(symbol_table)
-    } else {
-        Err(errors)
-    }
-}
-
-/// Analyze a single node
-fn analyze_node(node: &NodeDef, errors: &mut Vec<(String, usize)>) {
-    if node.body.is_empty() {
-        errors.push((format!("Node '{}' cannot be empty", node.name), 1));
-    }
-    // TODO: Add more
-}
-
-/// Verify node reference
-fn validate_node_references(
-    node: &NodeDef,
-    available_nodes: &HashSet<String>,
-    errors: &mut Vec<(String, usize)>,
-) {
-    if let Some(jump) = &node.jump
-        && let NodeJump::Identifier(target, _) = jump
-        && !available_nodes.contains(target)
-    {
-        errors.push((
-            format!(
-                "Undefined node '{}' referenced in node '{}'",
-                node.name, target
-            ),
-            1,
-        ));
-    }
-
-    // Check node references in selection
-    for stmt in &node.body {
-        if let NodeStmt::Choice(choices) = stmt {
-            for choice in choices {
-                validate_choice_target(&choice.target, &node.name, available_nodes, errors);
+            TopLevel::EventDef(event) => {
+                if event_names.insert(event.name.clone()) {
+                    symbol_table.events.push(event.clone());
+                }
             }
+            TopLevel::EnumDef(_) | TopLevel::TimelineDef(_) => {}
         }
     }
-}
 
-/// Validate selected target
-fn validate_choice_target(
-    target: &ChoiceDest,
-    node_name: &str,
-    available_nodes: &HashSet<String>,
-    errors: &mut Vec<(String, usize)>,
-) {
-    match target {
-        ChoiceDest::Identifier(target_name, _) => {
-            if !available_nodes.contains(target_name) {
-                errors.push((
-                    format!(
-                        "Undefined node '{}' is referenced in the selection of node '{}'",
-                        node_name, target_name
-                    ),
-                    1,
-                ));
-            }
-        }
-        ChoiceDest::NestedChoices(nested) => {
-            for choice in nested {
-                validate_choice_target(&choice.target, node_name, available_nodes, errors);
-            }
-        }
-        _ => {}
-    }
+    symbol_table
 }
 
 /// Find symbol definition
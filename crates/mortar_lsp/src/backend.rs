@@ -1,9 +1,11 @@
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use dashmap::DashMap;
-use mortar_compiler::Language;
+use mortar_compiler::{IncrementalParse, Language};
 use ropey::Rope;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tower_lsp_server::Client;
 use tower_lsp_server::lsp_types::*;
 use tracing::info;
@@ -18,8 +20,20 @@ pub struct Backend {
     pub documents: Arc<DashMap<Uri, (Rope, Option<i32>)>>,
     pub diagnostics: Arc<DashMap<Uri, Vec<Diagnostic>>>,
     pub symbol_tables: Arc<DashMap<Uri, SymbolTable>>,
+    /// The last parse of each open document, updated in place on edits
+    pub parses: Arc<DashMap<Uri, IncrementalParse>>,
+    /// What changed in each open document since its last parse
+    pub edits: Arc<DashMap<Uri, PendingEdit>>,
     pub language: Arc<RwLock<Language>>,
     pub debouncers: Arc<DashMap<Uri, tokio::task::JoinHandle<()>>>,
+    /// Folders of the open workspace, searched for definitions in other files
+    pub workspace_roots: Arc<RwLock<Vec<PathBuf>>>,
+    /// The symbols of every workspace document, open ones from their unsaved text
+    pub indexes: Arc<DashMap<Uri, Arc<IndexedDocument>>>,
+    /// Whether the files of the workspace folders have been read into `indexes`
+    pub workspace_indexed: Arc<AtomicBool>,
+    /// Held while the workspace folders are read, so that only one request reads them
+    pub workspace_scan: Arc<Mutex<()>>,
 }
 
 impl Backend {
@@ -30,8 +44,14 @@ impl Backend {
             documents: Arc::new(DashMap::new()),
             diagnostics: Arc::new(DashMap::new()),
             symbol_tables: Arc::new(DashMap::new()),
+            parses: Arc::new(DashMap::new()),
+            edits: Arc::new(DashMap::new()),
             language: Arc::new(RwLock::new(Language::English)), // Default to English
             debouncers: Arc::new(DashMap::new()),
+            workspace_roots: Arc::new(RwLock::new(Vec::new())),
+            indexes: Arc::new(DashMap::new()),
+            workspace_indexed: Arc::new(AtomicBool::new(false)),
+            workspace_scan: Arc::new(Mutex::new(())),
         }
     }
 
@@ -75,6 +95,9 @@ impl Backend {
 
         let symbols_count = self.symbol_tables.len();
         self.symbol_tables.clear();
+        self.parses.clear();
+        self.edits.clear();
+        self.reset_workspace_index().await;
         info!(
             "{} {}",
             symbols_count,
@@ -107,6 +130,10 @@ impl Backend {
 
         let symbols_count = self.symbol_tables.len();
         self.symbol_tables.clear();
+        self.parses.clear();
+        self.edits.clear();
+        self.indexes.clear();
+        self.workspace_indexed.store(false, Ordering::Release);
 
         info!(
             "Synchronization cleanup completed: {} documents, {} diagnostics, {} symbol tables",
@@ -142,27 +169,6 @@ impl Backend {
 
         Some(Position::new(line_idx as u32, char_offset as u32))
     }
-
-    pub fn get_current_word(&self, line: &str, char_idx: usize) -> String {
-        let chars: Vec<char> = line.chars().collect();
-
-        if char_idx >= chars.len() {
-            return String::new();
-        }
-
-        let mut start = char_idx;
-        let mut end = char_idx;
-
-        while start > 0 && (chars[start - 1].is_alphanumeric() || chars[start - 1] == '_') {
-            start -= 1;
-        }
-
-        while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
-            end += 1;
-        }
-
-        chars[start..end].iter().collect()
-    }
 }
 
 #[path = "backend/i18n.rs"]
@@ -171,18 +177,62 @@ pub mod i18n;
 #[path = "backend/diagnostics.rs"]
 pub mod diagnostics;
 
+#[path = "backend/code_actions.rs"]
+mod code_actions;
+
 #[path = "backend/completion.rs"]
 mod completion;
 
 #[path = "backend/semantic_tokens.rs"]
 mod semantic_tokens;
 
+#[path = "backend/formatting.rs"]
+mod formatting;
+
+#[path = "backend/document_symbols.rs"]
+mod document_symbols;
+
 #[path = "backend/document_analysis.rs"]
 mod document_analysis;
 
+#[path = "backend/document_sync.rs"]
+mod document_sync;
+
+#[path = "backend/hover.rs"]
+mod hover;
+
+#[path = "backend/inlay_hints.rs"]
+mod inlay_hints;
+
+#[path = "backend/navigation.rs"]
+mod navigation;
+
+#[path = "backend/rename.rs"]
+mod rename;
+
+#[path = "backend/signature_help.rs"]
+mod signature_help;
+
+#[path = "backend/workspace.rs"]
+mod workspace;
+
 #[path = "backend/lsp_handlers.rs"]
 mod lsp_handlers;
 
-pub use completion::CompletionContext;
-pub use diagnostics::{convert_diagnostics_to_lsp, parse_with_diagnostics};
+pub use code_actions::code_actions;
+pub use completion::{CompletionContext, completion_context, completion_items, current_word};
+pub use diagnostics::{convert_diagnostics_to_lsp, diagnose_parse, parse_with_diagnostics};
+pub use document_symbols::{document_symbols, workspace_symbols};
+pub use document_sync::{PendingEdit, apply_content_change};
+pub use formatting::{formatting_edits, range_formatting_edits};
+pub use hover::hover_info;
 pub use i18n::{detect_system_language, parse_language_from_args};
+pub use inlay_hints::inlay_hints;
+pub use navigation::{
+    IndexedDocument, document_highlights, find_definition, find_definition_in, find_references,
+    index_document, symbol_at,
+};
+pub use rename::{prepare_rename, rename_edits};
+pub use semantic_tokens::compute_semantic_tokens;
+pub use signature_help::signature_help;
+pub use workspace::find_mortar_files;
@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use mortar_compiler::ast::*;
+use mortar_compiler::printer::type_name;
+use mortar_compiler::visit::{self, Visit};
+use mortar_compiler::{Diagnostic as CompilerDiagnostic, DiagnosticKind, Language, ParseHandler};
+use tower_lsp_server::lsp_types::*;
+
+use crate::backend::diagnostics::{
+    byte_offset_to_position, byte_span_to_lsp_range, convert_diagnostics_to_lsp,
+    position_to_byte_offset, project_config,
+};
+use crate::backend::i18n::get_lsp_text;
+use crate::backend::{IndexedDocument, rename_edits};
+
+/// Quick fixes for the diagnostics in `range`, and for enum branches there that miss cases.
+///
+/// Renames to the conventional case also rename the uses in `others`.
+pub fn code_actions(
+    uri: &Uri,
+    content: &str,
+    range: Range,
+    others: &[(Uri, Arc<IndexedDocument>)],
+    language: Language,
+) -> Vec<CodeActionOrCommand> {
+    let (result, diagnostics) = ParseHandler::parse_source_code_with_config(
+        content,
+        uri.path().to_string(),
+        false,
+        language,
+        &project_config(uri),
+    );
+    // The parser recovers from syntax errors, so the program keeps every item
+    // it could read and fixes elsewhere in a broken file are still offered
+    let program = result.unwrap_or_else(|_| Program {
+        body: Vec::new(),
+        span: (0, content.len()),
+    });
+
+    let start = position_to_byte_offset(content, range.start);
+    let end = position_to_byte_offset(content, range.end);
+    let overlaps = |span: Span| span.0 <= end && start <= span.1;
+
+    let fixes = Fixes {
+        uri,
+        content,
+        program: &program,
+        language,
+    };
+    let mut actions = Vec::new();
+
+    for diagnostic in diagnostics.get_diagnostics() {
+        let Some(span) = diagnostic.span.filter(|span| overlaps(*span)) else {
+            continue;
+        };
+        let action = match &diagnostic.kind {
+            DiagnosticKind::NonSnakeCaseFunction { .. }
+            | DiagnosticKind::NonPascalCaseNode { .. } => fixes.rename(diagnostic, span, others),
+            DiagnosticKind::FunctionNotFound { function_name } => {
+                fixes.declare_function(function_name, span)
+            }
+            DiagnosticKind::NodeNotFound { node_name } => Some(fixes.create_node(node_name)),
+            DiagnosticKind::UnusedFunction {
+                function_name: name,
+            }
+            | DiagnosticKind::UnusedVariable {
+                variable_name: name,
+            }
+            | DiagnosticKind::UnusedConstant {
+                constant_name: name,
+            }
+            | DiagnosticKind::UnusedEnum { enum_name: name }
+            | DiagnosticKind::UnusedEvent { event_name: name }
+            | DiagnosticKind::UnusedTimeline {
+                timeline_name: name,
+            } => fixes.remove_declaration(name, span),
+            _ => None,
+        };
+
+        if let Some((title, edit)) = action {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(convert_diagnostics_to_lsp(
+                    uri,
+                    content,
+                    std::slice::from_ref(diagnostic),
+                    language,
+                )),
+                edit: Some(edit),
+                is_preferred: Some(true),
+                ..CodeAction::default()
+            }));
+        }
+    }
+
+    let mut branches = Branches::default();
+    branches.visit_program(&program);
+    for (selector, cases, span) in branches.found {
+        if overlaps(span)
+            && let Some((title, edit)) = fixes.add_branch_cases(&selector, &cases, span)
+        {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                edit: Some(edit),
+                ..CodeAction::default()
+            }));
+        }
+    }
+
+    actions
+}
+
+/// Builds the edits of each fix for one document.
+struct Fixes<'a> {
+    uri: &'a Uri,
+    content: &'a str,
+    program: &'a Program,
+    language: Language,
+}
+
+impl Fixes<'_> {
+    fn title(&self, key: &str, name: &str) -> String {
+        format!("{} `{}`", get_lsp_text(key, self.language), name)
+    }
+
+    /// A workspace edit replacing `span` of this document with `text`.
+    fn edit(&self, span: Span, text: String) -> WorkspaceEdit {
+        // `WorkspaceEdit` keys its changes by `Uri`, which is never mutated here
+        #[allow(clippy::mutable_key_type)]
+        let changes = HashMap::from([(
+            self.uri.clone(),
+            vec![TextEdit::new(
+                byte_span_to_lsp_range(self.content, span.0, span.1),
+                text,
+            )],
+        )]);
+        WorkspaceEdit::new(changes)
+    }
+
+    /// Rename to the name the diagnostic suggests, along with every use of it.
+    fn rename(
+        &self,
+        diagnostic: &CompilerDiagnostic,
+        span: Span,
+        others: &[(Uri, Arc<IndexedDocument>)],
+    ) -> Option<(String, WorkspaceEdit)> {
+        let suggestion = diagnostic.suggestions(self.language).into_iter().next()?;
+        let edit = rename_edits(
+            self.uri,
+            self.content,
+            byte_offset_to_position(self.content, span.0),
+            &suggestion.replacement,
+            others,
+            self.language,
+        )
+        .ok()?;
+        Some((
+            self.title("code_action_rename", &suggestion.replacement),
+            edit,
+        ))
+    }
+
+    /// Declare the function a call names, typing each parameter after its argument.
+    fn declare_function(&self, name: &str, name_span: Span) -> Option<(String, WorkspaceEdit)> {
+        let mut site = CallSite {
+            name_span,
+            found: None,
+        };
+        site.visit_program(self.program);
+        let (call, return_type) = site.found?;
+
+        let mut params: Vec<String> = Vec::new();
+        for (i, arg) in call.args.iter().enumerate() {
+            let mut param_name = match arg {
+                Arg::Identifier(name, _) => name.clone(),
+                _ => format!("arg{}", i + 1),
+            };
+            if params
+                .iter()
+                .any(|param| param.starts_with(&format!("{}:", param_name)))
+            {
+                param_name = format!("{}{}", param_name, i + 1);
+            }
+            params.push(format!("{}: {}", param_name, self.arg_type(arg)));
+        }
+
+        let mut declaration = format!("fn {}({})", name, params.join(", "));
+        if let Some(return_type) = return_type {
+            declaration.push_str(&format!(" -> {}", return_type));
+        }
+
+        // Next to the other declarations, or else at the end of the file
+        let last_function = self.program.body.iter().rev().find_map(|item| match item {
+            TopLevel::FunctionDecl(function) => Some(function.span.1),
+            _ => None,
+        });
+        let (offset, text) = match last_function {
+            Some(end) => (end, format!("\n{}", declaration)),
+            None => self.at_end(&declaration),
+        };
+        Some((
+            self.title("code_action_declare_function", name),
+            self.edit((offset, offset), text),
+        ))
+    }
+
+    fn arg_type(&self, arg: &Arg) -> String {
+        let known = match arg {
+            Arg::String(..) => Some("String"),
+            Arg::Number(..) => Some("Number"),
+            Arg::Boolean(..) => Some("Bool"),
+            Arg::Identifier(name, _) => self.program.body.iter().find_map(|item| match item {
+                TopLevel::VarDecl(var) if var.name == *name => Some(var.type_name.as_str()),
+                TopLevel::ConstDecl(constant) if constant.name == *name => {
+                    Some(constant.type_name.as_str())
+                }
+                _ => None,
+            }),
+            Arg::FuncCall(call) => self.program.body.iter().find_map(|item| match item {
+                TopLevel::FunctionDecl(function) if function.name == call.name => {
+                    function.return_type.as_deref()
+                }
+                _ => None,
+            }),
+        };
+        match known {
+            // Branch variables hold text
+            Some("Branch") | None => "String".to_string(),
+            Some(name) => type_name(name).to_string(),
+        }
+    }
+
+    /// Append an empty node to jump to.
+    fn create_node(&self, name: &str) -> (String, WorkspaceEdit) {
+        let (offset, text) = self.at_end(&format!("node {} {{\n    text: \"\"\n}}", name));
+        (
+            self.title("code_action_create_node", name),
+            self.edit((offset, offset), text),
+        )
+    }
+
+    /// Delete the lines of the top-level declaration whose name is at `name_span`.
+    fn remove_declaration(&self, name: &str, name_span: Span) -> Option<(String, WorkspaceEdit)> {
+        let item = self.program.body.iter().find(|item| {
+            let item_name_span = match item {
+                TopLevel::FunctionDecl(function) => function.name_span,
+                TopLevel::VarDecl(var) => var.name_span,
+                TopLevel::ConstDecl(constant) => constant.name_span,
+                TopLevel::EnumDef(enum_def) => enum_def.name_span,
+                TopLevel::EventDef(event) => event.name_span,
+                TopLevel::TimelineDef(timeline) => timeline.name_span,
+                TopLevel::NodeDef(_) => None,
+            };
+            item_name_span == Some(name_span)
+        })?;
+
+        Some((
+            self.title("code_action_remove_unused", name),
+            self.edit(self.whole_lines(item.span()), String::new()),
+        ))
+    }
+
+    /// Add a case with empty text for every variant the branch does not cover.
+    fn add_branch_cases(
+        &self,
+        selector: &str,
+        cases: &[BranchCase],
+        span: Span,
+    ) -> Option<(String, WorkspaceEdit)> {
+        let enum_name = self.program.body.iter().find_map(|item| match item {
+            TopLevel::VarDecl(var) if var.name == selector => Some(var.type_name.as_str()),
+            _ => None,
+        })?;
+        let enum_def = self.program.body.iter().find_map(|item| match item {
+            TopLevel::EnumDef(enum_def) if enum_def.name == enum_name => Some(enum_def),
+            _ => None,
+        })?;
+        let missing: Vec<&String> = enum_def
+            .variants
+            .iter()
+            .filter(|variant| !cases.iter().any(|case| case.condition == **variant))
+            .collect();
+        if missing.is_empty() {
+            return None;
+        }
+
+        // New cases go on their own lines before the closing bracket
+        let closing = span.1 - 1;
+        let closing_line = self.line_start(closing);
+        let indent = match cases.last() {
+            Some(case) => self.indent(case.span.0).to_string(),
+            None => format!("{}    ", self.indent(closing)),
+        };
+        let lines: Vec<String> = missing
+            .iter()
+            .map(|variant| format!("{}{}, \"\"", indent, variant))
+            .collect();
+        let (offset, text) = if self.content[closing_line..closing].trim().is_empty() {
+            (closing_line, format!("{}\n", lines.join("\n")))
+        } else {
+            (closing, format!("\n{}\n", lines.join("\n")))
+        };
+
+        let names: Vec<&str> = missing.iter().map(|variant| variant.as_str()).collect();
+        Some((
+            self.title("code_action_add_branch_cases", &names.join("`, `")),
+            self.edit((offset, offset), text),
+        ))
+    }
+
+    /// Where to append a top-level item, and the text that leaves one blank line before it.
+    fn at_end(&self, item: &str) -> (usize, String) {
+        let separator = if self.content.trim().is_empty() || self.content.ends_with("\n\n") {
+            ""
+        } else if self.content.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        (self.content.len(), format!("{}{}\n", separator, item))
+    }
+
+    fn line_start(&self, offset: usize) -> usize {
+        self.content[..offset]
+            .rfind('\n')
+            .map_or(0, |index| index + 1)
+    }
+
+    /// The whitespace that starts the line of `offset`.
+    fn indent(&self, offset: usize) -> &str {
+        let start = self.line_start(offset);
+        let line = &self.content[start..offset];
+        &line[..line.len() - line.trim_start().len()]
+    }
+
+    /// `span` grown to the lines it is on, and a blank line after it when one is also before it.
+    fn whole_lines(&self, (start, end): Span) -> Span {
+        let line_start = self.line_start(start);
+        let start = if self.content[line_start..start].trim().is_empty() {
+            line_start
+        } else {
+            start
+        };
+
+        let next_line = |offset: usize| {
+            self.content[offset..]
+                .find('\n')
+                .map_or(self.content.len(), |index| offset + index + 1)
+        };
+        let mut end = match next_line(end) {
+            line_end if self.content[end..line_end].trim().is_empty() => line_end,
+            _ => end,
+        };
+
+        let blank_before = start == 0 || self.content[..start].ends_with("\n\n");
+        let after = next_line(end);
+        if blank_before && end < self.content.len() && self.content[end..after].trim().is_empty() {
+            end = after;
+        }
+        (start, end)
+    }
+}
+
+/// Finds the call whose name is at `name_span`, and the type its position expects back.
+struct CallSite {
+    name_span: Span,
+    found: Option<(FuncCall, Option<&'static str>)>,
+}
+
+impl CallSite {
+    fn matches(&self, call: &FuncCall) -> bool {
+        self.found.is_none() && call.name_span == Some(self.name_span)
+    }
+}
+
+impl Visit for CallSite {
+    fn visit_condition(&mut self, condition: &Condition) {
+        match condition {
+            Condition::FuncCall(call) if self.matches(call) => {
+                self.found = Some((call.clone(), Some("Bool")));
+            }
+            _ => visit::walk_condition(self, condition),
+        }
+    }
+
+    fn visit_string_part(&mut self, part: &StringPart) {
+        match part {
+            StringPart::Expression(call) if self.matches(call) => {
+                self.found = Some((call.clone(), Some("String")));
+            }
+            _ => visit::walk_string_part(self, part),
+        }
+    }
+
+    fn visit_func_call(&mut self, call: &FuncCall) {
+        if self.matches(call) {
+            self.found = Some((call.clone(), None));
+        }
+        visit::walk_func_call(self, call);
+    }
+}
+
+/// Collects the enum branches of a program: their selector, cases and span.
+#[derive(Default)]
+struct Branches {
+    found: Vec<(String, Vec<BranchCase>, Span)>,
+    /// Where the declaration holding the branch being visited starts.
+    declaration_start: Option<usize>,
+}
+
+impl Visit for Branches {
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) {
+        self.declaration_start = Some(var_decl.span.0);
+        visit::walk_var_decl(self, var_decl);
+        self.declaration_start = None;
+    }
+
+    fn visit_branch_value(&mut self, branch: &BranchValue) {
+        if let Some(selector) = &branch.enum_type {
+            let start = self.declaration_start.unwrap_or(branch.span.0);
+            self.found.push((
+                selector.clone(),
+                branch.cases.clone(),
+                (start, branch.span.1),
+            ));
+        }
+        visit::walk_branch_value(self, branch);
+    }
+
+    fn visit_branch_def(&mut self, branch: &BranchDef) {
+        if let Some(selector) = &branch.enum_type {
+            let start = branch.name_span.map_or(branch.span.0, |span| span.0);
+            self.found.push((
+                selector.clone(),
+                branch.cases.clone(),
+                (start.min(branch.span.0), branch.span.1),
+            ));
+        }
+        visit::walk_branch_def(self, branch);
+    }
+}
@@ -1,401 +1,634 @@
+use mortar_compiler::ast::*;
+use mortar_compiler::printer::type_name;
+use mortar_compiler::{ParseHandler, Token, TokenInfo, is_identifier_continue, tokenize};
 use tower_lsp_server::lsp_types::*;
 
-use crate::analysis::SymbolTable;
-use crate::backend::Backend;
+use crate::backend::diagnostics::{position_to_byte_offset, utf16_column_to_byte};
 
-/// Autocomplete context type
+/// What the text before the cursor expects next.
 #[derive(Debug, Clone, PartialEq)]
 pub enum CompletionContext {
     TopLevel,
+    /// A statement in a node or in an `if` section.
     InNode,
-    InChoice,
-    InExpression,
+    /// A statement in a timeline.
+    InTimeline,
+    /// A field of an event definition.
+    InEvent,
+    /// The type of a variable, constant, parameter or return value.
+    Type,
+    /// After `Name.`: the variants of the enum `Name`.
+    EnumMember(String),
+    /// After the text of a choice and a `.`.
+    ChoiceModifier,
+    /// After `->`.
+    JumpTarget,
+    /// After `run`.
+    RunTarget,
+    /// After `with`, or an item of a `with events` list.
+    WithTarget,
+    /// The function an event calls.
+    EventAction,
+    /// A value in a condition, an assignment or a call.
+    Value,
+    /// Inside `{}` of an interpolated string.
+    Interpolation,
+    /// The start of a case of `branch<selector> [`; `None` for a boolean branch.
+    BranchCase(Option<String>),
 }
 
-impl Backend {
-    /// Context analysis based on entire document
-    pub fn analyze_document_context(&self, uri: &Uri, position: Position) -> CompletionContext {
-        if let Some(document_entry) = self.documents.get(uri) {
-            let rope = &document_entry.0;
-            let line_idx = position.line as usize;
+/// Completions for `position`, drawn from the declarations of `content`.
+///
+/// The document is parsed as it is, so declarations outside the item being
+/// edited are offered even while that item does not parse.
+pub fn completion_items(content: &str, position: Position) -> Vec<CompletionItem> {
+    let offset = position_to_byte_offset(content, position);
+    let prefix = word_before(content, offset);
+    let Some(context) = context_at(content, offset - prefix.len()) else {
+        return Vec::new();
+    };
 
-            let mut brace_depth = 0;
-            let mut in_node = false;
-            let mut in_function = false;
+    let (result, _diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(content, String::new(), false);
+    let program = result.unwrap_or(Program {
+        body: Vec::new(),
+        span: (0, 0),
+    });
+    let declarations = Declarations { program: &program };
 
-            for i in 0..=line_idx.min(rope.len_lines() - 1) {
-                let line_content = rope.line(i).to_string();
-                let trimmed = line_content.trim();
+    declarations
+        .items(&context)
+        .into_iter()
+        .filter(|item| item.label.starts_with(prefix))
+        .collect()
+}
 
-                if (trimmed.starts_with("node ") || trimmed.starts_with("nd ")) && brace_depth == 0
-                {
-                    in_node = false;
-                }
-                if (trimmed.starts_with("fn ") || trimmed.starts_with("function "))
-                    && brace_depth == 0
-                {
-                    in_function = false;
-                }
+/// The context at `position`, or `None` where nothing can be completed, such as in a string.
+pub fn completion_context(content: &str, position: Position) -> Option<CompletionContext> {
+    let offset = position_to_byte_offset(content, position);
+    context_at(content, offset - word_before(content, offset).len())
+}
 
-                for ch in line_content.chars() {
-                    match ch {
-                        '{' => {
-                            brace_depth += 1;
-                            if trimmed.starts_with("node ") || trimmed.starts_with("nd ") {
-                                in_node = true;
-                                in_function = false;
-                            } else if trimmed.starts_with("fn ") || trimmed.starts_with("function ")
-                            {
-                                in_function = true;
-                                in_node = false;
-                            }
-                        }
-                        '}' => {
-                            brace_depth -= 1;
-                            if brace_depth == 0 {
-                                in_node = false;
-                                in_function = false;
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+/// The part of the identifier at `offset` that comes before it.
+fn word_before(content: &str, offset: usize) -> &str {
+    let start = content[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, ch)| is_identifier_continue(ch))
+        .last()
+        .map_or(offset, |(index, _)| index);
+    &content[start..offset]
+}
 
-                if i == line_idx {
-                    break;
-                }
-            }
+/// Where an open bracket was opened.
+#[derive(Debug, Clone, PartialEq)]
+enum Frame {
+    Node,
+    Timeline,
+    Event,
+    Choice,
+    WithEvents,
+    /// The events of a branch case.
+    Events,
+    Branch(Option<String>),
+    When,
+    /// The arguments of a function call.
+    Call,
+    Other,
+}
 
-            if line_idx < rope.len_lines() {
-                let current_line = rope.line(line_idx).to_string();
-                let current_trimmed = current_line.trim();
-                if current_trimmed.contains("choice")
-                    || current_trimmed.contains("->")
-                    || (in_node
-                        && (current_trimmed.starts_with("\"") || current_trimmed.contains("\"")))
-                {
-                    return CompletionContext::InChoice;
-                }
-            }
+fn context_at(content: &str, offset: usize) -> Option<CompletionContext> {
+    let line_start = content[..offset].rfind('\n').map_or(0, |index| index + 1);
+    match string_state(&content[line_start..offset]) {
+        StringState::Outside => {}
+        StringState::Interpolation => return Some(CompletionContext::Interpolation),
+        StringState::Text | StringState::Comment => return None,
+    }
+
+    let tokens: Vec<TokenInfo> = tokenize(&content[..offset])
+        .into_iter()
+        .filter(|token| {
+            !matches!(
+                token.token,
+                Token::SingleLineComment(_) | Token::MultiLineComment(_)
+            )
+        })
+        .collect();
 
-            if in_function && brace_depth > 0 {
-                CompletionContext::InExpression
-            } else if in_node && brace_depth > 0 {
-                CompletionContext::InNode
-            } else {
-                CompletionContext::TopLevel
+    let mut frames = Vec::new();
+    for i in 0..tokens.len() {
+        match tokens[i].token {
+            Token::LeftBrace => frames.push(brace_frame(&tokens[..i])),
+            Token::LeftBracket => frames.push(bracket_frame(&tokens[..i])),
+            Token::LeftParen => frames.push(paren_frame(&tokens[..i])),
+            Token::RightBrace | Token::RightBracket | Token::RightParen => {
+                frames.pop();
             }
-        } else {
-            CompletionContext::TopLevel
+            _ => {}
         }
     }
 
-    /// Analyze the context of auto-completion
-    pub fn analyze_completion_context(&self, line: &str, _char_idx: usize) -> CompletionContext {
-        let trimmed = line.trim();
+    let token = |back: usize| {
+        tokens
+            .len()
+            .checked_sub(back)
+            .map(|index| &tokens[index].token)
+    };
+    let frame = frames.last();
 
-        if trimmed.is_empty() || (!trimmed.contains('{') && !trimmed.contains('}')) {
-            return CompletionContext::TopLevel;
+    let context = match (token(1), token(2)) {
+        (Some(Token::Dot), Some(Token::Identifier(name))) => {
+            CompletionContext::EnumMember(name.to_string())
         }
-
-        if trimmed.starts_with("node ")
-            || trimmed.starts_with("nd ")
-            || trimmed.starts_with("fn ")
-            || trimmed.starts_with("function ")
+        (Some(Token::Dot), Some(Token::RightParen)) => CompletionContext::ChoiceModifier,
+        (Some(Token::Dot), _) => return None,
+        (Some(Token::Arrow), Some(Token::RightParen)) if frame.is_none() => CompletionContext::Type,
+        (Some(Token::Arrow), _) => CompletionContext::JumpTarget,
+        (Some(Token::Run), _) => CompletionContext::RunTarget,
+        (Some(Token::With), _) => CompletionContext::WithTarget,
+        (Some(Token::Colon), Some(Token::Action)) => CompletionContext::EventAction,
+        (Some(Token::Colon), Some(Token::Identifier(_))) => CompletionContext::Type,
+        (Some(Token::Comma), Some(Token::Number(_)))
+            if matches!(frame, Some(Frame::WithEvents | Frame::Events)) =>
         {
-            return if trimmed.ends_with('{') {
-                CompletionContext::InNode
-            } else {
-                CompletionContext::TopLevel
-            };
+            CompletionContext::EventAction
         }
+        (Some(Token::Equals), _) => CompletionContext::Value,
+        _ if in_condition(&tokens) => CompletionContext::Value,
+        (last, _) => match frame {
+            None => CompletionContext::TopLevel,
+            Some(Frame::Node) => CompletionContext::InNode,
+            Some(Frame::Timeline) => CompletionContext::InTimeline,
+            Some(Frame::Event) => CompletionContext::InEvent,
+            Some(Frame::WithEvents) => CompletionContext::WithTarget,
+            Some(Frame::When | Frame::Call) => CompletionContext::Value,
+            Some(Frame::Branch(selector))
+                if matches!(
+                    last,
+                    Some(Token::LeftBracket | Token::String(_) | Token::RightBracket)
+                ) =>
+            {
+                CompletionContext::BranchCase(selector.clone())
+            }
+            _ => return None,
+        },
+    };
+    Some(context)
+}
 
-        if trimmed.contains("choice:") || trimmed.contains("->") {
-            return CompletionContext::InChoice;
+/// Whether the statement the tokens end in is an `if` whose condition is still open.
+fn in_condition(tokens: &[TokenInfo]) -> bool {
+    for token in tokens.iter().rev() {
+        match token.token {
+            Token::If => return true,
+            Token::LeftBrace
+            | Token::RightBrace
+            | Token::LeftBracket
+            | Token::RightBracket
+            | Token::Colon => return false,
+            _ => {}
         }
+    }
+    false
+}
 
-        if trimmed.starts_with("text:") || trimmed.starts_with("events:") {
-            return CompletionContext::InNode;
+/// What the keyword heading the statement before a `{` opens.
+fn brace_frame(before: &[TokenInfo]) -> Frame {
+    let header = before
+        .iter()
+        .rev()
+        .take_while(|token| {
+            !matches!(
+                token.token,
+                Token::LeftBrace | Token::RightBrace | Token::LeftBracket | Token::RightBracket
+            )
+        })
+        .find(|token| {
+            matches!(
+                token.token,
+                Token::Node
+                    | Token::If
+                    | Token::Else
+                    | Token::Timeline
+                    | Token::Event
+                    | Token::Enum
+            )
+        });
+    match header.map(|token| &token.token) {
+        Some(Token::Node | Token::If | Token::Else) => Frame::Node,
+        Some(Token::Timeline) => Frame::Timeline,
+        Some(Token::Event) => Frame::Event,
+        _ => Frame::Other,
+    }
+}
+
+/// Whether a `(` opens a call, a `when` condition or something else, like a parameter list.
+fn paren_frame(before: &[TokenInfo]) -> Frame {
+    let token = |back: usize| {
+        before
+            .len()
+            .checked_sub(back)
+            .map(|index| &before[index].token)
+    };
+    match (token(1), token(2)) {
+        (Some(Token::When), _) => Frame::When,
+        (Some(Token::Identifier(_)), Some(Token::Fn)) => Frame::Other,
+        (Some(Token::Identifier(_)), _) => Frame::Call,
+        _ => Frame::Other,
+    }
+}
+
+/// What the tokens before a `[` open.
+fn bracket_frame(before: &[TokenInfo]) -> Frame {
+    let token = |back: usize| {
+        before
+            .len()
+            .checked_sub(back)
+            .map(|index| &before[index].token)
+    };
+    match (token(1), token(2), token(3), token(4)) {
+        (Some(Token::Colon), Some(Token::Choice), _, _) | (Some(Token::Arrow), _, _, _) => {
+            Frame::Choice
         }
+        (Some(Token::Colon), Some(Token::Events), Some(Token::With), _) => Frame::WithEvents,
+        (Some(Token::Colon), Some(Token::Events), _, _) => Frame::Events,
+        (Some(Token::Branch), _, _, _) => Frame::Branch(None),
+        (
+            Some(Token::Greater),
+            Some(Token::Identifier(selector)),
+            Some(Token::Less),
+            Some(Token::Branch),
+        ) => Frame::Branch(Some(selector.to_string())),
+        _ => Frame::Other,
+    }
+}
 
-        CompletionContext::TopLevel
+enum StringState {
+    Outside,
+    Text,
+    Interpolation,
+    Comment,
+}
+
+/// Whether the end of `line` is inside a string or a line comment, and if so inside an interpolation.
+///
+/// Strings do not span lines, and one still being typed does not lex, so
+/// this looks at the characters of the line itself.
+fn string_state(line: &str) -> StringState {
+    let mut chars = line.chars().peekable();
+    // Whether the string is interpolated, and how deep in its braces
+    let mut string: Option<(bool, usize)> = None;
+
+    while let Some(ch) = chars.next() {
+        match (&mut string, ch) {
+            (None, '/') if chars.peek() == Some(&'/') => return StringState::Comment,
+            (None, '$') if chars.peek() == Some(&'"') => {
+                chars.next();
+                string = Some((true, 0));
+            }
+            (None, '"') => string = Some((false, 0)),
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some((true, depth)), '{') => *depth += 1,
+            (Some((true, depth)), '}') if *depth > 0 => *depth -= 1,
+            (Some((_, 0)), '"') => string = None,
+            _ => {}
+        }
+    }
+
+    match string {
+        None => StringState::Outside,
+        Some((true, depth)) if depth > 0 => StringState::Interpolation,
+        Some(_) => StringState::Text,
+    }
+}
+
+fn keyword(label: &str, insert_text: &str, detail: &str) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(CompletionItemKind::KEYWORD),
+        detail: Some(detail.to_string()),
+        insert_text: Some(insert_text.to_string()),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..CompletionItem::default()
+    }
+}
+
+fn symbol(label: &str, kind: CompletionItemKind, detail: String) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail: Some(detail),
+        ..CompletionItem::default()
     }
+}
+
+/// The declarations of a document, turned into completion items.
+struct Declarations<'a> {
+    program: &'a Program,
+}
 
-    pub fn generate_completion_items(&self, context: CompletionContext) -> Vec<CompletionItem> {
+impl Declarations<'_> {
+    fn items(&self, context: &CompletionContext) -> Vec<CompletionItem> {
         match context {
             CompletionContext::TopLevel => vec![
-                CompletionItem {
-                    label: "node".to_string(),
-                    kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Define a new story node".to_string()),
-                    insert_text: Some("node ${1:node_name} {\n    text: \"${2:text_content}\"\n}".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
-                CompletionItem {
-                    label: "nd".to_string(),
-                    kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Define a new story node (short form)".to_string()),
-                    insert_text: Some("nd ${1:node_name} {\n    text: \"${2:text_content}\"\n}".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
-                CompletionItem {
-                    label: "fn".to_string(),
-                    kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Define a function".to_string()),
-                    insert_text: Some("fn ${1:function_name}(${2:params}) -> ${3:ReturnType} {\n    ${4:// function body}\n}".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
+                keyword(
+                    "node",
+                    "node ${1:Name} {\n\ttext: \"$0\"\n}",
+                    "Define a story node",
+                ),
+                keyword("fn", "fn ${1:name}($2)", "Declare a function"),
+                keyword("let", "let ${1:name}: ${2:Type}", "Declare a variable"),
+                keyword(
+                    "const",
+                    "const ${1:name}: ${2:Type} = $0",
+                    "Declare a constant",
+                ),
+                keyword("enum", "enum ${1:Name} {\n\t$0\n}", "Define an enum"),
+                keyword(
+                    "event",
+                    "event ${1:Name} {\n\tindex: ${2:0}\n\taction: $0\n}",
+                    "Define an event",
+                ),
+                keyword(
+                    "timeline",
+                    "timeline ${1:Name} {\n\t$0\n}",
+                    "Define a timeline",
+                ),
             ],
-            CompletionContext::InNode => vec![
-                CompletionItem {
-                    label: "text".to_string(),
-                    kind: Some(CompletionItemKind::PROPERTY),
-                    detail: Some("Story text content".to_string()),
-                    insert_text: Some("text: \"${1:text_content}\"".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
-                CompletionItem {
-                    label: "choice".to_string(),
-                    kind: Some(CompletionItemKind::PROPERTY),
-                    detail: Some("Player choices".to_string()),
-                    insert_text: Some("choice: [\n    \"${1:choice_text}\" -> ${2:target_node}\n]".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
-                CompletionItem {
-                    label: "events".to_string(),
-                    kind: Some(CompletionItemKind::PROPERTY),
-                    detail: Some("Timed events".to_string()),
-                    insert_text: Some("events: [\n    ${1:delay}, ${2:action}\n]".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
-            ],
-            CompletionContext::InChoice => vec![
-                CompletionItem {
-                    label: "when".to_string(),
-                    kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Conditional choice".to_string()),
-                    insert_text: Some("when ${1:condition}".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
-                CompletionItem {
-                    label: "->".to_string(),
-                    kind: Some(CompletionItemKind::OPERATOR),
-                    detail: Some("Jump to target node".to_string()),
-                    insert_text: Some("-> ${1:target_node}".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
+            CompletionContext::InNode => {
+                let mut items = vec![
+                    keyword("text", "text: \"$0\"", "Story text content"),
+                    keyword(
+                        "choice",
+                        "choice: [\n\t\"${1:text}\" -> ${2:target}\n]",
+                        "Player choices",
+                    ),
+                    keyword("run", "run $0", "Run an event or timeline"),
+                    keyword(
+                        "with",
+                        "with events: [\n\t${1:0}, $0\n]",
+                        "Events timed to the previous text",
+                    ),
+                    keyword("if", "if ${1:condition} {\n\t$0\n}", "Conditional section"),
+                ];
+                items.extend(self.variables());
+                items
+            }
+            CompletionContext::InTimeline => vec![
+                keyword("run", "run $0", "Run an event or timeline"),
+                keyword("wait", "wait ${1:1}", "Wait some seconds"),
             ],
-            CompletionContext::InExpression => vec![
-                CompletionItem {
-                    label: "return".to_string(),
-                    kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Return statement".to_string()),
-                    insert_text: Some("return ${1:value}".to_string()),
-                    insert_text_format: Some(InsertTextFormat::SNIPPET),
-                    ..Default::default()
-                },
-                CompletionItem {
-                    label: "break".to_string(),
-                    kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Break statement".to_string()),
-                    ..Default::default()
-                },
+            CompletionContext::InEvent => vec![
+                keyword("index", "index: ${1:0}", "When the event fires"),
+                keyword("action", "action: $0", "The function the event calls"),
+                keyword("duration", "duration: ${1:1}", "How long the event lasts"),
             ],
+            CompletionContext::Type => {
+                let mut items: Vec<_> = ["String", "Number", "Bool"]
+                    .iter()
+                    .map(|name| symbol(name, CompletionItemKind::KEYWORD, "type".to_string()))
+                    .collect();
+                items.extend(self.enums());
+                items
+            }
+            CompletionContext::EnumMember(name) => self.variants(name),
+            CompletionContext::ChoiceModifier => vec![keyword(
+                "when",
+                "when(${1:condition})",
+                "Show the choice only when the condition holds",
+            )],
+            CompletionContext::JumpTarget => {
+                let mut items = self.nodes();
+                items.push(symbol(
+                    "return",
+                    CompletionItemKind::KEYWORD,
+                    "End the node".to_string(),
+                ));
+                items.push(symbol(
+                    "break",
+                    CompletionItemKind::KEYWORD,
+                    "Leave the choice".to_string(),
+                ));
+                items
+            }
+            CompletionContext::RunTarget => {
+                let mut items = self.events();
+                items.extend(self.timelines());
+                items
+            }
+            CompletionContext::WithTarget => {
+                let mut items = vec![keyword(
+                    "events",
+                    "events: [\n\t${1:0}, $0\n]",
+                    "Events timed to the previous text",
+                )];
+                items.extend(self.events());
+                items
+            }
+            CompletionContext::EventAction => self.functions(),
+            CompletionContext::Value => {
+                let mut items = self.variables();
+                items.extend(self.enums());
+                items.extend(self.functions());
+                for literal in ["true", "false"] {
+                    items.push(symbol(
+                        literal,
+                        CompletionItemKind::KEYWORD,
+                        "Bool".to_string(),
+                    ));
+                }
+                items
+            }
+            CompletionContext::Interpolation => {
+                let mut items = self.variables();
+                items.extend(self.functions());
+                items
+            }
+            CompletionContext::BranchCase(Some(selector)) => match self.variable_type(selector) {
+                Some(enum_name) => self.variants(enum_name),
+                None => Vec::new(),
+            },
+            CompletionContext::BranchCase(None) => self
+                .variables()
+                .into_iter()
+                .filter(|item| item.detail.as_deref() == Some("Bool"))
+                .collect(),
         }
     }
 
-    /// Generate filtered completion items based on current word and symbol table
-    pub fn generate_completion_items_filtered(
-        &self,
-        context: CompletionContext,
-        current_word: &str,
-        symbol_table: &SymbolTable,
-    ) -> Vec<CompletionItem> {
-        match context {
-            CompletionContext::TopLevel => {
-                let mut completions = Vec::new();
-
-                if "node".starts_with(current_word) {
-                    completions.push(CompletionItem {
-                        label: "node".to_string(),
-                        kind: Some(CompletionItemKind::KEYWORD),
-                        insert_text: Some("node ${1:node_name} {\n\t$0\n}".to_string()),
-                        insert_text_format: Some(InsertTextFormat::SNIPPET),
-                        detail: Some("Create a dialogue node".to_string()),
-                        documentation: Some(Documentation::String(
-                            "Create a new dialog node that defines dialog content and choices"
-                                .to_string(),
-                        )),
-                        ..CompletionItem::default()
-                    });
-                }
-
-                if "nd".starts_with(current_word) {
-                    completions.push(CompletionItem {
-                        label: "nd".to_string(),
-                        kind: Some(CompletionItemKind::KEYWORD),
-                        insert_text: Some("nd ${1:node_name} {\n\t$0\n}".to_string()),
-                        insert_text_format: Some(InsertTextFormat::SNIPPET),
-                        detail: Some("Create a dialogue node".to_string()),
-                        documentation: Some(Documentation::String(
-                            "Create a new dialog node that defines dialog content and choices"
-                                .to_string(),
-                        )),
-                        ..CompletionItem::default()
-                    });
-                }
+    fn variable_type(&self, name: &str) -> Option<&str> {
+        self.program.body.iter().find_map(|item| match item {
+            TopLevel::VarDecl(var) if var.name == name => Some(var.type_name.as_str()),
+            TopLevel::ConstDecl(constant) if constant.name == name => {
+                Some(constant.type_name.as_str())
+            }
+            _ => None,
+        })
+    }
 
-                if "fn".starts_with(current_word) {
-                    completions.push(CompletionItem {
-                        label: "fn".to_string(),
-                        kind: Some(CompletionItemKind::KEYWORD),
-                        insert_text: Some("fn ${1:function_name}()".to_string()),
-                        insert_text_format: Some(InsertTextFormat::SNIPPET),
-                        detail: Some("Create a function".to_string()),
-                        documentation: Some(Documentation::String(
-                            "Create a new function definition".to_string(),
-                        )),
-                        ..CompletionItem::default()
-                    });
-                }
+    fn variants(&self, enum_name: &str) -> Vec<CompletionItem> {
+        self.program
+            .body
+            .iter()
+            .find_map(|item| match item {
+                TopLevel::EnumDef(enum_def) if enum_def.name == enum_name => Some(enum_def),
+                _ => None,
+            })
+            .map(|enum_def| {
+                enum_def
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        symbol(
+                            variant,
+                            CompletionItemKind::ENUM_MEMBER,
+                            enum_def.name.clone(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 
-                if "function".starts_with(current_word) {
-                    completions.push(CompletionItem {
-                        label: "function".to_string(),
-                        kind: Some(CompletionItemKind::KEYWORD),
-                        insert_text: Some("function ${1:function_name}() {\n\t$0\n}".to_string()),
-                        insert_text_format: Some(InsertTextFormat::SNIPPET),
-                        detail: Some("Create a function".to_string()),
-                        documentation: Some(Documentation::String(
-                            "Create a new function definition (alternative syntax)".to_string(),
-                        )),
-                        ..CompletionItem::default()
-                    });
-                }
+    fn variables(&self) -> Vec<CompletionItem> {
+        self.program
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                TopLevel::VarDecl(var) => Some(symbol(
+                    &var.name,
+                    CompletionItemKind::VARIABLE,
+                    type_name(&var.type_name).to_string(),
+                )),
+                TopLevel::ConstDecl(constant) => Some(symbol(
+                    &constant.name,
+                    CompletionItemKind::CONSTANT,
+                    type_name(&constant.type_name).to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
 
-                completions
-            }
-            CompletionContext::InNode => {
-                let mut completions = Vec::new();
-
-                for keyword in &["text", "choice", "events"] {
-                    if keyword.starts_with(current_word) {
-                        let (insert_text, detail) = match *keyword {
-                            "text" => (
-                                "text: \"${1:text_content}\"".to_string(),
-                                "Story text content".to_string(),
-                            ),
-                            "choice" => (
-                                "choice: [\n\t\"${1:text}\" -> ${2:target_node}\n]".to_string(),
-                                "Add selection".to_string(),
-                            ),
-                            "events" => (
-                                "events: [\n\t${1:delay}, ${2:action}\n]".to_string(),
-                                "Timed events".to_string(),
-                            ),
-                            _ => (keyword.to_string(), "".to_string()),
-                        };
-                        completions.push(CompletionItem {
-                            label: keyword.to_string(),
-                            kind: Some(CompletionItemKind::PROPERTY),
-                            insert_text: Some(insert_text),
-                            insert_text_format: Some(InsertTextFormat::SNIPPET),
-                            detail: Some(detail),
-                            ..CompletionItem::default()
-                        });
-                    }
-                }
+    fn enums(&self) -> Vec<CompletionItem> {
+        self.program
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                TopLevel::EnumDef(enum_def) => Some(symbol(
+                    &enum_def.name,
+                    CompletionItemKind::ENUM,
+                    "enum".to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
 
-                completions
-            }
-            CompletionContext::InChoice => {
-                let mut completions = Vec::new();
-
-                for keyword in &["when", "return", "break"] {
-                    if keyword.starts_with(current_word) {
-                        let insert_text = match *keyword {
-                            "when" => Some("when ${1:condition}".to_string()),
-                            "return" => Some("return ${1:value}".to_string()),
-                            _ => None,
-                        };
-                        let has_snippet = insert_text.is_some();
-                        completions.push(CompletionItem {
-                            label: keyword.to_string(),
-                            kind: Some(CompletionItemKind::KEYWORD),
-                            insert_text,
-                            insert_text_format: if has_snippet {
-                                Some(InsertTextFormat::SNIPPET)
-                            } else {
-                                None
-                            },
-                            ..CompletionItem::default()
-                        });
-                    }
-                }
+    fn nodes(&self) -> Vec<CompletionItem> {
+        self.program
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                TopLevel::NodeDef(node) => Some(symbol(
+                    &node.name,
+                    CompletionItemKind::CLASS,
+                    "node".to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
 
-                // Add node completions
-                for node in &symbol_table.nodes {
-                    if node.starts_with(current_word) {
-                        completions.push(CompletionItem {
-                            label: node.clone(),
-                            kind: Some(CompletionItemKind::CLASS),
-                            detail: Some("Jump to node".to_string()),
-                            ..CompletionItem::default()
-                        });
-                    }
-                }
+    fn events(&self) -> Vec<CompletionItem> {
+        self.program
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                TopLevel::EventDef(event) => Some(symbol(
+                    &event.name,
+                    CompletionItemKind::EVENT,
+                    format!("event: {}", event.action.call.name),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
 
-                completions
-            }
-            CompletionContext::InExpression => {
-                let mut completions = Vec::new();
-
-                // Add function completions
-                for func in &symbol_table.functions {
-                    if func.name.starts_with(current_word) {
-                        completions.push(CompletionItem {
-                            label: func.name.clone(),
-                            kind: Some(CompletionItemKind::FUNCTION),
-                            detail: Some(format!(
-                                "fn {}({}){}",
-                                func.name,
-                                func.params
-                                    .iter()
-                                    .map(|p| format!("{}: {}", p.name, p.type_name))
-                                    .collect::<Vec<_>>()
-                                    .join(", "),
-                                if func.return_type.as_ref().is_none_or(|t| t.is_empty()) {
-                                    "".to_string()
-                                } else {
-                                    format!(" -> {}", func.return_type.as_ref().unwrap())
-                                }
-                            )),
-                            ..CompletionItem::default()
-                        });
-                    }
-                }
+    fn timelines(&self) -> Vec<CompletionItem> {
+        self.program
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                TopLevel::TimelineDef(timeline) => Some(symbol(
+                    &timeline.name,
+                    CompletionItemKind::EVENT,
+                    "timeline".to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
 
-                // Add type keywords
-                for type_keyword in &["String", "Number", "Boolean", "Bool", "true", "false"] {
-                    if type_keyword.starts_with(current_word) {
-                        completions.push(CompletionItem {
-                            label: type_keyword.to_string(),
-                            kind: Some(CompletionItemKind::KEYWORD),
-                            ..CompletionItem::default()
-                        });
-                    }
+    /// Declared functions, inserted as calls with a placeholder per parameter.
+    fn functions(&self) -> Vec<CompletionItem> {
+        self.program
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                TopLevel::FunctionDecl(function) => Some(function),
+                _ => None,
+            })
+            .map(|function| {
+                let params: Vec<String> = function
+                    .params
+                    .iter()
+                    .enumerate()
+                    .map(|(i, param)| format!("${{{}:{}}}", i + 1, param.name))
+                    .collect();
+                CompletionItem {
+                    label: function.name.clone(),
+                    kind: Some(CompletionItemKind::FUNCTION),
+                    detail: Some(signature(function)),
+                    insert_text: Some(format!("{}({})", function.name, params.join(", "))),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..CompletionItem::default()
                 }
+            })
+            .collect()
+    }
+}
 
-                completions
-            }
-        }
+/// `fn name(param: Type) -> Return`
+fn signature(function: &FunctionDecl) -> String {
+    let params: Vec<String> = function
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, type_name(&param.type_name)))
+        .collect();
+    let mut signature = format!("fn {}({})", function.name, params.join(", "));
+    if let Some(return_type) = &function.return_type {
+        signature.push_str(&format!(" -> {}", type_name(return_type)));
     }
+    signature
+}
+
+/// The identifier around a UTF-16 `column`, including one that ends right at it.
+pub fn current_word(line: &str, column: usize) -> String {
+    let cursor = utf16_column_to_byte(line, column as u32);
+
+    let start = line[..cursor]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, ch)| is_identifier_continue(ch))
+        .last()
+        .map_or(cursor, |(index, _)| index);
+    let end = line[cursor..]
+        .char_indices()
+        .find(|&(_, ch)| !is_identifier_continue(ch))
+        .map_or(line.len(), |(index, _)| cursor + index);
+
+    line[start..end].to_string()
 }
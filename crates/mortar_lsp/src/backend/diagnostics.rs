@@ -1,21 +1,40 @@
+use crate::backend::i18n::get_lsp_text;
 use mortar_compiler::{
-    Diagnostic as CompilerDiagnostic, DiagnosticCollector, Language, ParseHandler, Severity,
+    Diagnostic as CompilerDiagnostic, IncrementalParse, Language, ParseHandler, ProjectConfig,
+    Severity,
 };
-use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::SystemTime;
+use tower_lsp_server::UriExt;
+use tower_lsp_server::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+    Position, Range, Uri,
+};
+use tracing::warn;
 
 /// Convert compiler diagnostics to LSP diagnostics
 pub fn convert_diagnostics_to_lsp(
+    uri: &Uri,
     content: &str,
     diagnostics: &[CompilerDiagnostic],
+    language: Language,
 ) -> Vec<Diagnostic> {
     diagnostics
         .iter()
-        .map(|diag| convert_single_diagnostic(content, diag))
+        .map(|diag| convert_single_diagnostic(uri, content, diag, language))
         .collect()
 }
 
 /// Convert a single compiler diagnostic to LSP diagnostic
-fn convert_single_diagnostic(content: &str, diag: &CompilerDiagnostic) -> Diagnostic {
+fn convert_single_diagnostic(
+    uri: &Uri,
+    content: &str,
+    diag: &CompilerDiagnostic,
+    language: Language,
+) -> Diagnostic {
     let severity = match diag.severity {
         Severity::Error => Some(DiagnosticSeverity::ERROR),
         Severity::Warning => Some(DiagnosticSeverity::WARNING),
@@ -27,34 +46,43 @@ fn convert_single_diagnostic(content: &str, diag: &CompilerDiagnostic) -> Diagno
         Range::new(Position::new(0, 0), Position::new(0, 0))
     };
 
+    // Duplicate definitions point back at the first definition
+    let related_information = diag.kind.first_span().map(|(start, end)| {
+        vec![DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), byte_span_to_lsp_range(content, start, end)),
+            message: get_lsp_text("first_defined_here", language).to_string(),
+        }]
+    });
+
     Diagnostic {
         range,
         severity,
-        code: None,
+        code: Some(NumberOrString::String(diag.code().to_string())),
         code_description: None,
         source: Some("mortar".to_string()),
         message: diag.message.clone(),
-        related_information: None,
+        related_information,
         tags: None,
         data: None,
     }
 }
 
 /// Convert byte positions to LSP Range
-fn byte_span_to_lsp_range(content: &str, start: usize, end: usize) -> Range {
+pub(crate) fn byte_span_to_lsp_range(content: &str, start: usize, end: usize) -> Range {
     let start_pos = byte_offset_to_position(content, start);
     let end_pos = byte_offset_to_position(content, end);
     Range::new(start_pos, end_pos)
 }
 
 /// Convert byte offset to LSP Position
-fn byte_offset_to_position(content: &str, offset: usize) -> Position {
+///
+/// LSP columns count UTF-16 code units, so characters outside the BMP take two.
+pub(crate) fn byte_offset_to_position(content: &str, offset: usize) -> Position {
     let mut line = 0u32;
     let mut character = 0u32;
-    let mut current_offset = 0;
 
-    for ch in content.chars() {
-        if current_offset >= offset {
+    for (index, ch) in content.char_indices() {
+        if index >= offset {
             break;
         }
 
@@ -62,44 +90,147 @@ fn byte_offset_to_position(content: &str, offset: usize) -> Position {
             line += 1;
             character = 0;
         } else {
-            character += 1;
+            character += ch.len_utf16() as u32;
         }
-
-        current_offset += ch.len_utf8();
     }
 
     Position::new(line, character)
 }
 
+/// Convert LSP Position to byte offset, clamped to the end of its line
+pub(crate) fn position_to_byte_offset(content: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match content[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return content.len(),
+        }
+    }
+
+    let line = content[line_start..].split('\n').next().unwrap_or_default();
+    line_start + utf16_column_to_byte(line, position.character)
+}
+
+/// Byte index in `line` of a UTF-16 column, clamped to the line length.
+pub(crate) fn utf16_column_to_byte(line: &str, column: u32) -> usize {
+    let mut units = 0u32;
+    for (index, ch) in line.char_indices() {
+        if units >= column {
+            return index;
+        }
+        units += ch.len_utf16() as u32;
+    }
+    line.len()
+}
+
 /// Parse content and get diagnostics with language support
 pub fn parse_with_diagnostics(
+    uri: &Uri,
     content: &str,
-    file_name: String,
     language: Language,
 ) -> (Vec<Diagnostic>, Option<mortar_compiler::Program>) {
-    let (parse_result, diagnostics) = ParseHandler::parse_source_code_with_diagnostics_and_language(
-        content, file_name, false, // verbose_lexer
+    let (config, mut lsp_diagnostics) = checked_project_config(uri, language);
+    // Parsing with diagnostics already runs semantic analysis on success
+    let (parse_result, diagnostics) = ParseHandler::parse_source_code_with_config(
+        content,
+        uri.path().to_string(),
+        false, // verbose_lexer
         language,
+        &config,
     );
 
-    let mut lsp_diagnostics = convert_diagnostics_to_lsp(content, diagnostics.get_diagnostics());
+    lsp_diagnostics.extend(convert_diagnostics_to_lsp(
+        uri,
+        content,
+        diagnostics.get_diagnostics(),
+        language,
+    ));
 
-    // Analyze the program if parsing succeeded
-    let program = if let Ok(ref program) = parse_result {
-        // Create a new diagnostics collector for analysis
-        let mut analysis_diagnostics =
-            DiagnosticCollector::new_with_language("analysis".to_string(), language);
-        analysis_diagnostics.analyze_program(program);
+    (lsp_diagnostics, parse_result.ok())
+}
 
-        // Add analysis diagnostics to the result
-        let analysis_lsp_diagnostics =
-            convert_diagnostics_to_lsp(content, analysis_diagnostics.get_diagnostics());
-        lsp_diagnostics.extend(analysis_lsp_diagnostics);
+/// Diagnostics for a document kept parsed between edits, as `parse_with_diagnostics` gives them
+pub fn diagnose_parse(uri: &Uri, parse: &IncrementalParse, language: Language) -> Vec<Diagnostic> {
+    let (config, mut lsp_diagnostics) = checked_project_config(uri, language);
+    let diagnostics =
+        ParseHandler::diagnose_incremental(parse, uri.path().to_string(), language, &config);
+    lsp_diagnostics.extend(convert_diagnostics_to_lsp(
+        uri,
+        parse.source(),
+        diagnostics.get_diagnostics(),
+        language,
+    ));
+    lsp_diagnostics
+}
 
-        Some(program.clone())
-    } else {
-        None
+/// Each `mortar.toml` read so far, with the modification time it was read at
+/// and its config, or why it failed to load
+type ConfigCache = HashMap<PathBuf, (Option<SystemTime>, Result<ProjectConfig, String>)>;
+
+static PROJECT_CONFIGS: OnceLock<Mutex<ConfigCache>> = OnceLock::new();
+
+/// The config of the nearest mortar.toml, or its path and why it failed to load
+///
+/// Each file is only read again once it has changed on disk.
+fn load_project_config(uri: &Uri) -> Result<ProjectConfig, (PathBuf, String)> {
+    let Some(config_path) = uri
+        .to_file_path()
+        .and_then(|path| ProjectConfig::find(path.parent()?))
+    else {
+        return Ok(ProjectConfig::default());
     };
+    let modified = fs::metadata(&config_path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    let mut configs = PROJECT_CONFIGS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some((read_at, loaded)) = configs.get(&config_path)
+        && *read_at == modified
+    {
+        return loaded.clone().map_err(|error| (config_path, error));
+    }
 
-    (lsp_diagnostics, program)
+    let loaded = ProjectConfig::load(&config_path).map_err(|error| {
+        warn!("Failed to load {}: {}", config_path.display(), error);
+        error.to_string()
+    });
+    configs.insert(config_path.clone(), (modified, loaded.clone()));
+    loaded.map_err(|error| (config_path, error))
+}
+
+/// Lint levels come from the nearest mortar.toml, if there is one
+///
+/// A mortar.toml that fails to load gives the default levels here; the
+/// diagnostics of the document report it.
+pub(crate) fn project_config(uri: &Uri) -> ProjectConfig {
+    load_project_config(uri).unwrap_or_default()
+}
+
+/// The project config of `uri`, with an error diagnostic on the document
+/// pointing at its mortar.toml when that fails to load
+fn checked_project_config(uri: &Uri, language: Language) -> (ProjectConfig, Vec<Diagnostic>) {
+    match load_project_config(uri) {
+        Ok(config) => (config, Vec::new()),
+        Err((config_path, error)) => {
+            let message = format!("{} {}", get_lsp_text("config_load_failed", language), error);
+            let related_information = Uri::from_file_path(&config_path).map(|config_uri| {
+                vec![DiagnosticRelatedInformation {
+                    location: Location::new(config_uri, Range::default()),
+                    message: message.clone(),
+                }]
+            });
+            let diagnostic = Diagnostic {
+                range: Range::default(),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("mortar".to_string()),
+                message,
+                related_information,
+                ..Diagnostic::default()
+            };
+            (ProjectConfig::default(), vec![diagnostic])
+        }
+    }
 }
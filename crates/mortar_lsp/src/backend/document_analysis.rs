@@ -1,47 +1,65 @@
+use dashmap::mapref::entry::Entry;
+use mortar_compiler::IncrementalParse;
 use tokio;
 use tower_lsp_server::lsp_types::*;
 
 use crate::analysis::analyze_program;
-use crate::backend::{Backend, parse_with_diagnostics};
+use crate::backend::{Backend, IndexedDocument, diagnose_parse};
 
 impl Backend {
-    /// Analyze document content and generate diagnostic information with language support
-    pub async fn analyze_document(&self, uri: &Uri, content: &str) {
+    /// Analyze the open text of a document and publish its diagnostics, with language support
+    pub async fn analyze_document(&self, uri: &Uri) {
         let language = self.get_language().await;
-        let file_name = uri.path().to_string();
-        let content_owned = content.to_string();
+        let uri_owned = uri.clone();
+        let documents = self.documents.clone();
+        let edits = self.edits.clone();
+        let parses = self.parses.clone();
 
-        // Parse and analyze with diagnostics
-        let (diagnostics, program_opt) = tokio::task::spawn_blocking(move || {
-            parse_with_diagnostics(&content_owned, file_name, language)
+        // Reparse only the items the edits since the last analysis touched
+        let analysis = tokio::task::spawn_blocking(move || {
+            // The parse stays locked until the edits are applied, so that the
+            // edits taken here are always relative to its source
+            let entry = parses.entry(uri_owned.clone());
+            let (content, edit) = {
+                let document = documents.get(&uri_owned)?;
+                let edit = edits.remove(&uri_owned).map(|(_, edit)| edit);
+                (document.0.to_string(), edit)
+            };
+            let parse = match entry {
+                Entry::Occupied(entry) => {
+                    let mut parse = entry.into_ref();
+                    match edit {
+                        Some(edit) => {
+                            let changed = edit.range_in(parse.source());
+                            parse.edit(&content, changed)
+                        }
+                        None => parse.update(&content),
+                    };
+                    parse
+                }
+                Entry::Vacant(entry) => entry.insert(IncrementalParse::new(&content)),
+            };
+            let diagnostics = diagnose_parse(&uri_owned, &parse, language);
+            let table = analyze_program(parse.program());
+            let document = IndexedDocument::parsed(content, parse.program().clone());
+            Some((diagnostics, table, document))
         })
-        .await
-        .unwrap_or_else(|_| {
-            // If the task panicked, create a simple error diagnostic
-            (
-                vec![Diagnostic::new_simple(
-                    Range::new(Position::new(0, 0), Position::new(0, 0)),
-                    crate::backend::i18n::get_lsp_text("analysis_task_failed", language)
-                        .to_string(),
-                )],
-                None,
-            )
-        });
+        .await;
 
-        // Update symbol table if program was parsed successfully
-        if let Some(program) = program_opt
-            && let Ok(symbol_table) =
-                tokio::task::spawn_blocking(move || analyze_program(&program)).await
-        {
-            match symbol_table {
-                Ok(table) => {
-                    self.symbol_tables.insert(uri.clone(), table);
-                }
-                Err(_) => {
-                    // Symbol analysis failed, but we already have parse diagnostics
-                }
+        let diagnostics = match analysis {
+            // Closed while waiting for the analysis
+            Ok(None) => return,
+            Ok(Some((diagnostics, table, document))) => {
+                self.symbol_tables.insert(uri.clone(), table);
+                self.set_index(uri, document);
+                diagnostics
             }
-        }
+            // If the task panicked, create a simple error diagnostic
+            Err(_) => vec![Diagnostic::new_simple(
+                Range::new(Position::new(0, 0), Position::new(0, 0)),
+                crate::backend::i18n::get_lsp_text("analysis_task_failed", language).to_string(),
+            )],
+        };
 
         // Store and publish diagnostics
         self.diagnostics.insert(uri.clone(), diagnostics.clone());
@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use mortar_compiler::ast::*;
+use mortar_compiler::printer::type_name;
+use mortar_compiler::{ParseHandler, print_top_level};
+use tower_lsp_server::lsp_types::*;
+
+use crate::backend::IndexedDocument;
+use crate::backend::diagnostics::byte_span_to_lsp_range;
+
+/// The outline of `content`: every top-level item, with enum variants, and the
+/// choice blocks and `if` sections of nodes, as children.
+///
+/// Uses whatever the parser recovers, so the outline survives syntax errors.
+pub fn document_symbols(content: &str) -> Vec<DocumentSymbol> {
+    let (result, _diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(content, String::new(), false);
+    match result {
+        Ok(program) => program_symbols(&program, content),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn program_symbols(program: &Program, content: &str) -> Vec<DocumentSymbol> {
+    let outline = Outline { content };
+    program
+        .body
+        .iter()
+        .map(|item| outline.top_level(item))
+        .collect()
+}
+
+/// Top-level symbols and enum variants of every document whose name matches `query`.
+///
+/// The query matches when its characters appear in order in the name, ignoring case.
+/// Documents are outlined from the programs they were indexed from.
+pub fn workspace_symbols(
+    query: &str,
+    documents: &[(Uri, Arc<IndexedDocument>)],
+) -> Vec<WorkspaceSymbol> {
+    let mut symbols = Vec::new();
+    for (uri, document) in documents {
+        let Some(program) = &document.program else {
+            continue;
+        };
+        for symbol in program_symbols(program, &document.content) {
+            let variants = match symbol.kind {
+                SymbolKind::ENUM => symbol.children.clone().unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            for variant in variants {
+                if matches_query(&variant.name, query) {
+                    symbols.push(workspace_symbol(uri, variant, Some(symbol.name.clone())));
+                }
+            }
+            if matches_query(&symbol.name, query) {
+                symbols.push(workspace_symbol(uri, symbol, None));
+            }
+        }
+    }
+    symbols
+}
+
+fn workspace_symbol(
+    uri: &Uri,
+    symbol: DocumentSymbol,
+    container_name: Option<String>,
+) -> WorkspaceSymbol {
+    WorkspaceSymbol {
+        name: symbol.name,
+        kind: symbol.kind,
+        tags: None,
+        container_name,
+        location: OneOf::Left(Location::new(uri.clone(), symbol.selection_range)),
+        data: None,
+    }
+}
+
+fn matches_query(name: &str, query: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|wanted| name.any(|c| c == wanted))
+}
+
+struct Outline<'a> {
+    content: &'a str,
+}
+
+impl Outline<'_> {
+    fn symbol(
+        &self,
+        name: String,
+        detail: Option<String>,
+        kind: SymbolKind,
+        span: Span,
+        name_span: Option<Span>,
+        children: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        let (start, end) = span;
+        let (name_start, name_end) = name_span.unwrap_or(span);
+        #[allow(deprecated)]
+        DocumentSymbol {
+            name,
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: byte_span_to_lsp_range(self.content, start, end),
+            selection_range: byte_span_to_lsp_range(self.content, name_start, name_end),
+            children: (!children.is_empty()).then_some(children),
+        }
+    }
+
+    fn top_level(&self, item: &TopLevel) -> DocumentSymbol {
+        match item {
+            TopLevel::NodeDef(node) => self.symbol(
+                node.name.clone(),
+                None,
+                SymbolKind::CLASS,
+                node.span,
+                node.name_span,
+                self.sections(&node.body),
+            ),
+            TopLevel::FunctionDecl(function) => self.symbol(
+                function.name.clone(),
+                Some(print_top_level(item)),
+                SymbolKind::FUNCTION,
+                function.span,
+                function.name_span,
+                Vec::new(),
+            ),
+            TopLevel::VarDecl(var) => self.symbol(
+                var.name.clone(),
+                Some(type_name(&var.type_name).to_string()),
+                SymbolKind::VARIABLE,
+                var.span,
+                var.name_span,
+                Vec::new(),
+            ),
+            TopLevel::ConstDecl(constant) => self.symbol(
+                constant.name.clone(),
+                Some(type_name(&constant.type_name).to_string()),
+                SymbolKind::CONSTANT,
+                constant.span,
+                constant.name_span,
+                Vec::new(),
+            ),
+            TopLevel::EnumDef(enum_def) => {
+                let variants = enum_def
+                    .variants
+                    .iter()
+                    .zip(&enum_def.variant_spans)
+                    .map(|(variant, span)| {
+                        self.symbol(
+                            variant.clone(),
+                            None,
+                            SymbolKind::ENUM_MEMBER,
+                            *span,
+                            None,
+                            Vec::new(),
+                        )
+                    })
+                    .collect();
+                self.symbol(
+                    enum_def.name.clone(),
+                    None,
+                    SymbolKind::ENUM,
+                    enum_def.span,
+                    enum_def.name_span,
+                    variants,
+                )
+            }
+            TopLevel::EventDef(event) => self.symbol(
+                event.name.clone(),
+                Some(self.text(event.action.span).to_string()),
+                SymbolKind::EVENT,
+                event.span,
+                event.name_span,
+                Vec::new(),
+            ),
+            TopLevel::TimelineDef(timeline) => self.symbol(
+                timeline.name.clone(),
+                Some("timeline".to_string()),
+                SymbolKind::EVENT,
+                timeline.span,
+                timeline.name_span,
+                Vec::new(),
+            ),
+        }
+    }
+
+    /// The choice blocks and `if` sections among `body`, with the ones nested in them.
+    fn sections(&self, body: &[NodeStmt]) -> Vec<DocumentSymbol> {
+        body.iter()
+            .filter_map(|stmt| match stmt {
+                NodeStmt::Choice(items, span) => Some(self.symbol(
+                    "choice".to_string(),
+                    None,
+                    SymbolKind::ARRAY,
+                    *span,
+                    None,
+                    self.options(items),
+                )),
+                NodeStmt::IfElse(if_else) => {
+                    let mut children = self.sections(&if_else.then_body);
+                    if let Some(else_body) = &if_else.else_body {
+                        children.extend(self.sections(else_body));
+                    }
+                    // The header, up to the opening brace, names the section
+                    let header = self.text(if_else.span).split('{').next().unwrap_or("if");
+                    Some(self.symbol(
+                        header.trim().to_string(),
+                        None,
+                        SymbolKind::NAMESPACE,
+                        if_else.span,
+                        None,
+                        children,
+                    ))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn options(&self, items: &[ChoiceItem]) -> Vec<DocumentSymbol> {
+        items
+            .iter()
+            .map(|item| {
+                let (detail, children) = match &item.target {
+                    ChoiceDest::Identifier(target, _) => (Some(format!("-> {}", target)), vec![]),
+                    ChoiceDest::Return(_) => (Some("-> return".to_string()), vec![]),
+                    ChoiceDest::Break(_) => (Some("-> break".to_string()), vec![]),
+                    ChoiceDest::NestedChoices(nested, _) => (None, self.options(nested)),
+                };
+                self.symbol(
+                    format!("\"{}\"", item.text),
+                    detail,
+                    SymbolKind::ENUM_MEMBER,
+                    item.span,
+                    None,
+                    children,
+                )
+            })
+            .collect()
+    }
+
+    fn text(&self, span: Span) -> &str {
+        self.content.get(span.0..span.1).unwrap_or_default()
+    }
+}
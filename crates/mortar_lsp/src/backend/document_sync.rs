@@ -0,0 +1,79 @@
+use std::ops::Range;
+
+use ropey::Rope;
+use tower_lsp_server::lsp_types::*;
+
+/// The part of a document changed since it was last parsed, kept as the number
+/// of bytes no change touched at either end, which stays true as more changes come in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingEdit {
+    pub unchanged_start: usize,
+    pub unchanged_end: usize,
+}
+
+impl PendingEdit {
+    /// This edit followed by `next`.
+    pub fn then(self, next: PendingEdit) -> PendingEdit {
+        PendingEdit {
+            unchanged_start: self.unchanged_start.min(next.unchanged_start),
+            unchanged_end: self.unchanged_end.min(next.unchanged_end),
+        }
+    }
+
+    /// The bytes of `old`, the text last parsed, that the changes replaced.
+    pub fn range_in(&self, old: &str) -> Range<usize> {
+        let start = self.unchanged_start.min(old.len());
+        let end = old.len().saturating_sub(self.unchanged_end).max(start);
+        start..end
+    }
+}
+
+/// Apply one change from `textDocument/didChange` to the text of a document.
+///
+/// A change without a range replaces the whole text; a ranged one replaces
+/// just that range, with positions clamped to the document as the protocol asks.
+/// Returns the part of the text the change touched.
+pub fn apply_content_change(
+    rope: &mut Rope,
+    change: &TextDocumentContentChangeEvent,
+) -> PendingEdit {
+    let Some(range) = change.range else {
+        *rope = Rope::from_str(&change.text);
+        return PendingEdit {
+            unchanged_start: 0,
+            unchanged_end: 0,
+        };
+    };
+
+    let start = position_to_char(rope, range.start);
+    let end = position_to_char(rope, range.end).max(start);
+    let edit = PendingEdit {
+        unchanged_start: rope.char_to_byte(start),
+        unchanged_end: rope.len_bytes() - rope.char_to_byte(end),
+    };
+    rope.remove(start..end);
+    rope.insert(start, &change.text);
+    edit
+}
+
+/// Char index in `rope` of a position in UTF-16 code units.
+///
+/// Lines past the end map to the end of the text, and columns past the end
+/// of a line to the end of that line.
+fn position_to_char(rope: &Rope, position: Position) -> usize {
+    let line_index = position.line as usize;
+    if line_index >= rope.len_lines() {
+        return rope.len_chars();
+    }
+
+    let line = rope.line(line_index);
+    let mut length = line.len_chars();
+    while length > 0 && matches!(line.char(length - 1), '\n' | '\r') {
+        length -= 1;
+    }
+
+    let start = rope.line_to_char(line_index);
+    let end = start + length;
+    let unit = rope.char_to_utf16_cu(start) + position.character as usize;
+    rope.utf16_cu_to_char(unit.min(rope.char_to_utf16_cu(end)))
+}
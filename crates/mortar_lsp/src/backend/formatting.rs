@@ -0,0 +1,51 @@
+use mortar_compiler::{FormatOptions, format_range, format_source};
+use tower_lsp_server::lsp_types::*;
+
+use crate::backend::diagnostics::{
+    byte_offset_to_position, byte_span_to_lsp_range, position_to_byte_offset,
+};
+
+fn format_options(options: &FormattingOptions) -> FormatOptions {
+    FormatOptions {
+        indent_width: options.tab_size as usize,
+        use_tabs: !options.insert_spaces,
+    }
+}
+
+/// A single edit replacing the whole document, or no edits when it is already formatted.
+///
+/// Documents with syntax errors come back unchanged, so they get no edits either.
+pub fn formatting_edits(content: &str, options: &FormattingOptions) -> Vec<TextEdit> {
+    let formatted = format_source(content, format_options(options));
+    if formatted == content {
+        return Vec::new();
+    }
+
+    let end = byte_offset_to_position(content, content.len());
+    vec![TextEdit::new(
+        Range::new(Position::new(0, 0), end),
+        formatted,
+    )]
+}
+
+/// An edit that formats the top-level items overlapping `range`.
+pub fn range_formatting_edits(
+    content: &str,
+    range: Range,
+    options: &FormattingOptions,
+) -> Option<Vec<TextEdit>> {
+    let span = (
+        position_to_byte_offset(content, range.start),
+        position_to_byte_offset(content, range.end),
+    );
+
+    let ((start, end), text) = format_range(content, span, format_options(options))?;
+    if content[start..end] == text {
+        return Some(Vec::new());
+    }
+
+    Some(vec![TextEdit::new(
+        byte_span_to_lsp_range(content, start, end),
+        text,
+    )])
+}
@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use mortar_compiler::ast::*;
+use mortar_compiler::visit::{self, Visit};
+use mortar_compiler::{Language, Token, print_node_stmt, print_top_level, tokenize};
+use tower_lsp_server::lsp_types::*;
+
+use crate::backend::IndexedDocument;
+use crate::backend::diagnostics::{
+    byte_offset_to_position, byte_span_to_lsp_range, position_to_byte_offset,
+};
+use crate::backend::i18n::get_lsp_text;
+use crate::symbols::{Role, Symbol, SymbolIndex, SymbolKind};
+
+/// A parsed document a hover draws from.
+struct Parsed<'a> {
+    uri: &'a Uri,
+    content: &'a str,
+    program: &'a Program,
+    index: &'a SymbolIndex,
+}
+
+impl<'a> Parsed<'a> {
+    fn new(uri: &'a Uri, document: &'a IndexedDocument) -> Option<Self> {
+        Some(Self {
+            uri,
+            content: &document.content,
+            program: document.program.as_ref()?,
+            index: document.index.as_ref()?,
+        })
+    }
+
+    /// `file.mortar:line` for a byte offset of this document.
+    fn location(&self, offset: usize) -> String {
+        let line = byte_offset_to_position(self.content, offset).line + 1;
+        let path = self.uri.path();
+        let file = path.as_str().rsplit('/').next().unwrap_or_default();
+        format!("{}:{}", file, line)
+    }
+
+    /// The node whose body contains `offset`.
+    fn node_at(&self, offset: usize) -> Option<&str> {
+        self.program.body.iter().find_map(|item| match item {
+            TopLevel::NodeDef(node) if node.span.0 <= offset && offset < node.span.1 => {
+                Some(node.name.as_str())
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Markdown describing the symbol at `position`: its declaration, the doc
+/// comment above it, and where it is assigned or reached from.
+///
+/// Names defined in `others` are described from there, using the programs
+/// they were indexed from; names defined nowhere get no hover.
+pub fn hover_info(
+    uri: &Uri,
+    content: &str,
+    position: Position,
+    others: &[(Uri, Arc<IndexedDocument>)],
+    language: Language,
+) -> Option<Hover> {
+    let document = IndexedDocument::new(content.to_string());
+    let current = Parsed::new(uri, &document)?;
+    let occurrence = current
+        .index
+        .occurrence_at(position_to_byte_offset(content, position))?
+        .clone();
+    let symbol = &occurrence.symbol;
+
+    let mut documents = vec![current];
+    if !symbol.is_file_local() {
+        documents.extend(
+            others
+                .iter()
+                .filter_map(|(uri, document)| Parsed::new(uri, document)),
+        );
+    }
+
+    let (defining, definition) = documents
+        .iter()
+        .find_map(|document| Some((document, document.index.definition(symbol)?)))?;
+    let declaration = Declaration::find(defining.program, definition.span)?;
+
+    let mut value = format!("```mortar\n{}\n```", declaration.signature());
+    if let Some(doc) = doc_comment(defining.content, declaration.doc_start()) {
+        value.push_str("\n\n");
+        value.push_str(&doc);
+    }
+
+    match symbol.kind {
+        SymbolKind::Variable => {
+            let writes = assignments(symbol, &documents);
+            if !writes.is_empty() {
+                value.push_str(&format!(
+                    "\n\n**{}**\n{}",
+                    get_lsp_text("hover_assigned_in", language),
+                    writes.join("\n")
+                ));
+            }
+        }
+        SymbolKind::Node => {
+            let incoming = incoming(&symbol.name, &documents, language);
+            if incoming.is_empty() {
+                value.push_str(&format!(
+                    "\n\n*{}*",
+                    get_lsp_text("hover_no_incoming", language)
+                ));
+            } else {
+                value.push_str(&format!(
+                    "\n\n**{}**\n{}",
+                    get_lsp_text("hover_incoming", language),
+                    incoming.join("\n")
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    let (start, end) = occurrence.span;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(byte_span_to_lsp_range(content, start, end)),
+    })
+}
+
+/// The declaration a definition occurrence names.
+enum Declaration<'a> {
+    Node(&'a NodeDef),
+    TopLevel(&'a TopLevel),
+    /// A variable declared inside a node, such as a node-local branch.
+    Local(&'a VarDecl),
+    Variant(&'a EnumDef, usize),
+}
+
+impl<'a> Declaration<'a> {
+    fn find(program: &'a Program, name_span: Span) -> Option<Self> {
+        for item in &program.body {
+            let top_level = |span: Option<Span>| {
+                (span == Some(name_span)).then_some(Declaration::TopLevel(item))
+            };
+            let found = match item {
+                TopLevel::NodeDef(node) if node.name_span == Some(name_span) => {
+                    Some(Declaration::Node(node))
+                }
+                TopLevel::NodeDef(node) => node.body.iter().find_map(|stmt| match stmt {
+                    NodeStmt::VarDecl(var) if var.name_span == Some(name_span) => {
+                        Some(Declaration::Local(var))
+                    }
+                    _ => None,
+                }),
+                TopLevel::EnumDef(enum_def) => enum_def
+                    .variant_spans
+                    .iter()
+                    .position(|span| *span == name_span)
+                    .map(|variant| Declaration::Variant(enum_def, variant))
+                    .or_else(|| top_level(enum_def.name_span)),
+                TopLevel::FunctionDecl(function) => top_level(function.name_span),
+                TopLevel::VarDecl(var) => top_level(var.name_span),
+                TopLevel::ConstDecl(constant) => top_level(constant.name_span),
+                TopLevel::EventDef(event) => top_level(event.name_span),
+                TopLevel::TimelineDef(timeline) => top_level(timeline.name_span),
+            };
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    /// The declaration as Mortar source; nodes only show their header.
+    fn signature(&self) -> String {
+        match self {
+            Declaration::Node(node) => format!("node {}", node.name),
+            Declaration::TopLevel(item) => print_top_level(item),
+            Declaration::Local(var) => print_node_stmt(&NodeStmt::VarDecl((*var).clone())),
+            Declaration::Variant(enum_def, variant) => format!(
+                "{}.{}\n\n{}",
+                enum_def.name,
+                enum_def.variants[*variant],
+                print_top_level(&TopLevel::EnumDef((*enum_def).clone()))
+            ),
+        }
+    }
+
+    /// Where a doc comment for the declaration would end.
+    fn doc_start(&self) -> usize {
+        match self {
+            Declaration::Node(node) => node.span.0,
+            Declaration::TopLevel(item) => item.span().0,
+            Declaration::Local(var) => var.span.0,
+            Declaration::Variant(enum_def, variant) => enum_def.variant_spans[*variant].0,
+        }
+    }
+}
+
+/// The comments directly above `start`, without their markers.
+///
+/// Comments separated from the declaration by a blank line, or trailing
+/// another line of code, are not part of it.
+fn doc_comment(source: &str, start: usize) -> Option<String> {
+    let tokens = tokenize(&source[..start]);
+    let mut lines = Vec::new();
+    let mut next = start;
+
+    for (i, token) in tokens.iter().enumerate().rev() {
+        if !matches!(
+            token.token,
+            Token::SingleLineComment(_) | Token::MultiLineComment(_)
+        ) {
+            break;
+        }
+        if source[token.end..next].matches('\n').count() > 1 {
+            break;
+        }
+        let previous_end = if i == 0 { 0 } else { tokens[i - 1].end };
+        if i > 0 && !source[previous_end..token.start].contains('\n') {
+            break;
+        }
+
+        let text = match token.token {
+            Token::MultiLineComment(_) => token.text[2..token.text.len() - 2]
+                .lines()
+                .map(|line| line.trim().trim_start_matches('*').trim())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => token.text.trim_start_matches('/').trim().to_string(),
+        };
+        lines.push(text);
+        next = token.start;
+    }
+
+    lines.reverse();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// A line per assignment to `symbol`, naming the node it happens in.
+fn assignments(symbol: &Symbol, documents: &[Parsed]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for document in documents {
+        for occurrence in document.index.occurrences_of(symbol) {
+            if occurrence.role != Role::Write {
+                continue;
+            }
+            let offset = occurrence.span.0;
+            let node = document.node_at(offset).unwrap_or_default();
+            lines.push(format!("- `{}` ({})", node, document.location(offset)));
+        }
+    }
+    lines
+}
+
+/// A line per jump or choice leading to the node `target`.
+fn incoming(target: &str, documents: &[Parsed], language: Language) -> Vec<String> {
+    let mut lines = Vec::new();
+    for document in documents {
+        let mut collector = Incoming {
+            target,
+            current_node: None,
+            found: Vec::new(),
+        };
+        collector.visit_program(document.program);
+
+        for (from, choice, offset) in collector.found {
+            let via = match choice {
+                Some(text) => format!("{} \"{}\"", get_lsp_text("hover_choice", language), text),
+                None => get_lsp_text("hover_jump", language).to_string(),
+            };
+            lines.push(format!(
+                "- `{}`: {} ({})",
+                from,
+                via,
+                document.location(offset)
+            ));
+        }
+    }
+    lines
+}
+
+/// Collects the jumps and choices that lead to one node.
+struct Incoming<'a> {
+    target: &'a str,
+    current_node: Option<String>,
+    /// The node each comes from, the choice text for choices, and where it is written.
+    found: Vec<(String, Option<String>, usize)>,
+}
+
+impl Visit for Incoming<'_> {
+    fn visit_node_def(&mut self, node: &NodeDef) {
+        self.current_node = Some(node.name.clone());
+        visit::walk_node_def(self, node);
+        self.current_node = None;
+    }
+
+    fn visit_node_jump(&mut self, jump: &NodeJump) {
+        if let NodeJump::Identifier(name, span) = jump
+            && name == self.target
+            && let Some(from) = &self.current_node
+        {
+            self.found.push((from.clone(), None, span.0));
+        }
+    }
+
+    fn visit_choice_item(&mut self, item: &ChoiceItem) {
+        if let ChoiceDest::Identifier(name, span) = &item.target
+            && name == self.target
+            && let Some(from) = &self.current_node
+        {
+            self.found
+                .push((from.clone(), Some(item.text.clone()), span.0));
+        }
+        visit::walk_choice_item(self, item);
+    }
+}
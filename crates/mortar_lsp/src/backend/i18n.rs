@@ -112,6 +112,15 @@ fn init_lsp_texts() -> &'static HashMap<&'static str, HashMap<Language, &'static
         );
 
         // Error messages
+        texts.insert(
+            "config_load_failed",
+            [
+                (Language::English, "Failed to load mortar.toml:"),
+                (Language::Chinese, "加载 mortar.toml 失败:"),
+            ]
+            .into(),
+        );
+
         texts.insert(
             "analysis_task_failed",
             [
@@ -121,6 +130,15 @@ fn init_lsp_texts() -> &'static HashMap<&'static str, HashMap<Language, &'static
             .into(),
         );
 
+        texts.insert(
+            "first_defined_here",
+            [
+                (Language::English, "first defined here"),
+                (Language::Chinese, "首次定义于此"),
+            ]
+            .into(),
+        );
+
         texts.insert(
             "unable_set_subscriber",
             [
@@ -161,6 +179,166 @@ fn init_lsp_texts() -> &'static HashMap<&'static str, HashMap<Language, &'static
             .into(),
         );
 
+        // Rename refusals
+        texts.insert(
+            "rename_no_symbol",
+            [
+                (Language::English, "No symbol to rename at this position"),
+                (Language::Chinese, "此位置没有可重命名的符号"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "rename_undefined",
+            [
+                (
+                    Language::English,
+                    "Only symbols defined in the workspace can be renamed",
+                ),
+                (Language::Chinese, "只能重命名在工作区中定义的符号"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "rename_invalid_name",
+            [
+                (Language::English, "Not a valid Mortar name"),
+                (Language::Chinese, "不是有效的 Mortar 名称"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "rename_name_taken",
+            [
+                (Language::English, "A symbol with this name already exists"),
+                (Language::Chinese, "已存在同名符号"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "rename_node_not_pascal_case",
+            [
+                (Language::English, "Node names should be PascalCase"),
+                (Language::Chinese, "节点名称应使用大驼峰命名 (PascalCase)"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "rename_function_not_snake_case",
+            [
+                (Language::English, "Function names should be snake_case"),
+                (Language::Chinese, "函数名称应使用蛇形命名 (snake_case)"),
+            ]
+            .into(),
+        );
+
+        // Hover
+        texts.insert(
+            "hover_assigned_in",
+            [
+                (Language::English, "Assigned in"),
+                (Language::Chinese, "赋值位置"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "hover_incoming",
+            [
+                (Language::English, "Reached from"),
+                (Language::Chinese, "来源"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "hover_no_incoming",
+            [
+                (Language::English, "No jumps or choices lead here"),
+                (Language::Chinese, "没有跳转或选项通向此节点"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "hover_jump",
+            [(Language::English, "jump"), (Language::Chinese, "跳转")].into(),
+        );
+
+        texts.insert(
+            "hover_choice",
+            [(Language::English, "choice"), (Language::Chinese, "选项")].into(),
+        );
+
+        // Signature help
+        texts.insert(
+            "signature_run_arguments",
+            [
+                (Language::English, "Arguments for the event's action"),
+                (Language::Chinese, "传给事件动作的参数"),
+            ]
+            .into(),
+        );
+
+        // Code actions
+        texts.insert(
+            "code_action_rename",
+            [
+                (Language::English, "Rename to"),
+                (Language::Chinese, "重命名为"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "code_action_declare_function",
+            [
+                (Language::English, "Declare function"),
+                (Language::Chinese, "声明函数"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "code_action_create_node",
+            [
+                (Language::English, "Create node"),
+                (Language::Chinese, "创建节点"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "code_action_remove_unused",
+            [
+                (Language::English, "Remove unused"),
+                (Language::Chinese, "删除未使用的"),
+            ]
+            .into(),
+        );
+
+        texts.insert(
+            "code_action_add_branch_cases",
+            [
+                (Language::English, "Add missing cases"),
+                (Language::Chinese, "添加缺少的分支"),
+            ]
+            .into(),
+        );
+        texts.insert(
+            "inlay_hint_event_index",
+            [
+                (Language::English, "Event at index"),
+                (Language::Chinese, "事件索引"),
+            ]
+            .into(),
+        );
+
         texts
     })
 }
@@ -0,0 +1,300 @@
+use mortar_compiler::ast::*;
+use mortar_compiler::token::{string_chars, unescape_chars};
+use mortar_compiler::visit::{self, Visit};
+use mortar_compiler::{EventIndexOptions, Language, ParseHandler, TextLengthUnit, Token, tokenize};
+use tower_lsp_server::lsp_types::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::backend::diagnostics::{byte_offset_to_position, project_config};
+use crate::backend::i18n::get_lsp_text;
+
+/// Hints within `range`: where each `with events` index falls in the text before it,
+/// the parameter names of call arguments, and the value of constants used as index overrides.
+///
+/// Indices are counted in the length unit of the project's `mortar.toml`, as the diagnostics count them.
+pub fn inlay_hints(uri: &Uri, content: &str, range: Range, language: Language) -> Vec<InlayHint> {
+    let Ok(program) = ParseHandler::parse_source_code(content, false) else {
+        return Vec::new();
+    };
+
+    let mut hints = Hints {
+        content,
+        program: &program,
+        options: project_config(uri).event_index_options(),
+        language,
+        hints: Vec::new(),
+    };
+    hints.visit_program(&program);
+
+    hints
+        .hints
+        .into_iter()
+        .filter(|hint| range.start <= hint.position && hint.position <= range.end)
+        .collect()
+}
+
+struct Hints<'a> {
+    content: &'a str,
+    program: &'a Program,
+    options: EventIndexOptions,
+    language: Language,
+    hints: Vec<InlayHint>,
+}
+
+impl<'a> Hints<'a> {
+    fn function(&self, name: &str) -> Option<&'a FunctionDecl> {
+        self.program.body.iter().find_map(|item| match item {
+            TopLevel::FunctionDecl(function) if function.name == name => Some(function),
+            _ => None,
+        })
+    }
+
+    fn event(&self, name: &str) -> Option<&'a EventDef> {
+        self.program.body.iter().find_map(|item| match item {
+            TopLevel::EventDef(event) if event.name == name => Some(event),
+            _ => None,
+        })
+    }
+
+    fn constant(&self, name: &str) -> Option<f64> {
+        self.program.body.iter().find_map(|item| match item {
+            TopLevel::ConstDecl(constant) if constant.name == name => match constant.value {
+                VarValue::Number(value, _) => Some(value),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    fn hint(&self, offset: usize, label: String) -> InlayHint {
+        InlayHint {
+            position: byte_offset_to_position(self.content, offset),
+            label: InlayHintLabel::String(label),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        }
+    }
+
+    /// Mark the events of each `with events` in the text statement right before it.
+    fn body_markers(&mut self, body: &[NodeStmt]) {
+        let mut previous_text = None;
+
+        for stmt in body {
+            match stmt {
+                NodeStmt::Text(_, span) => {
+                    previous_text = self.literal_positions(*span);
+                }
+                NodeStmt::InterpolatedText(interpolated, _) => {
+                    previous_text = Some(self.interpolated_positions(interpolated));
+                }
+                NodeStmt::WithEvents(with_events) => {
+                    if let Some(positions) = previous_text.take() {
+                        for item in &with_events.events {
+                            self.item_markers(item, &positions);
+                        }
+                    }
+                }
+                NodeStmt::IfElse(if_else) => {
+                    previous_text = None;
+                    self.body_markers(&if_else.then_body);
+                    if let Some(else_body) = &if_else.else_body {
+                        self.body_markers(else_body);
+                    }
+                }
+                _ => {
+                    previous_text = None;
+                }
+            }
+        }
+    }
+
+    fn item_markers(&mut self, item: &WithEventItem, positions: &[usize]) {
+        match item {
+            WithEventItem::InlineEvent(event) => {
+                self.marker(event.index, &event.action.call.name, positions);
+            }
+            WithEventItem::EventRef(name, _) => {
+                if let Some(event) = self.event(name)
+                    && let Some(index) = event.index
+                {
+                    self.marker(index, &event.action.call.name, positions);
+                }
+            }
+            WithEventItem::EventRefWithOverride(name, _, index_override) => {
+                let index = match index_override {
+                    IndexOverride::Value(value, _) => Some(*value),
+                    IndexOverride::Variable(name, _) => self.constant(name),
+                };
+                if let Some(event) = self.event(name)
+                    && let Some(index) = index
+                {
+                    self.marker(index, &event.action.call.name, positions);
+                }
+            }
+            WithEventItem::EventList(items, _) => {
+                for item in items {
+                    self.item_markers(item, positions);
+                }
+            }
+        }
+    }
+
+    /// A hint named after the event's function at the `index`th unit of the text.
+    fn marker(&mut self, index: f64, function: &str, positions: &[usize]) {
+        if index < 0.0 || index.fract() != 0.0 {
+            return;
+        }
+        // Out of bounds indices already get a warning
+        let Some(&offset) = positions.get(index as usize) else {
+            return;
+        };
+
+        let tooltip = format!(
+            "{} {}",
+            get_lsp_text("inlay_hint_event_index", self.language),
+            index
+        );
+        self.hints.push(InlayHint {
+            tooltip: Some(InlayHintTooltip::String(tooltip)),
+            padding_left: Some(true),
+            padding_right: Some(true),
+            ..self.hint(offset, function.to_string())
+        });
+    }
+
+    /// The source offset of each unit of the string literal in `span`, then of its end.
+    fn literal_positions(&self, span: Span) -> Option<Vec<usize>> {
+        let source = self.content.get(span.0..span.1)?;
+        let literal = tokenize(source)
+            .into_iter()
+            .find(|token| matches!(token.token, Token::String(_)))?;
+
+        let base = span.0 + literal.start;
+        let chars: Vec<(usize, char)> = string_chars(literal.text)
+            .ok()?
+            .into_iter()
+            .map(|(at, ch)| (base + at, ch))
+            .collect();
+        // The value ends at the closing quotes
+        let quotes = if literal.text.trim_start_matches('r').starts_with("\"\"\"") {
+            3
+        } else {
+            1
+        };
+        Some(self.unit_positions(&chars, base + literal.text.len() - quotes))
+    }
+
+    /// Like [`Self::literal_positions`], with each interpolation counted as its assumed
+    /// length and placed at its opening brace.
+    fn interpolated_positions(&self, interpolated: &InterpolatedString) -> Vec<usize> {
+        let mut positions = Vec::new();
+
+        for part in &interpolated.parts {
+            match part {
+                StringPart::Text(_, span) => {
+                    let raw = &self.content[span.0..span.1];
+                    let chars: Vec<(usize, char)> = unescape_chars(raw)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(at, ch)| (span.0 + at, ch))
+                        .collect();
+                    let mut units = self.unit_positions(&chars, span.1);
+                    units.pop();
+                    positions.extend(units);
+                }
+                StringPart::Expression(_) | StringPart::Placeholder(_, _) => {
+                    let brace = part.span().0.saturating_sub(1);
+                    positions.extend(std::iter::repeat_n(
+                        brace,
+                        self.options.min_interpolation_length,
+                    ));
+                }
+            }
+        }
+
+        // The closing quote
+        positions.push(interpolated.span.1.saturating_sub(1));
+        positions
+    }
+
+    /// The offsets of the decoded `chars` narrowed to the configured unit, then `end`.
+    fn unit_positions(&self, chars: &[(usize, char)], end: usize) -> Vec<usize> {
+        let offsets = chars.iter().map(|&(at, _)| at);
+        match self.options.length_unit {
+            TextLengthUnit::Chars => offsets.chain([end]).collect(),
+            TextLengthUnit::Graphemes => {
+                let text: String = chars.iter().map(|&(_, ch)| ch).collect();
+                let char_starts: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+                text.grapheme_indices(true)
+                    .filter_map(|(start, _)| char_starts.binary_search(&start).ok())
+                    .map(|char_index| chars[char_index].0)
+                    .chain([end])
+                    .collect()
+            }
+        }
+    }
+
+    fn parameter_hints(&mut self, args: &[Arg], params: &[Param]) {
+        for (arg, param) in args.iter().zip(params) {
+            // `f(volume)` already says what `volume: Number` would
+            if matches!(arg, Arg::Identifier(name, _) if *name == param.name) {
+                continue;
+            }
+            self.hints.push(InlayHint {
+                kind: Some(InlayHintKind::PARAMETER),
+                padding_right: Some(true),
+                ..self.hint(arg.span().0, format!("{}:", param.name))
+            });
+        }
+    }
+}
+
+impl Visit for Hints<'_> {
+    fn visit_node_def(&mut self, node: &NodeDef) {
+        self.body_markers(&node.body);
+        visit::walk_node_def(self, node);
+    }
+
+    fn visit_branch_case(&mut self, case: &BranchCase) {
+        if let Some(events) = &case.events
+            && let Some(positions) = self.literal_positions(case.span)
+        {
+            for event in events {
+                self.marker(event.index, &event.action.call.name, &positions);
+            }
+        }
+        visit::walk_branch_case(self, case);
+    }
+
+    fn visit_func_call(&mut self, call: &FuncCall) {
+        if let Some(function) = self.function(&call.name) {
+            self.parameter_hints(&call.args, &function.params);
+        }
+        visit::walk_func_call(self, call);
+    }
+
+    fn visit_run_stmt(&mut self, run: &RunStmt) {
+        // The arguments of `run` go to the event's action
+        if let Some(event) = self.event(&run.event_name)
+            && let Some(function) = self.function(&event.action.call.name)
+        {
+            self.parameter_hints(&run.args, &function.params);
+        }
+        visit::walk_run_stmt(self, run);
+    }
+
+    fn visit_index_override(&mut self, index_override: &IndexOverride) {
+        if let IndexOverride::Variable(name, span) = index_override
+            && let Some(value) = self.constant(name)
+        {
+            self.hints.push(InlayHint {
+                padding_left: Some(true),
+                ..self.hint(span.1, format!("= {}", value))
+            });
+        }
+    }
+}
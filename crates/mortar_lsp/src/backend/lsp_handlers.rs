@@ -1,10 +1,10 @@
 use ropey::Rope;
 use tower_lsp_server::LanguageServer;
-use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::jsonrpc::{Error, Result};
 use tower_lsp_server::lsp_types::*;
 use tracing::info;
 
-use crate::backend::Backend;
+use crate::backend::{Backend, IndexedDocument, apply_content_change, completion_items};
 
 impl LanguageServer for Backend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
@@ -39,14 +39,31 @@ impl LanguageServer for Backend {
             );
         }
 
+        // Definitions may live in any file of the workspace
+        #[allow(deprecated)]
+        let folders = match (&params.workspace_folders, &params.root_uri) {
+            (Some(folders), _) => folders.clone(),
+            (None, Some(root_uri)) => vec![WorkspaceFolder {
+                uri: root_uri.clone(),
+                name: String::new(),
+            }],
+            (None, None) => Vec::new(),
+        };
+        self.set_workspace_folders(&folders).await;
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
-                text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
+                        save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                        ..TextDocumentSyncOptions::default()
+                    },
                 )),
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(true),
-                    trigger_characters: Some(vec![".".to_string()]),
+                    trigger_characters: Some(vec![".".to_string(), ">".to_string()]),
                     all_commit_characters: None,
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                     completion_item: None,
@@ -58,7 +75,24 @@ impl LanguageServer for Backend {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                document_highlight_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        work_done_progress_options: WorkDoneProgressOptions::default(),
+                        resolve_provider: None,
+                    },
+                )),
+                inlay_hint_provider: Some(OneOf::Left(true)),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
                 workspace: Some(WorkspaceServerCapabilities {
                     workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                         supported: Some(true),
@@ -107,6 +141,27 @@ impl LanguageServer for Backend {
         self.client
             .log_message(MessageType::INFO, "Mortar LSP server initialized")
             .await;
+
+        // Keep the workspace index in step with files changed outside the editor
+        let watchers = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String("**/*.mortar".to_string()),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "mortar-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(watchers).ok(),
+        };
+        if let Err(error) = self.client.register_capability(vec![registration]).await {
+            info!("File watching unavailable: {}", error);
+        }
+
+        let backend = self.clone();
+        tokio::spawn(async move {
+            backend.workspace_indexes().await;
+        });
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -119,6 +174,9 @@ impl LanguageServer for Backend {
         self.documents.clear();
         self.diagnostics.clear();
         self.symbol_tables.clear();
+        self.parses.clear();
+        self.edits.clear();
+        self.reset_workspace_index().await;
 
         info!("Shutdown completed");
         Ok(())
@@ -131,39 +189,75 @@ impl LanguageServer for Backend {
 
         let rope = Rope::from_str(&content);
         self.documents.insert(uri.clone(), (rope, version));
+        self.edits.remove(&uri);
 
         // Use the new analyze_document method
-        self.analyze_document(&uri, &content).await;
+        self.analyze_document(&uri).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
         let version = Some(params.text_document.version);
 
-        if let Some(change) = params.content_changes.into_iter().next() {
-            let content = change.text;
-            let rope = Rope::from_str(&content);
-            self.documents.insert(uri.clone(), (rope, version));
+        if params.content_changes.is_empty() {
+            return;
+        }
 
-            // Cancel any existing debounce task for this URI
-            if let Some(handle) = self.debouncers.get(&uri) {
-                handle.abort();
+        // Changes come in order, each relative to the text the previous one left
+        {
+            let mut document = self
+                .documents
+                .entry(uri.clone())
+                .or_insert_with(|| (Rope::new(), version));
+            for change in &params.content_changes {
+                let edit = apply_content_change(&mut document.0, change);
+                self.edits
+                    .entry(uri.clone())
+                    .and_modify(|pending| *pending = pending.then(edit))
+                    .or_insert(edit);
             }
+            document.1 = version;
+        }
+
+        // Cancel any existing debounce task for this URI
+        if let Some(handle) = self.debouncers.get(&uri) {
+            handle.abort();
+        }
 
-            let backend = self.clone();
-            let uri_clone = uri.clone();
-            let content_clone = content.clone();
+        let backend = self.clone();
+        let uri_clone = uri.clone();
 
-            let task = tokio::spawn(async move {
-                // Debounce delay
-                tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-                backend.analyze_document(&uri_clone, &content_clone).await;
-            });
+        let task = tokio::spawn(async move {
+            // Debounce delay
+            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+            backend.analyze_document(&uri_clone).await;
+        });
 
-            self.debouncers.insert(uri, task);
+        self.debouncers.insert(uri, task);
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let Some(content) = self.documents.get(&uri).map(|entry| entry.0.to_string()) else {
+            return;
+        };
+        let document = tokio::task::spawn_blocking(move || IndexedDocument::new(content)).await;
+        if let Ok(document) = document {
+            self.set_index(&uri, document);
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in params.changes {
+            self.reindex_file(&change.uri).await;
         }
     }
 
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        self.change_workspace_folders(&params.event).await;
+        self.reset_workspace_index().await;
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
 
@@ -175,14 +269,47 @@ impl LanguageServer for Backend {
         self.documents.remove(&uri);
         self.diagnostics.remove(&uri);
         self.symbol_tables.remove(&uri);
+        self.parses.remove(&uri);
+        self.edits.remove(&uri);
+        // Unsaved changes are gone, so the file counts as it is on disk
+        self.reindex_file(&uri).await;
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
 
-        let (rope, _version) = match self.documents.get(uri) {
-            Some(entry) => (entry.0.clone(), entry.1),
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        let completions = completion_items(&content, position);
+        Ok(Some(CompletionResponse::Array(completions)))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        let others = self.other_indexes(uri).await;
+        let language = self.get_language().await;
+        Ok(crate::backend::hover_info(
+            uri, &content, position, &others, language,
+        ))
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
             None => return Ok(None),
         };
 
@@ -191,104 +318,198 @@ impl LanguageServer for Backend {
             None => return Ok(None),
         };
 
-        let line_idx = position.line as usize;
-        let char_idx = position.character as usize;
+        let language = self.get_language().await;
+        Ok(crate::backend::signature_help(
+            &content,
+            position,
+            &symbol_table,
+            language,
+        ))
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
 
-        let line_content = if line_idx < rope.len_lines() {
-            rope.line(line_idx).to_string()
-        } else {
-            String::new()
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
         };
 
-        let current_word = self.get_current_word(&line_content, char_idx);
-        let context = self.analyze_document_context(uri, position);
+        if let Some(location) = crate::backend::find_definition(uri, &content, position) {
+            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+        }
 
-        // Use unified completion generation from completion.rs
-        let completions =
-            self.generate_completion_items_filtered(context, &current_word, &symbol_table);
+        // Only read the rest of the workspace for names this file does not define
+        let Some(symbol) = crate::backend::symbol_at(&content, position) else {
+            return Ok(None);
+        };
+        let others = self.other_indexes(uri).await;
 
-        Ok(Some(CompletionResponse::Array(completions)))
+        Ok(
+            crate::backend::find_definition_in(&symbol, &others)
+                .map(GotoDefinitionResponse::Scalar),
+        )
     }
 
-    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
-        let _uri = &params.text_document_position_params.text_document.uri;
-        let _position = params.text_document_position_params.position;
-
-        Ok(Some(Hover {
-            contents: HoverContents::Markup(MarkupContent {
-                kind: MarkupKind::Markdown,
-                value: "**Mortar Language**\n\nDSL for game dialogue and text event systems."
-                    .to_string(),
-            }),
-            range: None,
-        }))
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        let others = self.other_indexes(uri).await;
+        Ok(Some(crate::backend::find_references(
+            uri,
+            &content,
+            position,
+            params.context.include_declaration,
+            &others,
+        )))
     }
 
-    async fn signature_help(&self, _params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
-        Ok(None)
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        Ok(Some(crate::backend::document_highlights(
+            &content, position,
+        )))
     }
 
-    async fn goto_definition(
+    async fn prepare_rename(
         &self,
-        _params: GotoDefinitionParams,
-    ) -> Result<Option<GotoDefinitionResponse>> {
-        Ok(None)
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let uri = &params.text_document.uri;
+
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        let others = self.other_indexes(uri).await;
+        let language = self.get_language().await;
+        crate::backend::prepare_rename(uri, &content, params.position, &others, language)
+            .map(Some)
+            .map_err(Error::invalid_params)
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        let others = self.other_indexes(uri).await;
+        let language = self.get_language().await;
+        crate::backend::rename_edits(uri, &content, position, &params.new_name, &others, language)
+            .map(Some)
+            .map_err(Error::invalid_params)
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        let others = self.other_indexes(uri).await;
+        let language = self.get_language().await;
+        Ok(Some(crate::backend::code_actions(
+            uri,
+            &content,
+            params.range,
+            &others,
+            language,
+        )))
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let uri = &params.text_document.uri;
+        let content = match self.documents.get(uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
+
+        let language = self.get_language().await;
+        Ok(Some(crate::backend::inlay_hints(
+            uri,
+            &content,
+            params.range,
+            language,
+        )))
     }
 
     async fn document_symbol(
         &self,
         params: DocumentSymbolParams,
     ) -> Result<Option<DocumentSymbolResponse>> {
-        let uri = &params.text_document.uri;
+        let content = match self.documents.get(&params.text_document.uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
 
-        let symbol_table = match self.symbol_tables.get(uri) {
-            Some(table) => table.clone(),
+        Ok(Some(DocumentSymbolResponse::Nested(
+            crate::backend::document_symbols(&content),
+        )))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<OneOf<Vec<SymbolInformation>, Vec<WorkspaceSymbol>>>> {
+        let documents = self.workspace_indexes().await;
+        Ok(Some(OneOf::Right(crate::backend::workspace_symbols(
+            &params.query,
+            &documents,
+        ))))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let content = match self.documents.get(&params.text_document.uri) {
+            Some(entry) => entry.0.to_string(),
             None => return Ok(None),
         };
 
-        let mut symbols = Vec::new();
-
-        for node in &symbol_table.nodes {
-            symbols.push(DocumentSymbol {
-                name: node.clone(),
-                detail: None,
-                kind: SymbolKind::CLASS,
-                tags: None,
-                #[allow(deprecated)]
-                deprecated: Some(false),
-                range: Range::new(Position::new(0, 0), Position::new(0, 0)),
-                selection_range: Range::new(Position::new(0, 0), Position::new(0, 0)),
-                children: None,
-            });
-        }
+        Ok(Some(crate::backend::formatting_edits(
+            &content,
+            &params.options,
+        )))
+    }
 
-        for func in &symbol_table.functions {
-            symbols.push(DocumentSymbol {
-                name: func.name.clone(),
-                detail: Some(format!(
-                    "fn {}({}){}",
-                    func.name,
-                    func.params
-                        .iter()
-                        .map(|p| format!("{}: {}", p.name, p.type_name))
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                    func.return_type
-                        .as_ref()
-                        .map(|t| format!(" -> {}", t))
-                        .unwrap_or_default()
-                )),
-                kind: SymbolKind::FUNCTION,
-                tags: None,
-                #[allow(deprecated)]
-                deprecated: Some(false),
-                range: Range::new(Position::new(0, 0), Position::new(0, 0)),
-                selection_range: Range::new(Position::new(0, 0), Position::new(0, 0)),
-                children: None,
-            });
-        }
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let content = match self.documents.get(&params.text_document.uri) {
+            Some(entry) => entry.0.to_string(),
+            None => return Ok(None),
+        };
 
-        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+        Ok(crate::backend::range_formatting_edits(
+            &content,
+            params.range,
+            &params.options,
+        ))
     }
 
     async fn semantic_tokens_full(
@@ -303,7 +524,7 @@ impl LanguageServer for Backend {
         };
 
         let content = rope.to_string();
-        let tokens = self.analyze_semantic_tokens(&content);
+        let tokens = crate::backend::compute_semantic_tokens(&content);
 
         Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
             result_id: None,
@@ -338,18 +559,14 @@ impl LanguageServer for Backend {
                     self.set_language(language).await;
 
                     // Re-analyze all open documents with the new language
-                    let documents_snapshot: Vec<(Uri, String)> = self
+                    let open_documents: Vec<Uri> = self
                         .documents
                         .iter()
-                        .map(|entry| {
-                            let uri = entry.key().clone();
-                            let content = entry.value().0.to_string();
-                            (uri, content)
-                        })
+                        .map(|entry| entry.key().clone())
                         .collect();
 
-                    for (uri, content) in documents_snapshot {
-                        self.analyze_document(&uri, &content).await;
+                    for uri in open_documents {
+                        self.analyze_document(&uri).await;
                     }
 
                     let success_msg =
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use mortar_compiler::ParseHandler;
+use mortar_compiler::ast::Program;
+use tower_lsp_server::lsp_types::*;
+
+use crate::backend::diagnostics::{byte_span_to_lsp_range, position_to_byte_offset};
+use crate::symbols::{Occurrence, Role, Symbol, SymbolIndex};
+
+/// Index the symbols of `content`, if it parses far enough to give a program.
+pub fn index_document(content: &str) -> Option<SymbolIndex> {
+    let (result, _diagnostics) =
+        ParseHandler::parse_source_code_with_diagnostics(content, String::new(), false);
+    result
+        .ok()
+        .map(|program| SymbolIndex::new(&program, content))
+}
+
+/// A workspace document with its program and the index of its symbols, kept
+/// so that requests reaching into other files do not parse them again.
+#[derive(Debug, Clone)]
+pub struct IndexedDocument {
+    pub content: String,
+    /// `None` when the document does not parse far enough to give a program.
+    pub program: Option<Program>,
+    /// `None` along with `program`.
+    pub index: Option<SymbolIndex>,
+}
+
+impl IndexedDocument {
+    pub fn new(content: String) -> Self {
+        let (result, _diagnostics) =
+            ParseHandler::parse_source_code_with_diagnostics(&content, String::new(), false);
+        match result {
+            Ok(program) => Self::parsed(content, program),
+            Err(_) => Self {
+                content,
+                program: None,
+                index: None,
+            },
+        }
+    }
+
+    /// `content` indexed from a program already parsed from it.
+    pub fn parsed(content: String, program: Program) -> Self {
+        let index = SymbolIndex::new(&program, &content);
+        Self {
+            content,
+            program: Some(program),
+            index: Some(index),
+        }
+    }
+}
+
+/// The symbol at `position` in `content`.
+pub fn symbol_at(content: &str, position: Position) -> Option<Symbol> {
+    let index = index_document(content)?;
+    let offset = position_to_byte_offset(content, position);
+    index
+        .occurrence_at(offset)
+        .map(|occurrence| occurrence.symbol.clone())
+}
+
+/// Where the symbol at `position` is defined within `content` itself.
+pub fn find_definition(uri: &Uri, content: &str, position: Position) -> Option<Location> {
+    let index = index_document(content)?;
+    let offset = position_to_byte_offset(content, position);
+    let symbol = &index.occurrence_at(offset)?.symbol;
+    let definition = index.definition(symbol)?;
+    Some(location(uri, content, definition))
+}
+
+/// The first definition of `symbol` among `documents`, for names defined in another file.
+pub fn find_definition_in(
+    symbol: &Symbol,
+    documents: &[(Uri, Arc<IndexedDocument>)],
+) -> Option<Location> {
+    documents.iter().find_map(|(uri, document)| {
+        let definition = document.index.as_ref()?.definition(symbol)?;
+        Some(location(uri, &document.content, definition))
+    })
+}
+
+/// Every use of the symbol at `position`, in `content` and then in `others`.
+///
+/// Definitions are left out unless `include_declaration` is set.
+pub fn find_references(
+    uri: &Uri,
+    content: &str,
+    position: Position,
+    include_declaration: bool,
+    others: &[(Uri, Arc<IndexedDocument>)],
+) -> Vec<Location> {
+    let Some(index) = index_document(content) else {
+        return Vec::new();
+    };
+    let offset = position_to_byte_offset(content, position);
+    let Some(symbol) = index.occurrence_at(offset).map(|o| o.symbol.clone()) else {
+        return Vec::new();
+    };
+
+    let mut locations = references_in(uri, content, &index, &symbol, include_declaration);
+    if !symbol.is_file_local() {
+        for (other_uri, other) in others {
+            if let Some(other_index) = &other.index {
+                locations.extend(references_in(
+                    other_uri,
+                    &other.content,
+                    other_index,
+                    &symbol,
+                    include_declaration,
+                ));
+            }
+        }
+    }
+    locations
+}
+
+fn references_in(
+    uri: &Uri,
+    content: &str,
+    index: &SymbolIndex,
+    symbol: &Symbol,
+    include_declaration: bool,
+) -> Vec<Location> {
+    index
+        .occurrences_of(symbol)
+        .filter(|occurrence| include_declaration || !occurrence.is_definition())
+        .map(|occurrence| location(uri, content, occurrence))
+        .collect()
+}
+
+/// Every occurrence in `content` of the symbol at `position`, marking where it is defined or assigned.
+pub fn document_highlights(content: &str, position: Position) -> Vec<DocumentHighlight> {
+    let Some(index) = index_document(content) else {
+        return Vec::new();
+    };
+    let offset = position_to_byte_offset(content, position);
+    let Some(symbol) = index.occurrence_at(offset).map(|o| o.symbol.clone()) else {
+        return Vec::new();
+    };
+
+    index
+        .occurrences_of(&symbol)
+        .map(|occurrence| DocumentHighlight {
+            range: byte_span_to_lsp_range(content, occurrence.span.0, occurrence.span.1),
+            kind: Some(match occurrence.role {
+                Role::Definition | Role::Write => DocumentHighlightKind::WRITE,
+                Role::Read => DocumentHighlightKind::READ,
+            }),
+        })
+        .collect()
+}
+
+fn location(uri: &Uri, content: &str, occurrence: &Occurrence) -> Location {
+    let (start, end) = occurrence.span;
+    Location::new(uri.clone(), byte_span_to_lsp_range(content, start, end))
+}
@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use mortar_compiler::{Language, LintLevel, Token, is_pascal_case, is_snake_case, tokenize};
+use tower_lsp_server::lsp_types::*;
+
+use crate::backend::diagnostics::{
+    byte_span_to_lsp_range, position_to_byte_offset, project_config,
+};
+use crate::backend::i18n::get_lsp_text;
+use crate::backend::{IndexedDocument, index_document};
+use crate::symbols::{Occurrence, Symbol, SymbolIndex, SymbolKind};
+
+/// A document a rename looks at, with its symbol index.
+struct Indexed<'a> {
+    uri: &'a Uri,
+    content: &'a str,
+    index: Cow<'a, SymbolIndex>,
+}
+
+/// The symbol under the cursor, if it can be renamed, together with every indexed document.
+fn renamable<'a>(
+    uri: &'a Uri,
+    content: &'a str,
+    position: Position,
+    others: &'a [(Uri, Arc<IndexedDocument>)],
+    language: Language,
+) -> Result<(Occurrence, Vec<Indexed<'a>>), String> {
+    let no_symbol = || get_lsp_text("rename_no_symbol", language).to_string();
+
+    let index = index_document(content).ok_or_else(no_symbol)?;
+    let occurrence = index
+        .occurrence_at(position_to_byte_offset(content, position))
+        .cloned()
+        .ok_or_else(no_symbol)?;
+
+    let mut documents = vec![Indexed {
+        uri,
+        content,
+        index: Cow::Owned(index),
+    }];
+    if !occurrence.symbol.is_file_local() {
+        documents.extend(others.iter().filter_map(|(uri, document)| {
+            Some(Indexed {
+                uri,
+                content: &document.content,
+                index: Cow::Borrowed(document.index.as_ref()?),
+            })
+        }));
+    }
+
+    // Unresolved names may be typos or come from the game, so renaming them could break things
+    let defined = documents
+        .iter()
+        .any(|document| document.index.definition(&occurrence.symbol).is_some());
+    if !defined {
+        return Err(get_lsp_text("rename_undefined", language).to_string());
+    }
+
+    Ok((occurrence, documents))
+}
+
+/// The range and current name of the symbol at `position`, or why it cannot be renamed.
+pub fn prepare_rename(
+    uri: &Uri,
+    content: &str,
+    position: Position,
+    others: &[(Uri, Arc<IndexedDocument>)],
+    language: Language,
+) -> Result<PrepareRenameResponse, String> {
+    let (occurrence, _documents) = renamable(uri, content, position, others, language)?;
+    let (start, end) = occurrence.span;
+
+    Ok(PrepareRenameResponse::RangeWithPlaceholder {
+        range: byte_span_to_lsp_range(content, start, end),
+        placeholder: occurrence.symbol.name,
+    })
+}
+
+/// Edits renaming the symbol at `position` to `new_name` in every document that uses it.
+///
+/// Refused when `new_name` is not an identifier, is already taken by a symbol
+/// of the same kind, or breaks a naming-convention lint the project has not allowed.
+pub fn rename_edits(
+    uri: &Uri,
+    content: &str,
+    position: Position,
+    new_name: &str,
+    others: &[(Uri, Arc<IndexedDocument>)],
+    language: Language,
+) -> Result<WorkspaceEdit, String> {
+    let (occurrence, documents) = renamable(uri, content, position, others, language)?;
+    let symbol = &occurrence.symbol;
+    let refuse = |key: &str| format!("{}: `{}`", get_lsp_text(key, language), new_name);
+
+    if !is_identifier(new_name) {
+        return Err(refuse("rename_invalid_name"));
+    }
+    if new_name == symbol.name {
+        return Ok(WorkspaceEdit::default());
+    }
+
+    let renamed = Symbol {
+        name: new_name.to_string(),
+        ..symbol.clone()
+    };
+    if documents
+        .iter()
+        .any(|document| document.index.definition(&renamed).is_some())
+    {
+        return Err(refuse("rename_name_taken"));
+    }
+
+    match symbol.kind {
+        SymbolKind::Node
+            if !is_pascal_case(new_name) && !lint_allowed(uri, "non_pascal_case_node") =>
+        {
+            return Err(refuse("rename_node_not_pascal_case"));
+        }
+        SymbolKind::Function
+            if !is_snake_case(new_name) && !lint_allowed(uri, "non_snake_case_function") =>
+        {
+            return Err(refuse("rename_function_not_snake_case"));
+        }
+        _ => {}
+    }
+
+    // `WorkspaceEdit` keys its changes by `Uri`, which is never mutated here
+    #[allow(clippy::mutable_key_type)]
+    let mut changes = HashMap::new();
+    for document in &documents {
+        let edits: Vec<TextEdit> = document
+            .index
+            .occurrences_of(symbol)
+            .map(|occurrence| {
+                let (start, end) = occurrence.span;
+                TextEdit::new(
+                    byte_span_to_lsp_range(document.content, start, end),
+                    new_name.to_string(),
+                )
+            })
+            .collect();
+        if !edits.is_empty() {
+            changes.insert(document.uri.clone(), edits);
+        }
+    }
+
+    Ok(WorkspaceEdit {
+        changes: Some(changes),
+        ..WorkspaceEdit::default()
+    })
+}
+
+/// Whether `name` lexes as a single identifier, which also rules out keywords.
+fn is_identifier(name: &str) -> bool {
+    match tokenize(name).as_slice() {
+        [token] => token.text == name && matches!(token.token, Token::Identifier(_)),
+        _ => false,
+    }
+}
+
+/// Whether the nearest `mortar.toml` turns `lint` off.
+fn lint_allowed(uri: &Uri, lint: &str) -> bool {
+    project_config(uri).lint_config().level_of(lint) == LintLevel::Allow
+}
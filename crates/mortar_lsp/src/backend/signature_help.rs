@@ -0,0 +1,138 @@
+use mortar_compiler::ast::FunctionDecl;
+use mortar_compiler::printer::type_name;
+use mortar_compiler::{Language, Token, tokenize};
+use tower_lsp_server::lsp_types::*;
+
+use crate::analysis::SymbolTable;
+use crate::backend::diagnostics::position_to_byte_offset;
+use crate::backend::i18n::get_lsp_text;
+
+/// A call whose argument list is still open at the cursor.
+#[derive(Debug, PartialEq)]
+struct OpenCall<'a> {
+    name: &'a str,
+    /// Whether this is a `run` statement, whose arguments go to an event's action.
+    run: bool,
+    /// How many arguments come before the cursor.
+    argument: u32,
+}
+
+/// The signature of the call around `position`, with the argument under the cursor active.
+///
+/// Works on unfinished text, so the function's parameters come from the last
+/// `symbol_table` built from a successful parse.
+pub fn signature_help(
+    content: &str,
+    position: Position,
+    symbol_table: &SymbolTable,
+    language: Language,
+) -> Option<SignatureHelp> {
+    let call = open_call(content, position_to_byte_offset(content, position))?;
+
+    let (function, documentation) = if call.run {
+        let event = symbol_table
+            .events
+            .iter()
+            .find(|event| event.name == call.name)?;
+        let function = find_function(symbol_table, &event.action.call.name)?;
+        let documentation = format!(
+            "{} `{}`",
+            get_lsp_text("signature_run_arguments", language),
+            function.name
+        );
+        (function, Some(Documentation::String(documentation)))
+    } else {
+        (find_function(symbol_table, call.name)?, None)
+    };
+
+    let (label, parameters) = signature_label(function);
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation,
+            parameters: Some(parameters),
+            active_parameter: Some(call.argument),
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(call.argument),
+    })
+}
+
+fn find_function<'a>(symbol_table: &'a SymbolTable, name: &str) -> Option<&'a FunctionDecl> {
+    symbol_table
+        .functions
+        .iter()
+        .find(|function| function.name == name)
+}
+
+/// `name(param: Type, ...) -> Return`, with the UTF-16 offsets of each parameter.
+fn signature_label(function: &FunctionDecl) -> (String, Vec<ParameterInformation>) {
+    let mut label = format!("{}(", function.name);
+    let mut parameters = Vec::new();
+
+    for (i, param) in function.params.iter().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        let start = label.encode_utf16().count() as u32;
+        label.push_str(&format!("{}: {}", param.name, type_name(&param.type_name)));
+        let end = label.encode_utf16().count() as u32;
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: None,
+        });
+    }
+
+    label.push(')');
+    if let Some(return_type) = &function.return_type {
+        label.push_str(&format!(" -> {}", type_name(return_type)));
+    }
+    (label, parameters)
+}
+
+/// The innermost call left open before `offset`.
+///
+/// Only the text before the cursor is lexed, so an interpolated string the
+/// cursor is in falls apart into plain tokens and its calls are found too.
+fn open_call(content: &str, offset: usize) -> Option<OpenCall<'_>> {
+    // Inside a string argument, the argument starts at the string
+    let offset = tokenize(content)
+        .iter()
+        .find(|token| {
+            matches!(token.token, Token::String(_)) && token.start < offset && offset < token.end
+        })
+        .map_or(offset, |token| token.start);
+
+    let tokens = tokenize(&content[..offset]);
+    // `None` marks parentheses that do not belong to a call
+    let mut calls: Vec<Option<OpenCall>> = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token.token {
+            Token::LeftParen => {
+                let call = match i.checked_sub(1).map(|previous| &tokens[previous].token) {
+                    Some(Token::Identifier(name)) => Some(OpenCall {
+                        name,
+                        run: i >= 2 && tokens[i - 2].token == Token::Run,
+                        argument: 0,
+                    }),
+                    _ => None,
+                };
+                calls.push(call);
+            }
+            Token::RightParen => {
+                calls.pop();
+            }
+            Token::Comma => {
+                if let Some(Some(call)) = calls.last_mut() {
+                    call.argument += 1;
+                }
+            }
+            // A brace ends any call a mistake left open
+            Token::LeftBrace | Token::RightBrace => calls.clear(),
+            _ => {}
+        }
+    }
+
+    calls.pop().flatten()
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use tower_lsp_server::UriExt;
+use tower_lsp_server::lsp_types::*;
+
+use crate::backend::{Backend, IndexedDocument};
+
+/// Every `.mortar` file under `root`, skipping hidden directories and build output.
+pub fn find_mortar_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if path.is_dir() {
+                if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                    pending.push(path);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "mortar") {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+impl Backend {
+    /// Remember the workspace folders the client opened.
+    pub async fn set_workspace_folders(&self, folders: &[WorkspaceFolder]) {
+        let mut roots = self.workspace_roots.write().await;
+        *roots = folders
+            .iter()
+            .filter_map(|folder| folder.uri.to_file_path())
+            .map(|path| path.into_owned())
+            .collect();
+    }
+
+    /// Update the workspace folders after the client added or removed some.
+    pub async fn change_workspace_folders(&self, event: &WorkspaceFoldersChangeEvent) {
+        let mut roots = self.workspace_roots.write().await;
+        for removed in &event.removed {
+            if let Some(path) = removed.uri.to_file_path() {
+                roots.retain(|root| root.as_path() != path.as_ref());
+            }
+        }
+        for added in &event.added {
+            if let Some(path) = added.uri.to_file_path() {
+                roots.push(path.into_owned());
+            }
+        }
+    }
+
+    /// The indexed documents of the workspace except `uri`.
+    pub async fn other_indexes(&self, uri: &Uri) -> Vec<(Uri, Arc<IndexedDocument>)> {
+        let mut documents = self.workspace_indexes().await;
+        documents.retain(|(document_uri, _)| document_uri != uri);
+        documents
+    }
+
+    /// The indexed documents of the workspace, reading the workspace folders
+    /// the first time. Later changes come from the editor and file events.
+    pub async fn workspace_indexes(&self) -> Vec<(Uri, Arc<IndexedDocument>)> {
+        let _scan = self.workspace_scan.lock().await;
+        // Marked before reading, so that a reset while reading has the next request read again
+        if !self.workspace_indexed.swap(true, Ordering::AcqRel) {
+            let roots = self.workspace_roots.read().await.clone();
+            let documents = self.documents.clone();
+            let indexes = self.indexes.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                for path in roots.iter().flat_map(|root| find_mortar_files(root)) {
+                    let Some(uri) = Uri::from_file_path(&path) else {
+                        continue;
+                    };
+                    // Open documents are indexed from their own text
+                    if documents.contains_key(&uri) || indexes.contains_key(&uri) {
+                        continue;
+                    }
+                    if let Ok(content) = fs::read_to_string(&path) {
+                        let document = Arc::new(IndexedDocument::new(content));
+                        indexes.entry(uri).or_insert(document);
+                    }
+                }
+            })
+            .await;
+        }
+
+        self.indexes
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
+    /// Index `content` as the text of `uri`.
+    pub fn set_index(&self, uri: &Uri, document: IndexedDocument) {
+        self.indexes.insert(uri.clone(), Arc::new(document));
+    }
+
+    /// Index `uri` from disk, or forget it when it is gone or outside the workspace.
+    ///
+    /// Open documents are left alone, as their unsaved text is what counts.
+    pub async fn reindex_file(&self, uri: &Uri) {
+        if self.documents.contains_key(uri) {
+            return;
+        }
+        let roots = self.workspace_roots.read().await.clone();
+        let content = uri
+            .to_file_path()
+            .filter(|path| roots.iter().any(|root| path.starts_with(root)))
+            .and_then(|path| fs::read_to_string(path).ok());
+        match content {
+            Some(content) => {
+                let document =
+                    tokio::task::spawn_blocking(move || IndexedDocument::new(content)).await;
+                if let Ok(document) = document {
+                    self.set_index(uri, document);
+                }
+            }
+            None => {
+                self.indexes.remove(uri);
+            }
+        }
+    }
+
+    /// Forget the files of the workspace folders, to be read again on the next request.
+    pub async fn reset_workspace_index(&self) {
+        let _scan = self.workspace_scan.lock().await;
+        self.indexes
+            .retain(|uri, _| self.documents.contains_key(uri));
+        self.workspace_indexed.store(false, Ordering::Release);
+    }
+}
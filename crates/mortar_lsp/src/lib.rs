@@ -1,5 +1,6 @@
 pub mod analysis;
 pub mod backend;
 pub mod files;
+pub mod symbols;
 
 pub use backend::Backend;
@@ -0,0 +1,539 @@
+use std::collections::HashMap;
+
+use mortar_compiler::ast::*;
+use mortar_compiler::is_identifier_continue;
+use mortar_compiler::visit::{self, Visit};
+
+/// What a name in a Mortar file stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Node,
+    Function,
+    Event,
+    Timeline,
+    Variable,
+    Constant,
+    Enum,
+    EnumVariant,
+}
+
+impl SymbolKind {
+    /// Kinds that share a namespace can stand in for each other: `run` starts
+    /// events and timelines alike, and values may be variables or constants.
+    fn namespace(self) -> SymbolKind {
+        match self {
+            SymbolKind::Timeline => SymbolKind::Event,
+            SymbolKind::Constant => SymbolKind::Variable,
+            kind => kind,
+        }
+    }
+}
+
+/// A named thing, identified by kind and name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub name: String,
+    /// The enum of a variant, or the node that declares a node-local branch.
+    pub scope: Option<String>,
+}
+
+impl Symbol {
+    fn new(kind: SymbolKind, name: &str) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+            scope: None,
+        }
+    }
+
+    fn scoped(kind: SymbolKind, name: &str, scope: &str) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+            scope: Some(scope.to_string()),
+        }
+    }
+
+    /// Node-local branches cannot be seen from other files.
+    pub fn is_file_local(&self) -> bool {
+        self.scope.is_some() && self.kind != SymbolKind::EnumVariant
+    }
+
+    /// Whether both refer to the same thing, treating an unresolved `run`
+    /// target or value as any kind of its namespace.
+    pub fn same_as(&self, other: &Symbol) -> bool {
+        self.name == other.name
+            && self.scope == other.scope
+            && self.kind.namespace() == other.kind.namespace()
+    }
+}
+
+/// How an occurrence uses its symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Definition,
+    /// The target of an assignment such as `score = 1`.
+    Write,
+    Read,
+}
+
+/// One place a symbol's name is written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub symbol: Symbol,
+    /// Byte range of the name.
+    pub span: Span,
+    pub role: Role,
+}
+
+impl Occurrence {
+    pub fn is_definition(&self) -> bool {
+        self.role == Role::Definition
+    }
+}
+
+/// Every definition and reference of a named symbol in one document.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl SymbolIndex {
+    /// Index a parsed program. `source` is the text it was parsed from, used to
+    /// locate names the AST keeps no span for, such as type annotations.
+    pub fn new(program: &Program, source: &str) -> Self {
+        let mut collector = Collector {
+            source,
+            occurrences: Vec::new(),
+            definitions: HashMap::new(),
+            variable_types: HashMap::new(),
+            current_node: None,
+        };
+        collector.collect_definitions(program);
+        collector.visit_program(program);
+
+        let mut occurrences = collector.occurrences;
+        occurrences.sort_by_key(|occurrence| occurrence.span);
+        Self { occurrences }
+    }
+
+    /// The occurrence whose name contains `offset`, counting the position just past its end.
+    pub fn occurrence_at(&self, offset: usize) -> Option<&Occurrence> {
+        self.occurrences
+            .iter()
+            .find(|occurrence| occurrence.span.0 <= offset && offset <= occurrence.span.1)
+    }
+
+    /// The first definition of `symbol`.
+    pub fn definition(&self, symbol: &Symbol) -> Option<&Occurrence> {
+        self.occurrences
+            .iter()
+            .find(|occurrence| occurrence.is_definition() && occurrence.symbol.same_as(symbol))
+    }
+
+    /// Every occurrence of `symbol`, definitions included, in source order.
+    pub fn occurrences_of<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+    ) -> impl Iterator<Item = &'a Occurrence> + 'a {
+        self.occurrences
+            .iter()
+            .filter(move |occurrence| occurrence.symbol.same_as(symbol))
+    }
+}
+
+struct Collector<'a> {
+    source: &'a str,
+    occurrences: Vec<Occurrence>,
+    /// Kind of every name defined in the file, keyed by namespace and name.
+    definitions: HashMap<(SymbolKind, String), SymbolKind>,
+    /// Declared type of every variable, to resolve the cases of enum branches.
+    variable_types: HashMap<String, String>,
+    current_node: Option<String>,
+}
+
+impl Collector<'_> {
+    fn define(&mut self, symbol: Symbol, span: Option<Span>) {
+        if symbol.scope.is_none() {
+            self.definitions
+                .entry((symbol.kind.namespace(), symbol.name.clone()))
+                .or_insert(symbol.kind);
+        }
+        self.occurrence(symbol, span, Role::Definition);
+    }
+
+    fn reference(&mut self, symbol: Symbol, span: Option<Span>) {
+        self.occurrence(symbol, span, Role::Read);
+    }
+
+    fn occurrence(&mut self, symbol: Symbol, span: Option<Span>, role: Role) {
+        if let Some(span) = span {
+            self.occurrences.push(Occurrence { symbol, span, role });
+        }
+    }
+
+    /// A top-level symbol, with the kind of its definition when the file has one.
+    fn resolve(&self, kind: SymbolKind, name: &str) -> Symbol {
+        let kind = self
+            .definitions
+            .get(&(kind.namespace(), name.to_string()))
+            .copied()
+            .unwrap_or(kind);
+        Symbol::new(kind, name)
+    }
+
+    /// A value name: a branch declared in the current node, or else a variable or constant.
+    fn resolve_value(&self, name: &str) -> Symbol {
+        if let Some(node) = &self.current_node {
+            let local = Symbol::scoped(SymbolKind::Variable, name, node);
+            if self
+                .occurrences
+                .iter()
+                .any(|occurrence| occurrence.is_definition() && occurrence.symbol == local)
+            {
+                return local;
+            }
+        }
+        self.resolve(SymbolKind::Variable, name)
+    }
+
+    fn reference_value(&mut self, name: &str, span: Span) {
+        let symbol = self.resolve_value(name);
+        self.reference(symbol, Some(span));
+    }
+
+    fn reference_type(&mut self, type_name: &str, span: Option<Span>) {
+        if !matches!(
+            type_name,
+            "String" | "Number" | "Bool" | "Boolean" | "Branch"
+        ) {
+            let symbol = self.resolve(SymbolKind::Enum, type_name);
+            self.reference(symbol, span);
+        }
+    }
+
+    /// `Enum.member`, where `span` covers the whole expression.
+    fn reference_enum_member(&mut self, enum_name: &str, member: &str, span: Span) {
+        self.reference_type(enum_name, Some((span.0, span.0 + enum_name.len())));
+        self.reference(
+            Symbol::scoped(SymbolKind::EnumVariant, member, enum_name),
+            Some((span.1 - member.len(), span.1)),
+        );
+    }
+
+    /// Definitions come first, so references can resolve names declared later in the file.
+    fn collect_definitions(&mut self, program: &Program) {
+        for item in &program.body {
+            match item {
+                TopLevel::NodeDef(node) => {
+                    self.define(Symbol::new(SymbolKind::Node, &node.name), node.name_span);
+                    let mut branches = LocalBranches(Vec::new());
+                    branches.visit_node_def(node);
+                    for branch in branches.0 {
+                        self.define(
+                            Symbol::scoped(SymbolKind::Variable, &branch.name, &node.name),
+                            branch.name_span,
+                        );
+                    }
+                }
+                TopLevel::FunctionDecl(function) => {
+                    self.define(
+                        Symbol::new(SymbolKind::Function, &function.name),
+                        function.name_span,
+                    );
+                }
+                TopLevel::VarDecl(var_decl) => {
+                    self.variable_types
+                        .insert(var_decl.name.clone(), var_decl.type_name.clone());
+                    self.define(
+                        Symbol::new(SymbolKind::Variable, &var_decl.name),
+                        var_decl.name_span,
+                    );
+                }
+                TopLevel::ConstDecl(const_decl) => {
+                    self.define(
+                        Symbol::new(SymbolKind::Constant, &const_decl.name),
+                        const_decl.name_span,
+                    );
+                }
+                TopLevel::EnumDef(enum_def) => {
+                    self.define(
+                        Symbol::new(SymbolKind::Enum, &enum_def.name),
+                        enum_def.name_span,
+                    );
+                    for (variant, span) in enum_def.variants.iter().zip(&enum_def.variant_spans) {
+                        self.define(
+                            Symbol::scoped(SymbolKind::EnumVariant, variant, &enum_def.name),
+                            Some(*span),
+                        );
+                    }
+                }
+                TopLevel::EventDef(event_def) => {
+                    self.define(
+                        Symbol::new(SymbolKind::Event, &event_def.name),
+                        event_def.name_span,
+                    );
+                }
+                TopLevel::TimelineDef(timeline) => {
+                    self.define(
+                        Symbol::new(SymbolKind::Timeline, &timeline.name),
+                        timeline.name_span,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The selector of `branch<selector> [...]` and the cases it picks between,
+    /// where `span` is the range the selector is written in.
+    ///
+    /// Cases of an enum branch are variants of the selector's enum; cases of a
+    /// boolean branch are boolean values.
+    fn reference_branch(&mut self, selector: Option<&str>, cases: &[BranchCase], span: Span) {
+        match selector {
+            Some(selector) => {
+                let after_angle = self.source[span.0..span.1]
+                    .find('<')
+                    .map_or(span.0, |angle| span.0 + angle + 1);
+                let selector_span = find_word(self.source, (after_angle, span.1), selector);
+
+                // The selector is usually an enum variable, but may name the enum itself
+                let enum_name = if self
+                    .definitions
+                    .contains_key(&(SymbolKind::Enum, selector.to_string()))
+                {
+                    self.reference_type(selector, selector_span);
+                    Some(selector.to_string())
+                } else {
+                    if let Some(selector_span) = selector_span {
+                        self.reference_value(selector, selector_span);
+                    }
+                    self.variable_types.get(selector).cloned()
+                };
+
+                if let Some(enum_name) = enum_name {
+                    for case in cases {
+                        self.reference(
+                            Symbol::scoped(SymbolKind::EnumVariant, &case.condition, &enum_name),
+                            case_condition_span(case),
+                        );
+                    }
+                }
+            }
+            None => {
+                for case in cases {
+                    if let Some(span) = case_condition_span(case) {
+                        self.reference_value(&case.condition, span);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The span of a type annotation, searched for after `from`.
+    fn type_span(&self, type_name: &str, from: usize, to: usize) -> Option<Span> {
+        find_word(self.source, (from, to), type_name)
+    }
+}
+
+impl Visit for Collector<'_> {
+    fn visit_node_def(&mut self, node: &NodeDef) {
+        self.current_node = Some(node.name.clone());
+        visit::walk_node_def(self, node);
+        self.current_node = None;
+    }
+
+    fn visit_node_jump(&mut self, jump: &NodeJump) {
+        if let NodeJump::Identifier(target, span) = jump {
+            let symbol = self.resolve(SymbolKind::Node, target);
+            self.reference(symbol, Some(*span));
+        }
+    }
+
+    fn visit_choice_dest(&mut self, dest: &ChoiceDest) {
+        if let ChoiceDest::Identifier(target, span) = dest {
+            let symbol = self.resolve(SymbolKind::Node, target);
+            self.reference(symbol, Some(*span));
+        }
+        visit::walk_choice_dest(self, dest);
+    }
+
+    fn visit_func_call(&mut self, call: &FuncCall) {
+        let symbol = self.resolve(SymbolKind::Function, &call.name);
+        self.reference(symbol, call.name_span);
+        visit::walk_func_call(self, call);
+    }
+
+    fn visit_run_stmt(&mut self, run: &RunStmt) {
+        let symbol = self.resolve(SymbolKind::Event, &run.event_name);
+        self.reference(symbol, run.event_name_span);
+        visit::walk_run_stmt(self, run);
+    }
+
+    fn visit_with_event_item(&mut self, item: &WithEventItem) {
+        if let WithEventItem::EventRef(name, span)
+        | WithEventItem::EventRefWithOverride(name, span, _) = item
+        {
+            let symbol = self.resolve(SymbolKind::Event, name);
+            self.reference(symbol, Some(*span));
+        }
+        visit::walk_with_event_item(self, item);
+    }
+
+    fn visit_index_override(&mut self, index_override: &IndexOverride) {
+        if let IndexOverride::Variable(name, span) = index_override {
+            self.reference_value(name, *span);
+        }
+    }
+
+    fn visit_if_condition(&mut self, condition: &IfCondition) {
+        match condition {
+            IfCondition::Identifier(name, span) => self.reference_value(name, *span),
+            IfCondition::EnumMember(enum_name, member, span) => {
+                self.reference_enum_member(enum_name, member, *span)
+            }
+            _ => {}
+        }
+        visit::walk_if_condition(self, condition);
+    }
+
+    fn visit_condition(&mut self, condition: &Condition) {
+        if let Condition::Identifier(name, span) = condition {
+            self.reference_value(name, *span);
+        }
+        visit::walk_condition(self, condition);
+    }
+
+    fn visit_arg(&mut self, arg: &Arg) {
+        if let Arg::Identifier(name, span) = arg {
+            self.reference_value(name, *span);
+        }
+        visit::walk_arg(self, arg);
+    }
+
+    fn visit_string_part(&mut self, part: &StringPart) {
+        if let StringPart::Placeholder(name, span) = part {
+            self.reference_value(name, *span);
+        }
+        visit::walk_string_part(self, part);
+    }
+
+    fn visit_assignment(&mut self, assignment: &Assignment) {
+        let symbol = self.resolve_value(&assignment.var_name);
+        self.occurrence(symbol, assignment.var_name_span, Role::Write);
+        visit::walk_assignment(self, assignment);
+    }
+
+    fn visit_assign_value(&mut self, value: &AssignValue) {
+        match value {
+            AssignValue::Identifier(name, span) => self.reference_value(name, *span),
+            AssignValue::EnumMember(enum_name, member, span) => {
+                self.reference_enum_member(enum_name, member, *span)
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) {
+        let from = var_decl.name_span.map_or(var_decl.span.0, |span| span.1);
+        match &var_decl.value {
+            // The selector comes before the span of the branch value
+            Some(VarValue::Branch(branch)) => {
+                self.reference_branch(
+                    branch.enum_type.as_deref(),
+                    &branch.cases,
+                    (from, branch.span.0),
+                );
+            }
+            _ => {
+                let span = self.type_span(&var_decl.type_name, from, var_decl.span.1);
+                self.reference_type(&var_decl.type_name, span);
+            }
+        }
+        visit::walk_var_decl(self, var_decl);
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDecl) {
+        let from = const_decl
+            .name_span
+            .map_or(const_decl.span.0, |span| span.1);
+        let span = self.type_span(&const_decl.type_name, from, const_decl.span.1);
+        self.reference_type(&const_decl.type_name, span);
+        visit::walk_const_decl(self, const_decl);
+    }
+
+    fn visit_var_value(&mut self, value: &VarValue) {
+        if let VarValue::EnumMember(enum_name, member, span) = value {
+            self.reference_enum_member(enum_name, member, *span);
+        }
+        visit::walk_var_value(self, value);
+    }
+
+    fn visit_function_decl(&mut self, function: &FunctionDecl) {
+        if let Some(return_type) = &function.return_type {
+            let arrow = self.source[function.span.0..function.span.1]
+                .rfind("->")
+                .map_or(function.span.0, |arrow| function.span.0 + arrow);
+            let span = self.type_span(return_type, arrow, function.span.1);
+            self.reference_type(return_type, span);
+        }
+        visit::walk_function_decl(self, function);
+    }
+
+    fn visit_param(&mut self, param: &Param) {
+        let span = self.type_span(
+            &param.type_name,
+            param.span.0 + param.name.len(),
+            param.span.1,
+        );
+        self.reference_type(&param.type_name, span);
+    }
+
+    fn visit_branch_def(&mut self, branch: &BranchDef) {
+        self.reference_branch(branch.enum_type.as_deref(), &branch.cases, branch.span);
+        visit::walk_branch_def(self, branch);
+    }
+}
+
+/// Branches declared inside a node, including inside `if` bodies.
+struct LocalBranches<'a>(Vec<&'a BranchDef>);
+
+impl<'a> LocalBranches<'a> {
+    fn visit_node_def(&mut self, node: &'a NodeDef) {
+        self.visit_stmts(&node.body);
+    }
+
+    fn visit_stmts(&mut self, stmts: &'a [NodeStmt]) {
+        for stmt in stmts {
+            match stmt {
+                NodeStmt::Branch(branch) => self.0.push(branch),
+                NodeStmt::IfElse(if_else) => {
+                    self.visit_stmts(&if_else.then_body);
+                    if let Some(else_body) = &if_else.else_body {
+                        self.visit_stmts(else_body);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A branch case starts with its condition name.
+fn case_condition_span(case: &BranchCase) -> Option<Span> {
+    (!case.condition.is_empty()).then(|| (case.span.0, case.span.0 + case.condition.len()))
+}
+
+/// The first occurrence of `word` in `source[range]` that is not part of a longer name.
+fn find_word(source: &str, range: Span, word: &str) -> Option<Span> {
+    let text = source.get(range.0..range.1)?;
+    let is_boundary = |ch: Option<char>| !ch.is_some_and(is_identifier_continue);
+
+    text.match_indices(word).find_map(|(index, _)| {
+        let before = text[..index].chars().next_back();
+        let after = text[index + word.len()..].chars().next();
+        (is_boundary(before) && is_boundary(after))
+            .then(|| (range.0 + index, range.0 + index + word.len()))
+    })
+}
@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use mortar_compiler::Language;
+use mortar_lsp::backend::{IndexedDocument, apply_content_change, code_actions};
+use ropey::Rope;
+use tower_lsp_server::lsp_types::*;
+
+fn uri(name: &str) -> Uri {
+    format!("file:///{}", name).parse().unwrap()
+}
+
+/// The range of the `nth` occurrence of `needle`.
+fn range_of(content: &str, needle: &str, nth: usize) -> Range {
+    let offset = content.match_indices(needle).nth(nth).unwrap().0;
+    let position = |offset: usize| {
+        let before = &content[..offset];
+        Position::new(
+            before.matches('\n').count() as u32,
+            before.rsplit('\n').next().unwrap().encode_utf16().count() as u32,
+        )
+    };
+    Range::new(position(offset), position(offset + needle.len()))
+}
+
+fn actions(content: &str, needle: &str, nth: usize) -> Vec<CodeAction> {
+    actions_with(content, needle, nth, &[])
+}
+
+fn actions_with(
+    content: &str,
+    needle: &str,
+    nth: usize,
+    others: &[(Uri, String)],
+) -> Vec<CodeAction> {
+    code_actions(
+        &uri("a.mortar"),
+        content,
+        range_of(content, needle, nth),
+        &others
+            .iter()
+            .map(|(uri, content)| (uri.clone(), Arc::new(IndexedDocument::new(content.clone()))))
+            .collect::<Vec<_>>(),
+        Language::English,
+    )
+    .into_iter()
+    .map(|action| match action {
+        CodeActionOrCommand::CodeAction(action) => action,
+        CodeActionOrCommand::Command(_) => panic!("expected a code action"),
+    })
+    .collect()
+}
+
+fn action<'a>(actions: &'a [CodeAction], title: &str) -> &'a CodeAction {
+    actions
+        .iter()
+        .find(|action| action.title == title)
+        .unwrap_or_else(|| {
+            let titles: Vec<_> = actions.iter().map(|action| &action.title).collect();
+            panic!("no action `{}` among {:?}", title, titles)
+        })
+}
+
+/// `content` after the edits `action` makes to `uri`.
+fn apply(content: &str, action: &CodeAction, uri: &Uri) -> String {
+    let mut edits = action.edit.as_ref().unwrap().changes.as_ref().unwrap()[uri].clone();
+    edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+
+    let mut rope = Rope::from_str(content);
+    for edit in edits.iter().rev() {
+        apply_content_change(
+            &mut rope,
+            &TextDocumentContentChangeEvent {
+                range: Some(edit.range),
+                range_length: None,
+                text: edit.new_text.clone(),
+            },
+        );
+    }
+    rope.to_string()
+}
+
+#[test]
+fn test_declare_missing_function_with_argument_types() {
+    let source = r#"let gold: Number = 0
+fn play_sound(file: String)
+
+node Shop {
+    text: "Welcome"
+    with events: [
+        0, buy_item("sword", gold, true)
+    ]
+}
+"#;
+    let actions = actions(source, "buy_item", 0);
+    let declare = action(&actions, "Declare function `buy_item`");
+    assert_eq!(declare.kind, Some(CodeActionKind::QUICKFIX));
+    assert_eq!(declare.diagnostics.as_ref().unwrap().len(), 1);
+    assert_eq!(
+        apply(source, declare, &uri("a.mortar")),
+        source.replace(
+            "fn play_sound(file: String)\n",
+            "fn play_sound(file: String)\nfn buy_item(arg1: String, gold: Number, arg3: Bool)\n"
+        )
+    );
+}
+
+#[test]
+fn test_declare_missing_function_returning_what_its_place_needs() {
+    let source = "node Start {\n    text: $\"Hi {player_name()}\"\n}\n";
+    let interpolation_actions = actions(source, "player_name", 0);
+    assert_eq!(
+        apply(
+            source,
+            action(&interpolation_actions, "Declare function `player_name`"),
+            &uri("a.mortar")
+        ),
+        format!("{}\nfn player_name() -> String\n", source)
+    );
+
+    let source = "node Start {\n    choice: [\n        (\"Go\").when(can_go()) -> return\n    ]\n}";
+    let when_actions = actions(source, "can_go", 0);
+    assert_eq!(
+        apply(
+            source,
+            action(&when_actions, "Declare function `can_go`"),
+            &uri("a.mortar")
+        ),
+        format!("{}\n\nfn can_go() -> Bool\n", source)
+    );
+}
+
+#[test]
+fn test_create_stub_node_for_unknown_target() {
+    let source = "node Start {\n    text: \"Hi\"\n} -> Ending\n";
+    let actions = actions(source, "Ending", 0);
+    assert_eq!(
+        apply(
+            source,
+            action(&actions, "Create node `Ending`"),
+            &uri("a.mortar")
+        ),
+        format!("{}\nnode Ending {{\n    text: \"\"\n}}\n", source)
+    );
+}
+
+#[test]
+fn test_fixes_offered_despite_syntax_errors_elsewhere() {
+    let source = "let = 3\n\nfn PlaySound()\n\nnode Start {\n    text: \"Hi\"\n} -> Ending\n";
+    let actions = actions(source, "Ending", 0);
+    assert!(actions.iter().any(|a| a.title == "Create node `Ending`"));
+
+    let actions = actions_with(source, "PlaySound", 0, &[]);
+    assert!(actions.iter().any(|a| a.title.contains("`play_sound`")));
+}
+
+#[test]
+fn test_rename_to_conventional_case_renames_uses() {
+    let source = r#"fn PlaySound(file: String)
+
+node start_scene {
+    text: "Hi"
+    with events: [
+        0, PlaySound("a.wav")
+    ]
+} -> start_scene
+"#;
+    let actions = actions(source, "PlaySound", 0);
+    let renamed = apply(
+        source,
+        action(&actions, "Rename to `play_sound`"),
+        &uri("a.mortar"),
+    );
+    assert_eq!(renamed, source.replace("PlaySound", "play_sound"));
+
+    let actions = actions_with(
+        source,
+        "start_scene",
+        0,
+        &[(
+            uri("b.mortar"),
+            "node Other {} -> start_scene\n".to_string(),
+        )],
+    );
+    let rename = action(&actions, "Rename to `StartScene`");
+    assert_eq!(
+        apply(source, rename, &uri("a.mortar")),
+        source.replace("start_scene", "StartScene")
+    );
+    assert_eq!(
+        apply("node Other {} -> start_scene\n", rename, &uri("b.mortar")),
+        "node Other {} -> StartScene\n"
+    );
+}
+
+#[test]
+fn test_remove_unused_declarations() {
+    let source = r#"fn unused_helper()
+
+let spare: Number = 3
+
+enum Weather {
+    sunny
+    rainy
+}
+
+node Start {
+    text: "Hi"
+}
+"#;
+    let actions_at = |needle| actions(source, needle, 0);
+
+    assert_eq!(
+        apply(
+            source,
+            action(
+                &actions_at("unused_helper"),
+                "Remove unused `unused_helper`"
+            ),
+            &uri("a.mortar")
+        ),
+        source.replace("fn unused_helper()\n\n", "")
+    );
+    assert_eq!(
+        apply(
+            source,
+            action(&actions_at("spare"), "Remove unused `spare`"),
+            &uri("a.mortar")
+        ),
+        source.replace("let spare: Number = 3\n\n", "")
+    );
+    assert_eq!(
+        apply(
+            source,
+            action(&actions_at("Weather"), "Remove unused `Weather`"),
+            &uri("a.mortar")
+        ),
+        source.replace("enum Weather {\n    sunny\n    rainy\n}\n\n", "")
+    );
+}
+
+#[test]
+fn test_add_missing_enum_branch_cases() {
+    let source = r#"enum Weather {
+    sunny
+    rainy
+    snowy
+}
+
+let weather: Weather
+
+let sky: branch<weather> [
+    rainy, "grey"
+]
+
+node Start {
+    text: $"The sky is {sky}."
+}
+"#;
+    let actions = actions(source, "branch", 0);
+    let add = action(&actions, "Add missing cases `sunny`, `snowy`");
+    assert_eq!(
+        apply(source, add, &uri("a.mortar")),
+        source.replace(
+            "    rainy, \"grey\"\n",
+            "    rainy, \"grey\"\n    sunny, \"\"\n    snowy, \"\"\n"
+        )
+    );
+
+    // A branch with every case has nothing to add
+    let complete = source.replace(
+        "    rainy, \"grey\"\n",
+        "    rainy, \"grey\"\n    sunny, \"blue\"\n    snowy, \"white\"\n",
+    );
+    assert!(
+        actions_with(&complete, "branch", 0, &[])
+            .iter()
+            .all(|action| !action.title.starts_with("Add missing cases"))
+    );
+}
+
+#[test]
+fn test_no_actions_away_from_problems() {
+    let source = "fn PlaySound()\n\nnode Start {\n    text: \"Hi\"\n} -> Ending\n";
+    assert!(actions(source, "text", 0).is_empty());
+}
@@ -0,0 +1,205 @@
+use mortar_lsp::backend::{CompletionContext, completion_context, completion_items};
+use tower_lsp_server::lsp_types::*;
+
+const DECLARATIONS: &str = r#"
+enum Mood {
+    happy
+    sad
+}
+
+let mood: Mood
+let score: Number = 0
+let is_brave: Bool
+const title: String = "Hero"
+
+fn play_sound(file: String, volume: Number)
+fn get_name() -> String
+
+event Ding {
+    index: 0
+    action: play_sound("ding.wav", 1)
+}
+
+timeline Intro {
+    run Ding
+}
+
+node Start {
+    text: "Hello"
+}
+
+node Ending {
+    text: "Bye"
+}
+"#;
+
+/// The document with `‸` removed, and the position it marked.
+fn at(marked: &str) -> (String, Position) {
+    let source = format!("{}{}", DECLARATIONS, marked);
+    let offset = source.find('‸').expect("source should mark the cursor");
+    let before = &source[..offset];
+    let line = before.matches('\n').count() as u32;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let column = before[line_start..].encode_utf16().count() as u32;
+    (source.replace('‸', ""), Position::new(line, column))
+}
+
+fn context(marked: &str) -> Option<CompletionContext> {
+    let (source, position) = at(marked);
+    completion_context(&source, position)
+}
+
+fn labels(marked: &str) -> Vec<String> {
+    let (source, position) = at(marked);
+    completion_items(&source, position)
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+}
+
+#[test]
+fn test_enum_variants_after_dot() {
+    assert_eq!(
+        context("let other: Mood = Mood.‸"),
+        Some(CompletionContext::EnumMember("Mood".to_string()))
+    );
+    assert_eq!(labels("let other: Mood = Mood.‸"), ["happy", "sad"]);
+    assert_eq!(labels("node A {\n    mood = Mood.s‸\n}"), ["sad"]);
+    assert!(labels("node A {\n    mood = Unknown.‸\n}").is_empty());
+}
+
+#[test]
+fn test_nodes_after_arrow() {
+    let choice = "node A {\n    choice: [\n        \"Go\" -> ‸\n    ]\n}";
+    assert_eq!(context(choice), Some(CompletionContext::JumpTarget));
+    assert_eq!(labels(choice), ["Start", "Ending", "A", "return", "break"]);
+
+    assert_eq!(labels("node A {\n    text: \"Hi\"\n} -> E‸"), ["Ending"]);
+}
+
+#[test]
+fn test_events_and_timelines_after_run() {
+    assert_eq!(labels("node A {\n    run ‸\n}"), ["Ding", "Intro"]);
+    assert_eq!(labels("timeline Outro {\n    run I‸\n}"), ["Intro"]);
+}
+
+#[test]
+fn test_functions_with_parameter_snippets_in_with_events() {
+    let source = "node A {\n    text: \"Hi\"\n    with events: [\n        0, ‸\n    ]\n}";
+    assert_eq!(context(source), Some(CompletionContext::EventAction));
+
+    let (source, position) = at(source);
+    let items = completion_items(&source, position);
+    let play = items
+        .iter()
+        .find(|item| item.label == "play_sound")
+        .unwrap();
+    assert_eq!(
+        play.insert_text.as_deref(),
+        Some("play_sound(${1:file}, ${2:volume})")
+    );
+    assert_eq!(play.insert_text_format, Some(InsertTextFormat::SNIPPET));
+    assert_eq!(
+        play.detail.as_deref(),
+        Some("fn play_sound(file: String, volume: Number)")
+    );
+    let get_name = items.iter().find(|item| item.label == "get_name").unwrap();
+    assert_eq!(get_name.insert_text.as_deref(), Some("get_name()"));
+
+    let list_start = "node A {\n    text: \"Hi\"\n    with events: [\n        ‸\n    ]\n}";
+    assert_eq!(context(list_start), Some(CompletionContext::WithTarget));
+    assert!(labels(list_start).contains(&"Ding".to_string()));
+}
+
+#[test]
+fn test_event_action_field() {
+    assert_eq!(
+        labels("event Boom {\n    index: 1\n    action: p‸\n}"),
+        ["play_sound"]
+    );
+}
+
+#[test]
+fn test_variables_in_conditions() {
+    let condition = "node A {\n    if s‸\n}";
+    assert_eq!(context(condition), Some(CompletionContext::Value));
+    assert_eq!(labels(condition), ["score"]);
+
+    assert_eq!(
+        labels("node A {\n    if score > 1 && is_‸\n}"),
+        ["is_brave"]
+    );
+    assert_eq!(
+        labels("node A {\n    choice: [\n        (\"Go\").when(is_‸) -> Start\n    ]\n}"),
+        ["is_brave"]
+    );
+    assert_eq!(
+        labels("node A {\n    choice: [\n        (\"Go\").w‸\n    ]\n}"),
+        ["when"]
+    );
+}
+
+#[test]
+fn test_variables_and_functions_in_interpolations() {
+    let source = "node A {\n    text: $\"Hi {t‸\n}";
+    assert_eq!(context(source), Some(CompletionContext::Interpolation));
+    assert_eq!(labels(source), ["title"]);
+    assert_eq!(
+        labels("node A {\n    text: $\"Hi {get‸}\"\n}"),
+        ["get_name"]
+    );
+
+    // Plain text of strings gets nothing
+    assert_eq!(context("node A {\n    text: \"Hi s‸\"\n}"), None);
+    assert_eq!(context("node A {\n    text: $\"Hi {title} s‸\"\n}"), None);
+}
+
+#[test]
+fn test_branch_cases_from_selector_enum() {
+    let source = "let feeling: branch<mood> [\n    ‸\n]";
+    assert_eq!(
+        context(source),
+        Some(CompletionContext::BranchCase(Some("mood".to_string())))
+    );
+    assert_eq!(labels(source), ["happy", "sad"]);
+    assert_eq!(
+        labels("let feeling: branch<mood> [\n    happy, \"Glad\"\n    ‸\n]"),
+        ["happy", "sad"]
+    );
+
+    // Boolean branches are keyed by Bool variables
+    assert_eq!(labels("let bold: branch [\n    ‸\n]"), ["is_brave"]);
+}
+
+#[test]
+fn test_keywords_by_block() {
+    let top_level = labels("‸");
+    assert!(top_level.contains(&"node".to_string()));
+    assert!(top_level.contains(&"enum".to_string()));
+
+    let in_node = labels("node A {\n    ‸\n}");
+    assert!(in_node.contains(&"text".to_string()));
+    assert!(in_node.contains(&"choice".to_string()));
+    // Variables can be assigned
+    assert!(in_node.contains(&"score".to_string()));
+
+    let in_else = labels("node A {\n    if is_brave {\n    } else {\n        te‸\n    }\n}");
+    assert_eq!(in_else, ["text"]);
+
+    assert_eq!(labels("timeline T {\n    wa‸\n}"), ["wait"]);
+    assert_eq!(labels("event E {\n    ac‸\n}"), ["action"]);
+}
+
+#[test]
+fn test_types_in_declarations() {
+    assert_eq!(context("let x: ‸"), Some(CompletionContext::Type));
+    assert_eq!(labels("let x: M‸"), ["Mood"]);
+    assert_eq!(labels("fn f(a: N‸)"), ["Number"]);
+    assert_eq!(labels("fn f() -> S‸"), ["String"]);
+}
+
+#[test]
+fn test_nothing_in_comments() {
+    assert_eq!(context("// run ‸"), None);
+    assert!(labels("node A {\n    text: \"Hi\" // Mood.‸\n}").is_empty());
+}
@@ -0,0 +1,221 @@
+use mortar_lsp::backend::{
+    IndexedDocument, find_definition, find_definition_in, find_mortar_files, symbol_at,
+};
+use std::fs;
+use std::sync::Arc;
+use tower_lsp_server::lsp_types::{Location, Position, Range, Uri};
+
+const SOURCE: &str = r#"enum Mood {
+    happy
+    sad
+}
+
+let mood: Mood = Mood.happy
+let ready: Bool = true
+const Greeting: String = "Hi"
+
+let place: branch<mood> [
+    happy, "meadow"
+    sad, "swamp"
+]
+
+fn play(file: String)
+fn is_open() -> Bool
+
+event Ding { action: play(Greeting) }
+timeline Intro { run Ding }
+
+node Start {
+    text: $"You are in {place}."
+    with Ding
+    run Intro
+    object: branch [
+        ready, "a box"
+    ]
+    text: $"There is {object} here."
+    if ready && mood == Mood.sad {
+        text: "Hmm"
+    }
+    choice: [
+        "Go" when is_open() -> Next
+    ]
+} -> Next
+
+node Next {}
+"#;
+
+fn uri(name: &str) -> Uri {
+    format!("file:///{}", name).parse().unwrap()
+}
+
+/// `content` as the workspace index keeps it for `uri`.
+fn indexed(uri: Uri, content: &str) -> (Uri, Arc<IndexedDocument>) {
+    (uri, Arc::new(IndexedDocument::new(content.to_string())))
+}
+
+/// Position of the `nth` occurrence of `needle`, plus `shift` characters.
+fn position_of(content: &str, needle: &str, nth: usize, shift: u32) -> Position {
+    let offset = content
+        .match_indices(needle)
+        .nth(nth)
+        .unwrap_or_else(|| panic!("`{}` occurs fewer than {} times", needle, nth + 1))
+        .0;
+    let before = &content[..offset];
+    let line = before.matches('\n').count() as u32;
+    let column = before.rsplit('\n').next().unwrap().chars().count() as u32;
+    Position::new(line, column + shift)
+}
+
+/// The source text a definition lookup from the `nth` `needle` lands on, and its line.
+fn definition_of(needle: &str, nth: usize) -> (String, u32) {
+    let position = position_of(SOURCE, needle, nth, 1);
+    let location = find_definition(&uri("a.mortar"), SOURCE, position)
+        .unwrap_or_else(|| panic!("no definition for `{}` #{}", needle, nth));
+    (text_at(SOURCE, location.range), location.range.start.line)
+}
+
+fn text_at(content: &str, range: Range) -> String {
+    assert_eq!(range.start.line, range.end.line);
+    let line = content.lines().nth(range.start.line as usize).unwrap();
+    line.chars()
+        .skip(range.start.character as usize)
+        .take((range.end.character - range.start.character) as usize)
+        .collect()
+}
+
+#[test]
+fn test_definition_of_nodes() {
+    assert_eq!(definition_of("Next", 0), ("Next".to_string(), 36));
+    assert_eq!(definition_of("Next", 1), ("Next".to_string(), 36));
+}
+
+#[test]
+fn test_definition_of_functions_events_and_timelines() {
+    assert_eq!(definition_of("play(", 1), ("play".to_string(), 14));
+    assert_eq!(definition_of("is_open", 1), ("is_open".to_string(), 15));
+    assert_eq!(definition_of("Ding", 1), ("Ding".to_string(), 17));
+    assert_eq!(definition_of("Ding", 2), ("Ding".to_string(), 17));
+    assert_eq!(definition_of("Intro", 1), ("Intro".to_string(), 18));
+}
+
+#[test]
+fn test_definition_of_values() {
+    assert_eq!(definition_of("Greeting", 1), ("Greeting".to_string(), 7));
+    assert_eq!(definition_of("ready", 1), ("ready".to_string(), 6));
+    assert_eq!(definition_of("ready", 2), ("ready".to_string(), 6));
+    assert_eq!(definition_of("mood", 1), ("mood".to_string(), 5));
+    assert_eq!(definition_of("mood", 2), ("mood".to_string(), 5));
+}
+
+#[test]
+fn test_definition_of_branch_placeholders() {
+    assert_eq!(definition_of("{place}", 0), ("place".to_string(), 9));
+    assert_eq!(definition_of("{object}", 0), ("object".to_string(), 24));
+}
+
+#[test]
+fn test_definition_of_enums_and_variants() {
+    assert_eq!(definition_of("Mood", 1), ("Mood".to_string(), 0));
+    assert_eq!(definition_of("Mood", 2), ("Mood".to_string(), 0));
+    assert_eq!(definition_of("happy", 1), ("happy".to_string(), 1));
+    assert_eq!(definition_of("happy", 2), ("happy".to_string(), 1));
+    assert_eq!(definition_of("sad", 1), ("sad".to_string(), 2));
+    assert_eq!(definition_of("sad", 2), ("sad".to_string(), 2));
+}
+
+#[test]
+fn test_no_definition_outside_names() {
+    let uri = uri("a.mortar");
+    assert_eq!(
+        find_definition(&uri, SOURCE, position_of(SOURCE, "\"Hmm\"", 0, 1)),
+        None
+    );
+    assert_eq!(
+        find_definition(&uri, SOURCE, position_of(SOURCE, "node", 0, 1)),
+        None
+    );
+}
+
+#[test]
+fn test_definition_in_another_file() {
+    let content = "node Start {\n    run Fanfare\n    text: $\"{score}\"\n} -> Ending\n";
+    let others = vec![
+        indexed(uri("empty.mortar"), "node Other {}\n"),
+        indexed(
+            uri("shared.mortar"),
+            "let score: Number = 0\n\ntimeline Fanfare {\n    wait 1\n}\n\nnode Ending {}\n",
+        ),
+    ];
+
+    let lookup = |needle: &str| {
+        let position = position_of(content, needle, 0, 1);
+        assert_eq!(
+            find_definition(&uri("main.mortar"), content, position),
+            None
+        );
+        let symbol = symbol_at(content, position).unwrap();
+        find_definition_in(&symbol, &others).unwrap()
+    };
+
+    let shared = uri("shared.mortar");
+    assert_eq!(
+        lookup("Fanfare"),
+        Location::new(
+            shared.clone(),
+            Range::new(Position::new(2, 9), Position::new(2, 16))
+        )
+    );
+    assert_eq!(
+        lookup("score"),
+        Location::new(
+            shared.clone(),
+            Range::new(Position::new(0, 4), Position::new(0, 9))
+        )
+    );
+    assert_eq!(
+        lookup("Ending"),
+        Location::new(
+            shared,
+            Range::new(Position::new(6, 5), Position::new(6, 11))
+        )
+    );
+}
+
+#[test]
+fn test_definition_uses_utf16_positions() {
+    let content = "node 开始 {\n    text: \"🎉\"\n} -> 结束\nnode 结束 {}\n";
+    let location = find_definition(&uri("a.mortar"), content, Position::new(2, 6)).unwrap();
+
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(3, 5), Position::new(3, 7))
+    );
+}
+
+#[test]
+fn test_workspace_files_skip_hidden_and_build_directories() {
+    let root = std::env::temp_dir().join(format!("mortar-lsp-workspace-{}", std::process::id()));
+    for dir in ["story/act1", ".git", "target"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for file in [
+        "main.mortar",
+        "story/act1/intro.mortar",
+        "story/notes.txt",
+        ".git/old.mortar",
+        "target/copy.mortar",
+    ] {
+        fs::write(root.join(file), "").unwrap();
+    }
+
+    let files = find_mortar_files(&root);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        files,
+        vec![
+            root.join("main.mortar"),
+            root.join("story/act1/intro.mortar")
+        ]
+    );
+}
@@ -1 +1,112 @@
+use std::fs;
 
+use mortar_compiler::Language;
+use mortar_lsp::backend::parse_with_diagnostics;
+use tower_lsp_server::UriExt;
+use tower_lsp_server::lsp_types::{DiagnosticSeverity, NumberOrString, Uri};
+
+#[test]
+fn test_duplicate_node_reported_once_with_related_location() {
+    let uri: Uri = "file:///test.mortar".parse().unwrap();
+    let content = r#"node Start {
+    text: "First"
+}
+
+node Start {
+    text: "Second"
+}
+"#;
+
+    let (diagnostics, program) = parse_with_diagnostics(&uri, content, Language::English);
+
+    assert!(program.is_some());
+
+    let duplicates: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.message.contains("defined multiple times"))
+        .collect();
+    assert_eq!(duplicates.len(), 1);
+
+    let duplicate = duplicates[0];
+    assert_eq!(duplicate.range.start.line, 4);
+    assert_eq!(
+        duplicate.code,
+        Some(NumberOrString::String("M0201".to_string()))
+    );
+
+    let related = duplicate.related_information.as_ref().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].location.uri, uri);
+    assert_eq!(related[0].location.range.start.line, 0);
+}
+
+#[test]
+fn test_syntax_error_carries_parse_error_code() {
+    let uri: Uri = "file:///test.mortar".parse().unwrap();
+    let content = "let \"score\": Number = 0\n";
+
+    let (diagnostics, _program) = parse_with_diagnostics(&uri, content, Language::English);
+
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.code == Some(NumberOrString::String("M0002".to_string())))
+    );
+}
+
+#[test]
+fn test_project_config_found_under_non_ascii_folder() {
+    // The URI of this folder is percent-encoded
+    let root = std::env::temp_dir().join(format!("mortar 项目 {}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("mortar.toml"),
+        "[lints]\nunused_function = \"allow\"\n",
+    )
+    .unwrap();
+    let uri = Uri::from_file_path(root.join("story.mortar")).unwrap();
+    assert!(uri.as_str().contains('%'));
+
+    let content = "fn unused_helper()\n\nnode Start {\n    text: \"Hi\"\n}\n";
+    let (diagnostics, _) = parse_with_diagnostics(&uri, content, Language::English);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| !d.message.contains("unused_helper")),
+        "{:?}",
+        diagnostics
+    );
+}
+
+#[test]
+fn test_invalid_project_config_is_reported() {
+    let root = std::env::temp_dir().join(format!("mortar-bad-config-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("mortar.toml"),
+        "[lints]\nunused_varable = \"allow\"\n",
+    )
+    .unwrap();
+    let uri = Uri::from_file_path(root.join("story.mortar")).unwrap();
+
+    let content = "fn unused_helper()\n\nnode Start {\n    text: \"Hi\"\n}\n";
+    let (diagnostics, _) = parse_with_diagnostics(&uri, content, Language::English);
+    fs::remove_dir_all(&root).unwrap();
+
+    let config_error = &diagnostics[0];
+    assert_eq!(
+        config_error.message,
+        "Failed to load mortar.toml: Unknown lint 'unused_varable'"
+    );
+    assert_eq!(config_error.severity, Some(DiagnosticSeverity::ERROR));
+    let related = config_error.related_information.as_ref().unwrap();
+    assert!(related[0].location.uri.as_str().ends_with("/mortar.toml"));
+    // The lints of the file still run at their default levels
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.message.contains("unused_helper"))
+    );
+}
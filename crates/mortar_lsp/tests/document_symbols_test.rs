@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use mortar_lsp::backend::{IndexedDocument, document_symbols, workspace_symbols};
+use tower_lsp_server::lsp_types::{DocumentSymbol, OneOf, Position, Range, SymbolKind, Uri};
+
+const SOURCE: &str = r#"fn play_sound(file: String) -> Bool
+let gold: Number = 0
+pub const TITLE: String = "Tale"
+
+enum Mood {
+    calm
+    angry
+}
+
+event Thunder {
+    action: play_sound("thunder.wav")
+}
+
+timeline Storm {
+    run Thunder
+}
+
+node Start {
+    text: "Hello."
+    if gold > 5 {
+        choice: [
+            "Buy" -> Shop,
+            "More" -> [
+                "Leave" -> return
+            ]
+        ]
+    }
+}
+
+node Shop {}
+"#;
+
+fn uri(name: &str) -> Uri {
+    format!("file:///{}", name).parse().unwrap()
+}
+
+/// `content` as the workspace index keeps it for `uri`.
+fn indexed(uri: Uri, content: &str) -> (Uri, Arc<IndexedDocument>) {
+    (uri, Arc::new(IndexedDocument::new(content.to_string())))
+}
+
+fn outline(symbols: &[DocumentSymbol]) -> Vec<String> {
+    let mut lines = Vec::new();
+    fn walk(symbols: &[DocumentSymbol], depth: usize, lines: &mut Vec<String>) {
+        for symbol in symbols {
+            lines.push(format!(
+                "{}{} {:?} {}",
+                "  ".repeat(depth),
+                symbol.name,
+                symbol.kind,
+                symbol.detail.as_deref().unwrap_or("")
+            ));
+            walk(symbol.children.as_deref().unwrap_or(&[]), depth + 1, lines);
+        }
+    }
+    walk(symbols, 0, &mut lines);
+    lines
+        .iter()
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn test_document_symbols_cover_every_top_level_kind() {
+    assert_eq!(
+        outline(&document_symbols(SOURCE)),
+        vec![
+            "play_sound Function fn play_sound(file: String) -> Bool",
+            "gold Variable Number",
+            "TITLE Constant String",
+            "Mood Enum",
+            "  calm EnumMember",
+            "  angry EnumMember",
+            "Thunder Event play_sound(\"thunder.wav\")",
+            "Storm Event timeline",
+            "Start Class",
+            "  if gold > 5 Namespace",
+            "    choice Array",
+            "      \"Buy\" EnumMember -> Shop",
+            "      \"More\" EnumMember",
+            "        \"Leave\" EnumMember -> return",
+            "Shop Class",
+        ]
+    );
+}
+
+#[test]
+fn test_document_symbol_ranges_come_from_spans() {
+    let symbols = document_symbols(SOURCE);
+
+    let mood = &symbols[3];
+    assert_eq!(
+        mood.range,
+        Range::new(Position::new(4, 0), Position::new(7, 1))
+    );
+    assert_eq!(
+        mood.selection_range,
+        Range::new(Position::new(4, 5), Position::new(4, 9))
+    );
+    assert_eq!(
+        mood.children.as_ref().unwrap()[1].selection_range,
+        Range::new(Position::new(6, 4), Position::new(6, 9))
+    );
+
+    let start = &symbols[6];
+    assert_eq!(start.range.start, Position::new(17, 0));
+    assert_eq!(start.range.end, Position::new(27, 1));
+    assert_eq!(
+        start.children.as_ref().unwrap()[0].range.start,
+        Position::new(19, 4)
+    );
+}
+
+#[test]
+fn test_document_symbols_survive_syntax_errors() {
+    let names: Vec<_> = document_symbols("node Start {\n    text: \n}\n\nnode End {}\n")
+        .into_iter()
+        .map(|symbol| symbol.name)
+        .collect();
+
+    assert_eq!(names, vec!["Start", "End"]);
+}
+
+#[test]
+fn test_workspace_symbols_match_fuzzily_across_documents() {
+    let documents = vec![
+        indexed(uri("a.mortar"), SOURCE),
+        indexed(
+            uri("b.mortar"),
+            "node ShopKeeper {}\nenum Shape {\n    square\n}\n",
+        ),
+    ];
+
+    let found: Vec<_> = workspace_symbols("shp", &documents)
+        .into_iter()
+        .map(|symbol| {
+            let OneOf::Left(location) = symbol.location else {
+                panic!("expected a location");
+            };
+            (symbol.name, location.uri, location.range.start.line)
+        })
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            ("Shop".to_string(), uri("a.mortar"), 29),
+            ("ShopKeeper".to_string(), uri("b.mortar"), 0),
+            ("Shape".to_string(), uri("b.mortar"), 1),
+        ]
+    );
+}
+
+#[test]
+fn test_workspace_symbols_include_enum_variants() {
+    let documents = vec![indexed(uri("a.mortar"), SOURCE)];
+    let symbols = workspace_symbols("ANGRY", &documents);
+
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].kind, SymbolKind::ENUM_MEMBER);
+    assert_eq!(symbols[0].container_name.as_deref(), Some("Mood"));
+    assert_eq!(workspace_symbols("", &documents).len(), 10);
+}
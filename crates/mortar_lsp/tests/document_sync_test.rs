@@ -0,0 +1,135 @@
+use mortar_compiler::{IncrementalParse, Language};
+use mortar_lsp::backend::{
+    PendingEdit, apply_content_change, diagnose_parse, parse_with_diagnostics,
+};
+use ropey::Rope;
+use tower_lsp_server::lsp_types::*;
+
+fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: Some(Range::new(
+            Position::new(start.0, start.1),
+            Position::new(end.0, end.1),
+        )),
+        range_length: None,
+        text: text.to_string(),
+    }
+}
+
+fn applied(text: &str, changes: &[TextDocumentContentChangeEvent]) -> String {
+    let mut rope = Rope::from_str(text);
+    for change in changes {
+        apply_content_change(&mut rope, change);
+    }
+    rope.to_string()
+}
+
+#[test]
+fn test_change_without_range_replaces_text() {
+    let full = TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: "node B {}".to_string(),
+    };
+    assert_eq!(applied("node A {}\n", &[full]), "node B {}");
+}
+
+#[test]
+fn test_ranged_insert_delete_and_replace() {
+    let text = "node A {\n    text: \"hi\"\n}\n";
+    assert_eq!(
+        applied(text, &[change((1, 11), (1, 11), "oh ")]),
+        "node A {\n    text: \"oh hi\"\n}\n"
+    );
+    assert_eq!(
+        applied(text, &[change((0, 8), (1, 14), "")]),
+        "node A {\n}\n"
+    );
+    assert_eq!(
+        applied(text, &[change((0, 5), (0, 6), "Start")]),
+        "node Start {\n    text: \"hi\"\n}\n"
+    );
+}
+
+#[test]
+fn test_changes_apply_in_order() {
+    let changes = [
+        change((0, 5), (0, 6), "Start"),
+        change((0, 12), (0, 12), " "),
+        change((1, 0), (1, 0), "// end\n"),
+    ];
+    assert_eq!(applied("node A {}\n", &changes), "node Start { }\n// end\n");
+}
+
+#[test]
+fn test_columns_are_utf16() {
+    // `🎉` takes two UTF-16 units, `好` one
+    let text = "text: \"🎉好\"\n";
+    assert_eq!(
+        applied(text, &[change((0, 9), (0, 10), "坏")]),
+        "text: \"🎉坏\"\n"
+    );
+    assert_eq!(
+        applied(text, &[change((0, 7), (0, 9), "")]),
+        "text: \"好\"\n"
+    );
+}
+
+#[test]
+fn test_positions_are_clamped() {
+    let text = "node A {}\r\nnode B {}";
+    // Past the end of a line means its end, before the line break
+    assert_eq!(
+        applied(text, &[change((0, 40), (0, 40), " -> B")]),
+        "node A {} -> B\r\nnode B {}"
+    );
+    // Past the last line means the end of the text
+    assert_eq!(
+        applied(text, &[change((9, 0), (9, 3), "\n")]),
+        "node A {}\r\nnode B {}\n"
+    );
+}
+
+#[test]
+fn test_incremental_diagnostics_match_full_parse() {
+    let uri: Uri = "file:///tmp/sync_test.mortar".parse().unwrap();
+    let before = "node A {\n    text: \"hi\"\n}\n\nnode B {\n    text: \"bye\"\n} -> A\n";
+    let after = "node A {\n    text: \"hi\"\n} -> C\n\nnode B {\n    text: \"bye\"\n} -> A\n";
+
+    let mut parse = IncrementalParse::new(before);
+    assert!(parse.update(after));
+
+    let (expected, _program) = parse_with_diagnostics(&uri, after, Language::English);
+    assert!(!expected.is_empty());
+    assert_eq!(diagnose_parse(&uri, &parse, Language::English), expected);
+}
+
+#[test]
+fn test_pending_edit_covers_every_change() {
+    let before = "node A {\n    text: \"🎉 hi\"\n}\n\nnode B {\n    text: \"bye\"\n}\n";
+    let changes = [
+        change((5, 11), (5, 11), "good"),
+        change((1, 14), (1, 16), "hello"),
+        change((6, 1), (6, 1), " -> A"),
+    ];
+
+    let mut rope = Rope::from_str(before);
+    let mut pending: Option<PendingEdit> = None;
+    for change in &changes {
+        let edit = apply_content_change(&mut rope, change);
+        pending = Some(pending.map_or(edit, |pending| pending.then(edit)));
+    }
+    let after = rope.to_string();
+    assert_eq!(
+        after,
+        "node A {\n    text: \"🎉 hello\"\n}\n\nnode B {\n    text: \"goodbye\"\n} -> A\n"
+    );
+
+    let changed = pending.unwrap().range_in(before);
+    assert_eq!(&before[..changed.start], "node A {\n    text: \"🎉 ");
+    assert_eq!(&before[changed.end..], "\n");
+
+    let mut parse = IncrementalParse::new(before);
+    parse.edit(&after, changed);
+    assert_eq!(parse.program(), IncrementalParse::new(&after).program());
+}
@@ -0,0 +1,57 @@
+use mortar_lsp::backend::{formatting_edits, range_formatting_edits};
+use tower_lsp_server::lsp_types::{FormattingOptions, Position, Range};
+
+fn options() -> FormattingOptions {
+    FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..FormattingOptions::default()
+    }
+}
+
+#[test]
+fn test_formatting_replaces_whole_document() {
+    let content = "nd Start{\ntext:'Hi';\n}\n";
+
+    let edits = formatting_edits(content, &options());
+
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].range.start, Position::new(0, 0));
+    assert_eq!(edits[0].range.end, Position::new(3, 0));
+    assert_eq!(edits[0].new_text, "node Start {\n    text: \"Hi\"\n}\n");
+}
+
+#[test]
+fn test_formatting_respects_client_indentation() {
+    let content = "node Start {\ntext: \"Hi\"\n}\n";
+    let options = FormattingOptions {
+        tab_size: 2,
+        insert_spaces: false,
+        ..FormattingOptions::default()
+    };
+
+    let edits = formatting_edits(content, &options);
+
+    assert_eq!(edits[0].new_text, "node Start {\n\ttext: \"Hi\"\n}\n");
+}
+
+#[test]
+fn test_formatted_or_broken_document_has_no_edits() {
+    assert!(formatting_edits("node Start {\n    text: \"Hi\"\n}\n", &options()).is_empty());
+    assert!(formatting_edits("node Start {\ntext: 42\n}\n", &options()).is_empty());
+}
+
+#[test]
+fn test_range_formatting_only_touches_selected_item() {
+    let content = "nd First{text:'a'}\n\nnd Second{text:'b'}\n";
+    let range = Range::new(Position::new(2, 3), Position::new(2, 5));
+
+    let edits = range_formatting_edits(content, range, &options()).unwrap();
+
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].range,
+        Range::new(Position::new(2, 0), Position::new(2, 19))
+    );
+    assert_eq!(edits[0].new_text, "node Second {\n    text: \"b\"\n}");
+}
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use mortar_compiler::Language;
+use mortar_lsp::backend::{IndexedDocument, hover_info};
+use tower_lsp_server::lsp_types::{HoverContents, Position, Range, Uri};
+
+const SOURCE: &str = r#"// Plays a sound effect.
+// The file is looked up in the audio folder.
+fn play_sound(file: String, volume: Number) -> Bool
+
+/* How many coins the player carries. */
+let gold: Number = 0
+
+let unused = 1 // not a doc comment
+let mood: Mood = Mood.calm
+
+enum Mood {
+    calm
+    // Only after the storm
+    angry
+}
+
+event Thunder {
+    index: 2
+    action: play_sound("thunder.wav", 1)
+    duration: 1.5
+}
+
+node Start {
+    text: "Hello."
+    gold = 10
+    choice: [
+        "Go on" -> Market,
+        "Wait" -> [
+            "Really wait" -> Market
+        ]
+    ]
+}
+
+node Market {
+    gold = 5
+} -> Start
+
+node Orphan {}
+"#;
+
+fn uri(name: &str) -> Uri {
+    format!("file:///{}", name).parse().unwrap()
+}
+
+/// `content` as the workspace index keeps it for `uri`.
+fn indexed(uri: Uri, content: &str) -> (Uri, Arc<IndexedDocument>) {
+    (uri, Arc::new(IndexedDocument::new(content.to_string())))
+}
+
+fn position_of(content: &str, needle: &str, nth: usize) -> Position {
+    let offset = content.match_indices(needle).nth(nth).unwrap().0;
+    let before = &content[..offset];
+    Position::new(
+        before.matches('\n').count() as u32,
+        before.rsplit('\n').next().unwrap().chars().count() as u32 + 1,
+    )
+}
+
+fn hover_text(needle: &str, nth: usize) -> Option<String> {
+    hover_in(SOURCE, needle, nth, &[])
+}
+
+fn hover_in(
+    content: &str,
+    needle: &str,
+    nth: usize,
+    others: &[(Uri, Arc<IndexedDocument>)],
+) -> Option<String> {
+    let hover = hover_info(
+        &uri("story.mortar"),
+        content,
+        position_of(content, needle, nth),
+        others,
+        Language::English,
+    )?;
+    match hover.contents {
+        HoverContents::Markup(markup) => Some(markup.value),
+        _ => panic!("expected markdown"),
+    }
+}
+
+#[test]
+fn test_hover_function_shows_signature_and_doc_comment() {
+    assert_eq!(
+        hover_text("play_sound", 1).unwrap(),
+        "```mortar\nfn play_sound(file: String, volume: Number) -> Bool\n```\n\n\
+         Plays a sound effect.\nThe file is looked up in the audio folder."
+    );
+}
+
+#[test]
+fn test_hover_event_shows_its_definition() {
+    assert_eq!(
+        hover_text("Thunder", 0).unwrap(),
+        "```mortar\nevent Thunder {\n    index: 2\n    action: play_sound(\"thunder.wav\", 1)\n    duration: 1.5\n}\n```"
+    );
+}
+
+#[test]
+fn test_hover_variable_lists_assignments() {
+    assert_eq!(
+        hover_text("gold", 1).unwrap(),
+        "```mortar\nlet gold: Number = 0\n```\n\n\
+         How many coins the player carries.\n\n\
+         **Assigned in**\n\
+         - `Start` (story.mortar:25)\n\
+         - `Market` (story.mortar:35)"
+    );
+}
+
+#[test]
+fn test_hover_ignores_trailing_and_detached_comments() {
+    assert_eq!(
+        hover_text("mood", 0).unwrap(),
+        "```mortar\nlet mood: Mood = Mood.calm\n```"
+    );
+}
+
+#[test]
+fn test_hover_enum_and_variant() {
+    let enum_hover = hover_text("Mood", 2).unwrap();
+    assert!(enum_hover.starts_with("```mortar\nenum Mood {\n    calm\n    angry\n}\n```"));
+
+    let variant_hover = hover_text("angry", 0).unwrap();
+    assert!(variant_hover.starts_with("```mortar\nMood.angry\n\nenum Mood {"));
+    assert!(variant_hover.ends_with("```\n\nOnly after the storm"));
+}
+
+#[test]
+fn test_hover_node_lists_incoming_jumps_and_choices() {
+    assert_eq!(
+        hover_text("Market", 2).unwrap(),
+        "```mortar\nnode Market\n```\n\n\
+         **Reached from**\n\
+         - `Start`: choice \"Go on\" (story.mortar:27)\n\
+         - `Start`: choice \"Really wait\" (story.mortar:29)"
+    );
+    assert!(
+        hover_text("Start", 1)
+            .unwrap()
+            .ends_with("- `Market`: jump (story.mortar:36)")
+    );
+    assert!(
+        hover_text("Orphan", 0)
+            .unwrap()
+            .ends_with("*No jumps or choices lead here*")
+    );
+}
+
+#[test]
+fn test_hover_uses_definitions_from_other_files() {
+    let content = "node Intro {\n    gold = 1\n} -> Start\n";
+    let others = vec![indexed(uri("story.mortar"), SOURCE)];
+
+    let hover = hover_in(content, "Start", 0, &others).unwrap();
+    assert!(hover.contains("- `Intro`: jump (story.mortar:3)"));
+    assert!(hover.contains("- `Market`: jump (story.mortar:36)"));
+
+    let hover = hover_in(content, "gold", 0, &others).unwrap();
+    assert!(hover.starts_with("```mortar\nlet gold: Number = 0\n```"));
+}
+
+#[test]
+fn test_hover_outside_symbols_is_empty() {
+    assert_eq!(hover_text("Hello", 0), None);
+    assert_eq!(hover_text("node", 0), None);
+}
+
+#[test]
+fn test_hover_range_covers_the_name() {
+    let hover = hover_info(
+        &uri("story.mortar"),
+        SOURCE,
+        position_of(SOURCE, "gold", 1),
+        &[],
+        Language::Chinese,
+    )
+    .unwrap();
+
+    assert_eq!(
+        hover.range,
+        Some(Range::new(Position::new(24, 4), Position::new(24, 8)))
+    );
+    match hover.contents {
+        HoverContents::Markup(markup) => assert!(markup.value.contains("**赋值位置**")),
+        _ => panic!("expected markdown"),
+    }
+}
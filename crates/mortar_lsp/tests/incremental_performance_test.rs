@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use mortar_compiler::{IncrementalParse, ParseHandler};
+use mortar_lsp::backend::apply_content_change;
+use ropey::Rope;
+use tower_lsp_server::lsp_types::*;
+
+// A document of about 5000 lines: variables, functions and chained nodes
+fn large_document() -> String {
+    let mut content = String::new();
+    for i in 0..100 {
+        content.push_str(&format!("let score_{}: Number = {}\n", i, i));
+        content.push_str(&format!("fn effect_{}(name: String) -> Bool\n", i));
+    }
+    for i in 0..400 {
+        content.push_str(&format!(
+            r#"
+node scene_{i} {{
+    text: "这是第 {i} 个场景"
+    score_{m} = {i}
+    if score_{m} > 10 {{
+        text: $"分数 {{score_{m}}}"
+    }}
+    choice: [
+        "前进" -> scene_{next},
+        "返回" -> return
+    ]
+}}
+"#,
+            i = i,
+            m = i % 100,
+            next = (i + 1) % 400
+        ));
+    }
+    content
+}
+
+fn ranged_change(line: u32, character: u32, text: &str) -> TextDocumentContentChangeEvent {
+    let position = Position::new(line, character);
+    TextDocumentContentChangeEvent {
+        range: Some(Range::new(position, position)),
+        range_length: None,
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn test_incremental_reparse_performance() {
+    println!("📊 Testing incremental reparsing of a large document");
+
+    let content = large_document();
+    println!("Document lines: {}", content.lines().count());
+
+    // Typing one character at a time in the middle of the document
+    let edit_at = content.find("这是第 200 个场景").unwrap();
+    let edits: Vec<String> = (1..=20)
+        .map(|n| {
+            let typed = "好".repeat(n);
+            format!("{}{}{}", &content[..edit_at], typed, &content[edit_at..])
+        })
+        .collect();
+
+    println!("🟡 Full parse per keystroke...");
+    let start = Instant::now();
+    for source in &edits {
+        assert!(ParseHandler::parse_source_code(source, false).is_ok());
+    }
+    let full_duration = start.elapsed();
+
+    println!("🟠 Incremental update per keystroke...");
+    let mut parse = IncrementalParse::new(&content);
+    let mut reused = 0;
+    let start = Instant::now();
+    for source in &edits {
+        if parse.update(source) {
+            reused += 1;
+        }
+    }
+    let incremental_duration = start.elapsed();
+
+    println!("\n📈 Performance Statistics:");
+    println!("  20 full parses: {:?}", full_duration);
+    println!(
+        "  20 incremental updates: {:?} ({} reused earlier items)",
+        incremental_duration, reused
+    );
+
+    assert_eq!(
+        reused,
+        edits.len(),
+        "Every keystroke should reuse the parse"
+    );
+    assert_eq!(
+        parse.program(),
+        IncrementalParse::new(edits.last().unwrap()).program()
+    );
+
+    println!("✅ Incremental reparse performance test passed!");
+}
+
+#[test]
+fn test_ranged_change_performance() {
+    println!("🔄 Testing ranged document changes against full replacement");
+
+    let content = large_document();
+    let line = content.lines().count() as u32 / 2;
+
+    let start = Instant::now();
+    let mut rope = Rope::from_str(&content);
+    for i in 0..200 {
+        apply_content_change(&mut rope, &ranged_change(line, 4, &(i % 10).to_string()));
+    }
+    let ranged_duration = start.elapsed();
+
+    let start = Instant::now();
+    let mut text = content.clone();
+    let offset = content
+        .lines()
+        .take(line as usize)
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        + 4;
+    for i in 0..200 {
+        text.insert_str(offset, &(i % 10).to_string());
+        rope = Rope::from_str(&text);
+    }
+    let full_duration = start.elapsed();
+
+    println!("200 ranged changes took: {:?}", ranged_duration);
+    println!("200 full replacements took: {:?}", full_duration);
+
+    assert_eq!(rope.len_chars(), text.chars().count());
+    assert!(
+        ranged_duration < Duration::from_millis(500),
+        "Ranged changes too slow: {:?}",
+        ranged_duration
+    );
+
+    println!("✅ Ranged change performance test passed!");
+}
@@ -0,0 +1,260 @@
+use mortar_compiler::Language;
+use mortar_lsp::backend::inlay_hints;
+use tower_lsp_server::lsp_types::*;
+
+fn uri() -> Uri {
+    "file:///a.mortar".parse().unwrap()
+}
+
+/// Where `needle` starts in `content`, or ends if `after` is set.
+fn position_of(content: &str, needle: &str, after: bool) -> Position {
+    let mut offset = content.find(needle).unwrap();
+    if after {
+        offset += needle.len();
+    }
+    let before = &content[..offset];
+    Position::new(
+        before.matches('\n').count() as u32,
+        before.rsplit('\n').next().unwrap().encode_utf16().count() as u32,
+    )
+}
+
+fn whole(content: &str) -> Range {
+    Range::new(
+        Position::new(0, 0),
+        Position::new(content.lines().count() as u32 + 1, 0),
+    )
+}
+
+/// Every hint as its label and position.
+fn hints(content: &str) -> Vec<(String, Position)> {
+    hints_in(content, whole(content))
+}
+
+fn hints_in(content: &str, range: Range) -> Vec<(String, Position)> {
+    inlay_hints(&uri(), content, range, Language::English)
+        .into_iter()
+        .map(|hint| match hint.label {
+            InlayHintLabel::String(label) => (label, hint.position),
+            InlayHintLabel::LabelParts(_) => panic!("expected a plain label"),
+        })
+        .collect()
+}
+
+fn hint(label: &str, position: Position) -> (String, Position) {
+    (label.to_string(), position)
+}
+
+#[test]
+fn test_event_markers_at_their_index_in_text() {
+    let source = r#"fn set_color(color: String)
+fn play_sound(file: String)
+
+node Start {
+    text: "Hello world"
+    with events: [
+        0, set_color("red")
+        6, play_sound("ding.wav")
+    ]
+}
+"#;
+    let hints = hints(source);
+    assert!(hints.contains(&hint("set_color", position_of(source, "Hello", false))));
+    assert!(hints.contains(&hint("play_sound", position_of(source, "world", false))));
+
+    let full = inlay_hints(&uri(), source, whole(source), Language::English);
+    let marker = full
+        .iter()
+        .find(|hint| matches!(&hint.label, InlayHintLabel::String(label) if label == "play_sound"))
+        .unwrap();
+    assert!(matches!(
+        &marker.tooltip,
+        Some(InlayHintTooltip::String(tooltip)) if tooltip == "Event at index 6"
+    ));
+    assert_eq!(marker.kind, None);
+}
+
+#[test]
+fn test_event_markers_count_escapes_and_wide_chars_once() {
+    let source = r#"fn set_color(color: String)
+
+node Start {
+    text: "你\"好\" 世界"
+    with events: [
+        4, set_color("red")
+        5, set_color("blue")
+    ]
+}
+"#;
+    let hints = hints(source);
+    assert!(hints.contains(&hint("set_color", position_of(source, " 世界", false))));
+    assert!(hints.contains(&hint("set_color", position_of(source, "世界", false))));
+}
+
+#[test]
+fn test_event_markers_in_multi_line_text_skip_shared_indentation() {
+    let source = r#"fn ding()
+
+node Start {
+    text: """
+        ab
+          cd
+        """
+    with events: [
+        3, ding()
+    ]
+}
+"#;
+    // Index 3 is the first space kept before `cd`, after the indentation shared with `ab`
+    assert_eq!(hints(source), vec![hint("ding", Position::new(5, 8))]);
+}
+
+#[test]
+fn test_event_markers_in_interpolated_text() {
+    let source = r#"let name: String = "Ann"
+fn set_color(color: String)
+
+node Start {
+    text: $"Hi {name} there"
+    with events: [
+        3, set_color("red")
+        5, set_color("blue")
+        10, set_color("green")
+    ]
+}
+"#;
+    let hints = hints(source);
+    assert!(hints.contains(&hint("set_color", position_of(source, "{name}", false))));
+    assert!(hints.contains(&hint("set_color", position_of(source, "there", false))));
+    // The end of the text is a valid index too
+    assert!(hints.contains(&hint("set_color", position_of(source, "there", true))));
+}
+
+#[test]
+fn test_event_markers_for_event_references_and_branch_cases() {
+    let source = r#"fn set_color(color: String)
+
+event Flash {
+    index: 2
+    action: set_color("white")
+}
+
+enum Place {
+    forest
+    city
+}
+
+let place: Place
+
+let object: branch<place> [
+    forest, "trees", events: [
+        1, set_color("green")
+    ]
+    city, "cars"
+]
+
+node Start {
+    text: "abcdef"
+    with Flash
+}
+"#;
+    let hints = hints(source);
+    assert!(hints.contains(&hint("set_color", position_of(source, "cdef", false))));
+    assert!(hints.contains(&hint("set_color", position_of(source, "rees", false))));
+}
+
+#[test]
+fn test_parameter_names_on_arguments() {
+    let source = r#"fn play_sound(file: String, volume: Number)
+
+event Ding {
+    index: 0
+    action: play_sound("ding.wav", 1)
+}
+
+let volume: Number = 1
+
+node Start {
+    text: "Hi"
+    with events: [
+        0, play_sound("hi.wav", volume)
+    ]
+    run Ding("bell.wav", 2)
+}
+"#;
+    let hints = hints(source);
+    let parameter_hints: Vec<_> = hints
+        .iter()
+        .filter(|(label, _)| label.ends_with(':'))
+        .cloned()
+        .collect();
+    assert_eq!(
+        parameter_hints,
+        [
+            hint("file:", position_of(source, "\"ding.wav\"", false)),
+            hint("volume:", position_of(source, "1)", false)),
+            hint("file:", position_of(source, "\"hi.wav\"", false)),
+            // `volume` as the argument of `volume` needs no hint
+            hint("file:", position_of(source, "\"bell.wav\"", false)),
+            hint("volume:", position_of(source, "2)", false)),
+        ]
+    );
+
+    let full = inlay_hints(&uri(), source, whole(source), Language::English);
+    assert!(
+        full.iter()
+            .filter(|hint| hint.kind == Some(InlayHintKind::PARAMETER))
+            .all(|hint| hint.padding_right == Some(true))
+    );
+}
+
+#[test]
+fn test_constant_value_of_index_override() {
+    let source = r#"fn play_sound(file: String)
+
+event Ding {
+    index: 0
+    action: play_sound("ding.wav")
+}
+
+const beat: Number = 4
+let later: Number = 8
+
+node Start {
+    run Ding with beat
+    run Ding with later
+}
+"#;
+    let hints = hints(source);
+    assert!(hints.contains(&hint("= 4", position_of(source, "with beat", true))));
+    assert!(!hints.iter().any(|(label, _)| label == "= 8"));
+}
+
+#[test]
+fn test_only_hints_in_range() {
+    let source = r#"fn set_color(color: String)
+
+node Start {
+    text: "Hello"
+    with events: [
+        0, set_color("red")
+    ]
+}
+
+node Next {
+    text: "World"
+    with events: [
+        2, set_color("blue")
+    ]
+}
+"#;
+    let next_start = position_of(source, "node Next", false);
+    let hints = hints_in(source, Range::new(next_start, Position::new(20, 0)));
+    assert_eq!(
+        hints,
+        [
+            hint("set_color", position_of(source, "rld", false)),
+            hint("color:", position_of(source, "\"blue\"", false)),
+        ]
+    );
+}
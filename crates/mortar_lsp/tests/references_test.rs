@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use mortar_lsp::backend::{IndexedDocument, document_highlights, find_references};
+use tower_lsp_server::lsp_types::{DocumentHighlightKind, Location, Position, Uri};
+
+const SOURCE: &str = r#"enum Mood {
+    happy
+    sad
+}
+
+let mood: Mood = Mood.happy
+let score: Number = 0
+const Bonus: Number = 5
+
+let face: branch<mood> [
+    happy, "smile"
+    sad, "frown"
+]
+
+fn play_sound(file: String)
+fn has_key() -> Bool
+
+event Ding { action: play_sound("ding.wav") }
+timeline Intro { run Ding wait 1 run Ding }
+
+node Start {
+    text: $"{face} {play_sound("hi.wav")}"
+    with events: [0, play_sound("a.wav")]
+    with Ding
+    score = Bonus
+    mood = Mood.sad
+    choice: [
+        "Open" when has_key() -> Vault,
+        "Leave" -> Vault
+    ]
+} -> Vault
+
+node Vault {
+    if mood == Mood.happy {
+        text: "Yay"
+    }
+}
+"#;
+
+fn uri(name: &str) -> Uri {
+    format!("file:///{}", name).parse().unwrap()
+}
+
+/// `content` as the workspace index keeps it for `uri`.
+fn indexed(uri: Uri, content: &str) -> (Uri, Arc<IndexedDocument>) {
+    (uri, Arc::new(IndexedDocument::new(content.to_string())))
+}
+
+fn position_of(content: &str, needle: &str, nth: usize) -> Position {
+    let offset = content.match_indices(needle).nth(nth).unwrap().0;
+    let before = &content[..offset];
+    Position::new(
+        before.matches('\n').count() as u32,
+        before.rsplit('\n').next().unwrap().chars().count() as u32 + 1,
+    )
+}
+
+/// The line of each reference to the symbol at the `nth` `needle`.
+fn references(needle: &str, nth: usize, include_declaration: bool) -> Vec<u32> {
+    find_references(
+        &uri("a.mortar"),
+        SOURCE,
+        position_of(SOURCE, needle, nth),
+        include_declaration,
+        &[],
+    )
+    .iter()
+    .map(|location| location.range.start.line)
+    .collect()
+}
+
+#[test]
+fn test_references_to_a_node() {
+    assert_eq!(references("Vault", 0, false), vec![27, 28, 30]);
+    assert_eq!(references("Vault", 3, true), vec![27, 28, 30, 32]);
+}
+
+#[test]
+fn test_references_to_functions_events_and_timelines() {
+    assert_eq!(references("play_sound", 0, false), vec![17, 21, 22]);
+    assert_eq!(references("Ding", 0, true), vec![17, 18, 18, 23]);
+    assert_eq!(references("Intro", 0, true), vec![18]);
+    assert_eq!(references("has_key", 1, true), vec![15, 27]);
+}
+
+#[test]
+fn test_references_to_values() {
+    assert_eq!(references("score", 1, true), vec![6, 24]);
+    assert_eq!(references("Bonus", 0, false), vec![24]);
+    assert_eq!(references("mood", 2, true), vec![5, 9, 25, 33]);
+    assert_eq!(references("face", 1, true), vec![9, 21]);
+}
+
+#[test]
+fn test_references_to_enums_and_variants() {
+    assert_eq!(references("Mood", 0, false), vec![5, 5, 25, 33]);
+    assert_eq!(references("happy", 0, true), vec![1, 5, 10, 33]);
+    assert_eq!(references("sad", 2, false), vec![11, 25]);
+}
+
+#[test]
+fn test_references_across_the_workspace() {
+    let content =
+        "fn play_sound(file: String)\nnode Start {\n    with events: [0, play_sound(\"a\")]\n}\n";
+    let others = vec![
+        indexed(uri("b.mortar"), SOURCE),
+        indexed(uri("c.mortar"), "node Other {}\n"),
+    ];
+
+    let locations = find_references(
+        &uri("a.mortar"),
+        content,
+        position_of(content, "play_sound", 1),
+        true,
+        &others,
+    );
+    let found: Vec<(String, u32)> = locations
+        .iter()
+        .map(|Location { uri, range }| (uri.path().to_string(), range.start.line))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            ("/a.mortar".to_string(), 0),
+            ("/a.mortar".to_string(), 2),
+            ("/b.mortar".to_string(), 14),
+            ("/b.mortar".to_string(), 17),
+            ("/b.mortar".to_string(), 21),
+            ("/b.mortar".to_string(), 22),
+        ]
+    );
+}
+
+#[test]
+fn test_node_local_branches_stay_in_their_file() {
+    let content = "node Start {\n    mood: branch [\n        happy, \"yay\"\n    ]\n    text: $\"{mood}\"\n}\nlet happy: Bool = true\n";
+    let others = vec![indexed(uri("b.mortar"), SOURCE)];
+
+    let lines: Vec<u32> = find_references(
+        &uri("a.mortar"),
+        content,
+        position_of(content, "mood", 1),
+        true,
+        &others,
+    )
+    .iter()
+    .map(|location| location.range.start.line)
+    .collect();
+
+    assert_eq!(lines, vec![1, 4]);
+}
+
+#[test]
+fn test_document_highlights_mark_writes() {
+    let highlights = document_highlights(SOURCE, position_of(SOURCE, "score", 0));
+
+    let found: Vec<(u32, Option<DocumentHighlightKind>)> = highlights
+        .iter()
+        .map(|highlight| (highlight.range.start.line, highlight.kind))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (6, Some(DocumentHighlightKind::WRITE)),
+            (24, Some(DocumentHighlightKind::WRITE)),
+        ]
+    );
+
+    let highlights = document_highlights(SOURCE, position_of(SOURCE, "Bonus", 1));
+    let kinds: Vec<_> = highlights.iter().map(|highlight| highlight.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Some(DocumentHighlightKind::WRITE),
+            Some(DocumentHighlightKind::READ)
+        ]
+    );
+}
+
+#[test]
+fn test_no_highlights_outside_names() {
+    assert!(document_highlights(SOURCE, position_of(SOURCE, "\"Yay\"", 0)).is_empty());
+}
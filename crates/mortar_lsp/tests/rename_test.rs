@@ -0,0 +1,249 @@
+use std::fs;
+use std::sync::Arc;
+
+use mortar_compiler::Language;
+use mortar_lsp::backend::{IndexedDocument, prepare_rename, rename_edits};
+use tower_lsp_server::UriExt;
+use tower_lsp_server::lsp_types::{
+    Position, PrepareRenameResponse, Range, TextEdit, Uri, WorkspaceEdit,
+};
+
+const SOURCE: &str = r#"fn play_sound(file: String)
+let gold: Number = 0
+
+node ChoicePoint {
+    text: "Back at the ChoicePoint, with {gold} gold."
+    text: $"You have {gold} gold."
+    with events: [0, play_sound("a.wav")]
+    choice: [
+        "Again" -> ChoicePoint,
+        "Leave" -> Ending
+    ]
+}
+
+node Ending {} -> ChoicePoint
+"#;
+
+fn uri(name: &str) -> Uri {
+    format!("file:///{}", name).parse().unwrap()
+}
+
+/// `content` as the workspace index keeps it for `uri`.
+fn indexed(uri: Uri, content: &str) -> (Uri, Arc<IndexedDocument>) {
+    (uri, Arc::new(IndexedDocument::new(content.to_string())))
+}
+
+fn position_of(content: &str, needle: &str, nth: usize) -> Position {
+    let offset = content.match_indices(needle).nth(nth).unwrap().0;
+    let before = &content[..offset];
+    Position::new(
+        before.matches('\n').count() as u32,
+        before.rsplit('\n').next().unwrap().chars().count() as u32 + 1,
+    )
+}
+
+fn rename(needle: &str, nth: usize, new_name: &str) -> Result<WorkspaceEdit, String> {
+    rename_edits(
+        &uri("a.mortar"),
+        SOURCE,
+        position_of(SOURCE, needle, nth),
+        new_name,
+        &[],
+        Language::English,
+    )
+}
+
+fn edits_for(edit: &WorkspaceEdit, uri: &Uri) -> Vec<TextEdit> {
+    edit.changes
+        .as_ref()
+        .and_then(|changes| changes.get(uri))
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Apply the edits for `uri` to `content`.
+fn apply(content: &str, edit: &WorkspaceEdit, uri: &Uri) -> String {
+    let mut edits = edits_for(edit, uri);
+    edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    for edit in edits.iter().rev() {
+        assert_eq!(edit.range.start.line, edit.range.end.line);
+        let line = &mut lines[edit.range.start.line as usize];
+        let start = line
+            .char_indices()
+            .nth(edit.range.start.character as usize)
+            .map_or(line.len(), |(index, _)| index);
+        let end = line
+            .char_indices()
+            .nth(edit.range.end.character as usize)
+            .map_or(line.len(), |(index, _)| index);
+        line.replace_range(start..end, &edit.new_text);
+    }
+    lines.join("\n") + "\n"
+}
+
+#[test]
+fn test_rename_node_leaves_dialogue_text_alone() {
+    let edit = rename("ChoicePoint", 0, "Crossroads").unwrap();
+
+    assert_eq!(
+        apply(SOURCE, &edit, &uri("a.mortar")),
+        SOURCE
+            .replace("node ChoicePoint", "node Crossroads")
+            .replace("-> ChoicePoint", "-> Crossroads")
+    );
+    assert!(apply(SOURCE, &edit, &uri("a.mortar")).contains("Back at the ChoicePoint"));
+}
+
+#[test]
+fn test_rename_variable_updates_placeholders_only() {
+    let edit = rename("gold", 3, "coins").unwrap();
+    let renamed = apply(SOURCE, &edit, &uri("a.mortar"));
+
+    assert!(renamed.contains("let coins: Number = 0"));
+    assert!(renamed.contains("text: $\"You have {coins} gold.\""));
+    // A plain string has no placeholders
+    assert!(renamed.contains("with {gold} gold."));
+}
+
+#[test]
+fn test_rename_across_workspace_files() {
+    let other = "node Intro {} -> ChoicePoint\nnode Unrelated {}\n".to_string();
+    let others = vec![indexed(uri("b.mortar"), &other)];
+
+    let edit = rename_edits(
+        &uri("a.mortar"),
+        SOURCE,
+        position_of(SOURCE, "ChoicePoint", 3),
+        "Crossroads",
+        &others,
+        Language::English,
+    )
+    .unwrap();
+
+    assert_eq!(edit.changes.as_ref().unwrap().len(), 2);
+    assert_eq!(edits_for(&edit, &uri("a.mortar")).len(), 3);
+    assert_eq!(
+        apply(&other, &edit, &uri("b.mortar")),
+        "node Intro {} -> Crossroads\nnode Unrelated {}\n"
+    );
+}
+
+#[test]
+fn test_rename_defined_in_another_file() {
+    let content = "node Intro {} -> ChoicePoint\n";
+    let others = vec![indexed(uri("a.mortar"), SOURCE)];
+
+    let edit = rename_edits(
+        &uri("b.mortar"),
+        content,
+        position_of(content, "ChoicePoint", 0),
+        "Crossroads",
+        &others,
+        Language::English,
+    )
+    .unwrap();
+
+    assert_eq!(edits_for(&edit, &uri("b.mortar")).len(), 1);
+    assert_eq!(edits_for(&edit, &uri("a.mortar")).len(), 3);
+}
+
+#[test]
+fn test_rename_refusals() {
+    assert_eq!(
+        rename("ChoicePoint", 0, "Ending").unwrap_err(),
+        "A symbol with this name already exists: `Ending`"
+    );
+    assert_eq!(
+        rename("ChoicePoint", 0, "choice_point").unwrap_err(),
+        "Node names should be PascalCase: `choice_point`"
+    );
+    assert_eq!(
+        rename("play_sound", 1, "PlaySound").unwrap_err(),
+        "Function names should be snake_case: `PlaySound`"
+    );
+    assert_eq!(
+        rename("gold", 0, "node").unwrap_err(),
+        "Not a valid Mortar name: `node`"
+    );
+    assert_eq!(
+        rename("gold", 0, "two words").unwrap_err(),
+        "Not a valid Mortar name: `two words`"
+    );
+    // A function and a node may share a name
+    assert!(rename("play_sound", 0, "ending").is_ok());
+}
+
+#[test]
+fn test_rename_refuses_undefined_and_non_symbols() {
+    let content = "node Start {} -> Missing\n";
+    let uri = uri("a.mortar");
+
+    assert_eq!(
+        prepare_rename(
+            &uri,
+            content,
+            position_of(content, "Missing", 0),
+            &[],
+            Language::English
+        )
+        .unwrap_err(),
+        "Only symbols defined in the workspace can be renamed"
+    );
+    assert_eq!(
+        prepare_rename(
+            &uri,
+            SOURCE,
+            position_of(SOURCE, "Back", 0),
+            &[],
+            Language::Chinese
+        )
+        .unwrap_err(),
+        "此位置没有可重命名的符号"
+    );
+}
+
+#[test]
+fn test_prepare_rename_returns_the_name_range() {
+    let response = prepare_rename(
+        &uri("a.mortar"),
+        SOURCE,
+        position_of(SOURCE, "gold", 3),
+        &[],
+        Language::English,
+    )
+    .unwrap();
+
+    assert_eq!(
+        response,
+        PrepareRenameResponse::RangeWithPlaceholder {
+            range: Range::new(Position::new(5, 22), Position::new(5, 26)),
+            placeholder: "gold".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_allowed_lint_permits_unconventional_names() {
+    let root = std::env::temp_dir().join(format!("mortar-lsp-rename-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("mortar.toml"),
+        "[lints]\nnon_pascal_case_node = \"allow\"\n",
+    )
+    .unwrap();
+    let file = Uri::from_file_path(root.join("story.mortar")).unwrap();
+
+    let result = rename_edits(
+        &file,
+        SOURCE,
+        position_of(SOURCE, "Ending", 0),
+        "the_end",
+        &[],
+        Language::English,
+    );
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(result.is_ok());
+}
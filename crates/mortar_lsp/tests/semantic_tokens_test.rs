@@ -0,0 +1,29 @@
+use mortar_lsp::backend::compute_semantic_tokens;
+
+#[test]
+fn test_multi_line_strings_are_split_per_line() {
+    let content = "node A {\n    text: \"\"\"\n        Hello\n\n        \"\"\"\n}";
+    let tokens = compute_semantic_tokens(content);
+
+    // `node`, `A`, `{`, `text`, `:`, three string lines, `}`
+    let positions: Vec<(u32, u32, u32)> = tokens
+        .iter()
+        .map(|t| (t.delta_line, t.delta_start, t.length))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![
+            (0, 0, 4),
+            (0, 5, 1),
+            (0, 2, 1),
+            (1, 4, 4),
+            (0, 4, 1),
+            (0, 2, 3),
+            (1, 0, 13),
+            (2, 0, 11),
+            (1, 0, 1),
+        ]
+    );
+    assert_eq!(tokens[5].token_type, tokens[6].token_type);
+    assert_eq!(tokens[6].token_type, tokens[7].token_type);
+}
@@ -0,0 +1,127 @@
+use mortar_compiler::{Language, ParseHandler};
+use mortar_lsp::analysis::{SymbolTable, analyze_program};
+use mortar_lsp::backend::signature_help;
+use tower_lsp_server::lsp_types::{Documentation, ParameterLabel, Position, SignatureHelp};
+
+const DECLARATIONS: &str = r#"fn play_sound(file: String, volume: Number) -> Bool
+fn has_item(name: String) -> Bool
+fn format_gold(amount: Number) -> String
+
+event Thunder {
+    action: play_sound("thunder.wav", 1)
+}
+"#;
+
+fn symbol_table() -> SymbolTable {
+    let program = ParseHandler::parse_source_code(DECLARATIONS, false).unwrap();
+    analyze_program(&program)
+}
+
+/// Signature help with the cursor at the `|` in `text`.
+fn help_at(text: &str) -> Option<SignatureHelp> {
+    let offset = text.find('|').unwrap();
+    let content = text.replacen('|', "", 1);
+    let before = &content[..offset];
+    let position = Position::new(
+        before.matches('\n').count() as u32,
+        before.rsplit('\n').next().unwrap().encode_utf16().count() as u32,
+    );
+    signature_help(&content, position, &symbol_table(), Language::English)
+}
+
+fn active(text: &str) -> Option<(String, u32)> {
+    let help = help_at(text)?;
+    Some((help.signatures[0].label.clone(), help.active_parameter?))
+}
+
+#[test]
+fn test_signature_label_and_parameter_offsets() {
+    let help = help_at("node A {\n    text: \"Hi\"\n    with events: [0, play_sound(|\n}").unwrap();
+    let signature = &help.signatures[0];
+
+    assert_eq!(
+        signature.label,
+        "play_sound(file: String, volume: Number) -> Bool"
+    );
+    let offsets: Vec<_> = signature
+        .parameters
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|parameter| match parameter.label {
+            ParameterLabel::LabelOffsets(offsets) => offsets,
+            _ => panic!("expected offsets"),
+        })
+        .collect();
+    assert_eq!(offsets, vec![[11, 23], [25, 39]]);
+    assert_eq!(help.active_parameter, Some(0));
+}
+
+#[test]
+fn test_active_parameter_follows_commas() {
+    let label = "play_sound(file: String, volume: Number) -> Bool".to_string();
+
+    assert_eq!(
+        active("node A {\n    text: \"Hi\"\n    with events: [0, play_sound(\"a.wav\", |"),
+        Some((label.clone(), 1))
+    );
+    // A comma inside a string argument does not move to the next parameter
+    assert_eq!(
+        active("event Boom {\n    action: play_sound(\"a, b|\")\n}"),
+        Some((label.clone(), 0))
+    );
+    assert_eq!(
+        active("event Boom {\n    action: play_sound(\"a\", 1|)\n}"),
+        Some((label, 1))
+    );
+}
+
+#[test]
+fn test_nested_calls_use_the_innermost() {
+    assert_eq!(
+        active("node A {\n    text: $\"You have {format_gold(|)} gold.\"\n}"),
+        Some(("format_gold(amount: Number) -> String".to_string(), 0))
+    );
+    assert_eq!(
+        active("node A {\n    text: $\"You have {format_gold(|"),
+        Some(("format_gold(amount: Number) -> String".to_string(), 0))
+    );
+}
+
+#[test]
+fn test_when_conditions() {
+    assert_eq!(
+        active("node A {\n    choice: [\n        \"Open\" when has_item(|"),
+        Some(("has_item(name: String) -> Bool".to_string(), 0))
+    );
+}
+
+#[test]
+fn test_run_uses_the_event_action() {
+    let help = help_at("node A {\n    run Thunder(\"big.wav\", |").unwrap();
+
+    assert_eq!(
+        help.signatures[0].label,
+        "play_sound(file: String, volume: Number) -> Bool"
+    );
+    assert_eq!(help.active_parameter, Some(1));
+    assert_eq!(
+        help.signatures[0].documentation,
+        Some(Documentation::String(
+            "Arguments for the event's action `play_sound`".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_no_signature_outside_calls() {
+    assert_eq!(active("node A {\n    text: \"Hi|\"\n}"), None);
+    assert_eq!(
+        active("event Boom {\n    action: play_sound(\"a\")|\n}"),
+        None
+    );
+    assert_eq!(active("node A {\n    run Unknown(|"), None);
+    assert_eq!(active("node A {\n    text: missing(|"), None);
+    // A brace ends a call a mistake left open
+    assert_eq!(active("node A {\n    text: has_item(\n}\nnode B {|"), None);
+}
@@ -0,0 +1,44 @@
+use mortar_compiler::Language;
+use mortar_lsp::backend::{compute_semantic_tokens, current_word, parse_with_diagnostics};
+use tower_lsp_server::lsp_types::Uri;
+
+#[test]
+fn test_semantic_tokens_use_utf16_lengths() {
+    let tokens = compute_semantic_tokens("node 开始 {\n    text: \"🎉 好\"\n}");
+
+    // `node`, `开始`, `{`, `text`, `:`, the string, `}`
+    assert_eq!(tokens[1].delta_start, 5);
+    assert_eq!(tokens[1].length, 2);
+
+    let string = &tokens[5];
+    assert_eq!(string.delta_start, 2);
+    assert_eq!(string.length, 6);
+
+    let closing = &tokens[6];
+    assert_eq!(closing.delta_line, 1);
+    assert_eq!(closing.delta_start, 0);
+}
+
+#[test]
+fn test_current_word_takes_utf16_column() {
+    assert_eq!(current_word("text: 好感度", 9), "好感度");
+    assert_eq!(current_word("text: 好感度", 7), "好感度");
+    assert_eq!(current_word("🎉 名字", 5), "名字");
+    assert_eq!(current_word("text: ", 6), "");
+}
+
+#[test]
+fn test_diagnostic_ranges_use_utf16_columns() {
+    let uri: Uri = "file:///test.mortar".parse().unwrap();
+    let content = "node 开始 {\n    text: \"🎉\"\n} -> 不存在\n";
+
+    let (diagnostics, _) = parse_with_diagnostics(&uri, content, Language::English);
+    let missing = diagnostics
+        .iter()
+        .find(|d| d.message.contains("不存在"))
+        .expect("undefined node should be reported");
+
+    assert_eq!(missing.range.start.line, 2);
+    assert_eq!(missing.range.start.character, 5);
+    assert_eq!(missing.range.end.character, 8);
+}